@@ -0,0 +1,160 @@
+//! Export to the computer algebra systems GAP and Magma.
+//!
+//! Both systems number the elements of a quasigroup and the points of a design from 1, so every symbol and
+//! point is written one higher than here. Each export is a single assignment to the given variable name,
+//! ready to be pasted into a session or read from a file:
+//!
+//! - A latin square is a matrix: a list of rows in GAP, and a `Matrix` over the integers in Magma.
+//! - A quasigroup is built from its Cayley table, with `QuasigroupByCayleyTable` of the LOOPS package in
+//!   GAP. Magma has no quasigroups, so it gets the table as a matrix, as for a square.
+//! - A design is a `BlockDesign` of the DESIGN package in GAP, and a `Design` in Magma when it is a
+//!   Steiner system, or an `IncidenceStructure` otherwise, as for coverings and packings.
+//!
+//! Sources:
+//!
+//! - [GAP, Groups, Algorithms, Programming](https://www.gap-system.org)
+//! - [LOOPS, computing with quasigroups and loops in GAP, G. P. Nagy, P. Vojtěchovský](https://gap-packages.github.io/loops/)
+//! - [DESIGN, the design package for GAP, L. H. Soicher](https://gap-packages.github.io/design/)
+//! - Incidence structures and designs, in the Handbook of Magma functions, W. Bosma, J. Cannon (editors)
+
+use crate::covering::{CoveringDesign, PackingDesign};
+use crate::latin_square::LatinSquare;
+use crate::quasigroup::Quasigroup;
+use crate::triple_system::SteinerTripleSystem;
+
+/// Objects that can be written as GAP and Magma definitions.
+pub trait AlgebraExport {
+    /// A GAP statement assigning the object to `name`.
+    fn to_gap(&self, name: &str) -> String;
+
+    /// A Magma statement assigning the object to `name`.
+    fn to_magma(&self, name: &str) -> String;
+}
+
+/// The square as its matrix of symbols.
+///
+/// ```
+/// use combinatorial_patterns::algebra_export::AlgebraExport;
+/// use combinatorial_patterns::IncidenceCube;
+///
+/// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+/// assert_eq!(square.to_gap("L"), "L := [[1, 2, 3], [2, 3, 1], [3, 1, 2]];\n");
+/// assert_eq!(square.to_magma("L"), "L := Matrix(Integers(), 3, 3, [1, 2, 3, 2, 3, 1, 3, 1, 2]);\n");
+/// ```
+impl AlgebraExport for LatinSquare {
+    fn to_gap(&self, name: &str) -> String {
+        format!("{} := {};\n", name, gap_matrix(&self.square))
+    }
+
+    fn to_magma(&self, name: &str) -> String {
+        format!("{} := {};\n", name, magma_matrix(&self.square))
+    }
+}
+
+/// The quasigroup by its Cayley table.
+///
+/// ```
+/// use combinatorial_patterns::algebra_export::AlgebraExport;
+/// use combinatorial_patterns::{IncidenceCube, Quasigroup};
+///
+/// let quasigroup = Quasigroup::new(IncidenceCube::new_cyclic(2).unwrap().as_latin_square()).unwrap();
+/// assert_eq!(quasigroup.to_gap("Q"), "Q := QuasigroupByCayleyTable([[1, 2], [2, 1]]);\n");
+/// ```
+impl AlgebraExport for Quasigroup {
+    fn to_gap(&self, name: &str) -> String {
+        format!("{} := QuasigroupByCayleyTable({});\n", name, gap_matrix(&self.as_latin_square().square))
+    }
+
+    fn to_magma(&self, name: &str) -> String {
+        self.as_latin_square().to_magma(name)
+    }
+}
+
+/// The Steiner triple system as a 2-design with blocks of size 3.
+///
+/// ```
+/// use combinatorial_patterns::algebra_export::AlgebraExport;
+/// use combinatorial_patterns::triple_system::SteinerTripleSystem;
+///
+/// let system = SteinerTripleSystem::new(3).unwrap();
+/// assert_eq!(system.to_gap("D"), "D := BlockDesign(3, [[1, 2, 3]]);\n");
+/// assert_eq!(system.to_magma("D"), "D := Design< 2, 3 | {1, 2, 3} >;\n");
+/// ```
+impl AlgebraExport for SteinerTripleSystem {
+    fn to_gap(&self, name: &str) -> String {
+        let blocks: Vec<Vec<usize>> = self.blocks().iter().map(|block| block.to_vec()).collect();
+        gap_design(name, self.order(), &blocks)
+    }
+
+    fn to_magma(&self, name: &str) -> String {
+        let blocks: Vec<Vec<usize>> = self.blocks().iter().map(|block| block.to_vec()).collect();
+        format!("{} := Design< 2, {} | {} >;\n", name, self.order(), magma_blocks(&blocks))
+    }
+}
+
+/// The covering as an incidence structure on its points.
+impl AlgebraExport for CoveringDesign {
+    fn to_gap(&self, name: &str) -> String {
+        gap_design(name, self.points(), self.blocks())
+    }
+
+    fn to_magma(&self, name: &str) -> String {
+        magma_incidence_structure(name, self.points(), self.blocks())
+    }
+}
+
+/// The packing as an incidence structure on its points.
+///
+/// ```
+/// use combinatorial_patterns::algebra_export::AlgebraExport;
+/// use combinatorial_patterns::covering::PackingDesign;
+///
+/// let packing = PackingDesign::new(4, 2, 2, vec![vec![0, 1], vec![2, 3]]).unwrap();
+/// assert_eq!(packing.to_magma("P"), "P := IncidenceStructure< 4 | {1, 2}, {3, 4} >;\n");
+/// ```
+impl AlgebraExport for PackingDesign {
+    fn to_gap(&self, name: &str) -> String {
+        gap_design(name, self.points(), self.blocks())
+    }
+
+    fn to_magma(&self, name: &str) -> String {
+        magma_incidence_structure(name, self.points(), self.blocks())
+    }
+}
+
+/// A list of rows, numbered from 1.
+fn gap_matrix(rows: &[Vec<usize>]) -> String {
+    let rows: Vec<String> = rows.iter().map(|row| format!("[{}]", numbered(row))).collect();
+    format!("[{}]", rows.join(", "))
+}
+
+/// A square matrix given by its entries row by row, numbered from 1.
+fn magma_matrix(rows: &[Vec<usize>]) -> String {
+    let entries: Vec<usize> = rows.iter().flatten().copied().collect();
+    format!("Matrix(Integers(), {}, {}, [{}])", rows.len(), rows.len(), numbered(&entries))
+}
+
+fn gap_design(name: &str, points: usize, blocks: &[Vec<usize>]) -> String {
+    let blocks: Vec<String> = blocks.iter().map(|block| format!("[{}]", numbered(&sorted(block)))).collect();
+    format!("{} := BlockDesign({}, [{}]);\n", name, points, blocks.join(", "))
+}
+
+fn magma_incidence_structure(name: &str, points: usize, blocks: &[Vec<usize>]) -> String {
+    format!("{} := IncidenceStructure< {} | {} >;\n", name, points, magma_blocks(blocks))
+}
+
+fn magma_blocks(blocks: &[Vec<usize>]) -> String {
+    let blocks: Vec<String> = blocks.iter().map(|block| format!("{{{}}}", numbered(&sorted(block)))).collect();
+    blocks.join(", ")
+}
+
+fn sorted(block: &[usize]) -> Vec<usize> {
+    let mut block = block.to_vec();
+    block.sort_unstable();
+    block
+}
+
+/// The values, each plus 1, separated by commas.
+fn numbered(values: &[usize]) -> String {
+    values.iter().map(|value| (value + 1).to_string()).collect::<Vec<String>>().join(", ")
+}
@@ -0,0 +1,235 @@
+//! Audit records of how a randomization was produced, kept alongside the generated design.
+//!
+//! A record names the version of this crate, the algorithm, the seed of the random generator, the number
+//! of iterations the algorithm made, and the constraints the design had to meet. For a seeded square, that
+//! is enough to repeat the generation and check that it gives the same result, see `Audited::verify`.
+//! Records and the values they are attached to are written as one JSON object. Besides squares, records are
+//! kept for randomized complete block designs and Williams crossover designs, whose JSON holds the design in
+//! long format.
+//!
+//! The random choices are drawn from the `ChaCha8Rng` generator of the rand_chacha crate, a fixed algorithm,
+//! rather than rand's `StdRng`, which may change in any release. A record therefore repeats in any build of
+//! the version of this crate it names, on any platform, and not only with the locked version of rand.
+
+use std::fmt::Write;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::design::{BlockDesign, CrossoverDesign, Factor, LongFormat, LongTable};
+use crate::error::CombinatorialError;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+
+/// The algorithm name of squares from the Jacobson-Matthews chain.
+pub const JACOBSON_MATTHEWS: &str = "jacobson-matthews";
+
+/// The algorithm name of randomized complete block designs, shuffled block by block.
+pub const RANDOMIZED_COMPLETE_BLOCK: &str = "randomized-complete-block";
+
+/// The algorithm name of Williams crossover designs.
+pub const WILLIAMS: &str = "williams";
+
+/// How a design was generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AuditRecord {
+    /// The version of this crate that generated the design.
+    pub crate_version: String,
+    pub algorithm: String,
+    /// The seed of the random generator, if the design was random.
+    pub seed: Option<u64>,
+    /// The number of iterations of the algorithm, e.g. moves of a Markov chain.
+    pub iterations: u64,
+    /// The constraints the design was generated to meet, in words.
+    pub constraints: Vec<String>
+}
+
+impl AuditRecord {
+    /// A record for the running version of this crate.
+    pub fn new(algorithm: &str, seed: Option<u64>, iterations: u64, constraints: Vec<String>) -> AuditRecord {
+        AuditRecord {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            algorithm: algorithm.to_string(),
+            seed,
+            iterations,
+            constraints
+        }
+    }
+
+    /// The record as a JSON object.
+    pub fn to_json(&self) -> String {
+        let seed = self.seed.map(|seed| seed.to_string()).unwrap_or_else(|| "null".to_string());
+        let constraints: Vec<String> = self.constraints.iter().map(|constraint| json_string(constraint)).collect();
+        format!(
+            "{{\"crate_version\":{},\"algorithm\":{},\"seed\":{},\"iterations\":{},\"constraints\":[{}]}}",
+            json_string(&self.crate_version), json_string(&self.algorithm), seed, self.iterations, constraints.join(",")
+        )
+    }
+}
+
+/// A generated value with the record of how it was generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audited<T> {
+    pub value: T,
+    pub audit: AuditRecord
+}
+
+impl Audited<LatinSquare> {
+    /// The square and its record as a JSON object `{"audit": {..}, "square": [[..], ..]}`.
+    pub fn to_json(&self) -> String {
+        format!("{{\"audit\":{},\"square\":{}}}", self.audit.to_json(), json_rows(&self.value))
+    }
+
+    /// Repeats the generation the record describes, and returns true if it gives the same square in the
+    /// same number of iterations. Only seeded Jacobson-Matthews squares from this version of the crate can
+    /// be repeated; for anything else this returns false.
+    pub fn verify(&self) -> bool {
+        let Some(seed) = self.audit.seed else {
+            return false
+        };
+        if self.audit.algorithm != JACOBSON_MATTHEWS || self.audit.crate_version != env!("CARGO_PKG_VERSION") || !self.audit.constraints.is_empty() {
+            return false
+        }
+        let Ok(repeated) = random_square(self.value.size(), seed) else {
+            return false
+        };
+        repeated.audit == self.audit && repeated.value.square == self.value.square
+    }
+}
+
+impl Audited<BlockDesign> {
+    /// The design and its record as a JSON object `{"audit": {..}, "header": [..], "records": [[..], ..]}`,
+    /// with the records in long format.
+    pub fn to_json(&self) -> String {
+        format!("{{\"audit\":{},{}}}", self.audit.to_json(), json_table(&self.value.to_long_format()))
+    }
+
+    /// Repeats the randomization the record describes, and returns true if it gives the same design. Only
+    /// seeded designs from this version of the crate can be repeated; for anything else this returns false.
+    pub fn verify(&self) -> bool {
+        let Some(seed) = self.audit.seed else {
+            return false
+        };
+        if self.audit.algorithm != RANDOMIZED_COMPLETE_BLOCK || self.audit.crate_version != env!("CARGO_PKG_VERSION") {
+            return false
+        }
+        let repeated = randomized_complete(self.value.block_factor().clone(), self.value.treatments().clone(), seed);
+        repeated.audit == self.audit && repeated.value == self.value
+    }
+}
+
+impl Audited<CrossoverDesign> {
+    /// The design and its record as a JSON object `{"audit": {..}, "header": [..], "records": [[..], ..]}`,
+    /// with the records in long format.
+    pub fn to_json(&self) -> String {
+        format!("{{\"audit\":{},{}}}", self.audit.to_json(), json_table(&self.value.to_long_format()))
+    }
+}
+
+/// Generates the random square of the given order for a seed with the Jacobson-Matthews chain, with its audit record.
+/// Fails for order 0.
+///
+/// ```
+/// use combinatorial_patterns::audit;
+///
+/// let audited = audit::random_square(5, 2024).unwrap();
+/// assert_eq!(audited.audit.seed, Some(2024));
+/// assert!(audited.audit.iterations >= 125);
+/// assert!(audited.verify());
+/// assert!(audited.to_json().starts_with("{\"audit\":{\"crate_version\":"));
+///
+/// // The same square and iterations in every build of this version.
+/// let small = audit::random_square(4, 2024).unwrap();
+/// assert_eq!(small.value.square, [[2, 3, 1, 0], [1, 0, 3, 2], [0, 1, 2, 3], [3, 2, 0, 1]]);
+/// assert_eq!(small.audit.iterations, 150);
+///
+/// // A square that was changed afterwards no longer matches its record.
+/// let mut tampered = audited;
+/// tampered.value.square.swap(0, 1);
+/// assert!(!tampered.verify());
+/// assert!(audit::random_square(0, 2024).is_err());
+/// ```
+pub fn random_square(order: usize, seed: u64) -> Result<Audited<LatinSquare>, CombinatorialError> {
+    let mut cube = IncidenceCube::new_cyclic(order)?;
+    let iterations = cube.shuffle_with_rng(&mut ChaCha8Rng::seed_from_u64(seed));
+    Ok(Audited {
+        value: cube.as_latin_square(),
+        audit: AuditRecord::new(JACOBSON_MATTHEWS, Some(seed), iterations, Vec::new())
+    })
+}
+
+/// Randomizes a complete block design for a seed, with its audit record. The iterations are the blocks shuffled.
+///
+/// ```
+/// use combinatorial_patterns::audit;
+/// use combinatorial_patterns::design::Factor;
+///
+/// let audited = audit::randomized_complete(Factor::numbered("Block", 4), Factor::new("Variety", &["A", "B", "C"]), 7);
+/// assert_eq!(audited.audit.algorithm, audit::RANDOMIZED_COMPLETE_BLOCK);
+/// assert_eq!(audited.audit.seed, Some(7));
+/// assert_eq!(audited.audit.iterations, 4);
+/// assert!(audited.verify());
+/// assert!(audited.to_json().contains("\"header\":[\"Block\",\"Unit\",\"Variety\"]"));
+/// ```
+pub fn randomized_complete(blocks: Factor, treatments: Factor, seed: u64) -> Audited<BlockDesign> {
+    let iterations = blocks.levels.len() as u64;
+    Audited {
+        value: BlockDesign::randomized_complete(blocks, treatments, &mut ChaCha8Rng::seed_from_u64(seed)),
+        audit: AuditRecord::new(RANDOMIZED_COMPLETE_BLOCK, Some(seed), iterations, vec!["every treatment once per block".to_string()])
+    }
+}
+
+/// The Williams crossover design for the given treatments, with its audit record. The design is not random,
+/// so the record has no seed and no iterations.
+///
+/// ```
+/// use combinatorial_patterns::audit;
+/// use combinatorial_patterns::design::Factor;
+///
+/// let audited = audit::williams(Factor::new("Treatment", &["A", "B", "C"]));
+/// assert_eq!(audited.audit.seed, None);
+/// assert_eq!(audited.value.subjects(), 6);
+/// assert!(audited.to_json().contains("[\"Subject 1\",\"Period 1\",\"A\"]"));
+/// ```
+pub fn williams(treatments: Factor) -> Audited<CrossoverDesign> {
+    Audited {
+        value: CrossoverDesign::williams(treatments),
+        audit: AuditRecord::new(WILLIAMS, None, 0, vec!["balanced for first-order carryover".to_string()])
+    }
+}
+
+/// A long table as the JSON members `"header": [..], "records": [[..], ..]`.
+fn json_table(table: &LongTable) -> String {
+    let strings = |fields: &[String]| fields.iter().map(|field| json_string(field)).collect::<Vec<String>>().join(",");
+    let records: Vec<String> = table.records.iter().map(|record| format!("[{}]", strings(record))).collect();
+    format!("\"header\":[{}],\"records\":[{}]", strings(&table.header), records.join(","))
+}
+
+/// The rows of a square as a JSON array of arrays.
+pub(crate) fn json_rows(square: &LatinSquare) -> String {
+    let rows: Vec<String> = square.square.iter().map(|row| {
+        format!("[{}]", row.iter().map(|symbol| symbol.to_string()).collect::<Vec<String>>().join(","))
+    }).collect();
+    format!("[{}]", rows.join(","))
+}
+
+/// A string as a JSON string literal, with quotes, backslashes and control characters escaped.
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                write!(escaped, "\\u{:04x}", c as u32).expect("ProgrammingError: writing to a string can't fail.");
+            },
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,140 @@
+//! Batches of random latin squares that can each be regenerated from a seed.
+//!
+//! Square i of a batch with master seed m is generated from the seed m + i (wrapping), so a worker that
+//! knows only a record's seed and order can generate that square again on demand, without the rest of the
+//! batch. Records are written as NDJSON, one JSON object `{"seed": .., "order": .., "square": [[..], ..]}`
+//! per line.
+//!
+//! The squares are drawn with the Jacobson-Matthews chain, from the `ChaCha8Rng` generator of the rand_chacha
+//! crate seeded with the square's seed. Unlike rand's `StdRng`, whose algorithm may change in any release,
+//! ChaCha8 is a fixed algorithm, so a seed always gives the same random numbers, on every platform. The
+//! choices drawn from them go through the range sampling of rand 0.8, which keeps its values from one patch
+//! release to the next, so the same seed gives the same square in every build of this version of the crate.
+//! `audit::random_square` generates the same squares together with a record of how they were made.
+//! The batch functions draw them all with one `generator::Generator`, which reuses its buffers.
+//!
+//! `sample_distinct` skips the seeds whose squares repeat an earlier one, as matrices or up to isotopy, so
+//! replicates of an experiment can each get their own design while every record still regenerates on its own.
+//!
+//! Sources:
+//!
+//! - [NDJSON, newline delimited JSON](https://github.com/ndjson/ndjson-spec)
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::audit::{self, json_rows};
+use crate::canonical::{self, Equivalence};
+use crate::error::CombinatorialError;
+use crate::generator::Generator;
+use crate::latin_square::{LatinSquare, Symbol};
+
+/// A generated square with the seed and order that reproduce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub seed: u64,
+    pub order: usize,
+    pub square: LatinSquare
+}
+
+impl Record {
+    /// The record as one line of JSON, without the line break.
+    pub fn to_ndjson(&self) -> String {
+        format!("{{\"seed\":{},\"order\":{},\"square\":{}}}", self.seed, self.order, json_rows(&self.square))
+    }
+}
+
+/// The seed of square `index` in a batch with the given master seed.
+pub fn seed_of(master_seed: u64, index: u64) -> u64 {
+    master_seed.wrapping_add(index)
+}
+
+/// Generates the random square of the given order for a seed. The same seed and order always give the same square.
+/// Fails for order 0.
+///
+/// ```
+/// use combinatorial_patterns::batch;
+///
+/// assert_eq!(batch::square(6, 17).unwrap().square, batch::square(6, 17).unwrap().square);
+/// // The same square in every build, whatever the platform.
+/// assert_eq!(batch::square(4, 17).unwrap().square, [[0, 1, 3, 2], [3, 2, 0, 1], [1, 0, 2, 3], [2, 3, 1, 0]]);
+/// assert!(batch::square(0, 17).is_err());
+/// ```
+pub fn square(order: usize, seed: u64) -> Result<LatinSquare, CombinatorialError> {
+    Ok(audit::random_square(order, seed)?.value)
+}
+
+/// Returns an iterator over the records of `count` squares of the given order, from the master seed.
+/// Fails for order 0.
+///
+/// ```
+/// use combinatorial_patterns::batch;
+///
+/// let records: Vec<batch::Record> = batch::records(100, 5, 3).unwrap().collect();
+/// assert_eq!(records[2].seed, 102);
+/// // Any record can be regenerated from its own seed.
+/// assert_eq!(batch::square(5, records[2].seed).unwrap().square, records[2].square.square);
+/// assert!(records[0].to_ndjson().starts_with("{\"seed\":100,\"order\":5,\"square\":[["));
+/// ```
+pub fn records(master_seed: u64, order: usize, count: u64) -> Result<impl Iterator<Item = Record>, CombinatorialError> {
+    let mut generator = Generator::with_capacity(order)?;
+    Ok((0..count).map(move |index| {
+        let seed = seed_of(master_seed, index);
+        Record { seed, order, square: LatinSquare::from_rows(generator.square_for_seed(seed).square.clone()) }
+    }))
+}
+
+/// Writes the records of `count` squares of the given order as NDJSON, one line each. Fails with
+/// `InvalidInput` for order 0.
+pub fn write_ndjson(writer: &mut impl Write, master_seed: u64, order: usize, count: u64) -> io::Result<()> {
+    let mut generator = Generator::with_capacity(order).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    for index in 0..count {
+        let seed = seed_of(master_seed, index);
+        writeln!(writer, "{{\"seed\":{},\"order\":{},\"square\":{}}}", seed, order, json_rows(generator.square_for_seed(seed)))?;
+    }
+    Ok(())
+}
+
+/// When two squares count as the same for `sample_distinct`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distinctness {
+    /// Squares are the same if all their cells are equal.
+    Matrix,
+    /// Squares are the same if they are isotopic, which is decided by their forms from `canonical`.
+    Isotopy
+}
+
+/// The records of `count` pairwise distinct squares of the given order, trying the seeds of the batch with the
+/// master seed in turn and skipping those whose square repeats an earlier one. Returns None if `attempts`
+/// seeds give fewer than `count` distinct squares, or if a square of order 0 is asked for.
+///
+/// ```
+/// use combinatorial_patterns::batch::{self, Distinctness};
+///
+/// let records = batch::sample_distinct(7, 5, 10, Distinctness::Matrix, 100).unwrap();
+/// assert_eq!(records.len(), 10);
+///
+/// // There are only two isotopy classes of order 4.
+/// assert_eq!(batch::sample_distinct(7, 4, 2, Distinctness::Isotopy, 1000).unwrap().len(), 2);
+/// assert!(batch::sample_distinct(7, 4, 3, Distinctness::Isotopy, 1000).is_none());
+/// ```
+pub fn sample_distinct(master_seed: u64, order: usize, count: usize, distinctness: Distinctness, attempts: u64) -> Option<Vec<Record>> {
+    let mut seen: HashSet<Vec<Vec<Symbol>>> = HashSet::new();
+    let mut distinct = Vec::new();
+    if count == 0 {
+        return Some(distinct)
+    }
+    for record in records(master_seed, order, attempts).ok()? {
+        let key = match distinctness {
+            Distinctness::Matrix => record.square.square.clone(),
+            Distinctness::Isotopy => canonical::canonical_form(&record.square, Equivalence::Isotopy).square
+        };
+        if seen.insert(key) {
+            distinct.push(record);
+            if distinct.len() == count {
+                return Some(distinct)
+            }
+        }
+    }
+    None
+}
\ No newline at end of file
@@ -0,0 +1,215 @@
+//! Benchmark instances of quasigroup completion for CP and SAT solvers.
+//!
+//! Two families are standard:
+//!
+//! - Quasigroup with holes (QWH): a random latin square with some cells blanked. A completion is known to
+//!   exist, so a solver that finds none is wrong, which makes these good for testing as well as timing.
+//! - Quasigroup completion (QCP): cells filled one at a time, each with a random symbol not yet in its row
+//!   or column. The partial square is consistent but may have no completion.
+//!
+//! Both are easy when almost every cell is filled or almost every cell is empty, and hardest in between.
+//! For QCP the peak of difficulty, and the change from mostly completable to mostly not, is near 42% of the
+//! cells filled; `PHASE_TRANSITION` is that fraction. QWH peaks a little later, with somewhat more holes.
+//!
+//! Every instance is generated from a seed, with the random square of `batch::square` and the `ChaCha8Rng`
+//! generator, so a benchmark set is described by its orders, fractions and seeds. Instances are written in
+//! the text format of the original generators, a line `order n` and then rows with -1 for a hole, or as
+//! DIMACS CNF.
+//!
+//! Sources:
+//!
+//! - Problem structure in the presence of perturbations, Carla P. Gomes, Bart Selman, AAAI (1997)
+//! - Generating satisfiable problem instances, Dimitris Achlioptas, Carla P. Gomes, Henry Kautz, Bart Selman, AAAI (2000)
+//! - [DIMACS CNF format](https://people.sc.fsu.edu/~jburkardt/data/cnf/cnf.html)
+
+use std::fmt::Write;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::batch;
+use crate::partial::PartialLatinSquare;
+
+/// The fraction of filled cells near which QCP instances are hardest.
+pub const PHASE_TRANSITION: f64 = 0.42;
+
+/// Whether an instance is known to have a completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceKind {
+    /// Quasigroup with holes, blanked from a latin square.
+    Satisfiable,
+    /// Quasigroup completion, which may have no completion.
+    Unknown
+}
+
+/// A generated completion problem, with the seed that reproduces it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    pub kind: InstanceKind,
+    pub seed: u64,
+    pub clues: PartialLatinSquare
+}
+
+impl Instance {
+    /// A random square of the given order, from the seed, with the given fraction of its cells blanked.
+    /// Returns None unless the fraction is between 0 and 1, and for order 0.
+    ///
+    /// ```
+    /// use combinatorial_patterns::benchmark::Instance;
+    ///
+    /// let instance = Instance::with_holes(10, 0.42, 7).unwrap();
+    /// assert_eq!(instance.clues.filled_count(), 100 - 42);
+    /// assert!(instance.clues.complete().is_some());
+    /// assert_eq!(instance, Instance::with_holes(10, 0.42, 7).unwrap());
+    /// ```
+    pub fn with_holes(order: usize, holes: f64, seed: u64) -> Option<Instance> {
+        let mut cells = shuffled_cells(order, holes, seed)?;
+        let count = (holes * (order * order) as f64).round() as usize;
+        let mut clues = PartialLatinSquare::from_square(&batch::square(order, seed).ok()?);
+        for (row, column) in cells.drain(..count) {
+            clues.set(row, column, None);
+        }
+        Some(Instance { kind: InstanceKind::Satisfiable, seed, clues })
+    }
+
+    /// The given fraction of the cells of an empty square of the given order filled in a random order, each
+    /// with a random symbol not yet in its row or column, skipping cells with none left. Returns None unless
+    /// the fraction is between 0 and 1.
+    ///
+    /// ```
+    /// use combinatorial_patterns::benchmark::{Instance, InstanceKind};
+    ///
+    /// let instance = Instance::preassigned(10, 0.42, 7).unwrap();
+    /// assert_eq!(instance.kind, InstanceKind::Unknown);
+    /// assert!(instance.clues.is_consistent());
+    /// assert!(instance.clues.filled_count() <= 42);
+    /// ```
+    pub fn preassigned(order: usize, filled: f64, seed: u64) -> Option<Instance> {
+        let cells = shuffled_cells(order, filled, seed)?;
+        let count = (filled * (order * order) as f64).round() as usize;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(1));
+        let mut clues = PartialLatinSquare::new(order);
+        let mut in_row = vec![vec![false; order]; order];
+        let mut in_column = vec![vec![false; order]; order];
+        for (row, column) in cells.into_iter().take(count) {
+            let candidates: Vec<usize> = (0..order).filter(|&symbol| !in_row[row][symbol] && !in_column[column][symbol]).collect();
+            if candidates.is_empty() {
+                continue
+            }
+            let symbol = candidates[rng.gen_range(0..candidates.len())];
+            clues.set(row, column, Some(symbol));
+            in_row[row][symbol] = true;
+            in_column[column][symbol] = true;
+        }
+        Some(Instance { kind: InstanceKind::Unknown, seed, clues })
+    }
+
+    /// The instance as a line `order n` followed by one line per row, with -1 for a hole.
+    ///
+    /// ```
+    /// use combinatorial_patterns::benchmark::Instance;
+    ///
+    /// let text = Instance::with_holes(3, 1.0, 0).unwrap().to_text();
+    /// assert_eq!(text, "order 3\n-1 -1 -1\n-1 -1 -1\n-1 -1 -1\n");
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut text = format!("order {}\n", self.clues.order());
+        for row in self.clues.cells() {
+            let entries: Vec<String> = row.iter().map(|cell| match cell {
+                Some(symbol) => symbol.to_string(),
+                None => "-1".to_string()
+            }).collect();
+            text.push_str(&entries.join(" "));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The instance as DIMACS CNF. Variable (r * n + c) * n + s + 1 is true when cell (r, c) holds symbol s.
+    /// Every cell holds a symbol, and every symbol is in every row and every column, each exactly once, and
+    /// every clue is a unit clause.
+    ///
+    /// ```
+    /// use combinatorial_patterns::benchmark::Instance;
+    ///
+    /// let cnf = Instance::with_holes(2, 0.5, 3).unwrap().to_dimacs();
+    /// // 3 * 4 pairs with at least one, 3 * 4 with at most one, and 2 clues.
+    /// assert!(cnf.lines().any(|line| line == "p cnf 8 26"));
+    /// ```
+    pub fn to_dimacs(&self) -> String {
+        let order = self.clues.order();
+        let variable = |row: usize, column: usize, symbol: usize| (row * order + column) * order + symbol + 1;
+        let mut clauses: Vec<Vec<usize>> = Vec::new();
+        let mut negative: Vec<(usize, usize)> = Vec::new();
+        for first in 0..order {
+            for second in 0..order {
+                // The cell (first, second), symbol `second` in row `first`, and symbol `second` in column `first`.
+                let groups: [Vec<usize>; 3] = [
+                    (0..order).map(|symbol| variable(first, second, symbol)).collect(),
+                    (0..order).map(|column| variable(first, column, second)).collect(),
+                    (0..order).map(|row| variable(row, first, second)).collect()
+                ];
+                for group in groups {
+                    for (index, &a) in group.iter().enumerate() {
+                        for &b in &group[(index + 1)..] {
+                            negative.push((a, b));
+                        }
+                    }
+                    clauses.push(group);
+                }
+            }
+        }
+        let clues = self.clues.filled_cells();
+        let mut cnf = String::new();
+        let kind = match self.kind {
+            InstanceKind::Satisfiable => "quasigroup with holes",
+            InstanceKind::Unknown => "quasigroup completion"
+        };
+        let _ = writeln!(cnf, "c {} of order {}, seed {}", kind, order, self.seed);
+        let _ = writeln!(cnf, "p cnf {} {}", order * order * order, clauses.len() + negative.len() + clues.len());
+        for clause in clauses {
+            let literals: Vec<String> = clause.iter().map(|literal| literal.to_string()).collect();
+            let _ = writeln!(cnf, "{} 0", literals.join(" "));
+        }
+        for (a, b) in negative {
+            let _ = writeln!(cnf, "-{} -{} 0", a, b);
+        }
+        for (row, column, symbol) in clues {
+            let _ = writeln!(cnf, "{} 0", variable(row, column, symbol));
+        }
+        cnf
+    }
+}
+
+/// The cells of a square of the given order in a random order from the seed, or None unless the fraction
+/// is between 0 and 1.
+fn shuffled_cells(order: usize, fraction: f64, seed: u64) -> Option<Vec<(usize, usize)>> {
+    if !(0.0..=1.0).contains(&fraction) {
+        return None
+    }
+    let mut cells: Vec<(usize, usize)> = (0..order).flat_map(|row| (0..order).map(move |column| (row, column))).collect();
+    cells.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    Some(cells)
+}
+
+/// A set of instances of one kind, order and fraction, for the consecutive seeds from `first_seed`: the
+/// fraction is of holes for satisfiable instances and of filled cells for unknown ones. Returns None unless
+/// the fraction is between 0 and 1.
+///
+/// ```
+/// use combinatorial_patterns::benchmark::{self, InstanceKind};
+///
+/// let set = benchmark::instances(InstanceKind::Satisfiable, 8, 0.5, 100, 4).unwrap();
+/// assert_eq!(set.len(), 4);
+/// assert_eq!(set[3].seed, 103);
+/// ```
+pub fn instances(kind: InstanceKind, order: usize, fraction: f64, first_seed: u64, count: u64) -> Option<Vec<Instance>> {
+    (0..count).map(|index| {
+        let seed = batch::seed_of(first_seed, index);
+        match kind {
+            InstanceKind::Satisfiable => Instance::with_holes(order, fraction, seed),
+            InstanceKind::Unknown => Instance::preassigned(order, fraction, seed)
+        }
+    }).collect()
+}
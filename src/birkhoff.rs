@@ -0,0 +1,159 @@
+//! Birkhoff-von Neumann decompositions: writing a doubly stochastic matrix as a convex combination of
+//! permutation matrices, or a nonnegative integer matrix with equal row and column sums as a sum of them.
+//!
+//! The positive entries of such a matrix always contain a perfect matching between rows and columns,
+//! by Hall's theorem. Taking one, with the smallest entry on it as its weight, and subtracting, leaves
+//! a matrix of the same kind with at least one more zero, so at most n^2 - 2n + 2 permutations are needed.
+//!
+//! Latin squares are the 0/1 case with all sums n: the all ones matrix is the sum of the n permutation
+//! matrices of the symbols of any latin square of that order, see `symbol_permutations`.
+//!
+//! Sources:
+//!
+//! - [Doubly stochastic matrix, Wikipedia](https://en.wikipedia.org/wiki/Doubly_stochastic_matrix)
+
+use crate::latin_square::LatinSquare;
+use crate::matching::BipartiteGraph;
+
+/// A permutation as `permutation[row] = column`.
+pub type Permutation = Vec<usize>;
+
+/// Decomposes a nonnegative integer matrix whose rows and columns all have the same sum into permutation
+/// matrices with multiplicities. Returns None if the matrix is not square or its sums are not all equal.
+///
+/// ```
+/// use combinatorial_patterns::birkhoff;
+///
+/// let matrix = vec![vec![2, 1, 0], vec![0, 2, 1], vec![1, 0, 2]];
+/// let terms = birkhoff::decompose_integer(&matrix).unwrap();
+/// assert_eq!(terms.iter().map(|(count, _)| count).sum::<usize>(), 3);
+/// assert_eq!(birkhoff::compose_integer(&terms, 3), matrix);
+/// ```
+pub fn decompose_integer(matrix: &[Vec<usize>]) -> Option<Vec<(usize, Permutation)>> {
+    let order = matrix.len();
+    if matrix.iter().any(|row| row.len() != order) {
+        return None
+    }
+    let sum = matrix.first().map(|row| row.iter().sum::<usize>()).unwrap_or(0);
+    let balanced = matrix.iter().all(|row| row.iter().sum::<usize>() == sum)
+        && (0..order).all(|column| matrix.iter().map(|row| row[column]).sum::<usize>() == sum);
+    if !balanced {
+        return None
+    }
+
+    let mut remaining = matrix.to_vec();
+    let mut terms = Vec::new();
+    while remaining.iter().flatten().any(|&entry| entry > 0) {
+        let permutation = positive_matching(order, |row, column| remaining[row][column] > 0)
+            .expect("ProgrammingError: balanced matrices have a perfect matching on their positive entries.");
+        let count = permutation.iter().enumerate().map(|(row, &column)| remaining[row][column]).min().unwrap_or(0);
+        for (row, &column) in permutation.iter().enumerate() {
+            remaining[row][column] -= count;
+        }
+        terms.push((count, permutation));
+    }
+    Some(terms)
+}
+
+/// Decomposes a doubly stochastic matrix into a convex combination of permutation matrices, as weights
+/// with their permutations. Entries up to `tolerance` are treated as zero.
+///
+/// Returns None if the matrix is not square, has an entry below `-tolerance`, or has a row or column whose
+/// sum is further than `tolerance` times the order from 1.
+///
+/// ```
+/// use combinatorial_patterns::birkhoff;
+///
+/// let matrix = vec![vec![0.5, 0.5, 0.0], vec![0.25, 0.25, 0.5], vec![0.25, 0.25, 0.5]];
+/// let terms = birkhoff::decompose(&matrix, 1e-9).unwrap();
+/// assert!((terms.iter().map(|(weight, _)| weight).sum::<f64>() - 1.0).abs() < 1e-9);
+///
+/// let composed = birkhoff::compose(&terms, 3);
+/// assert!(composed.iter().flatten().zip(matrix.iter().flatten()).all(|(a, b)| (a - b).abs() < 1e-9));
+/// ```
+pub fn decompose(matrix: &[Vec<f64>], tolerance: f64) -> Option<Vec<(f64, Permutation)>> {
+    let order = matrix.len();
+    if matrix.iter().any(|row| row.len() != order) || matrix.iter().flatten().any(|&entry| entry < -tolerance) {
+        return None
+    }
+    let slack = tolerance * order as f64;
+    let stochastic = matrix.iter().all(|row| (row.iter().sum::<f64>() - 1.0).abs() <= slack)
+        && (0..order).all(|column| (matrix.iter().map(|row| row[column]).sum::<f64>() - 1.0).abs() <= slack);
+    if !stochastic {
+        return None
+    }
+
+    let mut remaining = matrix.to_vec();
+    let mut terms = Vec::new();
+    // Rounding can leave a tiny remainder without a perfect matching, which ends the decomposition.
+    while let Some(permutation) = positive_matching(order, |row, column| remaining[row][column] > tolerance) {
+        let weight = permutation.iter().enumerate()
+            .map(|(row, &column)| remaining[row][column])
+            .fold(f64::INFINITY, f64::min);
+        if order == 0 || weight <= tolerance {
+            break
+        }
+        for (row, &column) in permutation.iter().enumerate() {
+            remaining[row][column] -= weight;
+        }
+        terms.push((weight, permutation));
+    }
+    Some(terms)
+}
+
+/// Adds up permutation matrices with multiplicities, inverting `decompose_integer`.
+pub fn compose_integer(terms: &[(usize, Permutation)], order: usize) -> Vec<Vec<usize>> {
+    let mut matrix = vec![vec![0; order]; order];
+    for (count, permutation) in terms {
+        for (row, &column) in permutation.iter().enumerate() {
+            matrix[row][column] += count;
+        }
+    }
+    matrix
+}
+
+/// Adds up weighted permutation matrices, inverting `decompose`.
+pub fn compose(terms: &[(f64, Permutation)], order: usize) -> Vec<Vec<f64>> {
+    let mut matrix = vec![vec![0.0; order]; order];
+    for (weight, permutation) in terms {
+        for (row, &column) in permutation.iter().enumerate() {
+            matrix[row][column] += weight;
+        }
+    }
+    matrix
+}
+
+/// The permutation of each symbol of a latin square: `permutations[symbol][row]` is the column where the
+/// row holds the symbol. These are n disjoint permutation matrices adding up to the all ones matrix.
+///
+/// ```
+/// use combinatorial_patterns::{birkhoff, IncidenceCube};
+///
+/// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+/// let permutations = birkhoff::symbol_permutations(&square);
+/// assert_eq!(permutations[1], vec![1, 0, 2]);
+/// ```
+pub fn symbol_permutations(square: &LatinSquare) -> Vec<Permutation> {
+    let order = square.size();
+    let mut permutations = vec![vec![0; order]; order];
+    for (row, symbols) in square.square.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            permutations[symbol][row] = column;
+        }
+    }
+    permutations
+}
+
+/// A perfect matching of rows to columns using only cells where `allowed` holds.
+fn positive_matching(order: usize, allowed: impl Fn(usize, usize) -> bool) -> Option<Permutation> {
+    let mut graph = BipartiteGraph::new(order, order);
+    for row in 0..order {
+        for column in 0..order {
+            if allowed(row, column) {
+                graph.add_edge(row, column);
+            }
+        }
+    }
+    let matching = graph.perfect_matching()?;
+    Some((0..order).map(|row| matching.right_of(row).expect("ProgrammingError: perfect matchings cover every row.")).collect())
+}
@@ -0,0 +1,146 @@
+//! A fixed-capacity set of small integers, stored as bits.
+//!
+//! Searches over latin squares spend most of their time asking which symbols are still free in a row
+//! or column, which is a few word-wide bit operations with this representation.
+
+/// A set of integers in 0..capacity.
+///
+/// ```
+/// use combinatorial_patterns::bitset::BitSet;
+///
+/// let mut set = BitSet::new(100);
+/// set.insert(3);
+/// set.insert(70);
+/// assert!(set.contains(70));
+/// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![3, 70]);
+///
+/// let free = BitSet::full(100).difference(&set);
+/// assert_eq!(free.count(), 98);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    capacity: usize,
+    words: Vec<u64>
+}
+
+impl BitSet {
+    /// Creates an empty set that can hold 0..capacity.
+    pub fn new(capacity: usize) -> BitSet {
+        BitSet {
+            capacity,
+            words: vec![0; capacity.div_ceil(64)]
+        }
+    }
+
+    /// Creates the set of every integer in 0..capacity.
+    pub fn full(capacity: usize) -> BitSet {
+        let mut set = BitSet::new(capacity);
+        for value in 0..capacity {
+            set.insert(value);
+        }
+        set
+    }
+
+    /// Creates a set from the given values. Panics if any value is outside 0..capacity.
+    pub fn from_values(capacity: usize, values: &[usize]) -> BitSet {
+        let mut set = BitSet::new(capacity);
+        for &value in values {
+            set.insert(value);
+        }
+        set
+    }
+
+    /// The exclusive upper bound of the values the set can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds `value` to the set. Panics if it is outside 0..capacity.
+    pub fn insert(&mut self, value: usize) {
+        if value >= self.capacity {
+            panic!("Value {} does not fit in a bit set of capacity {}", value, self.capacity);
+        }
+        self.words[value / 64] |= 1 << (value % 64);
+    }
+
+    /// Removes `value` from the set, if present.
+    pub fn remove(&mut self, value: usize) {
+        if value < self.capacity {
+            self.words[value / 64] &= !(1 << (value % 64));
+        }
+    }
+
+    /// Returns true if `value` is in the set.
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity && self.words[value / 64] & (1 << (value % 64)) != 0
+    }
+
+    /// The number of values in the set.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns true if the set has no values.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// The smallest value in the set.
+    pub fn first(&self) -> Option<usize> {
+        self.words.iter().enumerate()
+            .find(|(_, &word)| word != 0)
+            .map(|(index, word)| index * 64 + word.trailing_zeros() as usize)
+    }
+
+    /// The values of the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(index * 64 + bit)
+            })
+        })
+    }
+
+    /// Adds every value of `other` to this set.
+    pub fn union_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= other;
+        }
+    }
+
+    /// Removes every value not in `other` from this set.
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (index, word) in self.words.iter_mut().enumerate() {
+            *word &= other.words.get(index).copied().unwrap_or(0);
+        }
+    }
+
+    /// Returns the values of this set that are not in `other`.
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        for (word, other) in result.words.iter_mut().zip(other.words.iter()) {
+            *word &= !other;
+        }
+        result
+    }
+
+    /// Returns the values in either set. The result has the larger of the two capacities.
+    pub fn union(&self, other: &BitSet) -> BitSet {
+        let (mut result, smaller) = if self.capacity >= other.capacity { (self.clone(), other) } else { (other.clone(), self) };
+        result.union_with(smaller);
+        result
+    }
+
+    /// Returns the values in both sets.
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+}
@@ -0,0 +1,188 @@
+//! Canonical forms of latin squares under isotopy and paratopy, for recognising squares up to equivalence.
+//!
+//! Two squares are isotopic when one becomes the other by permuting its rows, its columns, and its symbols,
+//! and paratopic, i.e. in the same main class, when that is possible after also taking a conjugate. Every
+//! square has one canonical form for each equivalence, shared by exactly the squares equivalent to it, so
+//! comparing forms, or their fingerprints in a hash set, decides equivalence.
+//!
+//! The form is built as `orderly` checks canonicity. Setting a row to 0..n-1 by renaming the symbols makes
+//! every other row a permutation of the columns, and reordering the columns conjugates it, so its cycle type
+//! is an invariant of the pair of rows. The cycle type is picked whose pairs have the fewest column orders
+//! between them, those that take their permutation to its smallest conjugate, which are as many as the
+//! centralizer of the permutation. The form is the smallest reduced square, reading row by row, with such a
+//! pair as its first two rows, found by trying those column orders rather than all n!. For paratopy the same
+//! runs over the six conjugates together.
+//!
+//! The form is reduced, but generally not the smallest reduced square of the class that `species` and
+//! `orderly` take as canonical, which would need the cycle type with the smallest conjugate instead, often
+//! one with many short cycles and a large centralizer. Random squares of order 100 take a moment. Tables of
+//! groups with many automorphisms, such as the elementary abelian groups, whose row permutations all have
+//! one cycle type with a large centralizer, are slow from order 16 on.
+//!
+//! Sources:
+//!
+//! - [Small latin squares, quasigroups, and loops, Brendan D. McKay, Alison Meynert, Wendy Myrvold](https://doi.org/10.1002/jcd.20105)
+//! - Classification algorithms for codes and designs, P. Kaski, P. R. J. Östergård (2006)
+
+use std::slice;
+
+use crate::latin_square::{conjugate_rows, ConjugateKind, LatinSquare, Symbol};
+use crate::orderly::{conjugators, inverse, smallest_conjugate};
+use crate::polya::Permutation;
+
+/// Which relabelings of a square count as giving the same square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Equivalence {
+    /// Permuting rows, columns, and symbols.
+    Isotopy,
+    /// Permuting rows, columns, and symbols, and the roles of the three.
+    Paratopy
+}
+
+/// The canonical form of a square as a hashable value: equal for two squares exactly when they have the
+/// same order and are equivalent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint {
+    order: usize,
+    equivalence: Equivalence,
+    cells: Vec<Symbol>
+}
+
+/// The canonical form of the square under the equivalence.
+///
+/// ```
+/// use combinatorial_patterns::{canonical, IncidenceCube, LatinSquare};
+/// use combinatorial_patterns::canonical::Equivalence;
+///
+/// let mut cube = IncidenceCube::new_cyclic(6).unwrap();
+/// cube.shuffle(&mut rand::thread_rng());
+/// let square = cube.as_latin_square();
+/// let form = canonical::canonical_form(&square, Equivalence::Isotopy);
+/// assert!(form.is_reduced());
+///
+/// // Moving rows and renaming symbols give the same form.
+/// let mut isotope = square.square.clone();
+/// isotope.rotate_left(3);
+/// let isotope: Vec<Vec<usize>> = isotope.iter().map(|row| row.iter().map(|&symbol| (symbol + 2) % 6).collect()).collect();
+/// let isotope = LatinSquare::try_from(isotope).unwrap();
+/// assert_eq!(canonical::canonical_form(&isotope, Equivalence::Isotopy).square, form.square);
+/// ```
+///
+/// # Panics
+/// If the square is not latin, see `LatinSquare::validate`.
+pub fn canonical_form(square: &LatinSquare, equivalence: Equivalence) -> LatinSquare {
+    LatinSquare::from_rows(canonical_rows(square, equivalence))
+}
+
+/// The fingerprint of the square under the equivalence, for deduplicating squares with a hash set.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use combinatorial_patterns::{canonical, LatinSquare};
+/// use combinatorial_patterns::canonical::Equivalence;
+///
+/// // Squares of order 6 fall into 22 isotopy classes and 12 main classes.
+/// let mut rng = rand::thread_rng();
+/// let squares: Vec<LatinSquare> = (0..100).map(|_| LatinSquare::new_random(6, &mut rng).unwrap()).collect();
+/// let isotopy: HashSet<_> = squares.iter().map(|square| canonical::fingerprint(square, Equivalence::Isotopy)).collect();
+/// let paratopy: HashSet<_> = squares.iter().map(|square| canonical::fingerprint(square, Equivalence::Paratopy)).collect();
+/// assert!(paratopy.len() <= isotopy.len() && isotopy.len() <= 22);
+///
+/// // A square and its conjugates are always paratopic.
+/// let square = &squares[0];
+/// assert_eq!(
+///     canonical::fingerprint(square, Equivalence::Paratopy),
+///     canonical::fingerprint(&square.transpose(), Equivalence::Paratopy)
+/// );
+/// ```
+pub fn fingerprint(square: &LatinSquare, equivalence: Equivalence) -> Fingerprint {
+    Fingerprint { order: square.size(), equivalence, cells: canonical_rows(square, equivalence).concat() }
+}
+
+/// Returns true if the squares are equivalent.
+pub fn is_equivalent(first: &LatinSquare, second: &LatinSquare, equivalence: Equivalence) -> bool {
+    fingerprint(first, equivalence) == fingerprint(second, equivalence)
+}
+
+fn canonical_rows(square: &LatinSquare, equivalence: Equivalence) -> Vec<Vec<Symbol>> {
+    match equivalence {
+        Equivalence::Isotopy => smallest_isotope(slice::from_ref(&square.square)),
+        Equivalence::Paratopy => {
+            let conjugates: Vec<Vec<Vec<Symbol>>> = ConjugateKind::ALL.iter().map(|&kind| conjugate_rows(&square.square, kind)).collect();
+            smallest_isotope(&conjugates)
+        }
+    }
+}
+
+/// The smallest reduced square isotopic to any of the squares, which all have the same order.
+fn smallest_isotope(squares: &[Vec<Vec<Symbol>>]) -> Vec<Vec<Symbol>> {
+    // For every square and every row set to 0..n-1, all rows as permutations of the columns.
+    let permutations: Vec<Vec<Permutation>> = squares.iter().flat_map(|rows| {
+        rows.iter().map(|first| {
+            let columns = inverse(first);
+            rows.iter().map(|row| row.iter().map(|&symbol| columns[symbol]).collect()).collect()
+        })
+    }).collect();
+    // Every other row with the rows it belongs to, and its smallest conjugate.
+    let mut pairs = Vec::new();
+    for rows in &permutations {
+        for permutation in rows {
+            if permutation.iter().enumerate().any(|(column, &image)| column != image) {
+                pairs.push((rows, permutation, smallest_conjugate(permutation)));
+            }
+        }
+    }
+    // The cycle type whose pairs have the fewest column orders between them, the smallest conjugate on ties.
+    let mut types: Vec<(&Permutation, u128)> = Vec::new();
+    for (_, _, smallest) in &pairs {
+        match types.iter_mut().find(|(existing, _)| *existing == smallest) {
+            Some((_, cost)) => *cost = cost.saturating_add(centralizer_order(smallest)),
+            None => types.push((smallest, centralizer_order(smallest)))
+        }
+    }
+    let Some(&(target, _)) = types.iter().min_by(|(first, first_cost), (second, second_cost)| {
+        first_cost.cmp(second_cost).then_with(|| first.cmp(second))
+    }) else {
+        // A square of order below 2 is the only one of its order.
+        return squares.first().cloned().unwrap_or_default()
+    };
+
+    let mut best: Option<Vec<Vec<Symbol>>> = None;
+    for (rows, permutation, _) in pairs.iter().filter(|(_, _, smallest)| smallest == target) {
+        for order_of_columns in conjugators(permutation, target) {
+            let position = inverse(&order_of_columns);
+            let mut image: Vec<Vec<Symbol>> = rows.iter()
+                .map(|permutation| order_of_columns.iter().map(|&old| position[permutation[old]]).collect())
+                .collect();
+            image.sort_unstable();
+            if best.as_ref().is_none_or(|smallest| image < *smallest) {
+                best = Some(image);
+            }
+        }
+    }
+    best.expect("ProgrammingError: a permutation is conjugate to its smallest conjugate.")
+}
+
+/// The number of permutations commuting with the given one, which has m_k cycles of each length k: the
+/// product of k^(m_k) m_k!, saturating.
+fn centralizer_order(permutation: &[usize]) -> u128 {
+    let mut lengths = vec![0_u32; permutation.len() + 1];
+    let mut seen = vec![false; permutation.len()];
+    for start in 0..permutation.len() {
+        let mut length = 0;
+        let mut point = start;
+        while !seen[point] {
+            seen[point] = true;
+            point = permutation[point];
+            length += 1;
+        }
+        lengths[length] += 1;
+    }
+    let mut order: u128 = 1;
+    for (length, &cycles) in lengths.iter().enumerate().skip(1) {
+        for count in 1..=cycles {
+            order = order.saturating_mul(length as u128).saturating_mul(count as u128);
+        }
+    }
+    order
+}
@@ -0,0 +1,165 @@
+//! Constant-weight binary codes from block designs.
+//!
+//! A binary code of length n and weight w is a set of codewords, n-bit words with exactly w ones. Such a
+//! codeword is the same as a w-subset of the positions 0..n, its support, so the blocks of a design on n
+//! points are the codewords of a constant-weight code. The Hamming distance between two codewords is
+//! 2(w - s), where s is the number of positions they share, and so a design whose blocks meet in few points
+//! gives a code with a large minimum distance:
+//!
+//! - A packing D(n, k, t) has any two blocks sharing at most t - 1 points, so the distance is at least
+//!   2(k - t + 1). Its Johnson bound is the Johnson bound on A(n, 2(k - t + 1), k), the most codewords a
+//!   code can have.
+//! - A Steiner triple system on n points is a packing D(n, 3, 2) meeting the bound, and gives an optimal
+//!   code with distance 4.
+//!
+//! A code with minimum distance d corrects (d - 1) / 2 errors: `ConstantWeightCode::decode` finds the
+//! codeword within that distance of a received word, if there is one, by comparing it with every codeword.
+//!
+//! Sources:
+//!
+//! - A new upper bound for error-correcting codes, S. M. Johnson, IRE Transactions on Information Theory 8 (1962)
+//! - A new table of constant weight codes, A. E. Brouwer, J. B. Shearer, N. J. A. Sloane, W. D. Smith, IEEE Transactions on Information Theory 36 (1990)
+//! - The theory of error-correcting codes, F. J. MacWilliams, N. J. A. Sloane, chapter 17
+
+use crate::covering::PackingDesign;
+use crate::triple_system::SteinerTripleSystem;
+
+/// A binary code whose codewords all have the same weight, stored by their supports.
+///
+/// ```
+/// use combinatorial_patterns::constant_weight::ConstantWeightCode;
+/// use combinatorial_patterns::triple_system::SteinerTripleSystem;
+///
+/// // The Fano plane: 7 codewords of length 7 and weight 3, any two at distance 4.
+/// let code = ConstantWeightCode::from_steiner_triple_system(&SteinerTripleSystem::new(7).unwrap());
+/// assert_eq!((code.length(), code.weight(), code.size()), (7, 3, 7));
+/// assert_eq!(code.minimum_distance(), Some(4));
+/// assert_eq!(code.correctable_errors(), 1);
+///
+/// // One flipped bit is corrected.
+/// let mut received = code.codeword(2).unwrap();
+/// received[5] = !received[5];
+/// assert_eq!(code.decode(&received), Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantWeightCode {
+    length: usize,
+    weight: usize,
+    supports: Vec<Vec<usize>>
+}
+
+impl ConstantWeightCode {
+    /// Creates a code from the supports of its codewords, sorting each. Returns None unless every support
+    /// has `weight` distinct positions below `length`, and no two supports are the same.
+    ///
+    /// ```
+    /// use combinatorial_patterns::constant_weight::ConstantWeightCode;
+    ///
+    /// let code = ConstantWeightCode::new(4, 2, vec![vec![0, 1], vec![3, 2]]).unwrap();
+    /// assert_eq!(code.codeword(1), Some(vec![false, false, true, true]));
+    /// assert!(ConstantWeightCode::new(4, 2, vec![vec![0, 1], vec![1, 0]]).is_none());
+    /// assert!(ConstantWeightCode::new(4, 2, vec![vec![0, 4]]).is_none());
+    /// ```
+    pub fn new(length: usize, weight: usize, supports: Vec<Vec<usize>>) -> Option<ConstantWeightCode> {
+        let mut sorted = Vec::with_capacity(supports.len());
+        for mut support in supports {
+            support.sort_unstable();
+            support.dedup();
+            if support.len() != weight || support.iter().any(|&position| position >= length) {
+                return None
+            }
+            sorted.push(support);
+        }
+        let mut distinct = sorted.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() != sorted.len() {
+            return None
+        }
+        Some(ConstantWeightCode { length, weight, supports: sorted })
+    }
+
+    /// The code of the blocks of a Steiner triple system on n points: length n, weight 3, and distance 4.
+    pub fn from_steiner_triple_system(system: &SteinerTripleSystem) -> ConstantWeightCode {
+        ConstantWeightCode::new(system.order(), 3, system.blocks().iter().map(|block| block.to_vec()).collect())
+            .expect("ProgrammingError: the blocks of a Steiner triple system are distinct triples.")
+    }
+
+    /// The code of the blocks of a packing D(n, k, t): length n, weight k, and distance at least
+    /// 2(k - t + 1).
+    ///
+    /// ```
+    /// use combinatorial_patterns::constant_weight::ConstantWeightCode;
+    /// use combinatorial_patterns::covering::PackingDesign;
+    ///
+    /// let packing = PackingDesign::best(8, 4, 3, 10_000, &mut rand::thread_rng()).unwrap();
+    /// let code = ConstantWeightCode::from_packing(&packing);
+    /// assert!(code.minimum_distance().unwrap() >= 4);
+    /// assert_eq!(code.size(), packing.blocks().len());
+    /// ```
+    pub fn from_packing(packing: &PackingDesign) -> ConstantWeightCode {
+        ConstantWeightCode::new(packing.points(), packing.block_size(), packing.blocks().to_vec())
+            .expect("ProgrammingError: the blocks of a packing are distinct k-subsets.")
+    }
+
+    /// The length n.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The weight w of every codeword.
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// The number of codewords.
+    pub fn size(&self) -> usize {
+        self.supports.len()
+    }
+
+    /// The supports of the codewords, each sorted.
+    pub fn supports(&self) -> &[Vec<usize>] {
+        &self.supports
+    }
+
+    /// The codeword at `index` as n bits, or None if there is no such codeword.
+    pub fn codeword(&self, index: usize) -> Option<Vec<bool>> {
+        let mut word = vec![false; self.length];
+        for &position in self.supports.get(index)? {
+            word[position] = true;
+        }
+        Some(word)
+    }
+
+    /// The smallest Hamming distance between two codewords, or None if there are fewer than two.
+    pub fn minimum_distance(&self) -> Option<usize> {
+        let mut shared = None;
+        for (index, first) in self.supports.iter().enumerate() {
+            for second in &self.supports[index + 1..] {
+                let common = first.iter().filter(|position| second.binary_search(position).is_ok()).count();
+                shared = Some(shared.map_or(common, |most: usize| most.max(common)));
+            }
+        }
+        shared.map(|shared| 2 * (self.weight - shared))
+    }
+
+    /// The number of flipped bits the code corrects, (d - 1) / 2 for minimum distance d, or n if there are
+    /// fewer than two codewords.
+    pub fn correctable_errors(&self) -> usize {
+        self.minimum_distance().map_or(self.length, |distance| (distance - 1) / 2)
+    }
+
+    /// The index of the codeword within `correctable_errors` of the received word, or None if there is
+    /// none, or the word does not have n bits.
+    pub fn decode(&self, received: &[bool]) -> Option<usize> {
+        if received.len() != self.length {
+            return None
+        }
+        let ones = received.iter().filter(|&&bit| bit).count();
+        let radius = self.correctable_errors();
+        self.supports.iter().position(|support| {
+            let common = support.iter().filter(|&&position| received[position]).count();
+            ones + self.weight - 2 * common <= radius
+        })
+    }
+}
@@ -0,0 +1,397 @@
+//! Latin squares with structure, described declaratively and generated by one front-end.
+//!
+//! `Constraints` collects the structure a square should have: fixed cells, forbidden (row, column, symbol)
+//! triples, distinct symbols on both main diagonals, symmetry about the main diagonal, idempotence (symbol
+//! i at (i, i)), and regions of cells that hold distinct symbols. `generate` compiles them to a method:
+//!
+//! - Without constraints, the Jacobson-Matthews chain of `LatinSquare::new_random`, which is close to uniform.
+//! - Otherwise a backtracking search on the `enumerate` engine, which fills the cell with the fewest
+//!   candidates first, trying its symbols in random order. Symmetry is built in by filling a cell and its
+//!   mirror together, idempotence and fixed cells narrow the candidates of their cells, and the diagonals
+//!   and regions are units like the rows and columns. The squares found are random but not uniform.
+//!
+//! The same search, in a fixed order, gives `solutions` and `count_solutions`, and `is_satisfied_by` checks
+//! a square directly.
+//!
+//! Sources:
+//!
+//! - Latin squares and their applications, J. Dénes, A. D. Keedwell
+//! - [Solving every Sudoku puzzle, P. Norvig](https://norvig.com/sudoku.html)
+
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::bitset::BitSet;
+use crate::enumerate::{self, Control, Node, Search};
+use crate::latin_square::{LatinSquare, Symbol};
+
+/// How `Constraints::generate` makes a square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// The Jacobson-Matthews chain, for squares without constraints.
+    Uniform,
+    /// Randomized backtracking search.
+    Search
+}
+
+/// The structure asked of a latin square of some order.
+///
+/// ```
+/// use combinatorial_patterns::constraints::{Constraints, Method};
+///
+/// let constraints = Constraints::new(5).with_symmetry().with_idempotence();
+/// assert_eq!(constraints.method(), Method::Search);
+/// let square = constraints.generate(&mut rand::thread_rng()).unwrap();
+/// assert!(constraints.is_satisfied_by(&square));
+/// assert!((0..5).all(|i| square.square[i][i] == i));
+///
+/// // Symmetric idempotent squares exist only for odd orders.
+/// assert!(Constraints::new(4).with_symmetry().with_idempotence().generate(&mut rand::thread_rng()).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    order: usize,
+    fixed: Vec<(usize, usize, Symbol)>,
+    forbidden: Vec<(usize, usize, Symbol)>,
+    diagonals: bool,
+    symmetric: bool,
+    idempotent: bool,
+    regions: Vec<Vec<(usize, usize)>>
+}
+
+impl Constraints {
+    /// No constraints beyond being a latin square of the given order.
+    pub fn new(order: usize) -> Constraints {
+        Constraints {
+            order,
+            fixed: Vec::new(),
+            forbidden: Vec::new(),
+            diagonals: false,
+            symmetric: false,
+            idempotent: false,
+            regions: Vec::new()
+        }
+    }
+
+    /// The same constraints with the given (row, column, symbol) cells fixed. Returns None if one is outside
+    /// the square.
+    ///
+    /// ```
+    /// use combinatorial_patterns::constraints::Constraints;
+    ///
+    /// let constraints = Constraints::new(4).with_fixed(&[(0, 0, 3), (1, 2, 3)]).unwrap();
+    /// let square = constraints.generate(&mut rand::thread_rng()).unwrap();
+    /// assert_eq!((square.square[0][0], square.square[1][2]), (3, 3));
+    ///
+    /// // The same symbol twice in a row leaves no square.
+    /// let constraints = Constraints::new(4).with_fixed(&[(0, 0, 3), (0, 2, 3)]).unwrap();
+    /// assert_eq!(constraints.count_solutions(1), 0);
+    /// assert!(Constraints::new(4).with_fixed(&[(0, 0, 4)]).is_none());
+    /// ```
+    pub fn with_fixed(self, cells: &[(usize, usize, Symbol)]) -> Option<Constraints> {
+        if !self.all_inside(cells) {
+            return None
+        }
+        let mut constraints = self;
+        constraints.fixed.extend_from_slice(cells);
+        Some(constraints)
+    }
+
+    /// The same constraints with the given (row, column, symbol) triples forbidden. Returns None if one is
+    /// outside the square.
+    ///
+    /// ```
+    /// use combinatorial_patterns::constraints::Constraints;
+    ///
+    /// // Avoid the symbols on the diagonal of the cyclic square.
+    /// let forbidden: Vec<(usize, usize, usize)> = (0..6).map(|i| (i, i, (2 * i) % 6)).collect();
+    /// let constraints = Constraints::new(6).with_forbidden(&forbidden).unwrap();
+    /// let square = constraints.generate(&mut rand::thread_rng()).unwrap();
+    /// assert!((0..6).all(|i| square.square[i][i] != (2 * i) % 6));
+    /// ```
+    pub fn with_forbidden(self, cells: &[(usize, usize, Symbol)]) -> Option<Constraints> {
+        if !self.all_inside(cells) {
+            return None
+        }
+        let mut constraints = self;
+        constraints.forbidden.extend_from_slice(cells);
+        Some(constraints)
+    }
+
+    /// The same constraints with distinct symbols on both main diagonals, a diagonal latin square.
+    pub fn with_diagonals(self) -> Constraints {
+        Constraints { diagonals: true, ..self }
+    }
+
+    /// The same constraints with the square equal to its transpose.
+    pub fn with_symmetry(self) -> Constraints {
+        Constraints { symmetric: true, ..self }
+    }
+
+    /// The same constraints with symbol i at (i, i) for every i.
+    pub fn with_idempotence(self) -> Constraints {
+        Constraints { idempotent: true, ..self }
+    }
+
+    /// The same constraints with distinct symbols in the given (row, column) cells. Returns None if a cell is
+    /// outside the square or repeated, or there are more cells than symbols.
+    ///
+    /// ```
+    /// use combinatorial_patterns::constraints::Constraints;
+    ///
+    /// // The four 2 x 2 boxes of a Sudoku of order 4.
+    /// let mut constraints = Constraints::new(4);
+    /// for (top, left) in [(0, 0), (0, 2), (2, 0), (2, 2)] {
+    ///     let cells = vec![(top, left), (top, left + 1), (top + 1, left), (top + 1, left + 1)];
+    ///     constraints = constraints.with_region(cells).unwrap();
+    /// }
+    /// assert_eq!(constraints.count_solutions(usize::MAX), 288);
+    /// ```
+    pub fn with_region(self, cells: Vec<(usize, usize)>) -> Option<Constraints> {
+        let order = self.order;
+        let mut seen = vec![vec![false; order]; order];
+        if cells.len() > order {
+            return None
+        }
+        for &(row, column) in &cells {
+            if row >= order || column >= order || std::mem::replace(&mut seen[row][column], true) {
+                return None
+            }
+        }
+        let mut constraints = self;
+        constraints.regions.push(cells);
+        Some(constraints)
+    }
+
+    /// The order of the square.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The method `generate` uses.
+    pub fn method(&self) -> Method {
+        if *self == Constraints::new(self.order) {
+            Method::Uniform
+        } else {
+            Method::Search
+        }
+    }
+
+    /// A random square meeting the constraints, by `method` with `rng`, or None if there is none.
+    pub fn generate(&self, rng: &mut impl Rng) -> Option<LatinSquare> {
+        match self.method() {
+            Method::Uniform => LatinSquare::new_random(self.order, rng).ok(),
+            Method::Search => self.search(Some(rng), 1).pop()
+        }
+    }
+
+    /// Up to `limit` squares meeting the constraints, in the order of the search.
+    pub fn solutions(&self, limit: usize) -> Vec<LatinSquare> {
+        self.search(None::<ThreadRng>, limit)
+    }
+
+    /// Counts the squares meeting the constraints, stopping once `cap` have been found.
+    pub fn count_solutions(&self, cap: usize) -> usize {
+        let Some(mut search) = self.compile(None::<ThreadRng>) else {
+            return 0
+        };
+        let mut count = 0;
+        if cap > 0 {
+            enumerate::run(&mut search, u64::MAX, |_| {
+                count += 1;
+                if count < cap { Control::Continue } else { Control::Stop }
+            });
+        }
+        count
+    }
+
+    /// Returns true if the square is a latin square of the order meeting every constraint.
+    pub fn is_satisfied_by(&self, square: &LatinSquare) -> bool {
+        let n = self.order;
+        if square.size() != n || square.square.iter().any(|row| row.len() != n || row.iter().any(|&symbol| symbol >= n)) {
+            return false
+        }
+        let at = |cell: usize| square.square[cell / n][cell % n];
+        self.units().iter().all(|unit| {
+            let mut seen = BitSet::new(n);
+            unit.iter().all(|&cell| {
+                let fresh = !seen.contains(at(cell));
+                seen.insert(at(cell));
+                fresh
+            })
+        })
+            && self.fixed.iter().all(|&(row, column, symbol)| square.square[row][column] == symbol)
+            && self.forbidden.iter().all(|&(row, column, symbol)| square.square[row][column] != symbol)
+            && (!self.symmetric || (0..n).all(|row| (0..n).all(|column| square.square[row][column] == square.square[column][row])))
+            && (!self.idempotent || (0..n).all(|index| square.square[index][index] == index))
+    }
+
+    fn all_inside(&self, cells: &[(usize, usize, Symbol)]) -> bool {
+        cells.iter().all(|&(row, column, symbol)| row < self.order && column < self.order && symbol < self.order)
+    }
+
+    /// The groups of cells, each as `row * order + column`, that hold distinct symbols: rows, columns,
+    /// diagonals and regions.
+    fn units(&self) -> Vec<Vec<usize>> {
+        let n = self.order;
+        let mut units: Vec<Vec<usize>> = (0..n).map(|row| (0..n).map(|column| row * n + column).collect()).collect();
+        units.extend((0..n).map(|column| (0..n).map(|row| row * n + column).collect()));
+        if self.diagonals {
+            units.push((0..n).map(|index| index * n + index).collect());
+            units.push((0..n).map(|index| index * n + n - 1 - index).collect());
+        }
+        units.extend(self.regions.iter().map(|region| region.iter().map(|&(row, column)| row * n + column).collect()));
+        units
+    }
+
+    fn search<R: Rng>(&self, rng: Option<R>, limit: usize) -> Vec<LatinSquare> {
+        let mut found = Vec::new();
+        let Some(mut search) = self.compile(rng) else {
+            return found
+        };
+        if limit > 0 {
+            let n = self.order;
+            enumerate::run(&mut search, u64::MAX, |search| {
+                let rows = (0..n).map(|row| (0..n).map(|column| {
+                    search.cells[row * n + column].expect("ProgrammingError: a solution has every cell filled.")
+                }).collect()).collect();
+                found.push(LatinSquare::from_rows(rows));
+                if found.len() < limit { Control::Continue } else { Control::Stop }
+            });
+        }
+        found
+    }
+
+    /// The search state for the constraints, or None if a cell has no allowed symbol.
+    fn compile<R: Rng>(&self, rng: Option<R>) -> Option<ConstrainedSearch<R>> {
+        let n = self.order;
+        let mut allowed = vec![BitSet::full(n); n * n];
+        for &(row, column, symbol) in &self.fixed {
+            allowed[row * n + column].intersect_with(&BitSet::from_values(n, &[symbol]));
+        }
+        if self.idempotent {
+            for index in 0..n {
+                allowed[index * n + index].intersect_with(&BitSet::from_values(n, &[index]));
+            }
+        }
+        for &(row, column, symbol) in &self.forbidden {
+            allowed[row * n + column].remove(symbol);
+        }
+        if allowed.iter().any(BitSet::is_empty) {
+            return None
+        }
+        let units = self.units();
+        let mut units_of = vec![Vec::new(); n * n];
+        for (index, unit) in units.iter().enumerate() {
+            for &cell in unit {
+                units_of[cell].push(index);
+            }
+        }
+        Some(ConstrainedSearch {
+            order: n,
+            symmetric: self.symmetric,
+            cells: vec![None; n * n],
+            allowed,
+            free: vec![BitSet::full(n); units.len()],
+            units_of,
+            rng
+        })
+    }
+}
+
+/// A search filling cells with symbols allowed in them and free in all of their units. With symmetry, a
+/// cell and its mirror are filled together.
+struct ConstrainedSearch<R> {
+    order: usize,
+    symmetric: bool,
+    cells: Vec<Option<Symbol>>,
+    allowed: Vec<BitSet>,
+    /// The symbols not yet in each unit.
+    free: Vec<BitSet>,
+    units_of: Vec<Vec<usize>>,
+    rng: Option<R>
+}
+
+impl<R> ConstrainedSearch<R> {
+    fn mirror(&self, cell: usize) -> Option<usize> {
+        let (row, column) = (cell / self.order, cell % self.order);
+        if self.symmetric && row != column {
+            Some(column * self.order + row)
+        } else {
+            None
+        }
+    }
+
+    fn candidates(&self, cell: usize) -> BitSet {
+        let mut candidates = self.allowed[cell].clone();
+        for &unit in &self.units_of[cell] {
+            candidates.intersect_with(&self.free[unit]);
+        }
+        if let Some(mirror) = self.mirror(cell) {
+            if self.units_of[cell].iter().any(|unit| self.units_of[mirror].contains(unit)) {
+                return BitSet::new(self.order)
+            }
+            candidates.intersect_with(&self.allowed[mirror]);
+            for &unit in &self.units_of[mirror] {
+                candidates.intersect_with(&self.free[unit]);
+            }
+        }
+        candidates
+    }
+
+    fn set(&mut self, cell: usize, symbol: Option<Symbol>) {
+        let previous = std::mem::replace(&mut self.cells[cell], symbol);
+        for index in 0..self.units_of[cell].len() {
+            let unit = self.units_of[cell][index];
+            match (symbol, previous) {
+                (Some(symbol), _) => self.free[unit].remove(symbol),
+                (None, Some(symbol)) => self.free[unit].insert(symbol),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+impl<R: Rng> Search for ConstrainedSearch<R> {
+    type Choice = (usize, Symbol);
+
+    fn expand(&mut self) -> Node<(usize, Symbol)> {
+        let mut best: Option<(usize, BitSet, usize)> = None;
+        for cell in (0..self.cells.len()).filter(|&cell| self.cells[cell].is_none()) {
+            let candidates = self.candidates(cell);
+            let count = candidates.count();
+            if count == 0 {
+                return Node::Branch(Vec::new())
+            }
+            if best.as_ref().is_none_or(|&(_, _, fewest)| count < fewest) {
+                best = Some((cell, candidates, count));
+                if count == 1 {
+                    break
+                }
+            }
+        }
+        let Some((cell, candidates, _)) = best else {
+            return Node::Solution
+        };
+        let mut choices: Vec<(usize, Symbol)> = candidates.iter().map(|symbol| (cell, symbol)).collect();
+        if let Some(rng) = &mut self.rng {
+            choices.shuffle(rng);
+        }
+        Node::Branch(choices)
+    }
+
+    fn apply(&mut self, &(cell, symbol): &(usize, Symbol)) {
+        self.set(cell, Some(symbol));
+        if let Some(mirror) = self.mirror(cell) {
+            self.set(mirror, Some(symbol));
+        }
+    }
+
+    fn undo(&mut self, &(cell, _): &(usize, Symbol)) {
+        if let Some(mirror) = self.mirror(cell) {
+            self.set(mirror, None);
+        }
+        self.set(cell, None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Convergence diagnostics for the Jacobson-Matthews chain behind `LatinSquare::new_random`.
+//!
+//! `shuffle` makes n^3 moves from the cyclic square, a rule of thumb rather than a proven mixing time. The
+//! diagnostics here run one long chain instead, recording a summary statistic of the square every `thinning`
+//! moves from proper squares, once the cube is proper again, and measure how quickly the statistic forgets
+//! its past:
+//!
+//! - The autocorrelation at lag k is the correlation between samples k apart. For a chain that mixes well at
+//!   a thinning it falls to about 0 within a few lags.
+//! - The integrated autocorrelation time tau = 1 + 2 * sum of the autocorrelations, summed over lags up to
+//!   Sokal's automatic window, the first M with M >= 5 tau. A run of N samples estimates the mean as well
+//!   as N / tau independent samples would, its effective sample size.
+//! - The Geweke score compares the means of the first 10% and the last 50% of the samples, in units of
+//!   their standard errors. Values beyond about 2 suggest the chain had not settled when it started recording.
+//!
+//! The recommended thinning is 2 tau samples, in moves, after which the correlation of an exponentially
+//! decaying chain is down to e^-2. The recommended burn-in is the number of moves until the statistic first
+//! reaches the mean of the second half of the run, started from the cyclic square, and at least one
+//! recommended thinning. Both come from one run of one statistic, so they are evidence for that statistic
+//! at that order, not bounds: repeat them, and with statistics that matter for the use at hand.
+//!
+//! Sources:
+//!
+//! - Monte Carlo methods in statistical mechanics: foundations and new algorithms, A. D. Sokal (1996)
+//! - Evaluating the accuracy of sampling-based approaches to the calculation of posterior moments, J. Geweke (1992)
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+use crate::switching;
+
+/// The number of intercalates of a square, a statistic far from its typical value in cyclic squares of even order.
+pub fn intercalate_count(square: &LatinSquare) -> f64 {
+    switching::intercalates(square).len() as f64
+}
+
+/// The statistic that is 1 when the cell holds the symbol and 0 otherwise, whose mean is 1 / n under the
+/// uniform distribution.
+pub fn cell_indicator(row: usize, column: usize, symbol: usize) -> impl Fn(&LatinSquare) -> f64 {
+    move |square: &LatinSquare| if square.square[row][column] == symbol { 1.0 } else { 0.0 }
+}
+
+/// Runs the chain from the cyclic square of the given order, recording the statistic after every `thinning`
+/// moves from proper squares, `samples` times, starting after the first interval. The moves are drawn from
+/// `rng`. Fails for order 0.
+///
+/// ```
+/// use combinatorial_patterns::convergence;
+///
+/// let mut rng = rand::thread_rng();
+/// let trace = convergence::trace(6, 216, 50, &mut rng, convergence::intercalate_count).unwrap();
+/// assert_eq!(trace.len(), 50);
+/// assert!(convergence::trace(0, 216, 50, &mut rng, convergence::intercalate_count).is_err());
+/// ```
+pub fn trace(order: usize, thinning: u64, samples: usize, rng: &mut impl Rng, mut statistic: impl FnMut(&LatinSquare) -> f64) -> Result<Vec<f64>, CombinatorialError> {
+    let mut cube = IncidenceCube::new_cyclic(order)?;
+    Ok((0..samples).map(|_| {
+        cube.advance(thinning, rng);
+        statistic(&cube.as_latin_square())
+    }).collect())
+}
+
+/// The autocorrelations of a series at lags 0 to `max_lag`, or fewer for a short series. A constant series has
+/// autocorrelation 1 at lag 0 and 0 after.
+///
+/// ```
+/// use combinatorial_patterns::convergence;
+///
+/// let alternating: Vec<f64> = (0..100).map(|index| (index % 2) as f64).collect();
+/// let correlations = convergence::autocorrelation(&alternating, 2);
+/// assert!((correlations[1] + 1.0).abs() < 0.05);
+/// assert!((correlations[2] - 1.0).abs() < 0.05);
+/// ```
+pub fn autocorrelation(series: &[f64], max_lag: usize) -> Vec<f64> {
+    let count = series.len();
+    if count == 0 {
+        return Vec::new()
+    }
+    let mean = series.iter().sum::<f64>() / count as f64;
+    let variance = series.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count as f64;
+    (0..=max_lag.min(count - 1)).map(|lag| {
+        if lag == 0 {
+            return 1.0
+        }
+        if variance == 0.0 {
+            return 0.0
+        }
+        let covariance = series.iter().zip(&series[lag..]).map(|(a, b)| (a - mean) * (b - mean)).sum::<f64>() / count as f64;
+        covariance / variance
+    }).collect()
+}
+
+/// The integrated autocorrelation time of a series, in samples, with Sokal's automatic window.
+pub fn integrated_autocorrelation_time(series: &[f64]) -> f64 {
+    let correlations = autocorrelation(series, series.len() / 2);
+    let mut tau = 1.0;
+    for (lag, correlation) in correlations.iter().enumerate().skip(1) {
+        tau += 2.0 * correlation;
+        if lag as f64 >= 5.0 * tau {
+            break
+        }
+    }
+    tau
+}
+
+/// The Geweke score of a series: the difference of the means of its first 10% and last 50%, divided by the
+/// standard error of that difference, with each half's variance inflated by its autocorrelation time.
+/// Returns 0 for series too short to split, or without variation.
+pub fn geweke_score(series: &[f64]) -> f64 {
+    let count = series.len();
+    let (first, last) = (&series[..count / 10], &series[(count - count / 2)..]);
+    if first.len() < 2 || last.len() < 2 {
+        return 0.0
+    }
+    let summary = |part: &[f64]| {
+        let mean = part.iter().sum::<f64>() / part.len() as f64;
+        let variance = part.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (part.len() - 1) as f64;
+        (mean, variance * integrated_autocorrelation_time(part).max(1.0) / part.len() as f64)
+    };
+    let ((first_mean, first_error), (last_mean, last_error)) = (summary(first), summary(last));
+    if first_error + last_error == 0.0 {
+        return 0.0
+    }
+    (first_mean - last_mean) / (first_error + last_error).sqrt()
+}
+
+/// The diagnostics of one run of the chain. `Display` writes them as a short report.
+///
+/// ```
+/// use combinatorial_patterns::convergence;
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+/// let report = convergence::diagnose(8, 512, 200, &mut rng, convergence::intercalate_count).unwrap();
+/// assert_eq!(report.autocorrelation.len(), 11);
+/// assert!(report.effective_sample_size <= 200.0 * 2.0);
+/// assert!(report.recommended_thinning >= 512);
+/// assert!(report.to_string().starts_with("Jacobson-Matthews chain of order 8"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvergenceReport {
+    pub order: usize,
+    /// The moves between samples.
+    pub thinning: u64,
+    pub samples: usize,
+    /// The mean of the statistic over the second half of the run.
+    pub mean: f64,
+    /// The autocorrelations at lags 0 to 10.
+    pub autocorrelation: Vec<f64>,
+    /// The integrated autocorrelation time, in samples.
+    pub integrated_time: f64,
+    pub effective_sample_size: f64,
+    pub geweke_score: f64,
+    /// The recommended moves between samples.
+    pub recommended_thinning: u64,
+    /// The recommended moves before the first sample.
+    pub recommended_burn_in: u64
+}
+
+/// Runs the chain as `trace` does and diagnoses the statistic. Fails for order 0.
+pub fn diagnose(order: usize, thinning: u64, samples: usize, rng: &mut impl Rng, statistic: impl FnMut(&LatinSquare) -> f64) -> Result<ConvergenceReport, CombinatorialError> {
+    let series = trace(order, thinning, samples, rng, statistic)?;
+    let second_half = &series[(series.len() / 2)..];
+    let mean = if second_half.is_empty() { 0.0 } else { second_half.iter().sum::<f64>() / second_half.len() as f64 };
+    let integrated_time = integrated_autocorrelation_time(&series);
+    let recommended_thinning = thinning * (2.0 * integrated_time).ceil().max(1.0) as u64;
+    // The statistic starts on one side of its mean, the cyclic square's value, and settles after first reaching it.
+    let start = series.first().copied().unwrap_or(mean);
+    let settled = series.iter().position(|&value| (value - mean) * (start - mean) <= 0.0).unwrap_or(series.len());
+    Ok(ConvergenceReport {
+        order,
+        thinning,
+        samples,
+        mean,
+        autocorrelation: autocorrelation(&series, 10),
+        integrated_time,
+        effective_sample_size: series.len() as f64 / integrated_time.max(f64::MIN_POSITIVE),
+        geweke_score: geweke_score(&series),
+        recommended_thinning,
+        recommended_burn_in: (thinning * (settled as u64 + 1)).max(recommended_thinning)
+    })
+}
+
+impl fmt::Display for ConvergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let correlations: Vec<String> = self.autocorrelation.iter().map(|correlation| format!("{:.3}", correlation)).collect();
+        writeln!(f, "Jacobson-Matthews chain of order {}: {} samples, {} moves apart", self.order, self.samples, self.thinning)?;
+        writeln!(f, "Mean of the second half: {:.4}", self.mean)?;
+        writeln!(f, "Autocorrelation by lag: {}", correlations.join(", "))?;
+        writeln!(f, "Integrated autocorrelation time: {:.2} samples, effective sample size {:.1}", self.integrated_time, self.effective_sample_size)?;
+        writeln!(f, "Geweke score: {:.2}", self.geweke_score)?;
+        write!(f, "Recommended: thinning {} moves, burn-in {} moves", self.recommended_thinning, self.recommended_burn_in)
+    }
+}
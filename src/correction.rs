@@ -0,0 +1,161 @@
+//! Locating and correcting corrupted cells of a grid that should be a latin square.
+//!
+//! A cell copied wrongly repeats its symbol in its row and in its column, so every corrupted cell is in a
+//! conflict: it shares its symbol with another cell of its row or column, or holds a symbol out of range.
+//! The smallest set of cells to correct is found among the conflicting cells, trying sets of one cell, then
+//! of two, and so on. A set is a candidate when clearing it leaves no conflict, and it is a repair when the
+//! cleared cells can be filled in to give a latin square, which a completion search settles exactly. For a
+//! single error the row and the column of the cell each miss one symbol, and the repair writes it if they
+//! agree.
+//!
+//! Every repair of the smallest size is reported, since a few errors can sometimes be explained in more than
+//! one way. The same reasoning makes latin squares useful for check digits: the table of a quasigroup detects
+//! every single error in a code word computed with it.
+//!
+//! Sources:
+//!
+//! - Error detecting decimal codes, H. M. Damm, Discrete Mathematics 307 (2007)
+//! - Latin squares and their applications, J. Dénes, A. D. Keedwell, chapter on codes
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::partial::PartialLatinSquare;
+
+/// A cell to rewrite: what it holds and what it should hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Correction {
+    pub row: usize,
+    pub column: usize,
+    pub found: usize,
+    pub corrected: usize
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, column {}: {} should be {}", self.row, self.column, self.found, self.corrected)
+    }
+}
+
+/// The corrections that together turn a grid into a latin square.
+pub type Repair = Vec<Correction>;
+
+/// The cells of a square grid in a conflict, in row-major order: those sharing their symbol with another cell
+/// of their row or column, and those holding a symbol of at least the order.
+///
+/// ```
+/// use combinatorial_patterns::correction;
+///
+/// let grid = vec![vec![0, 1, 2], vec![1, 1, 0], vec![2, 0, 1]];
+/// assert_eq!(correction::conflicting_cells(&grid), vec![(0, 1), (1, 0), (1, 1)]);
+/// ```
+pub fn conflicting_cells(grid: &[Vec<usize>]) -> Vec<(usize, usize)> {
+    let order = grid.len();
+    let mut conflicting = vec![vec![false; order]; order];
+    for (row, symbols) in grid.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            if symbol >= order {
+                conflicting[row][column] = true;
+            }
+        }
+    }
+    let mut mark = |cells: Vec<(usize, usize)>| {
+        let mut by_symbol: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        for (row, column) in cells {
+            by_symbol.entry(grid[row][column]).or_default().push((row, column));
+        }
+        for cells in by_symbol.values().filter(|cells| cells.len() > 1) {
+            for &(row, column) in cells {
+                conflicting[row][column] = true;
+            }
+        }
+    };
+    for line in 0..order {
+        mark((0..order).map(|column| (line, column)).collect());
+        mark((0..order).map(|row| (row, line)).collect());
+    }
+    (0..order).flat_map(|row| (0..order).map(move |column| (row, column)))
+        .filter(|&(row, column)| conflicting[row][column])
+        .collect()
+}
+
+/// Every repair with the fewest corrections, at most `max_corrections`, each with the corrections in row-major
+/// order. A latin square has the one empty repair. Returns None if the grid is not square, or no set of at
+/// most `max_corrections` conflicting cells can be corrected.
+///
+/// The number of sets tried grows as the number of conflicting cells choose the number of corrections, so
+/// this is meant for a few errors.
+///
+/// ```
+/// use combinatorial_patterns::{correction, IncidenceCube};
+///
+/// let mut grid = IncidenceCube::new_cyclic(5).unwrap().as_latin_square().square;
+/// grid[2][3] = 4;
+/// let repairs = correction::minimal_repairs(&grid, 2).unwrap();
+/// assert_eq!(repairs.len(), 1);
+/// assert_eq!(repairs[0][0].to_string(), "row 2, column 3: 4 should be 0");
+///
+/// // Two errors in different rows and columns.
+/// grid[4][0] = 1;
+/// assert_eq!(correction::minimal_repairs(&grid, 2).unwrap()[0].len(), 2);
+/// assert!(correction::minimal_repairs(&grid, 1).is_none());
+/// ```
+pub fn minimal_repairs(grid: &[Vec<usize>], max_corrections: usize) -> Option<Vec<Repair>> {
+    let order = grid.len();
+    if grid.iter().any(|row| row.len() != order) {
+        return None
+    }
+    let suspects = conflicting_cells(grid);
+    if suspects.is_empty() {
+        return Some(vec![Vec::new()])
+    }
+    for size in 1..=max_corrections.min(suspects.len()) {
+        let mut repairs = Vec::new();
+        let mut chosen = Vec::new();
+        try_subsets(&suspects, size, &mut chosen, &mut |cells| {
+            if let Some(repair) = repair(grid, cells) {
+                repairs.push(repair);
+            }
+        });
+        if !repairs.is_empty() {
+            return Some(repairs)
+        }
+    }
+    None
+}
+
+/// Calls `visit` with every subset of the given size of the cells.
+fn try_subsets<F: FnMut(&[(usize, usize)])>(cells: &[(usize, usize)], size: usize, chosen: &mut Vec<(usize, usize)>, visit: &mut F) {
+    if chosen.len() == size {
+        visit(chosen);
+        return
+    }
+    let needed = size - chosen.len();
+    for index in 0..cells.len() {
+        if cells.len() - index < needed {
+            break
+        }
+        chosen.push(cells[index]);
+        try_subsets(&cells[(index + 1)..], size, chosen, visit);
+        chosen.pop();
+    }
+}
+
+/// The corrections of the cells, if clearing them leaves a partial latin square that completes with every
+/// cleared cell changed.
+fn repair(grid: &[Vec<usize>], cells: &[(usize, usize)]) -> Option<Repair> {
+    let mut rows: Vec<Vec<Option<usize>>> = grid.iter().map(|row| row.iter().map(|&symbol| Some(symbol)).collect()).collect();
+    for &(row, column) in cells {
+        rows[row][column] = None;
+    }
+    let partial = PartialLatinSquare::from_rows(rows)?;
+    // A cleared cell that gets its own symbol back didn't need correcting, and the smaller set was tried already.
+    let square = partial.completions(usize::MAX).into_iter()
+        .find(|square| cells.iter().all(|&(row, column)| square.square[row][column] != grid[row][column]))?;
+    Some(cells.iter().map(|&(row, column)| Correction {
+        row,
+        column,
+        found: grid[row][column],
+        corrected: square.square[row][column]
+    }).collect())
+}
@@ -0,0 +1,458 @@
+//! Covering and packing designs: the relaxations of block designs where no exact design exists.
+//!
+//! A covering design C(v, k, t) is a set of k-subsets of v points, the blocks, such that every t-subset of
+//! the points is in at least one block; the covering number is the fewest blocks that do. A packing design
+//! D(v, k, t) has every t-subset in at most one block, and the packing number is the most blocks it can have.
+//! A Steiner system, where every t-subset is in exactly one block, is both, and is optimal for both.
+//!
+//! The classical bounds nest the trivial count of each point's share:
+//!
+//! - Schönheim: C(v, k, t) >= ceil(v / k * ceil((v - 1) / (k - 1) * .. ceil((v - t + 1) / (k - t + 1)))).
+//! - Johnson: D(v, k, t) <= floor(v / k * floor((v - 1) / (k - 1) * .. floor((v - t + 1) / (k - t + 1)))).
+//!
+//! `CoveringDesign::best` and `PackingDesign::best` start from a known optimal design where one applies:
+//! Steiner triple systems, a single block when k = v, all k-subsets when t = k, and the blocks of
+//! consecutive points when t = 1. Otherwise they build one greedily, repeatedly taking the block that covers
+//! the most uncovered t-subsets, or for packings the first block in lexicographic order that fits. Then a
+//! local search tries to do one block better at a time: it moves a point of a block to another point, by
+//! simulated annealing on the number of uncovered t-subsets, or of t-subsets covered twice, until it reaches
+//! 0 or runs out of steps.
+//!
+//! Sources:
+//!
+//! - On maximal systems of k-tuples, J. Schönheim, Studia Scientiarum Mathematicarum Hungarica 1 (1966)
+//! - A new upper bound for error-correcting codes, S. M. Johnson, IRE Transactions on Information Theory 8 (1962)
+//! - [La Jolla Covering Repository, D. M. Gordon](https://ljcr.dmgordon.org/cover.html)
+//! - New constructions for covering designs, D. M. Gordon, G. Kuperberg, O. Patashnik, Journal of Combinatorial Designs 3 (1995)
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::numbers;
+use crate::triple_system::SteinerTripleSystem;
+
+/// The most k-subsets the greedy constructions consider.
+pub const GREEDY_LIMIT: u128 = 1_000_000;
+
+/// The Schönheim lower bound on the covering number C(v, k, t), or None unless t <= k <= v.
+///
+/// ```
+/// use combinatorial_patterns::covering;
+///
+/// assert_eq!(covering::schonheim_bound(7, 3, 2), Some(7));
+/// assert_eq!(covering::schonheim_bound(10, 4, 3), Some(30));
+/// ```
+pub fn schonheim_bound(v: usize, k: usize, t: usize) -> Option<u128> {
+    if !(t <= k && k <= v) {
+        return None
+    }
+    Some((0..t).rev().fold(1, |inner, index| {
+        ((v - index) as u128 * inner).div_ceil((k - index) as u128)
+    }))
+}
+
+/// The Johnson upper bound on the packing number D(v, k, t), or None unless t <= k <= v.
+///
+/// ```
+/// use combinatorial_patterns::covering;
+///
+/// assert_eq!(covering::johnson_bound(7, 3, 2), Some(7));
+/// assert_eq!(covering::johnson_bound(8, 3, 2), Some(8));
+/// ```
+pub fn johnson_bound(v: usize, k: usize, t: usize) -> Option<u128> {
+    if !(t <= k && k <= v) {
+        return None
+    }
+    Some((0..t).rev().fold(1, |inner, index| {
+        (v - index) as u128 * inner / (k - index) as u128
+    }))
+}
+
+/// A set of k-subsets of 0..v covering every t-subset at least once.
+///
+/// ```
+/// use combinatorial_patterns::covering::CoveringDesign;
+///
+/// // No Steiner system S(2, 3, 6) exists, and 6 blocks cover the pairs.
+/// let design = CoveringDesign::best(6, 3, 2, 10_000, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(design.blocks().len(), 6);
+/// assert!(design.is_optimal());
+/// assert_eq!(design.to_string().lines().next(), Some("C(6, 3, 2) with 6 blocks, Schönheim bound 6"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoveringDesign {
+    points: usize,
+    block_size: usize,
+    strength: usize,
+    blocks: Vec<Vec<usize>>
+}
+
+impl CoveringDesign {
+    /// Checks the blocks: sorted k-subsets of 0..v that cover every t-subset. Returns None otherwise.
+    pub fn new(points: usize, block_size: usize, strength: usize, blocks: Vec<Vec<usize>>) -> Option<CoveringDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        if !blocks.iter().all(|block| subsets.is_block(block)) || subsets.coverage(&blocks).contains(&0) {
+            return None
+        }
+        Some(CoveringDesign { points, block_size, strength, blocks })
+    }
+
+    /// A known optimal covering if one of the constructions applies, or else the greedy covering.
+    /// Returns None unless t <= k <= v, or if there are more than `GREEDY_LIMIT` k-subsets to choose from.
+    ///
+    /// ```
+    /// use combinatorial_patterns::covering::CoveringDesign;
+    ///
+    /// // The Schönheim bound is 7, but C(9, 4, 2) is 8.
+    /// let design = CoveringDesign::greedy(9, 4, 2).unwrap();
+    /// assert_eq!(design.bound(), 7);
+    /// assert!(design.blocks().len() >= 8);
+    /// assert!(CoveringDesign::greedy(9, 4, 5).is_none());
+    /// ```
+    pub fn greedy(points: usize, block_size: usize, strength: usize) -> Option<CoveringDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        if let Some(blocks) = known(points, block_size, strength) {
+            return Some(CoveringDesign { points, block_size, strength, blocks })
+        }
+        if numbers::binomial(points, block_size)? > GREEDY_LIMIT {
+            return None
+        }
+        let candidates = subsets_of(&(0..points).collect::<Vec<usize>>(), block_size);
+        let mut covered = vec![false; subsets.count];
+        let mut uncovered = subsets.count;
+        let mut blocks = Vec::new();
+        while uncovered > 0 {
+            let best = candidates.iter().max_by_key(|block| {
+                subsets.ranks(block).iter().filter(|&&rank| !covered[rank]).count()
+            }).expect("ProgrammingError: there is a k-subset when k <= v.");
+            for rank in subsets.ranks(best) {
+                if !std::mem::replace(&mut covered[rank], true) {
+                    uncovered -= 1;
+                }
+            }
+            blocks.push(best.clone());
+        }
+        Some(CoveringDesign { points, block_size, strength, blocks })
+    }
+
+    /// A covering with the given number of blocks, found by local search from random blocks in at most
+    /// `steps` moves drawn from `rng`, or None if none was found or unless t <= k <= v.
+    pub fn search(points: usize, block_size: usize, strength: usize, blocks: usize, steps: usize, rng: &mut impl Rng) -> Option<CoveringDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        let blocks = local_search(&subsets, blocks, steps, rng, |count| (count == 0) as usize)?;
+        Some(CoveringDesign { points, block_size, strength, blocks })
+    }
+
+    /// The greedy covering, improved by `search` one block at a time, with `steps` moves for each attempt,
+    /// until it meets the Schönheim bound or an attempt fails.
+    pub fn best(points: usize, block_size: usize, strength: usize, steps: usize, rng: &mut impl Rng) -> Option<CoveringDesign> {
+        let mut design = CoveringDesign::greedy(points, block_size, strength)?;
+        while !design.is_optimal() {
+            match CoveringDesign::search(points, block_size, strength, design.blocks.len() - 1, steps, rng) {
+                Some(better) => design = better,
+                None => break
+            }
+        }
+        Some(design)
+    }
+
+    /// The number of points v.
+    pub fn points(&self) -> usize {
+        self.points
+    }
+
+    /// The block size k.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// The strength t.
+    pub fn strength(&self) -> usize {
+        self.strength
+    }
+
+    /// The blocks, each sorted.
+    pub fn blocks(&self) -> &[Vec<usize>] {
+        &self.blocks
+    }
+
+    /// The Schönheim bound for the parameters.
+    pub fn bound(&self) -> u128 {
+        schonheim_bound(self.points, self.block_size, self.strength).expect("ProgrammingError: a design has t <= k <= v.")
+    }
+
+    /// Returns true if the design meets the Schönheim bound, so no covering has fewer blocks.
+    pub fn is_optimal(&self) -> bool {
+        self.blocks.len() as u128 == self.bound()
+    }
+}
+
+impl fmt::Display for CoveringDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "C({}, {}, {}) with {} blocks, Schönheim bound {}", self.points, self.block_size, self.strength, self.blocks.len(), self.bound())?;
+        write_blocks(f, &self.blocks)
+    }
+}
+
+/// A set of k-subsets of 0..v covering every t-subset at most once.
+///
+/// ```
+/// use combinatorial_patterns::covering::PackingDesign;
+///
+/// let design = PackingDesign::best(7, 3, 2, 10_000, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(design.blocks().len(), 7);
+/// assert!(design.is_optimal());
+///
+/// // The Johnson bound of 3 is not reached for D(5, 3, 2): three triples of 5 points can't pairwise
+/// // share at most one point.
+/// let design = PackingDesign::best(5, 3, 2, 10_000, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(design.blocks().len(), 2);
+/// assert_eq!(design.bound(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackingDesign {
+    points: usize,
+    block_size: usize,
+    strength: usize,
+    blocks: Vec<Vec<usize>>
+}
+
+impl PackingDesign {
+    /// Checks the blocks: sorted k-subsets of 0..v that cover no t-subset twice. Returns None otherwise.
+    pub fn new(points: usize, block_size: usize, strength: usize, blocks: Vec<Vec<usize>>) -> Option<PackingDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        if !blocks.iter().all(|block| subsets.is_block(block)) || subsets.coverage(&blocks).iter().any(|&count| count > 1) {
+            return None
+        }
+        Some(PackingDesign { points, block_size, strength, blocks })
+    }
+
+    /// A known optimal packing if one of the constructions applies, or else the k-subsets taken in
+    /// lexicographic order whenever they fit. Returns None unless t <= k <= v, or if there are more than
+    /// `GREEDY_LIMIT` k-subsets.
+    pub fn greedy(points: usize, block_size: usize, strength: usize) -> Option<PackingDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        if let Some(blocks) = known(points, block_size, strength) {
+            return Some(PackingDesign { points, block_size, strength, blocks })
+        }
+        if numbers::binomial(points, block_size)? > GREEDY_LIMIT {
+            return None
+        }
+        let mut covered = vec![false; subsets.count];
+        let mut blocks = Vec::new();
+        for block in subsets_of(&(0..points).collect::<Vec<usize>>(), block_size) {
+            let ranks = subsets.ranks(&block);
+            if ranks.iter().all(|&rank| !covered[rank]) {
+                for rank in ranks {
+                    covered[rank] = true;
+                }
+                blocks.push(block);
+            }
+        }
+        Some(PackingDesign { points, block_size, strength, blocks })
+    }
+
+    /// A packing with the given number of blocks, found by local search from random blocks in at most
+    /// `steps` moves drawn from `rng`, or None if none was found or unless t <= k <= v.
+    pub fn search(points: usize, block_size: usize, strength: usize, blocks: usize, steps: usize, rng: &mut impl Rng) -> Option<PackingDesign> {
+        let subsets = Subsets::new(points, block_size, strength)?;
+        let blocks = local_search(&subsets, blocks, steps, rng, |count| count.saturating_sub(1))?;
+        Some(PackingDesign { points, block_size, strength, blocks })
+    }
+
+    /// The greedy packing, improved by `search` one block at a time, with `steps` moves for each attempt,
+    /// until it meets the Johnson bound or an attempt fails.
+    pub fn best(points: usize, block_size: usize, strength: usize, steps: usize, rng: &mut impl Rng) -> Option<PackingDesign> {
+        let mut design = PackingDesign::greedy(points, block_size, strength)?;
+        while !design.is_optimal() {
+            match PackingDesign::search(points, block_size, strength, design.blocks.len() + 1, steps, rng) {
+                Some(better) => design = better,
+                None => break
+            }
+        }
+        Some(design)
+    }
+
+    /// The number of points v.
+    pub fn points(&self) -> usize {
+        self.points
+    }
+
+    /// The block size k.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// The strength t.
+    pub fn strength(&self) -> usize {
+        self.strength
+    }
+
+    /// The blocks, each sorted.
+    pub fn blocks(&self) -> &[Vec<usize>] {
+        &self.blocks
+    }
+
+    /// The Johnson bound for the parameters.
+    pub fn bound(&self) -> u128 {
+        johnson_bound(self.points, self.block_size, self.strength).expect("ProgrammingError: a design has t <= k <= v.")
+    }
+
+    /// Returns true if the design meets the Johnson bound, so no packing has more blocks.
+    pub fn is_optimal(&self) -> bool {
+        self.blocks.len() as u128 == self.bound()
+    }
+}
+
+impl fmt::Display for PackingDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "D({}, {}, {}) with {} blocks, Johnson bound {}", self.points, self.block_size, self.strength, self.blocks.len(), self.bound())?;
+        write_blocks(f, &self.blocks)
+    }
+}
+
+fn write_blocks(f: &mut fmt::Formatter<'_>, blocks: &[Vec<usize>]) -> fmt::Result {
+    for block in blocks {
+        let points: Vec<String> = block.iter().map(|point| point.to_string()).collect();
+        write!(f, "\n{}", points.join(" "))?;
+    }
+    Ok(())
+}
+
+/// The blocks of a design that is both an optimal covering and an optimal packing, where one is known.
+fn known(points: usize, block_size: usize, strength: usize) -> Option<Vec<Vec<usize>>> {
+    let all: Vec<usize> = (0..points).collect();
+    if block_size == points {
+        return Some(vec![all])
+    }
+    if strength == block_size && numbers::binomial(points, block_size)? <= GREEDY_LIMIT {
+        return Some(subsets_of(&all, block_size))
+    }
+    if strength == 1 && points.is_multiple_of(block_size) {
+        return Some(all.chunks(block_size).map(|chunk| chunk.to_vec()).collect())
+    }
+    if (block_size, strength) == (3, 2) {
+        let system = SteinerTripleSystem::new(points)?;
+        return Some(system.blocks().iter().map(|block| {
+            let mut block = block.to_vec();
+            block.sort_unstable();
+            block
+        }).collect())
+    }
+    None
+}
+
+/// Moves points of `blocks` random k-subsets to bring the sum of `penalty` over the coverage counts of the
+/// t-subsets down to 0, accepting every move that doesn't increase it and others less often as the search
+/// cools, for at most `steps` moves drawn from `rng`.
+fn local_search(subsets: &Subsets, blocks: usize, steps: usize, rng: &mut impl Rng, penalty: impl Fn(usize) -> usize) -> Option<Vec<Vec<usize>>> {
+    let all: Vec<usize> = (0..subsets.points).collect();
+    let mut design: Vec<Vec<usize>> = (0..blocks).map(|_| {
+        let mut block: Vec<usize> = all.choose_multiple(rng, subsets.block_size).copied().collect();
+        block.sort_unstable();
+        block
+    }).collect();
+    let mut counts = subsets.coverage(&design);
+    let mut cost: usize = counts.iter().map(|&count| penalty(count)).sum();
+    if subsets.block_size == 0 || subsets.block_size == subsets.points || blocks == 0 {
+        return if cost == 0 { Some(design) } else { None }
+    }
+    for step in 0..steps {
+        if cost == 0 {
+            return Some(design)
+        }
+        // Simulated annealing, cooling from a temperature of 1 to 0.05 over the steps.
+        let temperature = 1.0 - 0.95 * step as f64 / steps as f64;
+        let index = rng.gen_range(0..blocks);
+        let out = design[index][rng.gen_range(0..subsets.block_size)];
+        let missing: Vec<usize> = all.iter().copied().filter(|point| !design[index].contains(point)).collect();
+        let into = *missing.choose(rng).expect("ProgrammingError: a block smaller than the points misses one.");
+        let before = subsets.ranks(&design[index]);
+        let mut moved: Vec<usize> = design[index].iter().map(|&point| if point == out { into } else { point }).collect();
+        moved.sort_unstable();
+        let after = subsets.ranks(&moved);
+        let mut change: isize = 0;
+        for &rank in &before {
+            change += penalty(counts[rank] - 1) as isize - penalty(counts[rank]) as isize;
+            counts[rank] -= 1;
+        }
+        for &rank in &after {
+            change += penalty(counts[rank] + 1) as isize - penalty(counts[rank]) as isize;
+            counts[rank] += 1;
+        }
+        if change <= 0 || rng.gen::<f64>() < (-(change as f64) / temperature).exp() {
+            design[index] = moved;
+            cost = (cost as isize + change) as usize;
+        } else {
+            for &rank in &after {
+                counts[rank] -= 1;
+            }
+            for &rank in &before {
+                counts[rank] += 1;
+            }
+        }
+    }
+    if cost == 0 { Some(design) } else { None }
+}
+
+/// Ranks the t-subsets of 0..v in colexicographic order, for counting how often blocks cover each.
+struct Subsets {
+    points: usize,
+    block_size: usize,
+    strength: usize,
+    /// binomials[n][r] for n up to v and r up to t.
+    binomials: Vec<Vec<usize>>,
+    count: usize
+}
+
+impl Subsets {
+    fn new(points: usize, block_size: usize, strength: usize) -> Option<Subsets> {
+        if !(strength <= block_size && block_size <= points) {
+            return None
+        }
+        let count = usize::try_from(numbers::binomial(points, strength)?).ok()?;
+        let binomials = (0..=points).map(|n| (0..=strength).map(|r| {
+            numbers::binomial(n, r).map_or(usize::MAX, |value| value as usize)
+        }).collect()).collect();
+        Some(Subsets { points, block_size, strength, binomials, count })
+    }
+
+    fn is_block(&self, block: &[usize]) -> bool {
+        block.len() == self.block_size && block.windows(2).all(|pair| pair[0] < pair[1]) && block.iter().all(|&point| point < self.points)
+    }
+
+    /// The ranks of the t-subsets of a sorted block.
+    fn ranks(&self, block: &[usize]) -> Vec<usize> {
+        subsets_of(block, self.strength).iter().map(|subset| {
+            subset.iter().enumerate().map(|(index, &point)| self.binomials[point][index + 1]).sum()
+        }).collect()
+    }
+
+    /// How many of the blocks cover each t-subset.
+    fn coverage(&self, blocks: &[Vec<usize>]) -> Vec<usize> {
+        let mut counts = vec![0; self.count];
+        for block in blocks {
+            for rank in self.ranks(block) {
+                counts[rank] += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Every subset of the given size of the sorted items, in lexicographic order.
+pub(crate) fn subsets_of(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()]
+    }
+    let mut found = Vec::new();
+    for (index, &first) in items.iter().enumerate() {
+        for mut rest in subsets_of(&items[(index + 1)..], size - 1) {
+            rest.insert(0, first);
+            found.push(rest);
+        }
+    }
+    found
+}
@@ -0,0 +1,238 @@
+//! Unconditionally secure cryptography from orthogonal arrays: threshold secret sharing and authentication codes.
+//!
+//! An orthogonal array of strength t and index 1 over q symbols is a list of rows in which any t columns hold
+//! every t-tuple of symbols in exactly one row. Taking one column for the secret and the others for the
+//! shares of w participants gives a (t, w) threshold scheme: the dealer picks a random row with the secret in
+//! its first column and hands participant i the symbol in column i + 1. Any t shares pick out a single row,
+//! which reveals the secret. Fewer than t shares are consistent with every secret in equally many rows,
+//! so they reveal nothing about it, whatever the computing power of the participants.
+//!
+//! The k MOLS of order n give an array of strength 2 with k + 2 columns, for a (2, k + 1) scheme on
+//! n possible secrets. Bush's construction over GF(q) gives strength t with q + 1 columns: a row for every
+//! polynomial f of degree below t, holding f(x) for each field element x and, in the last column, the
+//! coefficient of x^(t - 1). With the secret in column f(0), this is Shamir's scheme.
+//!
+//! An orthogonal array of strength 2 and index 1 with k columns over n symbols is also an authentication code
+//! for k source states. Sender and receiver share a secret key, a random row, and the tag of source state s
+//! is the symbol in column s. An opponent who sends a message (s, a) of their own is accepted by n of the
+//! n^2 keys, and one who replaces an observed (s, a) by (s', a') is accepted by one of the n keys consistent
+//! with (s, a). Both deceptions succeed with probability 1/n, the lowest possible with n tags. The k MOLS of
+//! order n give k + 2 source states.
+//!
+//! These schemes are for demonstration: shares and keys are drawn from the generator passed in, which should
+//! be a cryptographically secure one for real keys, and reconstruction scans the whole array.
+//!
+//! Sources:
+//!
+//! - [How to share a secret, A. Shamir](https://doi.org/10.1145/359168.359176)
+//! - Orthogonal arrays of index unity, K. A. Bush, Annals of Mathematical Statistics 23 (1952)
+//! - Some constructions and bounds for authentication codes, D. R. Stinson, Journal of Cryptology 1 (1988)
+//! - Combinatorial designs: constructions and analysis, D. R. Stinson, chapter 13
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::finite_field::FiniteField;
+use crate::latin_square::LatinSquare;
+use crate::mols;
+
+/// A (t, w) threshold scheme on the rows of an orthogonal array of strength t, whose first column is the secret.
+///
+/// ```
+/// use combinatorial_patterns::{cryptography::ThresholdScheme, mols};
+///
+/// // Three MOLS of order 4 share one of 4 secrets among 4 participants, any 2 of whom can recover it.
+/// let scheme = ThresholdScheme::from_mols(&mols::new_prime_power(4).unwrap()).unwrap();
+/// assert_eq!((scheme.threshold(), scheme.participants(), scheme.secrets()), (2, 4, 4));
+/// let shares = scheme.share(3, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(scheme.reconstruct(&[(0, shares[0]), (2, shares[2])]), Some(3));
+/// // One share is consistent with every secret.
+/// assert_eq!(scheme.reconstruct(&[(1, shares[1])]), None);
+/// assert_eq!(scheme.possible_secrets(&[(1, shares[1])]), vec![0, 1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdScheme {
+    threshold: usize,
+    secrets: usize,
+    rows: Vec<Vec<usize>>
+}
+
+impl ThresholdScheme {
+    /// The (2, k + 1) scheme of k MOLS of order n, for n secrets. Returns None unless there is at least one
+    /// square and the squares are mutually orthogonal.
+    pub fn from_mols(squares: &[LatinSquare]) -> Option<ThresholdScheme> {
+        if squares.is_empty() || !mols::are_mutually_orthogonal(squares) {
+            return None
+        }
+        Some(ThresholdScheme { threshold: 2, secrets: squares[0].size(), rows: mols::orthogonal_array(squares) })
+    }
+
+    /// The (t, w) scheme of Bush's orthogonal array over GF(q), for q secrets and q^t rows. Returns None
+    /// unless q is a prime power, 1 <= t <= q and 1 <= w <= q.
+    ///
+    /// ```
+    /// use combinatorial_patterns::cryptography::ThresholdScheme;
+    ///
+    /// let scheme = ThresholdScheme::bush(7, 3, 5).unwrap();
+    /// let shares = scheme.share(6, &mut rand::thread_rng()).unwrap();
+    /// assert_eq!(scheme.reconstruct(&[(0, shares[0]), (3, shares[3]), (4, shares[4])]), Some(6));
+    /// assert_eq!(scheme.possible_secrets(&[(0, shares[0]), (3, shares[3])]).len(), 7);
+    /// ```
+    pub fn bush(q: usize, threshold: usize, participants: usize) -> Option<ThresholdScheme> {
+        let field = FiniteField::new(q)?;
+        if threshold == 0 || threshold > q || participants == 0 || participants > q {
+            return None
+        }
+        let count = q.checked_pow(threshold as u32)?;
+        let rows = (0..count).map(|index| {
+            let coefficients: Vec<usize> = (0..threshold).map(|power| (index / q.pow(power as u32)) % q).collect();
+            let evaluate = |x: usize| coefficients.iter().rev().fold(0, |value, &coefficient| field.add(field.mul(value, x), coefficient));
+            // The secret f(0), then f(x) for the nonzero elements x, then the leading coefficient.
+            (0..q).map(evaluate).chain(std::iter::once(coefficients[threshold - 1])).take(participants + 1).collect()
+        }).collect();
+        Some(ThresholdScheme { threshold, secrets: q, rows })
+    }
+
+    /// The number of shares that recover the secret.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The number of participants w.
+    pub fn participants(&self) -> usize {
+        self.rows.first().map(|row| row.len() - 1).unwrap_or(0)
+    }
+
+    /// The number of possible secrets, which are 0..secrets.
+    pub fn secrets(&self) -> usize {
+        self.secrets
+    }
+
+    /// The rows of the orthogonal array, the secret first.
+    pub fn rows(&self) -> &[Vec<usize>] {
+        &self.rows
+    }
+
+    /// The shares of a secret for participants 0..w, from a row drawn with `rng`. Returns None if the secret
+    /// is out of range.
+    pub fn share(&self, secret: usize, rng: &mut impl Rng) -> Option<Vec<usize>> {
+        let candidates: Vec<&Vec<usize>> = self.rows.iter().filter(|row| row[0] == secret).collect();
+        let row = candidates.choose(rng)?;
+        Some(row[1..].to_vec())
+    }
+
+    /// The secrets consistent with some (participant, share) pairs, in increasing order.
+    pub fn possible_secrets(&self, shares: &[(usize, usize)]) -> Vec<usize> {
+        let mut secrets: Vec<usize> = self.rows.iter()
+            .filter(|row| shares.iter().all(|&(participant, share)| row.get(participant + 1) == Some(&share)))
+            .map(|row| row[0])
+            .collect();
+        secrets.sort_unstable();
+        secrets.dedup();
+        secrets
+    }
+
+    /// The secret, if the (participant, share) pairs determine it. That takes `threshold` consistent shares
+    /// of distinct participants; fewer leave every secret possible.
+    pub fn reconstruct(&self, shares: &[(usize, usize)]) -> Option<usize> {
+        match self.possible_secrets(shares)[..] {
+            [secret] => Some(secret),
+            _ => None
+        }
+    }
+}
+
+/// An authentication code with a key for every row of an orthogonal array of strength 2, a source state for
+/// every column and the symbols as tags.
+///
+/// ```
+/// use combinatorial_patterns::{cryptography::AuthenticationCode, mols};
+///
+/// // Two MOLS of order 3 authenticate 4 source states with 9 keys and 3 tags.
+/// let code = AuthenticationCode::from_mols(&mols::new_prime_power(3).unwrap()).unwrap();
+/// assert_eq!((code.sources(), code.keys(), code.tags()), (4, 9, 3));
+/// let key = code.random_key(&mut rand::thread_rng());
+/// let tag = code.tag(key, 2);
+/// assert!(code.verify(key, 2, tag));
+/// assert!(!code.verify(key, 2, (tag + 1) % 3));
+/// assert_eq!(code.impersonation_probability(), 1.0 / 3.0);
+/// assert_eq!(code.substitution_probability(), 1.0 / 3.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticationCode {
+    tags: usize,
+    rows: Vec<Vec<usize>>
+}
+
+impl AuthenticationCode {
+    /// The code of the orthogonal array of k MOLS of order n: k + 2 source states, n^2 keys and n tags.
+    /// Returns None unless there is at least one square and the squares are mutually orthogonal.
+    pub fn from_mols(squares: &[LatinSquare]) -> Option<AuthenticationCode> {
+        if squares.is_empty() || !mols::are_mutually_orthogonal(squares) {
+            return None
+        }
+        Some(AuthenticationCode { tags: squares[0].size(), rows: mols::orthogonal_array(squares) })
+    }
+
+    /// The number of source states, the messages that can be authenticated.
+    pub fn sources(&self) -> usize {
+        self.rows.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    /// The number of keys, or encoding rules.
+    pub fn keys(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The number of tags.
+    pub fn tags(&self) -> usize {
+        self.tags
+    }
+
+    /// A key drawn uniformly with `rng`.
+    pub fn random_key(&self, rng: &mut impl Rng) -> usize {
+        rng.gen_range(0..self.rows.len())
+    }
+
+    /// The tag of a source state under a key.
+    pub fn tag(&self, key: usize, source: usize) -> usize {
+        self.rows[key][source]
+    }
+
+    /// Returns true if the receiver holding the key accepts the message (source, tag).
+    pub fn verify(&self, key: usize, source: usize, tag: usize) -> bool {
+        self.rows.get(key).and_then(|row| row.get(source)) == Some(&tag)
+    }
+
+    /// The chance that the best message sent without seeing any is accepted, over a uniformly random key.
+    pub fn impersonation_probability(&self) -> f64 {
+        let best = (0..self.sources()).flat_map(|source| (0..self.tags).map(move |tag| (source, tag)))
+            .map(|(source, tag)| self.rows.iter().filter(|row| row[source] == tag).count())
+            .max()
+            .unwrap_or(0);
+        best as f64 / self.rows.len().max(1) as f64
+    }
+
+    /// The chance that the best replacement of a seen message by one for another source state is accepted,
+    /// maximized over the seen message too.
+    pub fn substitution_probability(&self) -> f64 {
+        let mut best: f64 = 0.0;
+        for source in 0..self.sources() {
+            for tag in 0..self.tags {
+                let consistent: Vec<&Vec<usize>> = self.rows.iter().filter(|row| row[source] == tag).collect();
+                if consistent.is_empty() {
+                    continue
+                }
+                for other in (0..self.sources()).filter(|&other| other != source) {
+                    let mut counts = vec![0; self.tags];
+                    for row in &consistent {
+                        counts[row[other]] += 1;
+                    }
+                    let most = counts.into_iter().max().unwrap_or(0);
+                    best = best.max(most as f64 / consistent.len() as f64);
+                }
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,302 @@
+//! Defining sets: sets of entries of a latin square that force the rest of it.
+//!
+//! A defining set of a latin square L is a set of its cells such that L is the only latin square
+//! agreeing with L on those cells. The size of the smallest defining set measures how much information
+//! the square carries beyond the latin property: small for highly structured squares such as group tables,
+//! larger for random ones.
+//!
+//! Two searches are provided:
+//!
+//! - `greedy_defining_set` drops cells one at a time, in random order, as long as the completion stays unique.
+//!   The result is a minimal defining set (no cell can be removed), also called a critical set, but not
+//!   necessarily a smallest one.
+//! - `smallest_defining_set` is exact. Every defining set must contain a cell of every latin trade, i.e. of
+//!   the cells where some other latin square differs from L. The search alternates between finding the
+//!   smallest set hitting every trade seen so far, and checking whether it defines L; if not, the other
+//!   completion found gives a new trade. It starts from the cycle switches of the square, which swap
+//!   two rows, columns, or symbols along a cycle, and include the intercalates.
+//!   This is exponential, and intended for orders up to about 6.
+//!
+//! Sources:
+//!
+//! - [Critical sets in latin squares and associated structures, Diane Donovan, James Lefevre](https://www.researchgate.net/publication/228563677_Critical_sets_in_Latin_squares_and_associated_structures)
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::latin_square::LatinSquare;
+use crate::partial::PartialLatinSquare;
+
+/// A cell as (row, column).
+pub type Cell = (usize, usize);
+
+/// Returns true if `square` is the only latin square agreeing with it on `cells`.
+///
+/// ```
+/// use combinatorial_patterns::{defining_set, IncidenceCube};
+///
+/// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+/// assert!(defining_set::is_defining_set(&square, &[(0, 0), (1, 1)]));
+/// // Fixing the first row leaves two ways to finish.
+/// assert!(!defining_set::is_defining_set(&square, &[(0, 0), (0, 1), (0, 2)]));
+/// ```
+pub fn is_defining_set(square: &LatinSquare, cells: &[Cell]) -> bool {
+    PartialLatinSquare::from_cells(square, cells).count_completions(2) == 1
+}
+
+/// Returns a minimal defining set of `square`, found by removing cells in an order drawn from `rng` while
+/// the completion stays unique. Cells are sorted in row-major order.
+pub fn greedy_defining_set(square: &LatinSquare, rng: &mut impl Rng) -> Vec<Cell> {
+    let order = square.size();
+    let mut cells: Vec<Cell> = (0..order).flat_map(|row| (0..order).map(move |column| (row, column))).collect();
+    cells.shuffle(rng);
+
+    let mut partial = PartialLatinSquare::from_square(square);
+    for &(row, column) in &cells {
+        partial.set(row, column, None);
+        if partial.count_completions(2) != 1 {
+            partial.set(row, column, Some(square.square[row][column]));
+        }
+    }
+    partial.filled_cells().into_iter().map(|(row, column, _)| (row, column)).collect()
+}
+
+/// Returns a defining set of `square` of the smallest possible size, in row-major order. The greedy runs
+/// that bound the search use a fixed seed, so the same square always gives the same set.
+///
+/// ```
+/// use combinatorial_patterns::{defining_set, IncidenceCube};
+///
+/// // The smallest defining sets of the cyclic square of order 4 have 4 cells.
+/// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+/// let smallest = defining_set::smallest_defining_set(&square);
+/// assert_eq!(smallest.len(), 4);
+/// assert!(defining_set::is_defining_set(&square, &smallest));
+/// ```
+pub fn smallest_defining_set(square: &LatinSquare) -> Vec<Cell> {
+    let order = square.size();
+    let index = |(row, column): Cell| row * order + column;
+    let mut trades: Vec<Vec<usize>> = cycle_switches(square).into_iter()
+        .map(|cells| cells.into_iter().map(index).collect())
+        .collect();
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let upper_bound = (0..order.max(1)).map(|_| greedy_defining_set(square, &mut rng))
+        .min_by_key(|cells| cells.len())
+        .expect("ProgrammingError: at least one greedy run.");
+
+    // Hitting sets only get larger as trades are added, so the size searched for never goes back down.
+    let mut size = 0;
+    loop {
+        if size >= upper_bound.len() {
+            return upper_bound
+        }
+        let chosen = match hitting_set(&trades, order * order, size) {
+            Some(chosen) => chosen,
+            None => {
+                size += 1;
+                continue
+            }
+        };
+
+        let mut cells: Vec<Cell> = chosen.iter().map(|&cell| (cell / order, cell % order)).collect();
+        cells.sort_unstable();
+        let new_trades = other_completion_trades(square, &cells, TRADES_PER_ROUND);
+        if new_trades.is_empty() {
+            return cells
+        }
+        for trade in new_trades {
+            let trade = minimal_trade(square, trade);
+            if !trades.contains(&trade) {
+                trades.push(trade);
+            }
+        }
+    }
+}
+
+/// How many other completions of a failed candidate are turned into trades at once.
+const TRADES_PER_ROUND: usize = 8;
+
+/// Returns the cells where `square` differs from up to `limit` other latin squares agreeing with it on `cells`.
+fn other_completion_trades(square: &LatinSquare, cells: &[Cell], limit: usize) -> Vec<Vec<usize>> {
+    let order = square.size();
+    PartialLatinSquare::from_cells(square, cells).completions(limit + 1).into_iter()
+        .filter(|completion| completion.square != square.square)
+        .take(limit)
+        .map(|other| (0..order * order).filter(|&cell| {
+            other.square[cell / order][cell % order] != square.square[cell / order][cell % order]
+        }).collect())
+        .collect()
+}
+
+/// Shrinks a trade until no proper subset of it is a trade, which makes it a stronger constraint.
+///
+/// Fixing every cell outside the trade, plus one cell inside it, and still finding another completion
+/// means the new difference is a smaller trade.
+fn minimal_trade(square: &LatinSquare, mut trade: Vec<usize>) -> Vec<usize> {
+    let order = square.size();
+    'shrinking: loop {
+        for &fixed in &trade {
+            let cells: Vec<Cell> = (0..order * order)
+                .filter(|cell| *cell == fixed || !trade.contains(cell))
+                .map(|cell| (cell / order, cell % order))
+                .collect();
+            if let Some(smaller) = other_completion_trades(square, &cells, 1).pop() {
+                trade = smaller;
+                continue 'shrinking
+            }
+        }
+        return trade
+    }
+}
+
+/// Maps a conjugate's (a, b, c) coordinates back to the (row, column) of the square.
+type ConjugateCell = fn(usize, usize, usize) -> Cell;
+
+/// The cycle switches of `square`, the latin trades that swap two rows, two columns, or two symbols
+/// along one cycle, as their cells. Intercalates are the cycles of length 2.
+///
+/// For rows r1 and r2, the columns c1, .., ck form a cycle when row r2 at c(i) holds the symbol row r1
+/// has at c(i + 1). Columns and symbols are handled by reading the square through its conjugates,
+/// where `table[a][b] = c` and `cell(a, b, c)` maps the conjugate's coordinates back to (row, column).
+fn cycle_switches(square: &LatinSquare) -> Vec<Vec<Cell>> {
+    let order = square.size();
+    let mut by_symbol_and_column = vec![vec![0; order]; order];
+    for (row, symbols) in square.square.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            by_symbol_and_column[symbol][column] = row;
+        }
+    }
+    let columns_by_row: Vec<Vec<usize>> = (0..order).map(|column| (0..order).map(|row| square.square[row][column]).collect()).collect();
+
+    let views: [(&Vec<Vec<usize>>, ConjugateCell); 3] = [
+        (&square.square, |row, column, _| (row, column)),
+        (&columns_by_row, |column, row, _| (row, column)),
+        (&by_symbol_and_column, |_, column, row| (row, column))
+    ];
+    let mut trades = Vec::new();
+    for (table, cell) in views {
+        // inverse[a][c] = b such that table[a][b] = c
+        let mut inverse = vec![vec![0; order]; order];
+        for (a, line) in table.iter().enumerate() {
+            for (b, &c) in line.iter().enumerate() {
+                inverse[a][c] = b;
+            }
+        }
+        for a1 in 0..order {
+            for a2 in (a1 + 1)..order {
+                let mut visited = vec![false; order];
+                for start in 0..order {
+                    if visited[start] {
+                        continue
+                    }
+                    let mut trade = Vec::new();
+                    let mut b = start;
+                    while !visited[b] {
+                        visited[b] = true;
+                        trade.push(cell(a1, b, table[a1][b]));
+                        trade.push(cell(a2, b, table[a2][b]));
+                        b = inverse[a2][table[a1][b]];
+                    }
+                    trades.push(trade);
+                }
+            }
+        }
+    }
+    trades
+}
+
+/// Depth-limited search for at most `limit` cells that hit every trade.
+/// Returns the cells if there are some.
+///
+/// Branches on the cells of the unhit trade with the fewest allowed cells. Cells tried in earlier
+/// branches are forbidden in later ones, so every set is considered once.
+fn hitting_set(trades: &[Vec<usize>], cell_count: usize, limit: usize) -> Option<Vec<usize>> {
+    let mut trades_of_cell = vec![Vec::new(); cell_count];
+    for (index, trade) in trades.iter().enumerate() {
+        for &cell in trade {
+            trades_of_cell[cell].push(index);
+        }
+    }
+    let mut search = HittingSet {
+        trades,
+        trades_of_cell,
+        hits: vec![0; trades.len()],
+        chosen: Vec::new(),
+        forbidden: vec![false; cell_count],
+        limit
+    };
+    if search.extend() {
+        Some(search.chosen)
+    } else {
+        None
+    }
+}
+
+/// Backtracking state of a hitting set search, keeping count of how many chosen cells hit each trade.
+struct HittingSet<'a> {
+    trades: &'a [Vec<usize>],
+    trades_of_cell: Vec<Vec<usize>>,
+    hits: Vec<usize>,
+    chosen: Vec<usize>,
+    forbidden: Vec<bool>,
+    limit: usize
+}
+
+impl HittingSet<'_> {
+    fn choose(&mut self, cell: usize) {
+        self.chosen.push(cell);
+        for &trade in &self.trades_of_cell[cell] {
+            self.hits[trade] += 1;
+        }
+    }
+
+    fn unchoose(&mut self) {
+        let cell = self.chosen.pop().expect("ProgrammingError: nothing chosen to undo.");
+        for &trade in &self.trades_of_cell[cell] {
+            self.hits[trade] -= 1;
+        }
+    }
+
+    fn extend(&mut self) -> bool {
+        let unhit: Vec<usize> = (0..self.trades.len()).filter(|&trade| self.hits[trade] == 0).collect();
+        if unhit.is_empty() {
+            return true
+        }
+
+        // Trades sharing no cells need one chosen cell each, which bounds what it takes to finish.
+        let mut used = vec![false; self.forbidden.len()];
+        let mut disjoint = 0;
+        for &trade in &unhit {
+            let cells = &self.trades[trade];
+            if cells.iter().all(|&cell| !used[cell]) {
+                cells.iter().for_each(|&cell| used[cell] = true);
+                disjoint += 1;
+            }
+        }
+        if self.chosen.len() + disjoint > self.limit {
+            return false
+        }
+
+        let branch = unhit.iter()
+            .map(|&trade| self.trades[trade].iter().copied().filter(|&cell| !self.forbidden[cell]).collect::<Vec<usize>>())
+            .min_by_key(|allowed| allowed.len())
+            .expect("ProgrammingError: unhit trades can't be empty here.");
+        let mut newly_forbidden = Vec::new();
+        let mut found = false;
+        for cell in branch {
+            self.choose(cell);
+            if self.extend() {
+                found = true;
+                break
+            }
+            self.unchoose();
+            self.forbidden[cell] = true;
+            newly_forbidden.push(cell);
+        }
+        for cell in newly_forbidden {
+            self.forbidden[cell] = false;
+        }
+        found
+    }
+}
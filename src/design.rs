@@ -0,0 +1,400 @@
+//! Experimental designs: latin squares with their rows, columns, and symbols labeled by named factors.
+//!
+//! A latin square design blocks the experimental units two ways, e.g. subjects as rows and periods as
+//! columns, and assigns a treatment to each unit, so that every treatment is given once to every subject and
+//! once in every period. A `Design` keeps the square, which says which treatment goes where, together
+//! with the names of the three factors and of their levels, and writes the plan as a labeled table or CSV.
+//!
+//! Crossover designs give every subject every treatment in turn, and randomized complete block designs
+//! give every block every treatment in random order. All designs can also be written in long format, one
+//! record per experimental unit with a column per factor, which is what model fitting in R or Python expects.
+//!
+//! Sources:
+//!
+//! - [Latin square design, NIST/SEMATECH e-Handbook of Statistical Methods](https://www.itl.nist.gov/div898/handbook/pri/section3/pri3321.htm)
+//! - [Experimental designs balanced for the estimation of residual effects of treatments, E. J. Williams](https://doi.org/10.1071/CH9490149)
+//! - [Tidy data, Hadley Wickham](https://doi.org/10.18637/jss.v059.i10)
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::latin_square::LatinSquare;
+
+/// A named factor of a design with the names of its levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factor {
+    pub name: String,
+    pub levels: Vec<String>
+}
+
+impl Factor {
+    /// A factor with the given level names.
+    pub fn new(name: &str, levels: &[&str]) -> Factor {
+        Factor { name: name.to_string(), levels: levels.iter().map(|level| level.to_string()).collect() }
+    }
+
+    /// A factor whose levels are named after it and numbered from 1, e.g. "Period 1", "Period 2", ..
+    pub fn numbered(name: &str, count: usize) -> Factor {
+        Factor { name: name.to_string(), levels: (1..=count).map(|level| format!("{} {}", name, level)).collect() }
+    }
+}
+
+/// A latin square with factors for its rows, columns, and symbols.
+///
+/// ```
+/// use combinatorial_patterns::IncidenceCube;
+/// use combinatorial_patterns::design::{Design, Factor};
+///
+/// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+/// let design = Design::new(
+///     square,
+///     Factor::new("Subject", &["Ann", "Bo", "Cy"]),
+///     Factor::numbered("Period", 3),
+///     Factor::new("Treatment", &["Placebo", "Low", "High"])
+/// ).unwrap();
+/// assert_eq!(design.treatment(1, 2), "Placebo");
+/// assert_eq!(design.to_csv().lines().next(), Some("Subject,Period 1,Period 2,Period 3"));
+/// assert_eq!(design.to_string().lines().nth(1), Some("Ann      Placebo   Low       High"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Design {
+    square: LatinSquare,
+    rows: Factor,
+    columns: Factor,
+    treatments: Factor
+}
+
+impl Design {
+    /// Labels a square. Returns None unless every factor has as many levels as the order of the square.
+    pub fn new(square: LatinSquare, rows: Factor, columns: Factor, treatments: Factor) -> Option<Design> {
+        let order = square.size();
+        if rows.levels.len() != order || columns.levels.len() != order || treatments.levels.len() != order {
+            return None
+        }
+        Some(Design { square, rows, columns, treatments })
+    }
+
+    /// Labels a square with numbered rows, columns, and treatments.
+    pub fn numbered(square: LatinSquare) -> Design {
+        let order = square.size();
+        Design {
+            square,
+            rows: Factor::numbered("Row", order),
+            columns: Factor::numbered("Column", order),
+            treatments: Factor::numbered("Treatment", order)
+        }
+    }
+
+    /// The square assigning treatments to rows and columns.
+    pub fn square(&self) -> &LatinSquare {
+        &self.square
+    }
+
+    /// The factor of the rows.
+    pub fn rows(&self) -> &Factor {
+        &self.rows
+    }
+
+    /// The factor of the columns.
+    pub fn columns(&self) -> &Factor {
+        &self.columns
+    }
+
+    /// The factor of the symbols.
+    pub fn treatments(&self) -> &Factor {
+        &self.treatments
+    }
+
+    /// The name of the treatment at the given row and column.
+    pub fn treatment(&self, row: usize, column: usize) -> &str {
+        &self.treatments.levels[self.square.square[row][column]]
+    }
+
+    /// The plan as a table of strings: a header with the row factor's name and the column levels, then each
+    /// row level followed by its treatments.
+    fn table(&self) -> Vec<Vec<&str>> {
+        let mut table = vec![std::iter::once(self.rows.name.as_str()).chain(self.columns.levels.iter().map(|level| level.as_str())).collect()];
+        for (row, level) in self.rows.levels.iter().enumerate() {
+            let treatments = (0..self.square.size()).map(|column| self.treatment(row, column));
+            table.push(std::iter::once(level.as_str()).chain(treatments).collect());
+        }
+        table
+    }
+
+    /// The plan as CSV, laid out like the table `Display` writes, quoting fields as needed.
+    pub fn to_csv(&self) -> String {
+        csv(&self.table())
+    }
+}
+
+impl fmt::Display for Design {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", aligned(&self.table()))
+    }
+}
+
+/// A design in long format: one record per experimental unit, with a column per factor, as statistical
+/// software expects for model fitting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LongTable {
+    pub header: Vec<String>,
+    pub records: Vec<Vec<String>>
+}
+
+impl LongTable {
+    /// The table as CSV with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut table = vec![self.header.clone()];
+        table.extend(self.records.iter().cloned());
+        csv(&table)
+    }
+}
+
+/// Designs that can be written in long format.
+pub trait LongFormat {
+    fn to_long_format(&self) -> LongTable;
+}
+
+/// A bare square as records of (row, column, symbol), numbered from 0.
+impl LongFormat for LatinSquare {
+    fn to_long_format(&self) -> LongTable {
+        LongTable {
+            header: vec!["row".to_string(), "column".to_string(), "symbol".to_string()],
+            records: cells(self.size()).map(|(row, column)| {
+                vec![row.to_string(), column.to_string(), self.square[row][column].to_string()]
+            }).collect()
+        }
+    }
+}
+
+/// Squares of the same order superimposed, such as a Graeco-latin square from a pair of orthogonal squares,
+/// as records of (row, column, symbol 1, symbol 2, ..), numbered from 0.
+///
+/// ```
+/// use combinatorial_patterns::mols;
+/// use combinatorial_patterns::design::LongFormat;
+///
+/// let squares = mols::new_prime_power(3).unwrap();
+/// let table = squares[..2].to_long_format();
+/// assert_eq!(table.header, vec!["row", "column", "symbol 1", "symbol 2"]);
+/// assert_eq!(table.records.len(), 9);
+/// ```
+impl LongFormat for [LatinSquare] {
+    fn to_long_format(&self) -> LongTable {
+        let order = self.first().map(|square| square.size()).unwrap_or(0);
+        let mut header = vec!["row".to_string(), "column".to_string()];
+        header.extend((1..=self.len()).map(|index| format!("symbol {}", index)));
+        LongTable {
+            header,
+            records: cells(order).map(|(row, column)| {
+                let mut record = vec![row.to_string(), column.to_string()];
+                record.extend(self.iter().map(|square| square.square[row][column].to_string()));
+                record
+            }).collect()
+        }
+    }
+}
+
+/// Records of (row level, column level, treatment), under the names of the factors.
+///
+/// ```
+/// use combinatorial_patterns::IncidenceCube;
+/// use combinatorial_patterns::design::{Design, LongFormat};
+///
+/// let design = Design::numbered(IncidenceCube::new_cyclic(2).unwrap().as_latin_square());
+/// assert_eq!(design.to_long_format().to_csv(), "Row,Column,Treatment\n\
+///     Row 1,Column 1,Treatment 1\n\
+///     Row 1,Column 2,Treatment 2\n\
+///     Row 2,Column 1,Treatment 2\n\
+///     Row 2,Column 2,Treatment 1\n");
+/// ```
+impl LongFormat for Design {
+    fn to_long_format(&self) -> LongTable {
+        LongTable {
+            header: vec![self.rows.name.clone(), self.columns.name.clone(), self.treatments.name.clone()],
+            records: cells(self.square.size()).map(|(row, column)| {
+                vec![self.rows.levels[row].clone(), self.columns.levels[column].clone(), self.treatment(row, column).to_string()]
+            }).collect()
+        }
+    }
+}
+
+/// A crossover design: every subject receives every treatment, one per period, in its own sequence.
+///
+/// Williams designs balance first-order carryover: every treatment directly follows every other treatment
+/// equally often. For n treatments they take n sequences when n is even, and 2n when n is odd, the second n
+/// being the first n reversed.
+///
+/// ```
+/// use combinatorial_patterns::design::{CrossoverDesign, Factor, LongFormat};
+///
+/// let design = CrossoverDesign::williams(Factor::new("Treatment", &["A", "B", "C", "D"]));
+/// assert_eq!(design.sequence(0), vec!["A", "B", "D", "C"]);
+/// let table = design.to_long_format();
+/// assert_eq!(table.header, vec!["Subject", "Period", "Treatment"]);
+/// assert_eq!(table.records[1], vec!["Subject 1", "Period 2", "B"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossoverDesign {
+    /// Per subject, the treatment of each period.
+    sequences: Vec<Vec<usize>>,
+    subjects: Factor,
+    periods: Factor,
+    treatments: Factor
+}
+
+impl CrossoverDesign {
+    /// The Williams design for the given treatments, with numbered subjects and periods.
+    pub fn williams(treatments: Factor) -> CrossoverDesign {
+        let n = treatments.levels.len();
+        // 0, 1, n - 1, 2, n - 2, .. and its shifts.
+        let first: Vec<usize> = (0..n).map(|k| if k % 2 == 1 { k.div_ceil(2) } else { (n - k / 2) % n }).collect();
+        let mut sequences: Vec<Vec<usize>> = (0..n).map(|shift| first.iter().map(|&treatment| (treatment + shift) % n).collect()).collect();
+        if n % 2 == 1 {
+            let reversed: Vec<Vec<usize>> = sequences.iter().map(|sequence| sequence.iter().rev().copied().collect()).collect();
+            sequences.extend(reversed);
+        }
+        CrossoverDesign {
+            subjects: Factor::numbered("Subject", sequences.len()),
+            periods: Factor::numbered("Period", n),
+            treatments,
+            sequences
+        }
+    }
+
+    /// The treatment names a subject receives, by period.
+    pub fn sequence(&self, subject: usize) -> Vec<&str> {
+        self.sequences[subject].iter().map(|&treatment| self.treatments.levels[treatment].as_str()).collect()
+    }
+
+    /// The number of subjects.
+    pub fn subjects(&self) -> usize {
+        self.sequences.len()
+    }
+}
+
+/// Records of (subject, period, treatment).
+impl LongFormat for CrossoverDesign {
+    fn to_long_format(&self) -> LongTable {
+        LongTable {
+            header: vec![self.subjects.name.clone(), self.periods.name.clone(), self.treatments.name.clone()],
+            records: self.sequences.iter().enumerate().flat_map(|(subject, sequence)| {
+                sequence.iter().enumerate().map(move |(period, &treatment)| {
+                    vec![self.subjects.levels[subject].clone(), self.periods.levels[period].clone(), self.treatments.levels[treatment].clone()]
+                })
+            }).collect()
+        }
+    }
+}
+
+impl fmt::Display for CrossoverDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = vec![std::iter::once(self.subjects.name.as_str()).chain(self.periods.levels.iter().map(|level| level.as_str())).collect::<Vec<&str>>()];
+        for (subject, level) in self.subjects.levels.iter().enumerate() {
+            table.push(std::iter::once(level.as_str()).chain(self.sequence(subject)).collect());
+        }
+        write!(f, "{}", aligned(&table))
+    }
+}
+
+/// A randomized complete block design (RCBD): every block has one unit per treatment, with the treatments
+/// in random order.
+///
+/// ```
+/// use combinatorial_patterns::design::{BlockDesign, Factor, LongFormat};
+///
+/// let design = BlockDesign::randomized_complete(Factor::numbered("Block", 4), Factor::new("Variety", &["A", "B", "C"]), &mut rand::thread_rng());
+/// let table = design.to_long_format();
+/// assert_eq!(table.header, vec!["Block", "Unit", "Variety"]);
+/// assert_eq!(table.records.len(), 12);
+/// let mut first_block = design.block(0);
+/// first_block.sort();
+/// assert_eq!(first_block, vec!["A", "B", "C"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDesign {
+    /// Per block, the treatment of each unit.
+    blocks: Vec<Vec<usize>>,
+    block_factor: Factor,
+    treatments: Factor
+}
+
+impl BlockDesign {
+    /// Randomizes the order of the treatments within each block independently, drawing from `rng`.
+    pub fn randomized_complete(blocks: Factor, treatments: Factor, rng: &mut impl Rng) -> BlockDesign {
+        let plans = (0..blocks.levels.len()).map(|_| {
+            let mut order: Vec<usize> = (0..treatments.levels.len()).collect();
+            order.shuffle(rng);
+            order
+        }).collect();
+        BlockDesign { blocks: plans, block_factor: blocks, treatments }
+    }
+
+    /// The factor of the blocks.
+    pub fn block_factor(&self) -> &Factor {
+        &self.block_factor
+    }
+
+    /// The factor of the treatments.
+    pub fn treatments(&self) -> &Factor {
+        &self.treatments
+    }
+
+    /// The treatment names of a block, by unit.
+    pub fn block(&self, block: usize) -> Vec<&str> {
+        self.blocks[block].iter().map(|&treatment| self.treatments.levels[treatment].as_str()).collect()
+    }
+}
+
+/// Records of (block, unit, treatment), with the units of each block numbered from 1.
+impl LongFormat for BlockDesign {
+    fn to_long_format(&self) -> LongTable {
+        LongTable {
+            header: vec![self.block_factor.name.clone(), "Unit".to_string(), self.treatments.name.clone()],
+            records: self.blocks.iter().enumerate().flat_map(|(block, treatments)| {
+                treatments.iter().enumerate().map(move |(unit, &treatment)| {
+                    vec![self.block_factor.levels[block].clone(), (unit + 1).to_string(), self.treatments.levels[treatment].clone()]
+                })
+            }).collect()
+        }
+    }
+}
+
+/// Every (row, column) of a square of the given order, row by row.
+fn cells(order: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..order).flat_map(move |row| (0..order).map(move |column| (row, column)))
+}
+
+/// A table with its columns padded to line up.
+pub(crate) fn aligned(table: &[Vec<&str>]) -> String {
+    let widths: Vec<usize> = (0..table.first().map(|row| row.len()).unwrap_or(0))
+        .map(|column| table.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+    let lines: Vec<String> = table.iter().map(|row| {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:width$}", cell, width = width)).collect();
+        cells.join("  ").trim_end().to_string()
+    }).collect();
+    lines.join("\n")
+}
+
+/// A table as CSV, one line per row.
+pub(crate) fn csv<S: AsRef<str>>(table: &[Vec<S>]) -> String {
+    table.iter()
+        .map(|row| row.iter().map(|field| csv_field(field.as_ref())).collect::<Vec<String>>().join(","))
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// A CSV field, quoted if it holds a comma, quote, or line break.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,260 @@
+//! Isomorphism of block designs, by canonical labeling.
+//!
+//! Two designs are isomorphic when a relabeling of the points maps the blocks of one onto the blocks of the
+//! other. A canonical labeling picks one relabeling for every design, depending only on its structure, so
+//! that isomorphic designs get the same relabeled design, their canonical form, and comparing forms decides
+//! isomorphism. The labeling is found as nauty and its relatives find one:
+//!
+//! - Refinement: the points are coloured, at first all alike, and every block is coloured by the colours of
+//!   its points, every point by its colour and the colours of its blocks, until no colour splits further.
+//! - Individualization: while some colour has several points, each of the points of the first such colour
+//!   in turn is given a colour of its own, and the refinement runs again, branching into a search tree.
+//! - Every leaf, where all points have different colours, labels the points by their colours, and the leaf
+//!   whose relabeled blocks are the smallest gives the canonical labeling.
+//!
+//! Highly regular designs such as Steiner triple systems refine little at first, so the tree branches on a
+//! few points before the colours split. There is no pruning by automorphisms, so designs with many of them
+//! search the whole tree: Steiner triple systems of 15 points take a fraction of a second, and of 31 points
+//! several seconds.
+//!
+//! Sources:
+//!
+//! - Practical graph isomorphism, B. D. McKay, Congressus Numerantium 30 (1981)
+//! - Practical graph isomorphism, II, B. D. McKay, A. Piperno, Journal of Symbolic Computation 60 (2014)
+//! - Classification algorithms for codes and designs, P. Kaski, P. R. J. Östergård (2006)
+
+use crate::covering::{CoveringDesign, PackingDesign};
+use crate::polya::Permutation;
+use crate::resolvable::ResolvableDesign;
+use crate::triple_system::SteinerTripleSystem;
+
+/// A set of points 0..v with a list of blocks, each a set of points.
+pub trait IncidenceStructure {
+    /// The number of points v.
+    fn point_count(&self) -> usize;
+
+    /// The blocks, as lists of points.
+    fn block_list(&self) -> Vec<Vec<usize>>;
+}
+
+impl IncidenceStructure for SteinerTripleSystem {
+    fn point_count(&self) -> usize {
+        self.order()
+    }
+
+    fn block_list(&self) -> Vec<Vec<usize>> {
+        self.blocks().iter().map(|block| block.to_vec()).collect()
+    }
+}
+
+impl IncidenceStructure for CoveringDesign {
+    fn point_count(&self) -> usize {
+        self.points()
+    }
+
+    fn block_list(&self) -> Vec<Vec<usize>> {
+        self.blocks().to_vec()
+    }
+}
+
+impl IncidenceStructure for PackingDesign {
+    fn point_count(&self) -> usize {
+        self.points()
+    }
+
+    fn block_list(&self) -> Vec<Vec<usize>> {
+        self.blocks().to_vec()
+    }
+}
+
+/// The blocks of every replicate together.
+impl IncidenceStructure for ResolvableDesign {
+    fn point_count(&self) -> usize {
+        self.treatments()
+    }
+
+    fn block_list(&self) -> Vec<Vec<usize>> {
+        self.replicates().concat()
+    }
+}
+
+/// The canonical labeling of the design: the new label of every point.
+///
+/// ```
+/// use combinatorial_patterns::design_isomorphism;
+/// use combinatorial_patterns::triple_system::SteinerTripleSystem;
+///
+/// let system = SteinerTripleSystem::new(9).unwrap();
+/// let mut labeling = design_isomorphism::canonical_labeling(&system);
+/// labeling.sort_unstable();
+/// assert_eq!(labeling, (0..9).collect::<Vec<usize>>());
+/// ```
+pub fn canonical_labeling(design: &impl IncidenceStructure) -> Permutation {
+    let points = design.point_count();
+    let blocks = design.block_list();
+    let mut containing = vec![Vec::new(); points];
+    for (index, block) in blocks.iter().enumerate() {
+        for &point in block {
+            containing[point].push(index);
+        }
+    }
+    let structure = Structure { blocks, containing };
+    let mut best = None;
+    structure.search(structure.refine(vec![0; points]), &mut best);
+    best.map(|(_, labeling)| labeling).unwrap_or_default()
+}
+
+/// The design relabeled by its canonical labeling, with every block sorted and the blocks in order. Two
+/// designs are isomorphic exactly when they have the same canonical form.
+pub fn canonical_form(design: &impl IncidenceStructure) -> Vec<Vec<usize>> {
+    relabeled(&design.block_list(), &canonical_labeling(design))
+}
+
+/// Returns true if some relabeling of the points maps the blocks of one design onto those of the other.
+///
+/// ```
+/// use combinatorial_patterns::design_isomorphism;
+/// use combinatorial_patterns::covering::PackingDesign;
+/// use combinatorial_patterns::triple_system::SteinerTripleSystem;
+///
+/// // Bose's system of order 15 with its points in some other order.
+/// let system = SteinerTripleSystem::new(15).unwrap();
+/// let shuffled: Vec<_> = system.blocks().iter().map(|block| block.map(|point| (point * 7 + 3) % 15)).collect();
+/// let shuffled = SteinerTripleSystem::from_blocks(15, shuffled).unwrap();
+/// assert!(design_isomorphism::is_isomorphic(&system, &shuffled));
+///
+/// // Two perfect matchings are isomorphic, a matching and a path are not.
+/// let matching = PackingDesign::new(4, 2, 2, vec![vec![0, 1], vec![2, 3]]).unwrap();
+/// let other = PackingDesign::new(4, 2, 2, vec![vec![0, 2], vec![1, 3]]).unwrap();
+/// let path = PackingDesign::new(4, 2, 2, vec![vec![0, 1], vec![1, 2]]).unwrap();
+/// assert!(design_isomorphism::is_isomorphic(&matching, &other));
+/// assert!(!design_isomorphism::is_isomorphic(&matching, &path));
+/// ```
+pub fn is_isomorphic(first: &impl IncidenceStructure, second: &impl IncidenceStructure) -> bool {
+    isomorphism(first, second).is_some()
+}
+
+/// A relabeling of the points of the first design that gives the second, if they are isomorphic.
+pub fn isomorphism(first: &impl IncidenceStructure, second: &impl IncidenceStructure) -> Option<Permutation> {
+    if first.point_count() != second.point_count() || first.block_list().len() != second.block_list().len() {
+        return None
+    }
+    let (first_labeling, second_labeling) = (canonical_labeling(first), canonical_labeling(second));
+    if relabeled(&first.block_list(), &first_labeling) != relabeled(&second.block_list(), &second_labeling) {
+        return None
+    }
+    let mut second_point = vec![0; second_labeling.len()];
+    for (point, &label) in second_labeling.iter().enumerate() {
+        second_point[label] = point;
+    }
+    Some(first_labeling.iter().map(|&label| second_point[label]).collect())
+}
+
+/// Sorts designs into isomorphism classes, returning the index of the first design of each class, in order
+/// of appearance, with the number of designs in the class.
+///
+/// ```
+/// use combinatorial_patterns::design_isomorphism;
+/// use combinatorial_patterns::covering::PackingDesign;
+///
+/// let designs: Vec<PackingDesign> = [vec![vec![0, 1], vec![2, 3]], vec![vec![0, 1], vec![1, 2]], vec![vec![1, 3], vec![0, 2]]]
+///     .into_iter()
+///     .map(|blocks| PackingDesign::new(4, 2, 2, blocks).unwrap())
+///     .collect();
+/// assert_eq!(design_isomorphism::classify(&designs), vec![(0, 2), (1, 1)]);
+/// ```
+pub fn classify<D: IncidenceStructure>(designs: &[D]) -> Vec<(usize, usize)> {
+    let mut forms: Vec<(usize, Vec<Vec<usize>>)> = Vec::new();
+    let mut classes: Vec<(usize, usize)> = Vec::new();
+    for (index, design) in designs.iter().enumerate() {
+        let form = (design.point_count(), canonical_form(design));
+        match forms.iter().position(|existing| *existing == form) {
+            Some(class) => classes[class].1 += 1,
+            None => {
+                forms.push(form);
+                classes.push((index, 1));
+            }
+        }
+    }
+    classes
+}
+
+/// The blocks with every point relabeled, each sorted, in order.
+fn relabeled(blocks: &[Vec<usize>], labeling: &[usize]) -> Vec<Vec<usize>> {
+    let mut relabeled: Vec<Vec<usize>> = blocks.iter().map(|block| {
+        let mut block: Vec<usize> = block.iter().map(|&point| labeling[point]).collect();
+        block.sort_unstable();
+        block
+    }).collect();
+    relabeled.sort_unstable();
+    relabeled
+}
+
+/// The blocks of a design and the blocks through every point.
+struct Structure {
+    blocks: Vec<Vec<usize>>,
+    containing: Vec<Vec<usize>>
+}
+
+impl Structure {
+    /// Splits the colours of the points until every point of a colour has blocks of the same colours. The
+    /// colours are numbered from 0 in an order that does not depend on the labels.
+    fn refine(&self, mut colours: Vec<usize>) -> Vec<usize> {
+        let mut count = distinct(&colours);
+        loop {
+            let block_colours = ranks(&self.blocks.iter().map(|block| {
+                let mut signature: Vec<usize> = block.iter().map(|&point| colours[point]).collect();
+                signature.sort_unstable();
+                signature
+            }).collect::<Vec<_>>());
+            colours = ranks(&self.containing.iter().zip(&colours).map(|(blocks, &colour)| {
+                let mut signature: Vec<usize> = blocks.iter().map(|&block| block_colours[block]).collect();
+                signature.sort_unstable();
+                (colour, signature)
+            }).collect::<Vec<_>>());
+            let refined = distinct(&colours);
+            if refined == count {
+                return colours
+            }
+            count = refined;
+        }
+    }
+
+    /// Searches the tree below the colouring, keeping the smallest relabeled blocks found with their labeling.
+    fn search(&self, colours: Vec<usize>, best: &mut Option<(Vec<Vec<usize>>, Permutation)>) {
+        let points = colours.len();
+        let mut sizes = vec![0; points];
+        for &colour in &colours {
+            sizes[colour] += 1;
+        }
+        let Some(target) = (0..points).find(|&colour| sizes[colour] > 1) else {
+            let form = relabeled(&self.blocks, &colours);
+            if best.as_ref().is_none_or(|(smallest, _)| form < *smallest) {
+                *best = Some((form, colours));
+            }
+            return
+        };
+        for point in (0..points).filter(|&point| colours[point] == target) {
+            // The point goes first within its colour, the rest of the colour after it.
+            let individualized = colours.iter().enumerate().map(|(other, &colour)| {
+                2 * colour + usize::from(colour == target && other != point)
+            }).collect();
+            self.search(self.refine(individualized), best);
+        }
+    }
+}
+
+fn distinct(colours: &[usize]) -> usize {
+    let mut sorted = colours.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted.len()
+}
+
+/// The rank of every value among the distinct values, from 0.
+fn ranks<T: Ord + Clone>(values: &[T]) -> Vec<usize> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    values.iter().map(|value| sorted.binary_search(value).expect("ProgrammingError: every value is in the sorted list.")).collect()
+}
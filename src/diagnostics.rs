@@ -0,0 +1,199 @@
+//! Balance diagnostics: how evenly a latin square spreads its symbols, beyond being latin.
+//!
+//! Every latin square balances symbols over rows and columns, but experiments often care about more. With
+//! rows as subjects and columns as periods, the ordered pairs of horizontally adjacent symbols are the
+//! carryover effects of a crossover design, balanced when every ordered pair of distinct symbols occurs
+//! equally often, as in row complete squares. Vertically adjacent pairs matter in field trials, where
+//! neighbouring plots interact, as do symbols repeated at the corners of a cell, and the diagonals show
+//! trends across the field. The spread of a symbol is the mean distance between its cells: small means
+//! clumped.
+//!
+//! The counts of ordered pairs are summarized by their range and by the chi-square statistic
+//! sum((count - e)^2 / e) against the expected count e of a perfectly balanced square, which is 0 exactly
+//! when the counts are balanced.
+//!
+//! Sources:
+//!
+//! - Experimental designs balanced for the estimation of residual effects of treatments, E. J. Williams, Australian Journal of Scientific Research 2 (1949)
+//! - Neighbour balance in field experiments, in Design of comparative experiments, R. A. Bailey
+
+use std::fmt;
+
+use crate::latin_square::LatinSquare;
+
+/// `counts[a][b]` is the number of cells with symbol a immediately left of a cell with symbol b.
+///
+/// ```
+/// use combinatorial_patterns::{diagnostics, IncidenceCube};
+///
+/// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+/// let counts = diagnostics::row_adjacency(&square);
+/// // A cyclic square always steps to the next symbol, so 0 comes before 1 in the three rows that don't end in 0.
+/// assert_eq!(counts[0][1], 3);
+/// assert_eq!(counts[0][2], 0);
+/// ```
+pub fn row_adjacency(square: &LatinSquare) -> Vec<Vec<usize>> {
+    let n = square.size();
+    let mut counts = vec![vec![0; n]; n];
+    for row in &square.square {
+        for pair in row.windows(2) {
+            counts[pair[0]][pair[1]] += 1;
+        }
+    }
+    counts
+}
+
+/// `counts[a][b]` is the number of cells with symbol a immediately above a cell with symbol b.
+pub fn column_adjacency(square: &LatinSquare) -> Vec<Vec<usize>> {
+    let n = square.size();
+    let mut counts = vec![vec![0; n]; n];
+    for rows in square.square.windows(2) {
+        for (&above, &below) in rows[0].iter().zip(&rows[1]) {
+            counts[above][below] += 1;
+        }
+    }
+    counts
+}
+
+/// How often each symbol appears on the main diagonal and on the anti-diagonal.
+pub fn diagonal_counts(square: &LatinSquare) -> (Vec<usize>, Vec<usize>) {
+    let n = square.size();
+    let mut main = vec![0; n];
+    let mut anti = vec![0; n];
+    for index in 0..n {
+        main[square.square[index][index]] += 1;
+        anti[square.square[index][n - 1 - index]] += 1;
+    }
+    (main, anti)
+}
+
+/// The number of pairs of cells with the same symbol that touch at a corner.
+pub fn corner_repeats(square: &LatinSquare) -> usize {
+    square.square.windows(2).map(|rows| {
+        (1..rows[0].len()).filter(|&column| rows[0][column - 1] == rows[1][column]).count()
+            + (1..rows[0].len()).filter(|&column| rows[0][column] == rows[1][column - 1]).count()
+    }).sum()
+}
+
+/// The mean Euclidean distance between the cells of each symbol, over all pairs of its cells.
+pub fn symbol_spread(square: &LatinSquare) -> Vec<f64> {
+    let n = square.size();
+    let mut cells = vec![Vec::new(); n];
+    for (row, symbols) in square.square.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            cells[symbol].push((row as f64, column as f64));
+        }
+    }
+    cells.iter().map(|cells| {
+        let pairs = cells.len() * cells.len().saturating_sub(1) / 2;
+        if pairs == 0 {
+            return 0.0
+        }
+        let total: f64 = cells.iter().enumerate()
+            .flat_map(|(index, a)| cells[(index + 1)..].iter().map(move |b| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()))
+            .sum();
+        total / pairs as f64
+    }).collect()
+}
+
+/// A summary of the counts of ordered pairs of distinct symbols.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairBalance {
+    /// The fewest times any ordered pair of distinct symbols occurs.
+    pub min: usize,
+    /// The most times any ordered pair of distinct symbols occurs.
+    pub max: usize,
+    /// The chi-square statistic against equal counts for all ordered pairs of distinct symbols.
+    pub chi_square: f64
+}
+
+impl PairBalance {
+    /// Summarizes counts of ordered pairs, such as those of `row_adjacency`, ignoring pairs of equal symbols.
+    pub fn of(counts: &[Vec<usize>]) -> PairBalance {
+        let off_diagonal: Vec<usize> = counts.iter().enumerate()
+            .flat_map(|(a, row)| row.iter().enumerate().filter(move |&(b, _)| a != b).map(|(_, &count)| count))
+            .collect();
+        if off_diagonal.is_empty() {
+            return PairBalance { min: 0, max: 0, chi_square: 0.0 }
+        }
+        let expected = off_diagonal.iter().sum::<usize>() as f64 / off_diagonal.len() as f64;
+        let chi_square = if expected > 0.0 {
+            off_diagonal.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum()
+        } else {
+            0.0
+        };
+        PairBalance {
+            min: *off_diagonal.iter().min().expect("ProgrammingError: the counts are not empty."),
+            max: *off_diagonal.iter().max().expect("ProgrammingError: the counts are not empty."),
+            chi_square
+        }
+    }
+
+    /// Returns true if every ordered pair of distinct symbols occurs equally often.
+    pub fn is_balanced(&self) -> bool {
+        self.min == self.max
+    }
+}
+
+/// The balance statistics of a square. `Display` writes them as a short report.
+///
+/// ```
+/// use combinatorial_patterns::{diagnostics, IncidenceCube, LatinSquare};
+///
+/// let square = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+/// let report = diagnostics::report(&square);
+/// assert!(!report.row_adjacency.is_balanced());
+/// assert_eq!(report.main_diagonal_symbols, 5);
+/// assert!(report.to_string().starts_with("Balance of a latin square of order 5"));
+///
+/// let random = LatinSquare::new_random(6, &mut rand::thread_rng()).unwrap();
+/// assert!(diagnostics::report(&random).spread.0 > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    pub order: usize,
+    /// The balance of horizontally adjacent pairs, the carryover effects of a crossover design.
+    pub row_adjacency: PairBalance,
+    /// The balance of vertically adjacent pairs.
+    pub column_adjacency: PairBalance,
+    /// The number of distinct symbols on the main diagonal.
+    pub main_diagonal_symbols: usize,
+    /// The number of distinct symbols on the anti-diagonal.
+    pub anti_diagonal_symbols: usize,
+    /// Pairs of equal symbols touching at a corner.
+    pub corner_repeats: usize,
+    /// The smallest, mean and largest spread over the symbols.
+    pub spread: (f64, f64, f64)
+}
+
+/// Computes the balance statistics of a square.
+pub fn report(square: &LatinSquare) -> BalanceReport {
+    let (main, anti) = diagonal_counts(square);
+    let spread = symbol_spread(square);
+    let (least, most) = spread.iter().fold((f64::INFINITY, 0.0_f64), |(least, most), &value| (least.min(value), most.max(value)));
+    let mean = if spread.is_empty() { 0.0 } else { spread.iter().sum::<f64>() / spread.len() as f64 };
+    BalanceReport {
+        order: square.size(),
+        row_adjacency: PairBalance::of(&row_adjacency(square)),
+        column_adjacency: PairBalance::of(&column_adjacency(square)),
+        main_diagonal_symbols: main.iter().filter(|&&count| count > 0).count(),
+        anti_diagonal_symbols: anti.iter().filter(|&&count| count > 0).count(),
+        corner_repeats: corner_repeats(square),
+        spread: (if spread.is_empty() { 0.0 } else { least }, mean, most)
+    }
+}
+
+impl fmt::Display for BalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs = |balance: &PairBalance| format!(
+            "each ordered pair {} to {} times, chi-square {:.2}{}",
+            balance.min, balance.max, balance.chi_square, if balance.is_balanced() { ", balanced" } else { "" }
+        );
+        writeln!(f, "Balance of a latin square of order {}", self.order)?;
+        writeln!(f, "Row adjacency (carryover): {}", pairs(&self.row_adjacency))?;
+        writeln!(f, "Column adjacency: {}", pairs(&self.column_adjacency))?;
+        writeln!(f, "Diagonals: {} of {} symbols on the main diagonal, {} on the anti-diagonal", self.main_diagonal_symbols, self.order, self.anti_diagonal_symbols)?;
+        writeln!(f, "Corner repeats: {}", self.corner_repeats)?;
+        write!(f, "Symbol spread: {:.2} to {:.2}, mean {:.2}", self.spread.0, self.spread.2, self.spread.1)
+    }
+}
@@ -0,0 +1,567 @@
+//! A backtracking engine for exhaustive searches.
+//!
+//! A search implements `Search`: at every state it either is a solution or offers choices, each of which it
+//! can apply and undo. The engine walks the tree of choices depth first, and the hooks cut it down:
+//!
+//! - `is_viable` prunes a state that cannot lead to a solution, checked after every choice.
+//! - `is_canonical` is the symmetry filter, pruning a state that is not the chosen representative of the
+//!   states equivalent to it, so each solution is found once up to the symmetry.
+//! - The callback gets every solution and returns `Control::Stop` to end the search early.
+//! - A node limit makes the search give up, as the design searches of the crate do in bounded time.
+//!
+//! For work splitting, `split` lists the prefixes of choices down to some depth, and `run_from` searches
+//! below one of them, so the prefixes can be handed to separate threads or machines. The subtrees below the
+//! prefixes are disjoint and together hold every solution.
+//!
+//! For searches that run for days, `resume` saves a `Checkpoint` every so many nodes: the position of
+//! the next choice to try at every depth, with the counts so far, which can be written to a file as text.
+//! Resuming from it walks back down to that position and goes on, as long as the search offers the same
+//! choices in the same order, which every search in the crate does.
+//!
+//! Along with every checkpoint comes a `Progress`, the estimated fraction of the tree searched: each choice
+//! at a state is taken to hold an equal share of the state's subtree, so the position in the tree gives the
+//! fraction before it. Subtrees are rarely equal, but the estimate improves as the search goes on, and
+//! with the time spent it tells a run of hours from one of weeks. `run_with_progress` reports it without
+//! checkpoints.
+//!
+//! The completion search of `PartialLatinSquare`, which also enumerates all latin squares from the empty one,
+//! the starter search behind perfect one-factorizations and Room squares, and `transversals` all run on it.
+//!
+//! ```
+//! use combinatorial_patterns::enumerate::{self, Node, Search};
+//!
+//! // The permutations of 0..n, up to reversal: a permutation is kept if it starts lower than it ends.
+//! struct Permutations { n: usize, prefix: Vec<usize> }
+//!
+//! impl Search for Permutations {
+//!     type Choice = usize;
+//!
+//!     fn expand(&mut self) -> Node<usize> {
+//!         if self.prefix.len() == self.n {
+//!             return Node::Solution
+//!         }
+//!         Node::Branch((0..self.n).filter(|value| !self.prefix.contains(value)).collect())
+//!     }
+//!
+//!     fn apply(&mut self, value: &usize) {
+//!         self.prefix.push(*value);
+//!     }
+//!
+//!     fn undo(&mut self, _: &usize) {
+//!         self.prefix.pop();
+//!     }
+//!
+//!     fn is_canonical(&self) -> bool {
+//!         self.prefix.len() < self.n || self.prefix[0] < self.prefix[self.n - 1]
+//!     }
+//! }
+//!
+//! let mut search = Permutations { n: 4, prefix: Vec::new() };
+//! assert_eq!(enumerate::count(&mut search, u64::MAX).solutions, 12);
+//!
+//! // Split into subproblems by the first two values, and search each on its own.
+//! let prefixes = enumerate::split(&mut search, 2);
+//! assert_eq!(prefixes.len(), 12);
+//! let total: u64 = prefixes.iter().map(|prefix| enumerate::run_from(&mut search, prefix, u64::MAX, |_| enumerate::Control::Continue).solutions).sum();
+//! assert_eq!(total, 12);
+//! ```
+//!
+//! Sources:
+//!
+//! - Backtrack programming, S. W. Golomb, L. D. Baumert, Journal of the ACM 12 (1965)
+//! - The Art of Computer Programming, volume 4B, D. E. Knuth, section 7.2.2
+//! - Estimating the efficiency of backtrack programs, D. E. Knuth, Mathematics of Computation 29 (1975)
+
+use std::time::Duration;
+
+use crate::latin_square::LatinSquare;
+
+/// What the search offers at a state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<C> {
+    /// The state is a solution.
+    Solution,
+    /// The choices to try, in order. No choices is a dead end.
+    Branch(Vec<C>)
+}
+
+/// A state for the engine to search from, changed in place by applying and undoing choices.
+pub trait Search {
+    type Choice;
+
+    /// Whether the current state is a solution, or else the choices from it.
+    fn expand(&mut self) -> Node<Self::Choice>;
+
+    /// Makes the choice, from the state that offered it.
+    fn apply(&mut self, choice: &Self::Choice);
+
+    /// Takes back the choice, the last one applied.
+    fn undo(&mut self, choice: &Self::Choice);
+
+    /// Returns false if the state can no longer lead to a solution.
+    fn is_viable(&self) -> bool {
+        true
+    }
+
+    /// Returns false if the state is not the representative of its equivalent states.
+    fn is_canonical(&self) -> bool {
+        true
+    }
+}
+
+/// Whether the search goes on after a solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop
+}
+
+/// How a search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Every state was searched.
+    Exhausted,
+    /// The callback stopped the search.
+    Stopped,
+    /// The node limit was reached.
+    LimitReached
+}
+
+/// The result of a search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub status: Status,
+    pub solutions: u64,
+    /// The number of choices applied.
+    pub nodes: u64
+}
+
+/// Where a search was, to go on from there: the index of the choice taken at every depth, ending with the
+/// next choice to try, and the solutions and nodes counted before.
+///
+/// ```
+/// use combinatorial_patterns::enumerate::Checkpoint;
+///
+/// let checkpoint = Checkpoint { path: vec![2, 0, 5], solutions: 17, nodes: 1000 };
+/// assert_eq!(checkpoint.to_text(), "solutions 17\nnodes 1000\npath 2 0 5\n");
+/// assert_eq!(Checkpoint::from_text(&checkpoint.to_text()), Some(checkpoint));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Checkpoint {
+    pub path: Vec<usize>,
+    pub solutions: u64,
+    pub nodes: u64
+}
+
+impl Checkpoint {
+    /// The start of the search, with nothing counted yet.
+    pub fn start() -> Checkpoint {
+        Checkpoint::default()
+    }
+
+    /// The checkpoint as three lines of text, for saving to a file.
+    pub fn to_text(&self) -> String {
+        let path: String = self.path.iter().map(|index| format!(" {}", index)).collect();
+        format!("solutions {}\nnodes {}\npath{}\n", self.solutions, self.nodes, path)
+    }
+
+    /// Reads a checkpoint written by `to_text`, or returns None if the text is not one.
+    pub fn from_text(text: &str) -> Option<Checkpoint> {
+        let mut lines = text.lines();
+        let mut field = |name: &str| lines.next()?.strip_prefix(name).map(str::trim);
+        let solutions = field("solutions")?.parse().ok()?;
+        let nodes = field("nodes")?.parse().ok()?;
+        let path = field("path")?.split_whitespace().map(|index| index.parse().ok()).collect::<Option<_>>()?;
+        Some(Checkpoint { path, solutions, nodes })
+    }
+}
+
+/// How far a search has come.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The estimated fraction of the tree searched, from 0 to 1.
+    pub fraction: f64,
+    pub solutions: u64,
+    pub nodes: u64
+}
+
+impl Progress {
+    /// The estimated number of nodes of the whole search, or None before any progress.
+    pub fn estimated_nodes(&self) -> Option<u64> {
+        if self.fraction > 0.0 { Some((self.nodes as f64 / self.fraction) as u64) } else { None }
+    }
+
+    /// The estimated time left, given the time spent so far on the whole search, or None before any
+    /// progress.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use combinatorial_patterns::enumerate::Progress;
+    ///
+    /// let progress = Progress { fraction: 0.25, solutions: 10, nodes: 1000 };
+    /// assert_eq!(progress.estimated_nodes(), Some(4000));
+    /// assert_eq!(progress.remaining(Duration::from_secs(60)), Some(Duration::from_secs(180)));
+    /// ```
+    pub fn remaining(&self, elapsed: Duration) -> Option<Duration> {
+        if self.fraction > 0.0 { Some(elapsed.mul_f64((1.0 - self.fraction) / self.fraction)) } else { None }
+    }
+}
+
+/// Searches from the current state like `run`, and every `interval` choices hands the progress to
+/// `report`.
+///
+/// ```
+/// use combinatorial_patterns::enumerate::{self, Control, Node, Search};
+///
+/// // Binary strings of length 8: the tree is balanced, so the estimate is exact.
+/// struct Strings { bits: Vec<bool> }
+///
+/// impl Search for Strings {
+///     type Choice = bool;
+///
+///     fn expand(&mut self) -> Node<bool> {
+///         if self.bits.len() == 8 { Node::Solution } else { Node::Branch(vec![false, true]) }
+///     }
+///
+///     fn apply(&mut self, bit: &bool) {
+///         self.bits.push(*bit);
+///     }
+///
+///     fn undo(&mut self, _: &bool) {
+///         self.bits.pop();
+///     }
+/// }
+///
+/// let mut reports = Vec::new();
+/// let outcome = enumerate::run_with_progress(&mut Strings { bits: Vec::new() }, u64::MAX, 10, |_| Control::Continue, |progress| {
+///     reports.push(progress.fraction);
+/// });
+/// assert_eq!(outcome.solutions, 256);
+/// assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+/// assert!(reports.iter().all(|&fraction| (0.0..1.0).contains(&fraction)));
+/// ```
+pub fn run_with_progress<S: Search>(
+    search: &mut S,
+    node_limit: u64,
+    interval: u64,
+    visit: impl FnMut(&S) -> Control,
+    mut report: impl FnMut(&Progress)
+) -> Outcome {
+    resume(search, &Checkpoint::start(), node_limit, interval, visit, |_, progress| report(progress)).0
+}
+
+/// Searches from the checkpoint, handing every solution to `visit`, saving a checkpoint and the progress
+/// with `save` every `interval` choices, and stopping after `node_limit` choices. The outcome counts the checkpoint's
+/// solutions and nodes too. Returns the checkpoint to resume from, unless the search was exhausted.
+///
+/// ```
+/// use combinatorial_patterns::enumerate::{self, Checkpoint, Control, Node, Search};
+///
+/// // Binary strings of length 10.
+/// struct Strings { bits: Vec<bool> }
+///
+/// impl Search for Strings {
+///     type Choice = bool;
+///
+///     fn expand(&mut self) -> Node<bool> {
+///         if self.bits.len() == 10 { Node::Solution } else { Node::Branch(vec![false, true]) }
+///     }
+///
+///     fn apply(&mut self, bit: &bool) {
+///         self.bits.push(*bit);
+///     }
+///
+///     fn undo(&mut self, _: &bool) {
+///         self.bits.pop();
+///     }
+/// }
+///
+/// // Interrupted every 100 nodes, and resumed from the last checkpoint each time.
+/// let mut search = Strings { bits: Vec::new() };
+/// let mut checkpoint = Checkpoint::start();
+/// let mut seen = Vec::new();
+/// loop {
+///     let (outcome, next) = enumerate::resume(&mut search, &checkpoint, 100, 10, |search| {
+///         seen.push(search.bits.clone());
+///         Control::Continue
+///     }, |_, _| {});
+///     match next {
+///         Some(next) => checkpoint = Checkpoint::from_text(&next.to_text()).unwrap(),
+///         None => {
+///             assert_eq!(outcome.solutions, 1024);
+///             break
+///         }
+///     }
+/// }
+/// assert_eq!(seen.len(), 1024);
+/// seen.dedup();
+/// assert_eq!(seen.len(), 1024);
+/// ```
+pub fn resume<S: Search>(
+    search: &mut S,
+    checkpoint: &Checkpoint,
+    node_limit: u64,
+    interval: u64,
+    mut visit: impl FnMut(&S) -> Control,
+    mut save: impl FnMut(&Checkpoint, &Progress)
+) -> (Outcome, Option<Checkpoint>) {
+    let mut walk = Walk {
+        outcome: Outcome { status: Status::Exhausted, solutions: checkpoint.solutions, nodes: checkpoint.nodes },
+        path: Vec::new(),
+        widths: Vec::new(),
+        limit: checkpoint.nodes.saturating_add(node_limit),
+        interval: interval.max(1),
+        since_save: 0,
+        next: None
+    };
+    walk.descend(search, &checkpoint.path, &mut visit, &mut save);
+    (walk.outcome, walk.next)
+}
+
+/// The progress of `resume`.
+struct Walk {
+    outcome: Outcome,
+    /// The index of the choice taken at every depth above the current state.
+    path: Vec<usize>,
+    /// The number of choices at every depth above the current state.
+    widths: Vec<usize>,
+    /// The total node count to stop at.
+    limit: u64,
+    interval: u64,
+    since_save: u64,
+    /// The checkpoint to resume from, once the search has ended early.
+    next: Option<Checkpoint>
+}
+
+impl Walk {
+    /// The checkpoint of the current state with `index` as the next choice.
+    fn checkpoint(&self, index: usize) -> Checkpoint {
+        let mut path = self.path.clone();
+        path.push(index);
+        Checkpoint { path, solutions: self.outcome.solutions, nodes: self.outcome.nodes }
+    }
+
+    /// The progress of the current state with `index` of its `width` choices as the next choice.
+    fn progress(&self, index: usize, width: usize) -> Progress {
+        let mut fraction = 0.0;
+        let mut share = 1.0;
+        for (&taken, &choices) in self.path.iter().zip(&self.widths).chain([(&index, &width)]) {
+            fraction += share * taken as f64 / choices as f64;
+            share /= choices as f64;
+        }
+        Progress { fraction, solutions: self.outcome.solutions, nodes: self.outcome.nodes }
+    }
+
+    /// Searches below the current state, first walking down `resume_path` if it is not empty. Returns false
+    /// once the search is to end.
+    fn descend<S: Search>(&mut self, search: &mut S, resume_path: &[usize], visit: &mut impl FnMut(&S) -> Control, save: &mut impl FnMut(&Checkpoint, &Progress)) -> bool {
+        let choices = match search.expand() {
+            Node::Solution => {
+                self.outcome.solutions += 1;
+                if visit(search) == Control::Stop {
+                    self.outcome.status = Status::Stopped;
+                    // Go on after this solution, from the next choice of the state above it.
+                    self.next = self.path.split_last().map(|(&last, above)| {
+                        let mut path = above.to_vec();
+                        path.push(last + 1);
+                        Checkpoint { path, solutions: self.outcome.solutions, nodes: self.outcome.nodes }
+                    });
+                    return false
+                }
+                return true
+            },
+            Node::Branch(choices) => choices
+        };
+        let start = resume_path.first().copied().unwrap_or(0);
+        for (index, choice) in choices.iter().enumerate().skip(start) {
+            let resuming = index == start && resume_path.len() > 1;
+            if !resuming {
+                if self.outcome.nodes >= self.limit {
+                    self.outcome.status = Status::LimitReached;
+                    self.next = Some(self.checkpoint(index));
+                    return false
+                }
+                if self.since_save >= self.interval {
+                    save(&self.checkpoint(index), &self.progress(index, choices.len()));
+                    self.since_save = 0;
+                }
+                self.outcome.nodes += 1;
+                self.since_save += 1;
+            }
+            search.apply(choice);
+            // The choice on the way back down was checked when it was first made.
+            let keep_going = if resuming || (search.is_viable() && search.is_canonical()) {
+                self.path.push(index);
+                self.widths.push(choices.len());
+                let below = if resuming { &resume_path[1..] } else { &[] };
+                let keep_going = self.descend(search, below, visit, save);
+                self.path.pop();
+                self.widths.pop();
+                keep_going
+            } else {
+                true
+            };
+            search.undo(choice);
+            if !keep_going {
+                return false
+            }
+        }
+        true
+    }
+}
+
+/// Searches from the current state, handing every solution to `visit`, and giving up after applying
+/// `node_limit` choices. The state is back as it was after the search.
+pub fn run<S: Search>(search: &mut S, node_limit: u64, mut visit: impl FnMut(&S) -> Control) -> Outcome {
+    let mut outcome = Outcome { status: Status::Exhausted, solutions: 0, nodes: 0 };
+    descend(search, node_limit, &mut visit, &mut outcome);
+    outcome
+}
+
+/// Counts the solutions below the current state, giving up after `node_limit` choices.
+pub fn count<S: Search>(search: &mut S, node_limit: u64) -> Outcome {
+    run(search, node_limit, |_| Control::Continue)
+}
+
+/// Searches below the prefix of choices, as listed by `split`, applying it first and undoing it after. The
+/// prefix is not checked against the hooks again.
+pub fn run_from<S: Search>(search: &mut S, prefix: &[S::Choice], node_limit: u64, visit: impl FnMut(&S) -> Control) -> Outcome {
+    for choice in prefix {
+        search.apply(choice);
+    }
+    let outcome = run(search, node_limit, visit);
+    for choice in prefix.iter().rev() {
+        search.undo(choice);
+    }
+    outcome
+}
+
+/// The prefixes of `depth` choices that pass the hooks, and the shorter ones that end at a solution, in the
+/// order of the search.
+pub fn split<S: Search>(search: &mut S, depth: usize) -> Vec<Vec<S::Choice>>
+where
+    S::Choice: Clone
+{
+    let mut prefixes = Vec::new();
+    let mut prefix = Vec::new();
+    collect_prefixes(search, depth, &mut prefix, &mut prefixes);
+    prefixes
+}
+
+fn collect_prefixes<S: Search>(search: &mut S, depth: usize, prefix: &mut Vec<S::Choice>, prefixes: &mut Vec<Vec<S::Choice>>)
+where
+    S::Choice: Clone
+{
+    if prefix.len() == depth {
+        prefixes.push(prefix.clone());
+        return
+    }
+    match search.expand() {
+        Node::Solution => prefixes.push(prefix.clone()),
+        Node::Branch(choices) => {
+            for choice in choices {
+                search.apply(&choice);
+                if search.is_viable() && search.is_canonical() {
+                    prefix.push(choice.clone());
+                    collect_prefixes(search, depth, prefix, prefixes);
+                    prefix.pop();
+                }
+                search.undo(&choice);
+            }
+        }
+    }
+}
+
+/// Returns false once the search is to end.
+fn descend<S: Search>(search: &mut S, node_limit: u64, visit: &mut impl FnMut(&S) -> Control, outcome: &mut Outcome) -> bool {
+    match search.expand() {
+        Node::Solution => {
+            outcome.solutions += 1;
+            if visit(search) == Control::Stop {
+                outcome.status = Status::Stopped;
+                return false
+            }
+            true
+        },
+        Node::Branch(choices) => {
+            for choice in choices {
+                if outcome.nodes >= node_limit {
+                    outcome.status = Status::LimitReached;
+                    return false
+                }
+                outcome.nodes += 1;
+                search.apply(&choice);
+                let keep_going = !(search.is_viable() && search.is_canonical()) || descend(search, node_limit, visit, outcome);
+                search.undo(&choice);
+                if !keep_going {
+                    return false
+                }
+            }
+            true
+        }
+    }
+}
+
+/// The transversals of a latin square, as the column chosen in each row: n cells, one in every row and
+/// column, with every symbol once.
+///
+/// ```
+/// use combinatorial_patterns::{enumerate, IncidenceCube};
+///
+/// // The transversals of the cyclic square of order 3 are its broken diagonals.
+/// let transversals = enumerate::transversals(&IncidenceCube::new_cyclic(3).unwrap().as_latin_square());
+/// assert_eq!(transversals, vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]]);
+/// // Cyclic squares of even order have none.
+/// assert!(enumerate::transversals(&IncidenceCube::new_cyclic(4).unwrap().as_latin_square()).is_empty());
+/// ```
+pub fn transversals(square: &LatinSquare) -> Vec<Vec<usize>> {
+    let order = square.size();
+    let mut search = Transversals {
+        square,
+        columns: Vec::with_capacity(order),
+        column_used: vec![false; order],
+        symbol_used: vec![false; order]
+    };
+    let mut found = Vec::new();
+    run(&mut search, u64::MAX, |search| {
+        found.push(search.columns.clone());
+        Control::Continue
+    });
+    found
+}
+
+/// Chooses a column in each row in turn, skipping used columns and symbols.
+struct Transversals<'a> {
+    square: &'a LatinSquare,
+    columns: Vec<usize>,
+    column_used: Vec<bool>,
+    symbol_used: Vec<bool>
+}
+
+impl Search for Transversals<'_> {
+    type Choice = usize;
+
+    fn expand(&mut self) -> Node<usize> {
+        let row = self.columns.len();
+        if row == self.square.size() {
+            return Node::Solution
+        }
+        Node::Branch((0..self.square.size())
+            .filter(|&column| !self.column_used[column] && !self.symbol_used[self.square.square[row][column]])
+            .collect())
+    }
+
+    fn apply(&mut self, &column: &usize) {
+        let symbol = self.square.square[self.columns.len()][column];
+        self.columns.push(column);
+        self.column_used[column] = true;
+        self.symbol_used[symbol] = true;
+    }
+
+    fn undo(&mut self, &column: &usize) {
+        self.columns.pop();
+        let symbol = self.square.square[self.columns.len()][column];
+        self.column_used[column] = false;
+        self.symbol_used[symbol] = false;
+    }
+}
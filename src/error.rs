@@ -0,0 +1,70 @@
+//! The error type of the fallible generation and conversion APIs of the crate.
+//!
+//! Functions that can fail for reasons of their arguments, such as an order of 0 or a coordinate outside the
+//! cube, return a `CombinatorialError`. Parsing and decoding keep their own errors, `LatinSquareError` and
+//! `DecodeError`, which name the offending row or byte. Panics are left for states that the
+//! crate itself never builds, marked `ProgrammingError` in their messages.
+
+use std::fmt;
+
+use crate::explain::CubeCell;
+use crate::latin_square::SearchCoord;
+
+/// Why a generation or a conversion failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinatorialError {
+    /// An order of 0 was asked for, which has no square to generate.
+    ZeroOrder,
+    /// A row that should be a permutation of 0..n repeats a symbol or holds one outside the range.
+    NotAPermutation,
+    /// A step that should be coprime to the order is not.
+    NotCoprime { step: usize, order: usize },
+    /// The order asked for does not fit in a `usize`.
+    OrderOverflow,
+    /// None of the objects asked for exist at the order.
+    NoneOfOrder(usize),
+    /// The rules of a puzzle admit no solution.
+    NoSolution,
+    /// A coordinate is not inside the cube.
+    OutOfCube { x: usize, y: usize, z: usize, order: usize },
+    /// No `On` entry was found along the axis through the coordinate where one was needed.
+    NoOnEntry { x: usize, y: usize, z: usize, axis: SearchCoord },
+    /// An entry that is already `On` was turned on.
+    AlreadyOn,
+    /// An entry that is already improper was turned off.
+    AlreadyImproper,
+    /// The cube is improper, so it is not the cube of a latin square.
+    ImproperCube,
+    /// A move cannot start from the triple: it is in the square of a proper cube, or not the improper
+    /// triple of an improper one.
+    NotAnOrigin(CubeCell),
+    /// A move cannot pick the row, column, and symbol of the triple, which lack On entries in the lines
+    /// through the origin.
+    NotATarget(CubeCell)
+}
+
+impl fmt::Display for CombinatorialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CombinatorialError::ZeroOrder => write!(f, "order 0 has no square to generate"),
+            CombinatorialError::NotAPermutation => write!(f, "the row is not a permutation of its symbols"),
+            CombinatorialError::NotCoprime { step, order } => write!(f, "step {} is not coprime to order {}", step, order),
+            CombinatorialError::OrderOverflow => write!(f, "the order overflows a usize"),
+            CombinatorialError::NoneOfOrder(order) => write!(f, "none exist of order {}", order),
+            CombinatorialError::NoSolution => write!(f, "the rules have no solution"),
+            CombinatorialError::OutOfCube { x, y, z, order } => {
+                write!(f, "coordinate ({}, {}, {}) is outside the cube of order {}", x, y, z, order)
+            },
+            CombinatorialError::NoOnEntry { x, y, z, axis } => {
+                write!(f, "no On entry along the {:?} axis through ({}, {}, {})", axis, x, y, z)
+            },
+            CombinatorialError::AlreadyOn => write!(f, "cannot turn on an entry that is already on"),
+            CombinatorialError::AlreadyImproper => write!(f, "cannot turn off an improper entry"),
+            CombinatorialError::ImproperCube => write!(f, "the cube is improper"),
+            CombinatorialError::NotAnOrigin(cell) => write!(f, "no move starts from the triple {}", cell),
+            CombinatorialError::NotATarget(cell) => write!(f, "a move cannot pick the row, column, and symbol of {}", cell)
+        }
+    }
+}
+
+impl std::error::Error for CombinatorialError {}
@@ -0,0 +1,135 @@
+//! Estimates of the number of latin squares, and of the completions of latin rectangles, by sequential importance sampling.
+//!
+//! Exact counts of latin squares are known only up to order 11. Beyond that, a square can be sampled
+//! row by row: with k rows in place, the number of ways to add the next one is the permanent of the
+//! availability matrix, and the next row is drawn uniformly among them. The product of those permanents
+//! over one run is an unbiased estimate of the number of squares, as the probability of having drawn the
+//! square is exactly its inverse. Averaging over runs gives the estimate and its standard error.
+//!
+//! Drawing a row uniformly takes permanents of minors, one column at a time, so each run takes about
+//! n^3 * 2^n steps. Values can overflow an f64 above order 23 or so.
+//!
+//! Sources:
+//!
+//! - [Sequential Monte Carlo methods for statistical analysis of tables, Yuguo Chen, Persi Diaconis, Susan P. Holmes, Jun S. Liu](https://doi.org/10.1198/016214504000001303)
+
+use rand::Rng;
+
+use crate::latin_square::Symbol;
+use crate::monte_carlo::{z_score, ConfidenceInterval};
+use crate::permanent::random_permutation;
+
+/// The result of a sampling estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// The mean of the sampled weights.
+    pub value: f64,
+    /// The standard error of the mean.
+    pub standard_error: f64,
+    /// The number of samples the estimate is based on.
+    pub samples: usize
+}
+
+impl Estimate {
+    /// The standard error relative to the value.
+    pub fn relative_error(&self) -> f64 {
+        self.standard_error / self.value
+    }
+
+    /// The normal confidence interval of the value, at the given confidence. Panics unless the confidence
+    /// is strictly between 0 and 1.
+    ///
+    /// ```
+    /// use combinatorial_patterns::estimate;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+    /// let estimate = estimate::latin_squares(6, 200, &mut rng);
+    /// assert!(estimate.confidence_interval(0.999999).contains(812851200.0));
+    /// ```
+    pub fn confidence_interval(&self, confidence: f64) -> ConfidenceInterval {
+        let spread = z_score(confidence) * self.standard_error;
+        ConfidenceInterval { lower: self.value - spread, upper: self.value + spread, confidence }
+    }
+
+    /// Summarizes sampled weights. Scales by the mean first, so that huge weights don't overflow when squared.
+    pub(crate) fn from_weights(weights: &[f64]) -> Estimate {
+        let samples = weights.len();
+        let value = weights.iter().sum::<f64>() / samples as f64;
+        let standard_error = if samples < 2 || value == 0.0 {
+            0.0
+        } else {
+            let spread: f64 = weights.iter().map(|weight| (weight / value - 1.0).powi(2)).sum();
+            value * (spread / ((samples - 1) * samples) as f64).sqrt()
+        };
+        Estimate { value, standard_error, samples }
+    }
+}
+
+/// Estimates the number of latin squares of the given order from `samples` runs, drawing from `rng`. Panics
+/// if there are no samples.
+///
+/// ```
+/// use combinatorial_patterns::estimate;
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+/// let estimate = estimate::latin_squares(5, 200, &mut rng);
+/// assert!((estimate.value - 161280.0).abs() < 5.0 * estimate.standard_error + 1.0);
+/// assert!(estimate.relative_error() < 0.1);
+/// ```
+pub fn latin_squares(order: usize, samples: usize, rng: &mut impl Rng) -> Estimate {
+    rectangle_completions(order, &[], samples, rng).expect("ProgrammingError: no rows is a latin rectangle.")
+}
+
+/// Estimates the number of ways to complete the given rows to a latin square of the given order, from `samples` runs,
+/// drawing from `rng`.
+///
+/// Returns None if the rows are not a latin rectangle: rows of length `order`, with symbols in 0..order,
+/// and no symbol repeating in a row or column. Panics if there are no samples.
+///
+/// ```
+/// use combinatorial_patterns::estimate;
+///
+/// // Two rows of order 4 can be completed in 4 ways, which the sampler always sees.
+/// let estimate = estimate::rectangle_completions(4, &[vec![0, 1, 2, 3], vec![1, 0, 3, 2]], 10, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(estimate.value, 4.0);
+/// assert_eq!(estimate.standard_error, 0.0);
+/// ```
+pub fn rectangle_completions(order: usize, rows: &[Vec<Symbol>], samples: usize, rng: &mut impl Rng) -> Option<Estimate> {
+    if samples == 0 {
+        panic!("An estimate needs at least one sample.");
+    }
+    if rows.len() > order {
+        return None
+    }
+    let mut free = vec![vec![true; order]; order];
+    for row in rows {
+        if row.len() != order {
+            return None
+        }
+        let mut in_row = vec![false; order];
+        for (column, &symbol) in row.iter().enumerate() {
+            if symbol >= order || in_row[symbol] || !free[column][symbol] {
+                return None
+            }
+            in_row[symbol] = true;
+            free[column][symbol] = false;
+        }
+    }
+
+    let weights: Vec<f64> = (0..samples).map(|_| {
+        let mut available = free.clone();
+        let mut weight = 1.0;
+        for _ in rows.len()..order {
+            let (ways, row) = random_permutation(&available, rng)
+                .expect("ProgrammingError: a latin rectangle always has a next row.");
+            weight *= ways as f64;
+            for (column, symbol) in row.into_iter().enumerate() {
+                available[column][symbol] = false;
+            }
+        }
+        weight
+    }).collect();
+    Some(Estimate::from_weights(&weights))
+}
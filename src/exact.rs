@@ -0,0 +1,129 @@
+//! Exactly uniform random latin squares of small orders.
+//!
+//! The Jacobson-Matthews chain of `LatinSquare::new_random` converges to the uniform distribution, but after
+//! finitely many moves it is only close to it, see `convergence`. Where even that bias has to be ruled out,
+//! this module samples exactly.
+//!
+//! Coupling from the past would need a chain whose coupled copies provably coalesce, and the moves of the
+//! Jacobson-Matthews walk have no order to make them monotone, so every state would have to be tracked.
+//! Instead, the sampler uses self-reducibility. Every latin square is obtained from exactly one reduced
+//! square, with its first row and column in order, by permuting the rows other than the first and then
+//! permuting the columns, so a uniform reduced square with uniform permutations gives a uniform square.
+//! The reduced square is filled cell by cell, choosing each symbol with probability proportional to the
+//! number of completions it leaves, counted by a backtracking search.
+//!
+//! The counts are exact, so the square is exactly uniform, given a uniform random number generator, at the
+//! cost of enumerating the reduced squares below each choice: 9408 for order 6 and 16942080 for order 7,
+//! which takes seconds. Order 8, with about 5 * 10^11 reduced squares, is out of reach.
+//!
+//! Sources:
+//!
+//! - [Small latin squares, quasigroups, and loops, Brendan D. McKay, Alison Meynert, Wendy Myrvold](https://doi.org/10.1002/jcd.20105)
+//! - Exact sampling with coupled Markov chains, J. G. Propp, D. B. Wilson, Random Structures and Algorithms 9 (1996)
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::latin_square::LatinSquare;
+
+/// The largest order `uniform_square` samples.
+pub const MAX_ORDER: usize = 7;
+
+/// A latin square drawn exactly uniformly among all squares of the given order with `rng`, or None if the
+/// order is above `MAX_ORDER`.
+///
+/// ```
+/// use combinatorial_patterns::{exact, Quasigroup};
+///
+/// let mut rng = rand::thread_rng();
+/// let square = exact::uniform_square(5, &mut rng).unwrap();
+/// assert!(Quasigroup::new(square).is_some());
+/// assert!(exact::uniform_square(8, &mut rng).is_none());
+/// ```
+pub fn uniform_square(order: usize, rng: &mut impl Rng) -> Option<LatinSquare> {
+    if order > MAX_ORDER {
+        return None
+    }
+    let mut reduced = Reduced {
+        order,
+        rows: vec![vec![0; order]; order],
+        row_used: vec![0; order],
+        column_used: vec![0; order]
+    };
+    for index in 0..order {
+        reduced.place(0, index, index);
+        if index > 0 {
+            reduced.place(index, 0, index);
+        }
+    }
+    let free = order.saturating_sub(1);
+    for position in 0..(free * free) {
+        let (row, column) = (1 + position / free, 1 + position % free);
+        let counts: Vec<(usize, u64)> = reduced.candidates(row, column).into_iter().map(|symbol| {
+            reduced.place(row, column, symbol);
+            let count = reduced.count(position + 1);
+            reduced.unplace(row, column, symbol);
+            (symbol, count)
+        }).collect();
+        let mut pick = rng.gen_range(0..counts.iter().map(|&(_, count)| count).sum::<u64>());
+        let &(symbol, _) = counts.iter().find(|&&(_, count)| {
+            let found = pick < count;
+            pick = pick.saturating_sub(count);
+            found
+        }).expect("ProgrammingError: a reduced partial square with completions has a symbol with completions.");
+        reduced.place(row, column, symbol);
+    }
+    let mut rows = reduced.rows;
+    if order > 1 {
+        rows[1..].shuffle(rng);
+    }
+    let mut columns: Vec<usize> = (0..order).collect();
+    columns.shuffle(rng);
+    let rows = rows.iter().map(|row| columns.iter().map(|&column| row[column]).collect()).collect();
+    Some(LatinSquare::from_rows(rows))
+}
+
+/// A reduced square being filled in row-major order, with the symbols used by each row and column as bits.
+struct Reduced {
+    order: usize,
+    rows: Vec<Vec<usize>>,
+    row_used: Vec<u16>,
+    column_used: Vec<u16>
+}
+
+impl Reduced {
+    fn place(&mut self, row: usize, column: usize, symbol: usize) {
+        self.rows[row][column] = symbol;
+        self.row_used[row] |= 1 << symbol;
+        self.column_used[column] |= 1 << symbol;
+    }
+
+    fn unplace(&mut self, row: usize, column: usize, symbol: usize) {
+        self.row_used[row] &= !(1 << symbol);
+        self.column_used[column] &= !(1 << symbol);
+    }
+
+    fn candidates(&self, row: usize, column: usize) -> Vec<usize> {
+        let used = self.row_used[row] | self.column_used[column];
+        (0..self.order).filter(|&symbol| used & (1 << symbol) == 0).collect()
+    }
+
+    /// The number of completions with the cells before `position`, counting row-major from (1, 1), filled.
+    fn count(&mut self, position: usize) -> u64 {
+        let free = self.order - 1;
+        if position == free * free {
+            return 1
+        }
+        let (row, column) = (1 + position / free, 1 + position % free);
+        let mut total = 0;
+        let mut available = !(self.row_used[row] | self.column_used[column]) & ((1 << self.order) - 1);
+        while available != 0 {
+            let symbol = available.trailing_zeros() as usize;
+            available &= available - 1;
+            self.place(row, column, symbol);
+            total += self.count(position + 1);
+            self.unplace(row, column, symbol);
+        }
+        total
+    }
+}
@@ -0,0 +1,186 @@
+//! Explained generation: a record of every Jacobson-Matthews move, with a narration of each, for worked examples.
+//!
+//! The incidence cube of a latin square has a 1 at every (row, column, symbol) triple of the square. A move
+//! picks a triple (x1, y1, z1) that is not in the square, and the lines through it: the row x2 where column y1
+//! has symbol z1, the column y2 where row x1 has symbol z1, and the symbol z2 in cell (x1, y1). It adds
+//! (x1, y1, z1), (x1, y2, z2), (x2, y1, z2) and (x2, y2, z1), and removes (x1, y1, z2), (x1, y2, z1),
+//! (x2, y1, z1) and (x2, y2, z2), which keeps every line summing to 1. If (x2, y2, z2) was not in the square,
+//! it now counts -1, and the cube is improper. The next move starts from that triple, choosing each line at
+//! random from the two available, until a move lands on a proper cube again.
+//!
+//! `explain::shuffle` records every move of a short walk; `ShuffleTrace` makes the moves one at a time, and
+//! leaves the cube to be looked at between them, for animating a walk of any length.
+//!
+//! Sources:
+//!
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+
+use std::fmt;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+use rand::Rng;
+
+/// A (row, column, symbol) cell of an incidence cube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CubeCell {
+    pub row: usize,
+    pub column: usize,
+    pub symbol: usize
+}
+
+impl fmt::Display for CubeCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.row, self.column, self.symbol)
+    }
+}
+
+/// One move of the Jacobson-Matthews chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// Whether the move started from the improper cell left by the previous move, rather than from a random
+    /// triple not in the square.
+    pub from_improper: bool,
+    /// The triple (x1, y1, z1) the move started from.
+    pub origin: CubeCell,
+    /// The chosen row x2, of a 1 at (x2, y1, z1).
+    pub row: usize,
+    /// The chosen column y2, of a 1 at (x1, y2, z1).
+    pub column: usize,
+    /// The chosen symbol z2, of a 1 at (x1, y1, z2).
+    pub symbol: usize,
+    /// The triple counting -1 after the move, if any.
+    pub improper: Option<CubeCell>
+}
+
+impl Step {
+    /// The four triples the move added.
+    pub fn added(&self) -> [CubeCell; 4] {
+        let CubeCell { row, column, symbol } = self.origin;
+        [
+            CubeCell { row, column, symbol },
+            CubeCell { row, column: self.column, symbol: self.symbol },
+            CubeCell { row: self.row, column, symbol: self.symbol },
+            CubeCell { row: self.row, column: self.column, symbol }
+        ]
+    }
+
+    /// The four triples the move removed.
+    pub fn removed(&self) -> [CubeCell; 4] {
+        let CubeCell { row, column, symbol } = self.origin;
+        [
+            CubeCell { row, column, symbol: self.symbol },
+            CubeCell { row, column: self.column, symbol },
+            CubeCell { row: self.row, column, symbol },
+            CubeCell { row: self.row, column: self.column, symbol: self.symbol }
+        ]
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let CubeCell { row, column, symbol } = self.origin;
+        if self.from_improper {
+            write!(f, "Continued from the improper triple (row {}, column {}, symbol {}), which counts -1. \
+                Picked row {} of the two with symbol {} in column {}, column {} of the two with it in row {}, \
+                and symbol {} of the two in cell ({}, {}). ",
+                row, column, symbol, self.row, symbol, column, self.column, row, self.symbol, row, column)?;
+        } else {
+            write!(f, "Chose the triple (row {}, column {}, symbol {}), which is not in the square. \
+                Column {} has symbol {} in row {}, row {} has it in column {}, and cell ({}, {}) holds symbol {}. ",
+                row, column, symbol, column, symbol, self.row, row, self.column, row, column, self.symbol)?;
+        }
+        let list = |cells: [CubeCell; 4]| cells.iter().map(|cell| cell.to_string()).collect::<Vec<String>>().join(", ");
+        write!(f, "Added {} and removed {}. ", list(self.added()), list(self.removed()))?;
+        match self.improper {
+            Some(cell) => write!(f, "The triple {} was not in the square, so it now counts -1 and the square is improper.", cell),
+            None => write!(f, "The square is proper.")
+        }
+    }
+}
+
+/// The moves of a shuffle, made one at a time as the iterator advances, so that the cube can be looked at
+/// between them, as when animating the walk. It ends once the moves from proper cubes asked for are made
+/// and the cube is proper again.
+///
+/// ```
+/// use combinatorial_patterns::IncidenceCube;
+/// use combinatorial_patterns::latin_square::ShuffleConfig;
+///
+/// let mut cube = IncidenceCube::new_cyclic(5).unwrap();
+/// let mut trace = cube.shuffle_trace(&ShuffleConfig::new().with_moves(20), rand::thread_rng());
+/// let mut moves = 0;
+/// while let Some(step) = trace.next() {
+///     moves += 1;
+///     // The improper triple, if any, counts -1 in the cube as it stands after the move.
+///     assert_eq!(step.improper.is_none(), trace.cube().as_latin_square().is_valid());
+/// }
+/// assert!(moves >= 20);
+/// assert!(cube.as_latin_square().is_valid());
+/// ```
+#[derive(Debug)]
+pub struct ShuffleTrace<'a, R: Rng> {
+    cube: &'a mut IncidenceCube,
+    remaining: u64,
+    rng: R
+}
+
+impl<'a, R: Rng> ShuffleTrace<'a, R> {
+    pub(crate) fn new(cube: &'a mut IncidenceCube, moves: u64, rng: R) -> ShuffleTrace<'a, R> {
+        ShuffleTrace { cube, remaining: moves, rng }
+    }
+
+    /// The cube after the moves made so far.
+    pub fn cube(&self) -> &IncidenceCube {
+        self.cube
+    }
+}
+
+impl<R: Rng> Iterator for ShuffleTrace<'_, R> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        self.cube.traced_move(&mut self.remaining, &mut self.rng)
+    }
+}
+
+/// A generated square with the moves that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    pub square: LatinSquare
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, step) in self.steps.iter().enumerate() {
+            writeln!(f, "Step {}: {}", index + 1, step)?;
+        }
+        write!(f, "\n{}", self.square)
+    }
+}
+
+/// Shuffles the cyclic square of the given order with at least `moves` moves, continuing until the square
+/// is proper, and returns the result with every move. A handful of moves is enough for a worked example;
+/// `IncidenceCube::shuffle` makes order^3 of them. The moves are drawn from `rng`, and need an order of at
+/// least 2. Fails for order 0.
+///
+/// ```
+/// use combinatorial_patterns::explain;
+///
+/// let mut rng = rand::thread_rng();
+/// let explanation = explain::shuffle(4, 2, &mut rng).unwrap();
+/// let text = explanation.to_string();
+/// assert!(text.starts_with("Step 1: Chose the triple"));
+/// assert!(text.contains("The square is proper."));
+///
+/// // The first triple added is the one the move started from.
+/// let first = explanation.steps[0];
+/// assert_eq!(first.added()[0], first.origin);
+/// assert!(explain::shuffle(0, 2, &mut rng).is_err());
+/// ```
+pub fn shuffle(order: usize, moves: usize, rng: &mut impl Rng) -> Result<Explanation, CombinatorialError> {
+    let mut cube = IncidenceCube::new_cyclic(order)?;
+    let steps = cube.shuffle_explained(moves, rng);
+    Ok(Explanation { steps, square: cube.as_latin_square() })
+}
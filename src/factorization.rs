@@ -0,0 +1,316 @@
+//! Perfect 1-factorizations of complete graphs.
+//!
+//! A 1-factorization of K_2n partitions its edges into 2n - 1 perfect matchings, the factors. It is perfect if
+//! the union of any two factors is a Hamiltonian cycle, rather than several shorter cycles. Two infinite
+//! families are known:
+//!
+//! - GK_2n, the canonical factorization on Z_(2n - 1) plus a point at infinity, whose factor i pairs infinity
+//!   with i and i - k with i + k. It is perfect exactly when 2n - 1 is prime.
+//! - GA_2p, on two copies a_i and b_i of Z_p for an odd prime p: p - 1 factors {a_i b_(i + d)} for the nonzero
+//!   d, and the p translates of {a_0 b_0} with the pairs {a_k, a_-k} and {b_k, b_-k}.
+//!
+//! Other orders fall back to search. The translates of a starter in Z_(2n - 1), as in `starter`, form a
+//! 1-factorization, which is perfect if the factor of the starter forms a Hamiltonian cycle with every
+//! other. The search tries starters first, and then backtracks over all factorizations, building one factor
+//! at a time and rejecting an edge as soon as it closes a short cycle with an earlier factor.
+//!
+//! A 1-factorization is the same as a symmetric latin square of order 2n with a constant diagonal: cell (x, y)
+//! holds the factor of the edge xy, and the diagonal holds 2n - 1.
+//!
+//! Sources:
+//!
+//! - [Perfect 1-factorization, Wikipedia](https://en.wikipedia.org/wiki/Graph_factorization#Perfect_1-factorization)
+//! - Perfect one-factorizations of the complete graph, E. Seah, Ars Combinatoria (1991)
+//! - One-factorizations, W. D. Wallis, chapter 16
+
+use crate::latin_square::LatinSquare;
+use crate::starter::{Starter, StarterKind};
+
+/// The number of edges the general search for a perfect 1-factorization tries before it gives up.
+pub const PERFECT_SEARCH_LIMIT: usize = 10_000_000;
+
+/// A partition of the edges of the complete graph on 0..vertices into perfect matchings.
+///
+/// ```
+/// use combinatorial_patterns::factorization::{self, OneFactorization};
+///
+/// // 7 is prime, so GK_8 is perfect; 9 isn't, so GK_10 is not, but GA_10 is.
+/// assert!(OneFactorization::gk(8).unwrap().is_perfect());
+/// assert!(!OneFactorization::gk(10).unwrap().is_perfect());
+/// assert!(OneFactorization::ga(10).unwrap().is_perfect());
+///
+/// let factorization = factorization::perfect(12).unwrap();
+/// assert_eq!(factorization.factors().len(), 11);
+/// let square = factorization.as_latin_square();
+/// assert_eq!(square.square[3][5], square.square[5][3]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OneFactorization {
+    vertices: usize,
+    factors: Vec<Vec<(usize, usize)>>
+}
+
+impl OneFactorization {
+    /// A 1-factorization from its factors. Returns None unless every factor is a perfect matching of the
+    /// vertices and every edge is in exactly one factor.
+    pub fn new(vertices: usize, factors: Vec<Vec<(usize, usize)>>) -> Option<OneFactorization> {
+        if vertices % 2 == 1 || factors.len() != vertices.saturating_sub(1) {
+            return None
+        }
+        let mut seen = vec![vec![false; vertices]; vertices];
+        for factor in &factors {
+            let mut matched = vec![false; vertices];
+            for &(a, b) in factor {
+                if a >= vertices || b >= vertices || a == b || matched[a] || matched[b] || seen[a][b] {
+                    return None
+                }
+                matched[a] = true;
+                matched[b] = true;
+                seen[a][b] = true;
+                seen[b][a] = true;
+            }
+            if matched.iter().any(|&matched| !matched) {
+                return None
+            }
+        }
+        Some(OneFactorization { vertices, factors })
+    }
+
+    /// GK_2n, the canonical 1-factorization, with infinity numbered 2n - 1. Returns None for an odd or zero
+    /// number of vertices.
+    pub fn gk(vertices: usize) -> Option<OneFactorization> {
+        if vertices == 0 || vertices % 2 == 1 {
+            return None
+        }
+        let turning = vertices - 1;
+        let factors = (0..turning).map(|i| {
+            std::iter::once((turning, i))
+                .chain((1..(vertices / 2)).map(|k| ((i + turning - k) % turning, (i + k) % turning)))
+                .collect()
+        }).collect();
+        Some(OneFactorization { vertices, factors })
+    }
+
+    /// GA_2p, with a_i numbered i and b_i numbered p + i. Returns None unless vertices = 2p for an odd p; the
+    /// factorization is perfect when p is prime.
+    pub fn ga(vertices: usize) -> Option<OneFactorization> {
+        let p = vertices / 2;
+        if vertices % 2 == 1 || p.is_multiple_of(2) {
+            return None
+        }
+        let (a, b) = (|i: usize| i % p, |i: usize| p + i % p);
+        let mut factors: Vec<Vec<(usize, usize)>> = (1..p).map(|d| (0..p).map(|i| (a(i), b(i + d))).collect()).collect();
+        for k in 0..p {
+            let mut factor = vec![(a(k), b(k))];
+            factor.extend((1..p.div_ceil(2)).map(|i| (a(k + i), a(k + p - i))));
+            factor.extend((1..p.div_ceil(2)).map(|i| (b(k + i), b(k + p - i))));
+            factors.push(factor);
+        }
+        Some(OneFactorization { vertices, factors })
+    }
+
+    /// The 1-factorization of K_(n + 1) induced by a starter in Z_n: factor g pairs g with infinity, numbered
+    /// n, and holds the translates by g of the pairs of the starter.
+    pub fn from_starter(starter: &Starter) -> OneFactorization {
+        let n = starter.modulus();
+        let factors = (0..n).map(|shift| {
+            std::iter::once((shift, n))
+                .chain(starter.pairs().iter().map(|&(x, y)| ((x + shift) % n, (y + shift) % n)))
+                .collect()
+        }).collect();
+        OneFactorization { vertices: n + 1, factors }
+    }
+
+    /// The number of vertices.
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
+
+    /// The factors, as lists of edges.
+    pub fn factors(&self) -> &[Vec<(usize, usize)>] {
+        &self.factors
+    }
+
+    /// Returns true if the union of every two factors is a Hamiltonian cycle.
+    pub fn is_perfect(&self) -> bool {
+        let partners: Vec<Vec<usize>> = self.factors.iter().map(|factor| partners(self.vertices, factor)).collect();
+        partners.iter().enumerate().all(|(i, first)| {
+            partners[(i + 1)..].iter().all(|second| cycle_length(first, second, 0) == self.vertices)
+        })
+    }
+
+    /// Returns true if factor 0 forms a Hamiltonian cycle with every factor g up to half the modulus, which
+    /// for a factorization induced by a starter means it is perfect: translation carries the pair of factors
+    /// 0 and g onto every pair with difference g, and the pair 0, -g onto 0, g.
+    fn is_perfect_starter(&self) -> bool {
+        let first = partners(self.vertices, &self.factors[0]);
+        self.factors[1..=(self.vertices / 2 - 1)].iter()
+            .all(|factor| cycle_length(&first, &partners(self.vertices, factor), 0) == self.vertices)
+    }
+
+    /// The symmetric latin square of the factorization: cell (x, y) holds the factor of the edge xy, and
+    /// the diagonal holds 2n - 1.
+    pub fn as_latin_square(&self) -> LatinSquare {
+        let n = self.vertices;
+        let mut rows = vec![vec![n.saturating_sub(1); n]; n];
+        for (index, factor) in self.factors.iter().enumerate() {
+            for &(a, b) in factor {
+                rows[a][b] = index;
+                rows[b][a] = index;
+            }
+        }
+        LatinSquare::from_rows(rows)
+    }
+}
+
+/// A perfect 1-factorization of the complete graph on the given number of vertices: GK_2n when 2n - 1 is
+/// prime, GA_2p when n = p is an odd prime, and otherwise the result of searches of at most
+/// `STARTER_SEARCH_LIMIT` pairs for a perfect starter and then of at most `PERFECT_SEARCH_LIMIT` edges for
+/// any perfect factorization. Returns None for odd numbers of vertices, or when the searches give up.
+///
+/// ```
+/// use combinatorial_patterns::factorization;
+///
+/// // 15 isn't prime and neither is 8, so 16 vertices take a search.
+/// assert!(factorization::perfect(16).unwrap().is_perfect());
+/// assert!(factorization::perfect(7).is_none());
+/// ```
+pub fn perfect(vertices: usize) -> Option<OneFactorization> {
+    if vertices == 0 || vertices % 2 == 1 {
+        return None
+    }
+    if vertices == 2 || is_prime(vertices - 1) {
+        return OneFactorization::gk(vertices)
+    }
+    if is_prime(vertices / 2) && vertices / 2 > 2 {
+        return OneFactorization::ga(vertices)
+    }
+    let perfect_starter = Starter::find(vertices - 1, StarterKind::Any, |starter| OneFactorization::from_starter(starter).is_perfect_starter());
+    if let Some(starter) = perfect_starter {
+        return Some(OneFactorization::from_starter(&starter))
+    }
+    let mut search = Search {
+        vertices,
+        partner: vec![vec![usize::MAX; vertices]; vertices - 1],
+        used: vec![vec![false; vertices]; vertices],
+        nodes: 0
+    };
+    // Every factorization can be relabelled so that factor 0 pairs 2i with 2i + 1, and factor k pairs 0 with k + 1.
+    for vertex in (0..vertices).step_by(2) {
+        search.pair(0, vertex, vertex + 1);
+    }
+    if !search.extend(1) {
+        return None
+    }
+    let factors = search.partner.iter().map(|partner| {
+        (0..vertices).filter(|&vertex| vertex < partner[vertex]).map(|vertex| (vertex, partner[vertex])).collect()
+    }).collect();
+    OneFactorization::new(vertices, factors)
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2 && (2..).take_while(|divisor| divisor * divisor <= n).all(|divisor| !n.is_multiple_of(divisor))
+}
+
+/// The partner of every vertex in a perfect matching.
+fn partners(vertices: usize, factor: &[(usize, usize)]) -> Vec<usize> {
+    let mut partner = vec![usize::MAX; vertices];
+    for &(a, b) in factor {
+        partner[a] = b;
+        partner[b] = a;
+    }
+    partner
+}
+
+/// The number of vertices on the cycle through `start` of the union of two perfect matchings.
+fn cycle_length(first: &[usize], second: &[usize], start: usize) -> usize {
+    let mut length = 0;
+    let mut vertex = start;
+    loop {
+        vertex = second[first[vertex]];
+        length += 2;
+        if vertex == start {
+            return length
+        }
+    }
+}
+
+/// Backtracking state of the search for a perfect 1-factorization.
+struct Search {
+    vertices: usize,
+    /// `partner[factor][vertex]`, or usize::MAX while unmatched.
+    partner: Vec<Vec<usize>>,
+    used: Vec<Vec<bool>>,
+    nodes: usize
+}
+
+impl Search {
+    fn pair(&mut self, factor: usize, a: usize, b: usize) {
+        self.partner[factor][a] = b;
+        self.partner[factor][b] = a;
+        self.used[a][b] = true;
+        self.used[b][a] = true;
+    }
+
+    fn unpair(&mut self, factor: usize, a: usize, b: usize) {
+        self.partner[factor][a] = usize::MAX;
+        self.partner[factor][b] = usize::MAX;
+        self.used[a][b] = false;
+        self.used[b][a] = false;
+    }
+
+    /// Returns true if adding the edge ab to the factor closes no cycle shorter than Hamiltonian with an earlier factor.
+    fn keeps_perfect(&self, factor: usize, a: usize, b: usize) -> bool {
+        (0..factor).all(|earlier| {
+            // Follow the alternating path from b, starting along the earlier factor, until it ends or returns to a.
+            let mut vertex = b;
+            let mut length = 1;
+            loop {
+                vertex = self.partner[earlier][vertex];
+                length += 1;
+                if vertex == a {
+                    return length == self.vertices
+                }
+                let next = self.partner[factor][vertex];
+                if next == usize::MAX {
+                    return true
+                }
+                vertex = next;
+                length += 1;
+            }
+        })
+    }
+
+    /// Completes the factors from `factor` on. Returns false if there is no completion, or the search gave up.
+    fn extend(&mut self, factor: usize) -> bool {
+        if factor == self.vertices - 1 {
+            return true
+        }
+        let Some(a) = (0..self.vertices).find(|&vertex| self.partner[factor][vertex] == usize::MAX) else {
+            return self.extend(factor + 1)
+        };
+        let choices: Vec<usize> = if a == 0 {
+            vec![factor + 1]
+        } else {
+            ((a + 1)..self.vertices).collect()
+        };
+        for b in choices {
+            if self.partner[factor][b] != usize::MAX || self.used[a][b] || !self.keeps_perfect(factor, a, b) {
+                continue
+            }
+            self.nodes += 1;
+            if self.nodes > PERFECT_SEARCH_LIMIT {
+                return false
+            }
+            self.pair(factor, a, b);
+            if self.extend(factor) {
+                return true
+            }
+            self.unpair(factor, a, b);
+            if self.nodes > PERFECT_SEARCH_LIMIT {
+                return false
+            }
+        }
+        false
+    }
+}
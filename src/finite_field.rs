@@ -0,0 +1,250 @@
+//! Finite fields GF(q) for prime powers q, the classical source of complete sets of MOLS.
+//!
+//! Elements are numbered 0..q. An element is read as a polynomial over Z_p whose coefficients are
+//! its base-p digits, least significant first, so 0 and 1 are always the additive and multiplicative
+//! identities, and for prime q the field is just the integers mod q.
+//! Multiplication goes through log/antilog tables of a primitive element.
+
+/// The finite field with `order` elements.
+///
+/// ```
+/// use combinatorial_patterns::finite_field::FiniteField;
+///
+/// let field = FiniteField::new(9).unwrap();
+/// assert_eq!(field.characteristic(), 3);
+/// for a in 1..9 {
+///     assert_eq!(field.mul(a, field.inv(a).unwrap()), 1);
+/// }
+/// assert!(FiniteField::new(6).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FiniteField {
+    order: usize,
+    characteristic: usize,
+    degree: u32,
+    // exp[i] = g^i for the primitive element g, and log[exp[i]] = i.
+    exp: Vec<usize>,
+    log: Vec<usize>
+}
+
+/// Returns (p, k) if `n` = p^k for a prime p and k >= 1.
+pub fn prime_power(n: usize) -> Option<(usize, u32)> {
+    if n < 2 {
+        return None
+    }
+    let p = (2..=n).find(|d| n.is_multiple_of(*d))?;
+    let mut remaining = n;
+    let mut k = 0;
+    while remaining.is_multiple_of(p) {
+        remaining /= p;
+        k += 1;
+    }
+    if remaining == 1 {
+        Some((p, k))
+    } else {
+        None
+    }
+}
+
+impl FiniteField {
+    /// Creates GF(order). Returns None if `order` is not a prime power.
+    pub fn new(order: usize) -> Option<FiniteField> {
+        let (characteristic, degree) = prime_power(order)?;
+        let modulus = irreducible_polynomial(characteristic, degree as usize);
+        let multiply = |a: usize, b: usize| polynomial_mul_mod(a, b, &modulus, characteristic);
+
+        // Find a primitive element by walking the powers of each candidate until one reaches every nonzero element.
+        let (exp, log) = if order == 2 {
+            (vec![1], vec![0, 0])
+        } else {
+            (2..order).find_map(|candidate| {
+                let mut exp = Vec::with_capacity(order - 1);
+                let mut log = vec![0; order];
+                let mut power = 1;
+                for i in 0..(order - 1) {
+                    if i > 0 && power == 1 {
+                        return None
+                    }
+                    exp.push(power);
+                    log[power] = i;
+                    power = multiply(power, candidate);
+                }
+                Some((exp, log))
+            })?
+        };
+
+        Some(FiniteField {
+            order,
+            characteristic,
+            degree,
+            exp,
+            log
+        })
+    }
+
+    /// The number of elements of the field.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The prime p such that the order is p^k.
+    pub fn characteristic(&self) -> usize {
+        self.characteristic
+    }
+
+    /// The k such that the order is p^k.
+    pub fn degree(&self) -> u32 {
+        self.degree
+    }
+
+    /// A generator of the multiplicative group of the field.
+    pub fn primitive_element(&self) -> usize {
+        if self.order == 2 { 1 } else { self.exp[1] }
+    }
+
+    /// Returns a + b.
+    pub fn add(&self, a: usize, b: usize) -> usize {
+        let p = self.characteristic;
+        let (mut a, mut b, mut sum, mut place) = (a, b, 0, 1);
+        while a > 0 || b > 0 {
+            sum += ((a % p + b % p) % p) * place;
+            a /= p;
+            b /= p;
+            place *= p;
+        }
+        sum
+    }
+
+    /// Returns -a.
+    pub fn neg(&self, a: usize) -> usize {
+        let p = self.characteristic;
+        let (mut a, mut negated, mut place) = (a, 0, 1);
+        while a > 0 {
+            negated += ((p - a % p) % p) * place;
+            a /= p;
+            place *= p;
+        }
+        negated
+    }
+
+    /// Returns a - b.
+    pub fn sub(&self, a: usize, b: usize) -> usize {
+        self.add(a, self.neg(b))
+    }
+
+    /// Returns a * b.
+    pub fn mul(&self, a: usize, b: usize) -> usize {
+        if a == 0 || b == 0 {
+            return 0
+        }
+        self.exp[(self.log[a] + self.log[b]) % (self.order - 1)]
+    }
+
+    /// Returns the multiplicative inverse of a, or None for 0.
+    pub fn inv(&self, a: usize) -> Option<usize> {
+        if a == 0 {
+            return None
+        }
+        Some(self.exp[(self.order - 1 - self.log[a]) % (self.order - 1)])
+    }
+
+    /// Returns a / b, or None if b is 0.
+    pub fn div(&self, a: usize, b: usize) -> Option<usize> {
+        self.inv(b).map(|inverse| self.mul(a, inverse))
+    }
+
+    /// Returns a^exponent.
+    pub fn pow(&self, a: usize, exponent: usize) -> usize {
+        if exponent == 0 {
+            return 1
+        }
+        if a == 0 {
+            return 0
+        }
+        self.exp[(self.log[a] * (exponent % (self.order - 1))) % (self.order - 1)]
+    }
+
+    /// Returns the elements of the subfield with `order` elements, sorted, if the field has one.
+    ///
+    /// GF(p^k) has a subfield of order p^d exactly when d divides k.
+    pub fn subfield(&self, order: usize) -> Option<Vec<usize>> {
+        let (p, d) = prime_power(order)?;
+        if p != self.characteristic || !self.degree.is_multiple_of(d) {
+            return None
+        }
+        // The nonzero elements of the subfield are the powers of g^((q - 1) / (p^d - 1)).
+        let step = (self.order - 1) / (order - 1);
+        let mut elements: Vec<usize> = (0..(order - 1)).map(|i| self.exp[i * step]).collect();
+        elements.push(0);
+        elements.sort_unstable();
+        Some(elements)
+    }
+}
+
+/// Multiplies two polynomials encoded as base-p digits, modulo the monic polynomial `modulus`,
+/// given as its coefficients with the leading 1 last.
+fn polynomial_mul_mod(a: usize, b: usize, modulus: &[usize], p: usize) -> usize {
+    let degree = modulus.len() - 1;
+    let digits = |mut value: usize| {
+        let mut digits = vec![0; degree];
+        for digit in digits.iter_mut() {
+            *digit = value % p;
+            value /= p;
+        }
+        digits
+    };
+    let (a, b) = (digits(a), digits(b));
+    let mut product = vec![0; 2 * degree];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = (product[i + j] + x * y) % p;
+        }
+    }
+    // Reduce from the top, subtracting multiples of the modulus.
+    for top in (degree..product.len()).rev() {
+        let coefficient = product[top];
+        if coefficient == 0 {
+            continue
+        }
+        for (i, &m) in modulus.iter().enumerate() {
+            let position = top - degree + i;
+            product[position] = (product[position] + p * p - coefficient * m % p) % p;
+        }
+    }
+    product[..degree].iter().rev().fold(0, |value, &digit| value * p + digit)
+}
+
+/// Finds a monic irreducible polynomial of the given degree over Z_p, returned as its coefficients
+/// with the leading 1 last. Irreducibility is checked by trial division by every monic polynomial
+/// of at most half the degree.
+fn irreducible_polynomial(p: usize, degree: usize) -> Vec<usize> {
+    let monic = |degree: usize, index: usize| {
+        let mut coefficients: Vec<usize> = (0..degree).map(|i| (index / p.pow(i as u32)) % p).collect();
+        coefficients.push(1);
+        coefficients
+    };
+    (0..p.pow(degree as u32)).map(|index| monic(degree, index)).find(|candidate| {
+        (1..=degree / 2).all(|divisor_degree| {
+            (0..p.pow(divisor_degree as u32)).all(|index| {
+                !divides(&monic(divisor_degree, index), candidate, p)
+            })
+        })
+    }).expect("ProgrammingError: irreducible polynomials exist for every degree.")
+}
+
+/// Returns true if the monic polynomial `divisor` divides `dividend` over Z_p.
+fn divides(divisor: &[usize], dividend: &[usize], p: usize) -> bool {
+    let mut remainder = dividend.to_vec();
+    let divisor_degree = divisor.len() - 1;
+    for top in (divisor_degree..remainder.len()).rev() {
+        let coefficient = remainder[top];
+        if coefficient == 0 {
+            continue
+        }
+        for (i, &d) in divisor.iter().enumerate() {
+            let position = top - divisor_degree + i;
+            remainder[position] = (remainder[position] + p * p - coefficient * d % p) % p;
+        }
+    }
+    remainder[..divisor_degree].iter().all(|&c| c == 0)
+}
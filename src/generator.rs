@@ -0,0 +1,88 @@
+//! A workspace for generating many random latin squares of one order without reallocating.
+//!
+//! `LatinSquare::new_random` builds a fresh incidence cube, n * n vectors of n entries, and a fresh square
+//! for every square it draws, and in batches of small squares the allocations take a good share of the
+//! time. A `Generator` keeps the cube, the square it writes, and the random generator, and on every draw
+//! puts the cube back to the cyclic one in place before shuffling it. The squares are the same as those of
+//! a fresh cube: `square_for_seed` gives exactly `batch::square`, and the batch functions run on it.
+//!
+//! Sources:
+//!
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+
+/// Buffers for drawing random squares of one order, reused from one square to the next.
+///
+/// ```
+/// use combinatorial_patterns::batch;
+/// use combinatorial_patterns::generator::Generator;
+///
+/// let mut generator = Generator::with_capacity(6).unwrap();
+/// let first = generator.next_square().square.clone();
+/// assert_eq!(first.len(), 6);
+///
+/// // The same square as a fresh cube shuffled with the same seed.
+/// assert_eq!(generator.square_for_seed(17).square, batch::square(6, 17).unwrap().square);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generator {
+    order: usize,
+    cube: IncidenceCube,
+    square: LatinSquare,
+    rng: ChaCha8Rng
+}
+
+impl Generator {
+    /// A generator of squares of the given order, seeded from the operating system. Fails for order 0.
+    pub fn with_capacity(order: usize) -> Result<Generator, CombinatorialError> {
+        Generator::from_rng(order, ChaCha8Rng::from_entropy())
+    }
+
+    /// A generator of squares of the given order whose sequence of squares is fixed by the seed. Fails for
+    /// order 0.
+    ///
+    /// ```
+    /// use combinatorial_patterns::generator::Generator;
+    ///
+    /// let (mut first, mut second) = (Generator::from_seed(5, 3).unwrap(), Generator::from_seed(5, 3).unwrap());
+    /// for _ in 0..3 {
+    ///     assert_eq!(first.next_square().square, second.next_square().square);
+    /// }
+    /// assert!(Generator::from_seed(0, 3).is_err());
+    /// ```
+    pub fn from_seed(order: usize, seed: u64) -> Result<Generator, CombinatorialError> {
+        Generator::from_rng(order, ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn from_rng(order: usize, rng: ChaCha8Rng) -> Result<Generator, CombinatorialError> {
+        Ok(Generator { order, cube: IncidenceCube::new_cyclic(order)?, square: LatinSquare::new_empty(order), rng })
+    }
+
+    /// The order of the squares.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Draws the next square, with the generator's own random generator. The square is overwritten by the
+    /// next draw; copy its rows to keep it.
+    pub fn next_square(&mut self) -> &LatinSquare {
+        self.cube.reset_cyclic();
+        self.cube.shuffle_with_rng(&mut self.rng);
+        self.cube.write_latin_square(&mut self.square);
+        &self.square
+    }
+
+    /// Draws the square of the given seed, the same as `batch::square` for this order. The square is
+    /// overwritten by the next draw.
+    pub fn square_for_seed(&mut self, seed: u64) -> &LatinSquare {
+        self.cube.reset_cyclic();
+        self.cube.shuffle_with_rng(&mut ChaCha8Rng::seed_from_u64(seed));
+        self.cube.write_latin_square(&mut self.square);
+        &self.square
+    }
+}
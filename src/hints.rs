@@ -0,0 +1,393 @@
+//! Hints and difficulty grades for latin square puzzles, by the techniques people use to solve them.
+//!
+//! A puzzle state keeps the pencil marks of every empty cell: the symbols not yet ruled out. Each technique
+//! looks for a pattern in the units of the rules, rows and columns for plain latin squares, plus boxes,
+//! diagonals and cages for Sudoku variants, and either places a symbol or rules out candidates:
+//!
+//! - a hidden single is a symbol with one possible cell left in a unit;
+//! - a naked single is a cell with one candidate left;
+//! - an intersection is a symbol whose cells in one unit all lie in a second unit, so that it can be ruled out
+//!   in the rest of the second unit;
+//! - a naked pair or triple is k cells of a unit with only k candidates between them, which can be ruled out
+//!   in the rest of the unit;
+//! - a hidden pair or triple is k symbols with only k possible cells in a unit, which can hold nothing else.
+//!
+//! The next hint is the easiest technique that applies, and a puzzle's grade is the hardest technique that
+//! solving it by always taking the easiest one needs, or `Difficulty::Search` if the techniques get stuck.
+//! The techniques don't use cage sums, so Killer puzzles usually need search.
+//!
+//! Sources:
+//!
+//! - [Sudoku solving algorithms, Wikipedia](https://en.wikipedia.org/wiki/Sudoku_solving_algorithms)
+//! - [Hodoku, solving techniques](https://hodoku.sourceforge.net/en/techniques.php)
+
+use std::fmt;
+
+use crate::bitset::BitSet;
+use crate::latin_square::Symbol;
+use crate::partial::PartialLatinSquare;
+use crate::sudoku::{Puzzle, Rules};
+
+/// A solving technique, from easiest to hardest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    Intersection,
+    NakedPair,
+    HiddenPair,
+    NakedTriple,
+    HiddenTriple
+}
+
+impl Technique {
+    /// Every technique, from easiest to hardest.
+    pub const ALL: [Technique; 7] = [
+        Technique::HiddenSingle, Technique::NakedSingle, Technique::Intersection, Technique::NakedPair,
+        Technique::HiddenPair, Technique::NakedTriple, Technique::HiddenTriple
+    ];
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Technique::HiddenSingle => "Hidden single",
+            Technique::NakedSingle => "Naked single",
+            Technique::Intersection => "Intersection",
+            Technique::NakedPair => "Naked pair",
+            Technique::HiddenPair => "Hidden pair",
+            Technique::NakedTriple => "Naked triple",
+            Technique::HiddenTriple => "Hidden triple"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One logical step: a technique found in a unit, with the symbol it places or the candidates it rules out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub technique: Technique,
+    /// The name of the unit the pattern is in, such as "row 3" or "box 0".
+    pub unit: String,
+    /// The (row, column, symbol) to fill in, for singles.
+    pub placement: Option<(usize, usize, Symbol)>,
+    /// The (row, column, symbol) candidates to rule out, for the other techniques.
+    pub eliminations: Vec<(usize, usize, Symbol)>
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.placement {
+            Some((row, column, symbol)) => write!(f, "{} in {}: row {}, column {} is {}.", self.technique, self.unit, row, column, symbol),
+            None => {
+                let eliminations: Vec<String> = self.eliminations.iter()
+                    .map(|(row, column, symbol)| format!("{} from row {}, column {}", symbol, row, column))
+                    .collect();
+                write!(f, "{} in {}: remove {}.", self.technique, self.unit, eliminations.join(", "))
+            }
+        }
+    }
+}
+
+/// How hard a puzzle is to solve by the techniques.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// No empty cells.
+    Trivial,
+    /// The hardest technique needed.
+    Technique(Technique),
+    /// The techniques get stuck, or the puzzle has no solution.
+    Search
+}
+
+/// A puzzle being solved: the filled cells and the candidates of the empty ones.
+///
+/// ```
+/// use combinatorial_patterns::hints::{PuzzleState, Technique};
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::sudoku::Rules;
+///
+/// let clues = PartialLatinSquare::from_rows(vec![
+///     vec![Some(0), Some(1), None],
+///     vec![None, None, None],
+///     vec![None, None, Some(0)]
+/// ]).unwrap();
+/// let mut state = PuzzleState::new(Rules::latin(3), &clues).unwrap();
+/// let hint = state.next_hint().unwrap();
+/// assert_eq!(hint.technique, Technique::HiddenSingle);
+/// assert_eq!(hint.to_string(), "Hidden single in row 0: row 0, column 2 is 2.");
+/// state.apply(&hint);
+/// while let Some(hint) = state.next_hint() {
+///     state.apply(&hint);
+/// }
+/// assert!(state.is_solved());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PuzzleState {
+    order: usize,
+    units: Vec<Vec<usize>>,
+    names: Vec<String>,
+    cells: Vec<Option<Symbol>>,
+    candidates: Vec<BitSet>
+}
+
+impl PuzzleState {
+    /// The state with only the clues filled in, every other symbol a candidate unless a peer holds it.
+    /// Returns None if the clues have the wrong order or break a unit.
+    pub fn new(rules: Rules, clues: &PartialLatinSquare) -> Option<PuzzleState> {
+        let puzzle = Puzzle::new(rules, clues.clone())?;
+        Some(PuzzleState::from_puzzle(&puzzle))
+    }
+
+    /// The state of a puzzle before any step.
+    pub fn from_puzzle(puzzle: &Puzzle) -> PuzzleState {
+        let n = puzzle.rules().order();
+        let mut state = PuzzleState {
+            order: n,
+            units: puzzle.rules().units(),
+            names: unit_names(puzzle.rules()),
+            cells: vec![None; n * n],
+            candidates: vec![BitSet::full(n); n * n]
+        };
+        for (row, column, symbol) in puzzle.clues().filled_cells() {
+            state.place(row * n + column, symbol);
+        }
+        state
+    }
+
+    /// The symbol at (row, column), if the cell is filled.
+    pub fn get(&self, row: usize, column: usize) -> Option<Symbol> {
+        self.cells[row * self.order + column]
+    }
+
+    /// The candidates left for an empty cell, or nothing for a filled one.
+    pub fn candidates(&self, row: usize, column: usize) -> &BitSet {
+        &self.candidates[row * self.order + column]
+    }
+
+    /// The filled cells so far.
+    pub fn grid(&self) -> PartialLatinSquare {
+        let mut grid = PartialLatinSquare::new(self.order);
+        for (cell, symbol) in self.cells.iter().enumerate() {
+            grid.set(cell / self.order, cell % self.order, *symbol);
+        }
+        grid
+    }
+
+    /// Returns true if every cell is filled.
+    pub fn is_solved(&self) -> bool {
+        self.cells.iter().all(|cell| cell.is_some())
+    }
+
+    /// The easiest step that applies, or None if the puzzle is solved, stuck, or has an empty cell without candidates.
+    pub fn next_hint(&self) -> Option<Hint> {
+        if (0..self.cells.len()).any(|cell| self.cells[cell].is_none() && self.candidates[cell].is_empty()) {
+            return None
+        }
+        Technique::ALL.iter().find_map(|&technique| self.find(technique))
+    }
+
+    /// Fills in the placement of a hint and rules out its eliminations.
+    pub fn apply(&mut self, hint: &Hint) {
+        if let Some((row, column, symbol)) = hint.placement {
+            self.place(row * self.order + column, symbol);
+        }
+        for &(row, column, symbol) in &hint.eliminations {
+            self.candidates[row * self.order + column].remove(symbol);
+        }
+    }
+
+    /// The first pattern of a technique, if any.
+    pub fn find(&self, technique: Technique) -> Option<Hint> {
+        match technique {
+            Technique::HiddenSingle => self.hidden_single(),
+            Technique::NakedSingle => self.naked_single(),
+            Technique::Intersection => self.intersection(),
+            Technique::NakedPair => self.naked_subset(technique, 2),
+            Technique::HiddenPair => self.hidden_subset(technique, 2),
+            Technique::NakedTriple => self.naked_subset(technique, 3),
+            Technique::HiddenTriple => self.hidden_subset(technique, 3)
+        }
+    }
+
+    fn place(&mut self, cell: usize, symbol: Symbol) {
+        self.cells[cell] = Some(symbol);
+        self.candidates[cell] = BitSet::new(self.order);
+        for unit in self.units.iter().filter(|unit| unit.contains(&cell)) {
+            for &peer in unit {
+                self.candidates[peer].remove(symbol);
+            }
+        }
+    }
+
+    fn hint(&self, technique: Technique, unit: usize, placement: Option<(usize, Symbol)>, eliminations: Vec<(usize, Symbol)>) -> Hint {
+        let n = self.order;
+        Hint {
+            technique,
+            unit: self.names[unit].clone(),
+            placement: placement.map(|(cell, symbol)| (cell / n, cell % n, symbol)),
+            eliminations: eliminations.into_iter().map(|(cell, symbol)| (cell / n, cell % n, symbol)).collect()
+        }
+    }
+
+    /// The empty cells of a unit that can still hold a symbol.
+    fn places(&self, unit: usize, symbol: Symbol) -> Vec<usize> {
+        self.units[unit].iter().copied().filter(|&cell| self.candidates[cell].contains(symbol)).collect()
+    }
+
+    /// The symbols a full unit still needs.
+    fn missing(&self, unit: usize) -> Vec<Symbol> {
+        (0..self.order).filter(|&symbol| self.units[unit].iter().all(|&cell| self.cells[cell] != Some(symbol))).collect()
+    }
+
+    fn is_full_size(&self, unit: usize) -> bool {
+        self.units[unit].len() == self.order
+    }
+
+    fn hidden_single(&self) -> Option<Hint> {
+        (0..self.units.len()).filter(|&unit| self.is_full_size(unit)).find_map(|unit| {
+            self.missing(unit).into_iter().find_map(|symbol| match self.places(unit, symbol)[..] {
+                [cell] => Some(self.hint(Technique::HiddenSingle, unit, Some((cell, symbol)), Vec::new())),
+                _ => None
+            })
+        })
+    }
+
+    fn naked_single(&self) -> Option<Hint> {
+        let cell = (0..self.cells.len()).find(|&cell| self.cells[cell].is_none() && self.candidates[cell].count() == 1)?;
+        let symbol = self.candidates[cell].first().expect("ProgrammingError: the cell has one candidate.");
+        let unit = self.units.iter().position(|unit| unit.contains(&cell)).expect("ProgrammingError: every cell is in its row.");
+        Some(self.hint(Technique::NakedSingle, unit, Some((cell, symbol)), Vec::new()))
+    }
+
+    fn intersection(&self) -> Option<Hint> {
+        for unit in (0..self.units.len()).filter(|&unit| self.is_full_size(unit)) {
+            for symbol in self.missing(unit) {
+                let places = self.places(unit, symbol);
+                if places.len() < 2 {
+                    continue
+                }
+                for other in (0..self.units.len()).filter(|&other| other != unit) {
+                    if !places.iter().all(|cell| self.units[other].contains(cell)) {
+                        continue
+                    }
+                    let eliminations: Vec<(usize, Symbol)> = self.places(other, symbol).into_iter()
+                        .filter(|cell| !places.contains(cell))
+                        .map(|cell| (cell, symbol))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(self.hint(Technique::Intersection, unit, None, eliminations))
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn naked_subset(&self, technique: Technique, size: usize) -> Option<Hint> {
+        for unit in 0..self.units.len() {
+            let open: Vec<usize> = self.units[unit].iter().copied().filter(|&cell| self.cells[cell].is_none()).collect();
+            if open.len() <= size {
+                continue
+            }
+            for chosen in subsets(&open, size) {
+                let mut union = BitSet::new(self.order);
+                for &cell in &chosen {
+                    union.union_with(&self.candidates[cell]);
+                }
+                if union.count() != size {
+                    continue
+                }
+                let eliminations: Vec<(usize, Symbol)> = open.iter()
+                    .filter(|cell| !chosen.contains(cell))
+                    .flat_map(|&cell| self.candidates[cell].intersection(&union).iter().map(move |symbol| (cell, symbol)).collect::<Vec<_>>())
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(self.hint(technique, unit, None, eliminations))
+                }
+            }
+        }
+        None
+    }
+
+    fn hidden_subset(&self, technique: Technique, size: usize) -> Option<Hint> {
+        for unit in (0..self.units.len()).filter(|&unit| self.is_full_size(unit)) {
+            let missing = self.missing(unit);
+            if missing.len() <= size {
+                continue
+            }
+            for chosen in subsets(&missing, size) {
+                let mut cells: Vec<usize> = chosen.iter().flat_map(|&symbol| self.places(unit, symbol)).collect();
+                cells.sort_unstable();
+                cells.dedup();
+                if cells.len() != size {
+                    continue
+                }
+                let keep = BitSet::from_values(self.order, &chosen);
+                let eliminations: Vec<(usize, Symbol)> = cells.iter()
+                    .flat_map(|&cell| self.candidates[cell].difference(&keep).iter().map(move |symbol| (cell, symbol)).collect::<Vec<_>>())
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(self.hint(technique, unit, None, eliminations))
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Grades a puzzle by the hardest technique needed when always taking the easiest hint.
+///
+/// ```
+/// use combinatorial_patterns::hints::{self, Difficulty};
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::sudoku::{self, Rules};
+///
+/// let puzzle = sudoku::generate(&Rules::sudoku(3), &mut rand::thread_rng()).unwrap();
+/// assert!(hints::grade(&puzzle) > Difficulty::Trivial);
+///
+/// let solved = sudoku::Puzzle::new(Rules::sudoku(3), PartialLatinSquare::from_square(&puzzle.solve().unwrap())).unwrap();
+/// assert_eq!(hints::grade(&solved), Difficulty::Trivial);
+/// ```
+pub fn grade(puzzle: &Puzzle) -> Difficulty {
+    let mut state = PuzzleState::from_puzzle(puzzle);
+    let mut hardest = Difficulty::Trivial;
+    while let Some(hint) = state.next_hint() {
+        hardest = hardest.max(Difficulty::Technique(hint.technique));
+        state.apply(&hint);
+    }
+    if state.is_solved() {
+        hardest
+    } else {
+        Difficulty::Search
+    }
+}
+
+/// The names of the units of some rules, in the order of `Rules::units`.
+fn unit_names(rules: &Rules) -> Vec<String> {
+    let n = rules.order();
+    let mut names: Vec<String> = (0..n).map(|row| format!("row {}", row)).collect();
+    names.extend((0..n).map(|column| format!("column {}", column)));
+    if rules.box_shape().is_some() {
+        names.extend((0..n).map(|index| format!("box {}", index)));
+    }
+    if rules.diagonals() {
+        names.push("the main diagonal".to_string());
+        names.push("the anti-diagonal".to_string());
+    }
+    names.extend((0..rules.cages().len()).map(|cage| format!("cage {}", cage)));
+    names
+}
+
+/// The subsets of `size` items, each in the order of `items`.
+fn subsets(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()]
+    }
+    (0..items.len()).flat_map(|first| {
+        subsets(&items[(first + 1)..], size - 1).into_iter().map(move |mut rest| {
+            rest.insert(0, items[first]);
+            rest
+        })
+    }).collect()
+}
@@ -0,0 +1,120 @@
+//! Reading latin squares in the formats of published datasets and other tools.
+//!
+//! - Digit strings: a square on one line, row by row, one character per cell, as in the lists of squares
+//!   of McKay and others. Symbols are the digits, then the letters, so orders up to 36 fit.
+//! - GAP tables: the Cayley table as a list of rows, as printed by GAP and the LOOPS package, optionally
+//!   wrapped in `QuasigroupByCayleyTable( .. )` or an assignment, as `algebra_export` writes them.
+//! - Text banks: many squares one after another, a row per line with the symbols separated by spaces or
+//!   commas, and blank lines or comment lines starting with `#` anywhere, as in the banks of diagonal
+//!   latin squares from volunteer computing projects.
+//!
+//! Every parser checks that the result is a latin square, and numbers the symbols from 0: squares whose
+//! symbols are 1..n, as in GAP, are shifted down by one. Anything else returns None.
+//!
+//! Sources:
+//!
+//! - [Combinatorial data: latin squares, B. D. McKay](https://users.cecs.anu.edu.au/~bdm/data/latin.html)
+//! - [LOOPS, computing with quasigroups and loops in GAP, G. P. Nagy, P. Vojtěchovský](https://gap-packages.github.io/loops/)
+
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::sudoku::Rules;
+
+/// Reads a square written row by row on one line, a digit or letter per cell.
+///
+/// ```
+/// use combinatorial_patterns::import;
+///
+/// let square = import::from_digits("012120201").unwrap();
+/// assert_eq!(square.square, vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]]);
+/// // Symbols from 1 are shifted down.
+/// assert_eq!(import::from_digits("1221").unwrap().square, vec![vec![0, 1], vec![1, 0]]);
+/// assert!(import::from_digits("0110").is_some());
+/// assert!(import::from_digits("0011").is_none());
+/// assert!(import::from_digits("01201").is_none());
+/// ```
+pub fn from_digits(text: &str) -> Option<LatinSquare> {
+    let symbols: Vec<Symbol> = text.trim().chars().map(|symbol| symbol.to_digit(36).map(|digit| digit as Symbol)).collect::<Option<_>>()?;
+    let order = (0..=symbols.len()).find(|order| order * order >= symbols.len())?;
+    if order * order != symbols.len() {
+        return None
+    }
+    validated(symbols.chunks(order.max(1)).map(|row| row.to_vec()).collect())
+}
+
+/// Reads a Cayley table as a GAP list of rows, with or without a surrounding call or assignment.
+///
+/// ```
+/// use combinatorial_patterns::import;
+///
+/// let square = import::from_gap("Q := QuasigroupByCayleyTable([ [ 1, 2, 3 ], [ 3, 1, 2 ], [ 2, 3, 1 ] ]);").unwrap();
+/// assert_eq!(square.square, vec![vec![0, 1, 2], vec![2, 0, 1], vec![1, 2, 0]]);
+/// assert!(import::from_gap("[[1, 2], [2, 2]]").is_none());
+/// ```
+pub fn from_gap(text: &str) -> Option<LatinSquare> {
+    let (start, end) = (text.find('[')?, text.rfind(']')?);
+    let inner = text.get(start + 1..end)?.trim();
+    if inner.is_empty() {
+        return validated(Vec::new())
+    }
+    // The rows are "[1, 2, 3", then ", [3, 1, 2", and so on, after splitting at the closing brackets.
+    let rows: Vec<Vec<Symbol>> = inner.split(']')
+        .map(|row| row.trim_start_matches(|separator: char| separator == ',' || separator.is_whitespace()))
+        .filter(|row| !row.is_empty())
+        .map(|row| parse_symbols(row.strip_prefix('[')?))
+        .collect::<Option<_>>()?;
+    validated(rows)
+}
+
+/// Reads every square of a text bank, in order. Returns None if some square is not a latin square, or
+/// the rows do not split into squares.
+///
+/// ```
+/// use combinatorial_patterns::import;
+///
+/// let bank = "# two squares of order 3\n0 1 2\n1 2 0\n2 0 1\n\n0 2 1\n2 1 0\n1 0 2\n";
+/// let squares = import::from_bank(bank).unwrap();
+/// assert_eq!(squares.len(), 2);
+/// assert_eq!(squares[1].square[0], vec![0, 2, 1]);
+/// assert!(import::from_bank("0 1\n1 0\n0 1\n").is_none());
+/// ```
+pub fn from_bank(text: &str) -> Option<Vec<LatinSquare>> {
+    let rows: Vec<Vec<Symbol>> = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_symbols)
+        .collect::<Option<_>>()?;
+    let mut squares = Vec::new();
+    let mut rest = &rows[..];
+    while let Some(first) = rest.first() {
+        let order = first.len();
+        if order == 0 || rest.len() < order {
+            return None
+        }
+        squares.push(validated(rest[..order].to_vec())?);
+        rest = &rest[order..];
+    }
+    Some(squares)
+}
+
+/// Symbols separated by commas or whitespace.
+fn parse_symbols(text: &str) -> Option<Vec<Symbol>> {
+    text.split(|separator: char| separator == ',' || separator.is_whitespace())
+        .filter(|symbol| !symbol.is_empty())
+        .map(|symbol| symbol.parse().ok())
+        .collect()
+}
+
+/// The square of the rows with symbols from 0, if it is a latin square with symbols 0..n or 1..n.
+fn validated(mut rows: Vec<Vec<Symbol>>) -> Option<LatinSquare> {
+    let order = rows.len();
+    if rows.iter().any(|row| row.len() != order) {
+        return None
+    }
+    if !rows.iter().flatten().any(|&symbol| symbol == 0) {
+        for symbol in rows.iter_mut().flatten() {
+            *symbol -= 1;
+        }
+    }
+    let square = LatinSquare::from_rows(rows);
+    if Rules::latin(order).is_solution(&square) { Some(square) } else { None }
+}
@@ -0,0 +1,409 @@
+//! Incomplete latin squares (latin squares with a hole) and incomplete MOLS.
+//!
+//! An incomplete latin square of order n with hole H, |H| = h, is an n x n array on n symbols where:
+//!
+//! - the cells (x, y) with both x and y in H are empty (the hole),
+//! - a row or column indexed outside H contains every symbol exactly once,
+//! - a row or column indexed inside H contains every symbol outside H exactly once.
+//!
+//! They exist exactly when n >= 2h. Filling the hole with any latin square of order h on the symbols of H
+//! gives a latin square of order n, and conversely every latin square with a subsquare is an incomplete
+//! latin square once the subsquare is removed.
+//!
+//! Incomplete MOLS are incomplete latin squares sharing the same hole, in which every ordered pair of
+//! symbols that are not both in H appears exactly once among the cells outside the hole. Filling their
+//! hole with MOLS of order h gives MOLS of order n, which is how constructions reach orders where direct
+//! methods don't work.
+//!
+//! Sources:
+//!
+//! - [Handbook of Combinatorial Designs, Charles J. Colbourn, Jeffrey H. Dinitz (Chapter III.4)](https://www.routledge.com/Handbook-of-Combinatorial-Designs/Colbourn-Dinitz/p/book/9781584885061)
+//! - [Embedding partial latin squares, Trevor Evans](https://doi.org/10.2307/2308960)
+
+use crate::finite_field::FiniteField;
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::matching::{BipartiteGraph, Matching};
+
+/// An n x n array with an empty h x h hole, as described in the module documentation.
+///
+/// The same index set is used for the hole's rows, columns, and symbols.
+///
+/// ```
+/// use combinatorial_patterns::incomplete::IncompleteLatinSquare;
+/// use combinatorial_patterns::IncidenceCube;
+///
+/// let incomplete = IncompleteLatinSquare::new(7, 3).unwrap();
+/// assert!(incomplete.is_valid());
+/// assert_eq!(incomplete.get(0, 1), None);
+///
+/// let filled = incomplete.fill(&IncidenceCube::new_cyclic(3).unwrap().as_latin_square()).unwrap();
+/// assert_eq!(filled.size(), 7);
+/// assert!(IncompleteLatinSquare::new(7, 4).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncompleteLatinSquare {
+    order: usize,
+    hole: Vec<usize>,
+    cells: Vec<Vec<Option<Symbol>>>
+}
+
+impl IncompleteLatinSquare {
+    /// Creates an incomplete latin square of the given order, with hole {0, .., hole_size - 1}.
+    ///
+    /// Built by embedding a cyclic square of order `hole_size` with `embed` and removing it again.
+    /// Returns None if `order < 2 * hole_size`, where none exists.
+    pub fn new(order: usize, hole_size: usize) -> Option<IncompleteLatinSquare> {
+        let subsquare = LatinSquare::from_rows((0..hole_size).map(|row| {
+            (0..hole_size).map(|column| (row + column) % hole_size).collect()
+        }).collect());
+        let hole: Vec<usize> = (0..hole_size).collect();
+        IncompleteLatinSquare::from_subsquare(&embed(&subsquare, order)?, &hole)
+    }
+
+    /// Removes the subsquare on rows and columns `hole` from `square`.
+    ///
+    /// Returns None unless the cells of the subsquare hold exactly the symbols of `hole` too,
+    /// and the result is a valid incomplete latin square.
+    pub fn from_subsquare(square: &LatinSquare, hole: &[usize]) -> Option<IncompleteLatinSquare> {
+        let order = square.size();
+        let mut in_hole = vec![false; order];
+        for &index in hole {
+            *in_hole.get_mut(index)? = true;
+        }
+        let mut cells = vec![vec![None; order]; order];
+        for (x, row) in square.square.iter().enumerate() {
+            for (y, &symbol) in row.iter().enumerate() {
+                match (in_hole[x] && in_hole[y], in_hole.get(symbol)) {
+                    (true, Some(true)) => {},
+                    (true, _) => return None,
+                    (false, _) => cells[x][y] = Some(symbol)
+                }
+            }
+        }
+        let mut sorted_hole = hole.to_vec();
+        sorted_hole.sort_unstable();
+        sorted_hole.dedup();
+
+        let incomplete = IncompleteLatinSquare { order, hole: sorted_hole, cells };
+        if incomplete.is_valid() {
+            Some(incomplete)
+        } else {
+            None
+        }
+    }
+
+    /// Checks the incomplete latin square properties described in the module documentation.
+    pub fn is_valid(&self) -> bool {
+        let order = self.order;
+        let in_hole = self.hole_membership();
+        if self.cells.len() != order || self.cells.iter().any(|row| row.len() != order) {
+            return false
+        }
+
+        for line in 0..order {
+            let mut seen_in_row = vec![false; order];
+            let mut seen_in_column = vec![false; order];
+            for other in 0..order {
+                let hole_cell = in_hole[line] && in_hole[other];
+                for (cell, seen) in [(self.cells[line][other], &mut seen_in_row), (self.cells[other][line], &mut seen_in_column)] {
+                    match (cell, hole_cell) {
+                        (None, true) => {},
+                        (Some(symbol), false) if symbol < order && !seen[symbol] => seen[symbol] = true,
+                        _ => return false
+                    }
+                }
+            }
+            // Lines through the hole miss exactly the hole's symbols, which the counting already forces,
+            // as long as none of the symbols they do contain belong to the hole.
+            if in_hole[line] {
+                let hole_symbol_used = self.hole.iter().any(|&symbol| seen_in_row[symbol] || seen_in_column[symbol]);
+                if hole_symbol_used {
+                    return false
+                }
+            }
+        }
+        true
+    }
+
+    /// Fills the hole with `subsquare`, a latin square of order h on symbols 0..h,
+    /// where row, column, and symbol i of the subsquare become the i-th smallest hole index.
+    ///
+    /// Returns None if the subsquare has the wrong order or is not a latin square on 0..h.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::incomplete::IncompleteLatinSquare;
+    ///
+    /// let incomplete = IncompleteLatinSquare::new(6, 2).unwrap();
+    /// let filled = incomplete.fill(&LatinSquare::new_back_circulant(2).unwrap()).unwrap();
+    /// assert!(filled.is_valid());
+    ///
+    /// let mut out_of_range = LatinSquare::new_back_circulant(2).unwrap();
+    /// out_of_range.square[0][0] = 5;
+    /// assert!(incomplete.fill(&out_of_range).is_none());
+    /// assert!(incomplete.fill(&LatinSquare::new_back_circulant(3).unwrap()).is_none());
+    /// ```
+    pub fn fill(&self, subsquare: &LatinSquare) -> Option<LatinSquare> {
+        if subsquare.size() != self.hole.len() || !subsquare.is_valid() {
+            return None
+        }
+        let mut rows: Vec<Vec<Symbol>> = self.cells.iter()
+            .map(|row| row.iter().map(|cell| cell.unwrap_or(0)).collect())
+            .collect();
+        for (i, &x) in self.hole.iter().enumerate() {
+            for (j, &y) in self.hole.iter().enumerate() {
+                rows[x][y] = self.hole[subsquare.square[i][j]];
+            }
+        }
+        Some(LatinSquare::from_rows(rows))
+    }
+
+    /// The order of the square.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The sorted rows, columns, and symbols of the hole.
+    pub fn hole(&self) -> &[usize] {
+        &self.hole
+    }
+
+    /// The symbol at (row, column), or None inside the hole.
+    pub fn get(&self, row: usize, column: usize) -> Option<Symbol> {
+        self.cells[row][column]
+    }
+
+    /// All cells row by row, with None inside the hole.
+    pub fn cells(&self) -> &[Vec<Option<Symbol>>] {
+        &self.cells
+    }
+
+    fn hole_membership(&self) -> Vec<bool> {
+        let mut in_hole = vec![false; self.order];
+        for &index in &self.hole {
+            in_hole[index] = true;
+        }
+        in_hole
+    }
+}
+
+/// A set of incomplete latin squares with a common hole, orthogonal outside of it.
+///
+/// ```
+/// use combinatorial_patterns::incomplete::IncompleteMols;
+/// use combinatorial_patterns::mols;
+///
+/// // GF(9) contains GF(3), which gives 2 incomplete MOLS of order 9 with a hole of size 3.
+/// let incomplete = IncompleteMols::new_prime_power(9, 3).unwrap();
+/// assert_eq!(incomplete.squares().len(), 2);
+/// assert!(incomplete.is_valid());
+///
+/// // Filling the hole with 2 MOLS of order 3 gives 2 MOLS of order 9.
+/// let filled = incomplete.fill(&mols::new_prime_power(3).unwrap()).unwrap();
+/// assert!(mols::are_mutually_orthogonal(&filled));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncompleteMols {
+    order: usize,
+    hole: Vec<usize>,
+    squares: Vec<IncompleteLatinSquare>
+}
+
+impl IncompleteMols {
+    /// Wraps incomplete latin squares as incomplete MOLS.
+    ///
+    /// Returns None unless they all share the same order and hole, and are orthogonal outside it.
+    pub fn new(squares: Vec<IncompleteLatinSquare>) -> Option<IncompleteMols> {
+        let first = squares.first()?;
+        let (order, hole) = (first.order, first.hole.clone());
+        let incomplete = IncompleteMols { order, hole, squares };
+        if incomplete.is_valid() {
+            Some(incomplete)
+        } else {
+            None
+        }
+    }
+
+    /// Removes a common subsquare on rows and columns `hole` from each of `squares`, which must be MOLS.
+    pub fn from_mols(squares: &[LatinSquare], hole: &[usize]) -> Option<IncompleteMols> {
+        let incomplete = squares.iter()
+            .map(|square| IncompleteLatinSquare::from_subsquare(square, hole))
+            .collect::<Option<Vec<IncompleteLatinSquare>>>()?;
+        IncompleteMols::new(incomplete)
+    }
+
+    /// Constructs `hole_size - 1` incomplete MOLS of a prime power order with a hole of size `hole_size`,
+    /// where GF(hole_size) is a subfield of GF(order).
+    ///
+    /// The squares `t * x + y` with t in the nonzero subfield elements keep the subfield as a common subsquare.
+    /// A hole of size 1 is always possible, and gives `order - 1` squares, as every square of the complete set
+    /// has 0 at the origin.
+    /// Returns None if the order is not a prime power, or has no subfield of that size.
+    pub fn new_prime_power(order: usize, hole_size: usize) -> Option<IncompleteMols> {
+        let field = FiniteField::new(order)?;
+        let (hole, multipliers): (Vec<usize>, Vec<usize>) = if hole_size == 1 {
+            (vec![0], (1..order).collect())
+        } else {
+            let subfield = field.subfield(hole_size)?;
+            (subfield.clone(), subfield.into_iter().filter(|&t| t != 0).collect())
+        };
+        let squares: Vec<LatinSquare> = multipliers.iter().map(|&t| {
+            LatinSquare::from_rows((0..order).map(|x| {
+                (0..order).map(|y| field.add(field.mul(t, x), y)).collect()
+            }).collect())
+        }).collect();
+        IncompleteMols::from_mols(&squares, &hole)
+    }
+
+    /// The direct product with a set of MOLS of order m, giving incomplete MOLS of order n * m
+    /// with a hole of size h * m. Keeps as many squares as the smaller of the two sets.
+    ///
+    /// Rows, columns, and symbols are numbered as in `mols::direct_product`.
+    pub fn direct_product(&self, squares: &[LatinSquare]) -> Option<IncompleteMols> {
+        let inner = squares.first()?.size();
+        let order = self.order * inner;
+        let hole: Vec<usize> = self.hole.iter().flat_map(|&h| (0..inner).map(move |i| h * inner + i)).collect();
+        let products = self.squares.iter().zip(squares.iter()).map(|(outer, square)| {
+            let cells = (0..order).map(|row| {
+                (0..order).map(|column| {
+                    outer.cells[row / inner][column / inner].map(|symbol| {
+                        symbol * inner + square.square[row % inner][column % inner]
+                    })
+                }).collect()
+            }).collect();
+            IncompleteLatinSquare { order, hole: hole.clone(), cells }
+        }).collect();
+        IncompleteMols::new(products)
+    }
+
+    /// Checks that every square is a valid incomplete latin square with the common hole,
+    /// and that every two squares are orthogonal outside of the hole.
+    pub fn is_valid(&self) -> bool {
+        let order = self.order;
+        let all_valid = self.squares.iter().all(|square| {
+            square.order == order && square.hole == self.hole && square.is_valid()
+        });
+        if !all_valid {
+            return false
+        }
+        self.squares.iter().enumerate().all(|(i, a)| {
+            self.squares[(i + 1)..].iter().all(|b| {
+                let mut seen = vec![vec![false; order]; order];
+                a.cells.iter().flatten().zip(b.cells.iter().flatten()).all(|pair| match pair {
+                    (Some(s), Some(t)) if !seen[*s][*t] => {
+                        seen[*s][*t] = true;
+                        true
+                    },
+                    (None, None) => true,
+                    _ => false
+                })
+            })
+        })
+    }
+
+    /// Fills the common hole with one latin square of order h per incomplete square, as in
+    /// `IncompleteLatinSquare::fill`. If the fillings are MOLS, the result is a set of MOLS of order n.
+    ///
+    /// Returns None if there are not enough fillings, or they have the wrong order.
+    pub fn fill(&self, subsquares: &[LatinSquare]) -> Option<Vec<LatinSquare>> {
+        if subsquares.len() < self.squares.len() {
+            return None
+        }
+        self.squares.iter().zip(subsquares.iter())
+            .map(|(square, subsquare)| square.fill(subsquare))
+            .collect()
+    }
+
+    /// The order of the squares.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The sorted rows, columns, and symbols of the common hole.
+    pub fn hole(&self) -> &[usize] {
+        &self.hole
+    }
+
+    /// The incomplete latin squares.
+    pub fn squares(&self) -> &[IncompleteLatinSquare] {
+        &self.squares
+    }
+}
+
+/// Embeds a latin square of order h with symbols 0..h as the top left subsquare of a latin square of
+/// order n, following Evans' theorem. Returns None if `order < 2 * h`, or if the rows do not form a latin
+/// square on the symbols 0..h, such as after writing to `square` directly.
+///
+/// The h rows are first extended column by column to an h x n latin rectangle. Ryser's condition says
+/// that can continue as long as every symbol appears at least h + c - n times in the first c columns,
+/// so each new column is a matching between rows and unused symbols that must include the symbols
+/// right at that limit. The rectangle is then completed row by row with perfect matchings.
+///
+/// ```
+/// use combinatorial_patterns::{incomplete, IncidenceCube, Quasigroup};
+///
+/// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+/// let embedded = incomplete::embed(&square, 8).unwrap();
+/// assert_eq!(embedded.square[2][..3], square.square[2][..]);
+/// assert!(Quasigroup::new(embedded).is_some());
+///
+/// let mut broken = square.clone();
+/// broken.square[0][0] = 1;
+/// assert!(incomplete::embed(&broken, 8).is_none());
+/// ```
+pub fn embed(square: &LatinSquare, order: usize) -> Option<LatinSquare> {
+    let h = square.square.len();
+    if order < 2 * h || !square.is_valid() {
+        return None
+    }
+
+    let mut rows: Vec<Vec<Symbol>> = square.square.clone();
+    for column in h..order {
+        let mut appearances = vec![0; order];
+        rows.iter().flatten().for_each(|&symbol| appearances[symbol] += 1);
+
+        let mut graph = BipartiteGraph::new(h, order);
+        for (r, row) in rows.iter().enumerate() {
+            for symbol in (0..order).filter(|symbol| !row.contains(symbol)) {
+                graph.add_edge(r, symbol);
+            }
+        }
+        let mut matching = Matching::empty(&graph);
+        // Symbols at Ryser's limit must appear in the new column, so they get matched first.
+        let limit = (h + column) as isize - order as isize;
+        for symbol in (0..order).filter(|&symbol| appearances[symbol] as isize == limit) {
+            matching.augment_from_right(&graph, symbol);
+        }
+        for r in 0..h {
+            matching.augment_from_left(&graph, r);
+        }
+        for (r, row) in rows.iter_mut().enumerate() {
+            row.push(matching.right_of(r).expect("ProgrammingError: Ryser's condition failed to extend the rectangle."));
+        }
+    }
+
+    complete_rectangle(&mut rows, order);
+    Some(LatinSquare::from_rows(rows))
+}
+
+/// Completes a latin rectangle of full width on symbols 0..order to a latin square, one perfect
+/// matching between columns and their unused symbols per new row.
+pub(crate) fn complete_rectangle(rows: &mut Vec<Vec<Symbol>>, order: usize) {
+    while rows.len() < order {
+        let mut used = vec![vec![false; order]; order];
+        for row in rows.iter() {
+            for (column, &symbol) in row.iter().enumerate() {
+                used[column][symbol] = true;
+            }
+        }
+        let mut graph = BipartiteGraph::new(order, order);
+        for (column, used_symbols) in used.iter().enumerate() {
+            for symbol in (0..order).filter(|&symbol| !used_symbols[symbol]) {
+                graph.add_edge(column, symbol);
+            }
+        }
+        let matching = graph.perfect_matching()
+            .expect("ProgrammingError: latin rectangles always have a perfect matching to extend them.");
+        rows.push((0..order).map(|column| matching.right_of(column).unwrap()).collect());
+    }
+}
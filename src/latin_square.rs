@@ -11,179 +11,1536 @@
 //! - [Generation of Random Latin Squares Step by Step and Graphically, Ignacio Gallego Sagastume](http://sedici.unlp.edu.ar/bitstream/handle/10915/42155/Documento_completo.pdf?sequence=1)
 
 
-use rand::{thread_rng, Rng};
+#[cfg(feature = "serde")]
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use rand::distributions::Distribution;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::io::{self, Read, Write};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::mem;
+use std::ops::{Index, IndexMut};
 
-type Symbol = usize;
+use crate::canonical::{self, Equivalence, Fingerprint};
+use crate::error::CombinatorialError;
+use crate::explain::{CubeCell, ShuffleTrace, Step};
+use crate::render::{Alphabet, DisplayOptions};
+use crate::switching;
 
-#[derive(Debug)]
+pub type Symbol = usize;
+
+/// Whether symbols count from 0 or from 1 where they meet the outside: in rows given to or taken from a
+/// square, in text parsed or displayed, and in the errors about them. Squares always hold 0..n inside, as
+/// every construction of the crate makes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SymbolBase {
+    #[default]
+    Zero,
+    One
+}
+
+impl SymbolBase {
+    /// The first symbol, 0 or 1.
+    pub fn first(self) -> Symbol {
+        match self {
+            SymbolBase::Zero => 0,
+            SymbolBase::One => 1
+        }
+    }
+
+    /// The alphabet writing symbols in the base.
+    pub fn alphabet(self) -> Alphabet {
+        match self {
+            SymbolBase::Zero => Alphabet::Numbers,
+            SymbolBase::One => Alphabet::NumbersFromOne
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum CubeEntry {
     On,
     Off,
     Improper
 }
 
-impl CubeEntry {
-    /// Returns one step lower of the current cube entry.
-    /// 
-    /// Direction is On -> Off -> Improper -> !panic
-    /// Panics if Improper entry is turned off, as this should only occur due to a programming error.
-    pub fn toggle_off(&self) -> CubeEntry {
-        match self {
-            CubeEntry::On => CubeEntry::Off,
-            CubeEntry::Off => CubeEntry::Improper,
-            CubeEntry::Improper => panic!("ProgrammingError: Cannot turn off improper cell.")
-        }
+impl CubeEntry {
+    /// Returns one step lower of the current cube entry.
+    /// 
+    /// Direction is On -> Off -> Improper -> error
+    /// Fails if Improper entry is turned off, as this should only occur due to a programming error.
+    pub fn toggle_off(&self) -> Result<CubeEntry, CombinatorialError> {
+        match self {
+            CubeEntry::On => Ok(CubeEntry::Off),
+            CubeEntry::Off => Ok(CubeEntry::Improper),
+            CubeEntry::Improper => Err(CombinatorialError::AlreadyImproper)
+        }
+    }
+    /// Returns one step higher of the current cube entry.
+    /// 
+    /// Direction is Improper -> Off -> On -> error
+    /// Fails if on entry is turned on, as this should only occur due to a programming error.
+    pub fn toggle_on(&self) -> Result<CubeEntry, CombinatorialError> {
+        match self {
+            CubeEntry::Off => Ok(CubeEntry:: On),
+            CubeEntry::Improper => Ok(CubeEntry::Off),
+            CubeEntry::On => Err(CombinatorialError::AlreadyOn)
+        }
+    }
+
+    /// Helper to get cube entry as i32 value.
+    /// Mark T. Jacobson, Peter Matthews approaches usually calculate by adding/substracting.
+    /// This function allows the string output of a cube in a format more generally used with latin squares.
+    /// 
+    /// On = 1, Off = 0, Improper = -1.
+    pub fn as_int(&self) -> i32 {
+        match self {
+            CubeEntry::On => 1,
+            CubeEntry::Off => 0,
+            CubeEntry::Improper => -1
+        }
+    }
+}
+
+/// One of the six conjugates of a latin square, named by the roles of the original rows (r), columns (c),
+/// and symbols (s) as the rows, columns, and symbols of the conjugate: `Csr` reads every (r, c, s) triple
+/// as row c, column s, symbol r. `Rcs` is the square itself and `Crs` its transpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConjugateKind {
+    Rcs,
+    Crs,
+    Rsc,
+    Src,
+    Csr,
+    Scr
+}
+
+impl ConjugateKind {
+    /// Every conjugate, the square itself first.
+    pub const ALL: [ConjugateKind; 6] = [
+        ConjugateKind::Rcs, ConjugateKind::Crs, ConjugateKind::Rsc, ConjugateKind::Src, ConjugateKind::Csr, ConjugateKind::Scr
+    ];
+
+    /// The positions in (r, c, s) of the conjugate's row, column, and symbol.
+    fn roles(&self) -> [usize; 3] {
+        match self {
+            ConjugateKind::Rcs => [0, 1, 2],
+            ConjugateKind::Crs => [1, 0, 2],
+            ConjugateKind::Rsc => [0, 2, 1],
+            ConjugateKind::Src => [2, 0, 1],
+            ConjugateKind::Csr => [1, 2, 0],
+            ConjugateKind::Scr => [2, 1, 0]
+        }
+    }
+}
+
+/// Reads the (row, column, symbol) triples of `rows` in the roles of the conjugate. The rows must form a
+/// latin square.
+pub(crate) fn conjugate_rows(rows: &[Vec<Symbol>], kind: ConjugateKind) -> Vec<Vec<Symbol>> {
+    let roles = kind.roles();
+    let order = rows.len();
+    let mut result = vec![vec![0; order]; order];
+    for (row, symbols) in rows.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            let triple = [row, column, symbol];
+            result[triple[roles[0]]][triple[roles[1]]] = triple[roles[2]];
+        }
+    }
+    result
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SearchCoord {
+    X,
+    Y,
+    Z
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Coordinate {
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+impl Coordinate {
+    /// Mutates the incidence cube's specified search coordinate by one.
+    /// Helper to allow incrementing an axis without knowing in advance which coordinate we want to increment.
+    pub fn increment(&mut self, coord: SearchCoord) {
+        match coord {
+            SearchCoord::X => self.x += 1,
+            SearchCoord::Y => self.y += 1,
+            SearchCoord::Z => self.z += 1
+        };
+    }
+
+    /// Helper to find the value of the coordinate we are searching for.
+    /// Abstraction for operating withotu knowing what coordinate we are using in advance.
+    pub fn search_axis(&self, coord: SearchCoord) -> usize {
+        match coord {
+            SearchCoord::X => self.x,
+            SearchCoord::Y => self.y,
+            SearchCoord::Z => self.z
+        }
+    }
+
+    /// The coordinate as a (row, column, symbol) cell of the cube.
+    fn as_cube_cell(&self) -> CubeCell {
+        CubeCell { row: self.x, column: self.y, symbol: self.z }
+    }
+
+    /// Creaets a new coordinate with th specified coordinates. The search coordinate is initialized
+    ///     as Zero, as it will be iterated over to search for a specific value later.
+    /// the usize specified that matches the search coordinate will be ignored.
+    pub fn init_for_search(x: usize, y: usize, z: usize, search: SearchCoord) -> Coordinate {
+        match search {
+            SearchCoord::X => Coordinate {x: 0, y, z},
+            SearchCoord::Y => Coordinate {x, y: 0, z},
+            SearchCoord::Z => Coordinate {x, y, z: 0}
+        }
+    }
+}
+
+/// An n x n grid of n symbols, where each symbol appears in each row and column only once.
+/// 
+/// Can be used to create a latin square of any size.
+/// 
+/// To create a random valid latin square, simply call `LatinSquare::new_random(dimensions, &mut rng)`
+/// 
+/// An example rust main that would generate and output the resulting square:
+///
+/// ```
+/// # use std::env;
+/// # use combinatorial_patterns::LatinSquare;
+/// fn _main() {
+///    println!("making cube...");
+///    let args: Vec<String> = env::args().collect();
+///    let size = args[1].parse().unwrap_or(4);
+///    println!("{}", LatinSquare::new_random(size, &mut rand::thread_rng()).unwrap());
+/// }
+/// ```
+///
+/// Squares compare and hash by their rows and symbols alone.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+///
+/// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+/// let mut copy = square.clone();
+/// assert_eq!(copy, square);
+/// copy.set(0, 0, copy[(0, 0)]);
+/// assert_eq!(copy, square);
+/// let squares: HashSet<LatinSquare> = [square, copy, LatinSquare::new_cyclic_with_step(4, 3).unwrap()].into_iter().collect();
+/// assert_eq!(squares.len(), 2);
+/// ```
+///
+/// The symbols can be of any type, such as the names of treatments, through `LatinSquare::from_symbols`.
+/// The rows then hold the index of every symbol in `symbols`, so the constructions and the algorithms of
+/// the crate, which work on squares of indices, apply to them unchanged. The constructions give squares of
+/// `usize` whose symbols are the identity 0..n, so that their cells are their symbols; relabeling one keeps
+/// the indices in the cells, and `normalized` writes the new symbols into them. For squares shown from 1,
+/// see `SymbolBase`.
+///
+/// ```
+/// use combinatorial_patterns::LatinSquare;
+///
+/// let square = LatinSquare::from_symbols(vec![vec!['x', 'o'], vec!['o', 'x']]).unwrap();
+/// assert_eq!(square.symbols(), ['x', 'o']);
+/// assert_eq!(square.square, [[0, 1], [1, 0]]);
+/// assert_eq!(square.symbol(1, 0), Some(&'o'));
+/// assert_eq!(square.to_string(), "Latin square of size 2\n\nx o\no x");
+/// assert!(square.indices().is_valid());
+///
+/// // Squares are equal when they show the same symbols, however the symbols are numbered.
+/// let swapped = LatinSquare::try_from(vec![vec![1, 0], vec![0, 1]]).unwrap().with_alphabet(&['o', 'x']).unwrap();
+/// assert_eq!(swapped.to_string(), square.to_string());
+/// assert_eq!(swapped, square);
+/// assert_eq!([swapped, square].into_iter().collect::<std::collections::HashSet<_>>().len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct LatinSquare<T = Symbol> {
+    size: usize,
+    /// The rows, as the index of the symbol in every cell.
+    pub square: Vec<Vec<Symbol>>,
+    symbols: Vec<T>,
+    dirty: bool
+}
+
+impl<T: Clone + Eq + Hash> LatinSquare<T> {
+    /// Builds a square from rows of symbols of any type, numbering the symbols in order of first appearance,
+    /// row by row, and checking the numbered rows like `TryFrom`. Borrowed symbols, such as `&str` cut from
+    /// a line of input, work as well as owned ones.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::latin_square::LatinSquareError;
+    ///
+    /// let rows = vec![vec!["tea", "coffee"], vec!["coffee", "tea"]];
+    /// let square = LatinSquare::from_symbols(rows.clone()).unwrap();
+    /// assert_eq!(square.symbol_rows(), rows);
+    /// assert_eq!(square.index_of(&"coffee"), Some(1));
+    ///
+    /// let line = String::from("b a\na b");
+    /// let rows: Vec<Vec<&str>> = line.lines().map(|row| row.split(' ').collect()).collect();
+    /// let square = LatinSquare::from_symbols(rows).unwrap();
+    /// assert_eq!(square.symbols(), ["b", "a"]);
+    ///
+    /// let error = LatinSquare::from_symbols(vec![vec!["a", "b"], vec!["c", "a"]]).err();
+    /// assert_eq!(error, Some(LatinSquareError::OutOfRange { row: 1, column: 0, symbol: 2, order: 2 }));
+    /// ```
+    pub fn from_symbols(rows: Vec<Vec<T>>) -> Result<LatinSquare<T>, LatinSquareError> {
+        let mut symbols = Vec::new();
+        let mut indices = HashMap::new();
+        let rows: Vec<Vec<Symbol>> = rows.into_iter().map(|row| {
+            row.into_iter().map(|symbol| *indices.entry(symbol.clone()).or_insert_with(|| {
+                symbols.push(symbol);
+                symbols.len() - 1
+            })).collect()
+        }).collect();
+        let square = LatinSquare::try_from(rows)?;
+        Ok(LatinSquare { size: square.size, square: square.square, symbols, dirty: false })
+    }
+
+    /// The symbols, each at its index.
+    pub fn symbols(&self) -> &[T] {
+        &self.symbols
+    }
+
+    /// The symbol at (row, column), if the cell and its index are in range.
+    pub fn symbol(&self, row: usize, column: usize) -> Option<&T> {
+        self.square.get(row)?.get(column).and_then(|&index| self.symbols.get(index))
+    }
+
+    /// The index of the symbol, if it is one of the square.
+    pub fn index_of(&self, symbol: &T) -> Option<Symbol> {
+        self.symbols.iter().position(|candidate| candidate == symbol)
+    }
+
+    /// The rows, as symbols.
+    ///
+    /// # Panics
+    /// If a cell holds an index past the symbols, after writing to `square` directly.
+    pub fn symbol_rows(&self) -> Vec<Vec<T>> {
+        self.square.iter().map(|row| row.iter().map(|&index| self.symbols[index].clone()).collect()).collect()
+    }
+
+    /// The square of the indices of the symbols, undoing any relabeling.
+    pub fn indices(&self) -> LatinSquare {
+        LatinSquare::from_rows(self.square.clone())
+    }
+
+    /// The square with every symbol relabeled, or None if two symbols get the same label. The cells keep
+    /// the indices of the symbols.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// let named = square.map_symbols(|&symbol| format!("drug-{}", symbol + 1)).unwrap();
+    /// assert_eq!(named.symbol_rows()[1], ["drug-2", "drug-3", "drug-1"]);
+    /// assert_eq!(named.index_of(&"drug-3".to_string()), Some(2));
+    /// assert!(square.map_symbols(|&symbol| symbol / 2).is_none());
+    ///
+    /// // Mapping back.
+    /// let back = named.map_symbols(|name| name[5..].parse::<usize>().unwrap() - 1).unwrap();
+    /// assert_eq!(back, square);
+    /// assert_eq!(named.indices(), square);
+    ///
+    /// // Borrowed labels work too.
+    /// let labels = vec![String::from("A"), String::from("B"), String::from("C")];
+    /// let borrowed = square.map_symbols(|&symbol| labels[symbol].as_str()).unwrap();
+    /// assert_eq!(borrowed.symbol(0, 2), Some(&"C"));
+    /// ```
+    pub fn map_symbols<S: Clone + Eq + Hash>(&self, mut relabel: impl FnMut(&T) -> S) -> Option<LatinSquare<S>> {
+        self.relabeled(self.symbols.iter().map(&mut relabel).collect())
+    }
+
+    /// The square with the symbol of index k relabeled as the k-th of the alphabet, or None if the alphabet
+    /// has fewer symbols than the square or repeats one of them.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// let treatments = square.with_alphabet(&["A", "B", "C", "D", "E", "F"]).unwrap();
+    /// assert_eq!(treatments.symbol_rows()[0], ["A", "B", "C", "D"]);
+    /// assert_eq!(treatments.symbols(), ["A", "B", "C", "D"]);
+    /// assert!(square.with_alphabet(&["A", "B", "C"]).is_none());
+    /// assert_eq!(treatments.with_alphabet(&[0, 1, 2, 3]), Some(square.clone()));
+    /// ```
+    pub fn with_alphabet<S: Clone + Eq + Hash>(&self, alphabet: &[S]) -> Option<LatinSquare<S>> {
+        self.relabeled(alphabet.get(..self.symbols.len())?.to_vec())
+    }
+
+    /// The square with the given symbols at the indices, if they are distinct.
+    fn relabeled<S: Clone + Eq + Hash>(&self, symbols: Vec<S>) -> Option<LatinSquare<S>> {
+        if symbols.iter().collect::<HashSet<&S>>().len() < symbols.len() {
+            return None
+        }
+        Some(LatinSquare { size: self.size, square: self.square.clone(), symbols, dirty: self.dirty })
+    }
+}
+
+impl<T> LatinSquare<T> {
+    /// The order of the square, i.e. the number of rows, columns, and symbols.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl LatinSquare {
+    /// The square with its symbols written into the cells, leaving the symbols 0..n, so that indexing, the
+    /// encodings, and the conjugates read what `Display` shows. Returns None unless the symbols are 0..n in
+    /// some order.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// let shifted = square.map_symbols(|&symbol| (symbol + 1) % 3).unwrap();
+    /// assert_eq!(shifted.to_string(), "Latin square of size 3\n\n1 2 0\n2 0 1\n0 1 2");
+    /// assert_eq!(shifted.square[0], [0, 1, 2]);
+    ///
+    /// let shifted = shifted.normalized().unwrap();
+    /// assert_eq!(shifted.square[0], [1, 2, 0]);
+    /// assert_eq!(shifted.symbols(), [0, 1, 2]);
+    /// assert_eq!(shifted.to_string().parse::<LatinSquare>().unwrap(), shifted);
+    /// let mut csv = Vec::new();
+    /// shifted.to_csv(&mut csv).unwrap();
+    /// assert_eq!(LatinSquare::from_csv(csv.as_slice()).unwrap(), shifted);
+    /// assert!(square.map_symbols(|&symbol| symbol + 1).unwrap().normalized().is_none());
+    /// ```
+    pub fn normalized(&self) -> Option<LatinSquare> {
+        if self.symbols.iter().any(|&symbol| symbol >= self.size) {
+            return None
+        }
+        let rows = self.square.iter().map(|row| row.iter().map(|&index| self.symbols[index]).collect()).collect();
+        Some(LatinSquare { size: self.size, square: rows, symbols: (0..self.size).collect(), dirty: self.dirty })
+    }
+
+    fn new_square(dimensions: usize, value_initializer: fn(usize, usize, usize) -> usize) -> LatinSquare {
+        let rows = (0..dimensions).map(|rownum| {
+            (0..dimensions).map(|colnum| {
+                value_initializer(dimensions, colnum, rownum)
+            }).collect::<Vec<usize>>()
+        }).collect::<Vec<Vec<usize>>>();
+        LatinSquare {
+            size: dimensions,
+            square: rows,
+            symbols: (0..dimensions).collect(),
+            dirty: false
+        }
+    }
+
+    /// Creates a new latin square where each row is a 1-cell shift.
+    /// e.g. if `dimensions` is 3,
+    /// 
+    /// 0 1 2
+    /// 1 2 0
+    /// 2 0 1
+    /// 
+    /// Generally used as the starting point for a random latin square.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    ///
+    /// assert_eq!(LatinSquare::new_cyclic(3).unwrap().square, [[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+    /// assert_eq!(LatinSquare::new_cyclic(5).unwrap(), IncidenceCube::new_cyclic(5).unwrap().as_latin_square());
+    /// assert!(LatinSquare::new_cyclic(1).unwrap().is_valid());
+    /// assert!(LatinSquare::new_cyclic(0).is_err());
+    /// ```
+    ///
+    /// Fails for order 0.
+    pub fn new_cyclic(dimensions: usize) -> Result<LatinSquare, CombinatorialError> {
+        if dimensions == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        Ok(LatinSquare::new_square(dimensions, |dimensions, colnum, rownum| {
+            (colnum + rownum) % dimensions
+        }))
+    }
+
+    /// Creates the latin square whose row r is the given first row shifted left by r cells, so that cell
+    /// (r, c) holds `first_row[(r + c) mod n]`.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{CombinatorialError, LatinSquare};
+    ///
+    /// let square = LatinSquare::new_cyclic_with_first_row(&[2, 0, 3, 1]).unwrap();
+    /// assert_eq!(square.square[1], [0, 3, 1, 2]);
+    /// assert!(square.is_valid());
+    /// assert_eq!(LatinSquare::new_cyclic_with_first_row(&[0, 1, 1]), Err(CombinatorialError::NotAPermutation));
+    /// assert_eq!(LatinSquare::new_cyclic_with_first_row(&[]), Err(CombinatorialError::ZeroOrder));
+    /// ```
+    ///
+    /// Fails for an empty row, and unless the row is a permutation of 0..n.
+    pub fn new_cyclic_with_first_row(first_row: &[Symbol]) -> Result<LatinSquare, CombinatorialError> {
+        let dimensions = first_row.len();
+        if dimensions == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        let mut seen = vec![false; dimensions];
+        for &symbol in first_row {
+            if symbol >= dimensions || mem::replace(&mut seen[symbol], true) {
+                return Err(CombinatorialError::NotAPermutation)
+            }
+        }
+        let rows = (0..dimensions)
+            .map(|row| (0..dimensions).map(|column| first_row[(row + column) % dimensions]).collect())
+            .collect();
+        Ok(LatinSquare::from_rows(rows))
+    }
+
+    /// Creates the latin square whose row r is row 0, the symbols 0..n, shifted left by k * r cells, so
+    /// that cell (r, c) holds (k * r + c) mod n. Every row is a permutation, and the columns are too exactly
+    /// when k is coprime to n. A step of 1 gives the back circulant square and a step of n - 1 the circulant one.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{CombinatorialError, LatinSquare, Quasigroup};
+    ///
+    /// let square = LatinSquare::new_cyclic_with_step(5, 2).unwrap();
+    /// assert_eq!(square.square[1], vec![2, 3, 4, 0, 1]);
+    /// assert!(Quasigroup::new(square).is_some());
+    /// assert_eq!(LatinSquare::new_cyclic_with_step(6, 2), Err(CombinatorialError::NotCoprime { step: 2, order: 6 }));
+    /// assert_eq!(LatinSquare::new_cyclic_with_step(0, 1), Err(CombinatorialError::ZeroOrder));
+    /// ```
+    ///
+    /// Fails for order 0, and unless the step is coprime to the order.
+    pub fn new_cyclic_with_step(dimensions: usize, step: usize) -> Result<LatinSquare, CombinatorialError> {
+        if dimensions == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        if dimensions > 1 && gcd(step, dimensions) != 1 {
+            return Err(CombinatorialError::NotCoprime { step, order: dimensions })
+        }
+        let rows = (0..dimensions)
+            .map(|row| (0..dimensions).map(|column| ((step % dimensions) * row + column) % dimensions).collect())
+            .collect();
+        Ok(LatinSquare::from_rows(rows))
+    }
+
+    /// Creates the back circulant latin square, whose cell (r, c) holds (r + c) mod n: every row is the
+    /// one above shifted one cell left, and every anti-diagonal is constant. It is the Cayley table of Z_n.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    ///
+    /// assert_eq!(LatinSquare::new_back_circulant(3).unwrap().square[1], [1, 2, 0]);
+    /// assert!(LatinSquare::new_back_circulant(0).is_err());
+    /// ```
+    ///
+    /// Fails for order 0.
+    pub fn new_back_circulant(dimensions: usize) -> Result<LatinSquare, CombinatorialError> {
+        if dimensions == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        Ok(LatinSquare::new_cyclic_with_step(dimensions, 1)
+            .expect("ProgrammingError: 1 is coprime to every order."))
+    }
+
+    /// Creates the Cayley table of the abelian group Z_(n_1) x ... x Z_(n_k), of order n_1 * ... * n_k,
+    /// whose elements are numbered in mixed radix, the first factor varying fastest. Cyclic groups give
+    /// back circulant squares; products such as Z_2 x Z_2 give squares that are not isotopic to one.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{LatinSquare, Quasigroup};
+    ///
+    /// let klein = LatinSquare::new_abelian_group(&[2, 2]).unwrap();
+    /// assert_eq!(klein.square[1], vec![1, 0, 3, 2]);
+    /// // Every element of Z_2 x Z_2 is its own inverse, so the diagonal is all 0.
+    /// assert!((0..4).all(|index| klein.square[index][index] == 0));
+    /// assert!(Quasigroup::new(klein).is_some());
+    /// assert!(LatinSquare::new_abelian_group(&[3, 0]).is_err());
+    /// ```
+    ///
+    /// Fails if a modulus is 0, or the order overflows.
+    pub fn new_abelian_group(moduli: &[usize]) -> Result<LatinSquare, CombinatorialError> {
+        if moduli.contains(&0) {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        let order = moduli.iter().try_fold(1_usize, |order, &modulus| order.checked_mul(modulus))
+            .ok_or(CombinatorialError::OrderOverflow)?;
+        let add = |mut a: usize, mut b: usize| {
+            let (mut sum, mut place) = (0, 1);
+            for &modulus in moduli {
+                sum += ((a % modulus + b % modulus) % modulus) * place;
+                a /= modulus;
+                b /= modulus;
+                place *= modulus;
+            }
+            sum
+        };
+        let rows = (0..order).map(|row| (0..order).map(|column| add(row, column)).collect()).collect();
+        Ok(LatinSquare::from_rows(rows))
+    }
+
+    /// Creates a new randomized latin square using the Mark T. Jacobson, Peter Matthews approach.
+    /// 
+    /// Every random choice is drawn from `rng`, so one generator can serve many squares, and a seeded one
+    /// always gives the same square.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::sudoku::Rules;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let square = LatinSquare::new_random(6, &mut rng).unwrap();
+    /// assert!(Rules::latin(6).is_solution(&square));
+    /// assert!(LatinSquare::new_random(0, &mut rng).is_err());
+    /// ```
+    /// 
+    /// Fails for order 0.
+    ///
+    /// TODO:: Add functionality here to add restrictions on structure/cyclcic nature.
+    pub fn new_random(dimensions: usize, rng: &mut impl Rng) -> Result<LatinSquare, CombinatorialError> {
+        let mut cube = IncidenceCube::new_cyclic(dimensions)?;
+        cube.shuffle(rng);
+        Ok(cube.as_latin_square())
+    }
+
+    /// Creates a random latin square like `new_random`, with every random choice drawn from a generator
+    /// seeded with `seed`, so the same seed always gives the same square. It is the square of `batch::square`.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{batch, LatinSquare};
+    ///
+    /// let square = LatinSquare::new_random_seeded(7, 2024).unwrap();
+    /// assert_eq!(square.square, LatinSquare::new_random_seeded(7, 2024).unwrap().square);
+    /// assert_eq!(square.square, batch::square(7, 2024).unwrap().square);
+    /// ```
+    ///
+    /// Fails for order 0.
+    pub fn new_random_seeded(dimensions: usize, seed: u64) -> Result<LatinSquare, CombinatorialError> {
+        let mut cube = IncidenceCube::new_cyclic(dimensions)?;
+        cube.shuffle_seeded(seed);
+        Ok(cube.as_latin_square())
+    }
+
+    /// Creates a new latin square where every cell is 0.
+    /// This isn't a valid latin square.
+    /// In other words, just a Vec<Vec<usize>> of size `dimensions`, pre-populated with zeros.
+    pub fn new_empty(dimensions: usize) -> LatinSquare {
+        LatinSquare::new_square(dimensions, |_, _, _| 0)
+    }
+
+    /// Wraps already-built rows in a latin square without checking them.
+    /// Intended for constructions elsewhere in the crate that are latin by design.
+    pub(crate) fn from_rows(rows: Vec<Vec<Symbol>>) -> LatinSquare {
+        LatinSquare {
+            size: rows.len(),
+            symbols: (0..rows.len()).collect(),
+            square: rows,
+            dirty: false
+        }
+    }
+
+    /// The rows, in order.
+    pub fn rows(&self) -> impl Iterator<Item = &[Symbol]> + '_ {
+        self.square.iter().map(|row| row.as_slice())
+    }
+
+    /// The columns, in order, each as the symbols from the top row down.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// let columns: Vec<Vec<usize>> = square.columns().map(|column| column.collect()).collect();
+    /// assert_eq!(columns[1], square.rows().map(|row| row[1]).collect::<Vec<usize>>());
+    /// ```
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = Symbol> + '_> + '_ {
+        (0..self.size).map(move |column| self.square.iter().map(move |row| row[column]))
+    }
+
+    /// The (row, column) cells holding the symbol, row by row. A latin square has one in every row.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// let cells: Vec<(usize, usize)> = square.positions_of(0).collect();
+    /// assert_eq!(cells.len(), 4);
+    /// assert!(cells.iter().all(|&(row, column)| square[(row, column)] == 0));
+    /// ```
+    pub fn positions_of(&self, symbol: Symbol) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.square.iter().enumerate().flat_map(move |(row, symbols)| {
+            symbols.iter().enumerate().filter(move |&(_, &cell)| cell == symbol).map(move |(column, _)| (row, column))
+        })
+    }
+
+    /// The square reflected in its main diagonal, i.e. the `Crs` conjugate.
+    pub fn transpose(&self) -> LatinSquare {
+        self.conjugate(ConjugateKind::Crs)
+    }
+
+    /// The conjugate of the square, reading its (row, column, symbol) triples in other roles.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::latin_square::ConjugateKind;
+    ///
+    /// let square = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+    /// for kind in ConjugateKind::ALL {
+    ///     assert!(square.conjugate(kind).is_valid());
+    /// }
+    /// assert_eq!(square.transpose()[(1, 3)], square[(3, 1)]);
+    /// // Symbol s at (r, c) becomes symbol r at (c, s).
+    /// let symbol = square[(1, 3)];
+    /// assert_eq!(square.conjugate(ConjugateKind::Csr)[(3, symbol)], 1);
+    /// assert_eq!(square.conjugate(ConjugateKind::Rcs).square, square.square);
+    /// ```
+    ///
+    /// # Panics
+    /// If the square is not latin, see `validate`.
+    pub fn conjugate(&self, kind: ConjugateKind) -> LatinSquare {
+        LatinSquare::from_rows(conjugate_rows(&self.square, kind))
+    }
+
+    /// Returns true if the first row and the first column both read 0, 1, ..., n - 1.
+    pub fn is_reduced(&self) -> bool {
+        self.rows().next().is_none_or(|first| first.iter().copied().eq(0..self.size))
+            && self.square.iter().map(|row| row[0]).eq(0..self.size)
+    }
+
+    /// The reduced square of the same rows and columns: the columns reordered so that the first row reads
+    /// 0..n, then the rows so that the first column does. Both orders are forced, so there is exactly one.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    ///
+    /// let square = LatinSquare::try_from(vec![vec![2, 0, 1], vec![0, 1, 2], vec![1, 2, 0]]).unwrap();
+    /// assert!(!square.is_reduced());
+    /// let reduced = square.to_reduced();
+    /// assert!(reduced.is_reduced());
+    /// assert_eq!(reduced.square, vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]]);
+    /// ```
+    ///
+    /// # Panics
+    /// If the square is not latin, see `validate`.
+    pub fn to_reduced(&self) -> LatinSquare {
+        let order = self.size;
+        let Some(first) = self.square.first() else {
+            return LatinSquare::from_rows(Vec::new())
+        };
+        let mut column_of = vec![0; order];
+        for (column, &symbol) in first.iter().enumerate() {
+            column_of[symbol] = column;
+        }
+        let mut rows: Vec<Vec<Symbol>> = self.square.iter()
+            .map(|row| column_of.iter().map(|&column| row[column]).collect())
+            .collect();
+        rows.sort_unstable_by_key(|row| row[0]);
+        LatinSquare::from_rows(rows)
+    }
+
+    /// The canonical form of the square under isotopy or paratopy, see the `canonical` module.
+    pub fn canonical_form(&self, equivalence: Equivalence) -> LatinSquare {
+        canonical::canonical_form(self, equivalence)
+    }
+
+    /// The hashable canonical form of the square, equal for equivalent squares, see the `canonical` module.
+    pub fn fingerprint(&self, equivalence: Equivalence) -> Fingerprint {
+        canonical::fingerprint(self, equivalence)
+    }
+
+    /// Writes the square as CSV, a row per line with the symbols separated by commas.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// let mut csv = Vec::new();
+    /// square.to_csv(&mut csv).unwrap();
+    /// assert_eq!(String::from_utf8(csv.clone()).unwrap(), "0,1,2\n1,2,0\n2,0,1\n");
+    /// assert_eq!(LatinSquare::from_csv(csv.as_slice()).unwrap(), square);
+    ///
+    /// let error = LatinSquare::from_csv("\"0\",\"1\"\n0,1\n".as_bytes()).err().unwrap();
+    /// assert_eq!(error.to_string(), "column 0: symbol 0 repeats in rows 0 and 1; column 1: symbol 1 repeats in rows 0 and 1");
+    /// ```
+    pub fn to_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        for row in &self.square {
+            let fields: Vec<String> = row.iter().map(|symbol| symbol.to_string()).collect();
+            writeln!(writer, "{}", fields.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a square written as CSV, as spreadsheets export it: a row per line, the symbols separated by
+    /// commas and optionally quoted, and blank lines skipped. The rows are checked like `TryFrom`, and an
+    /// invalid square fails with `InvalidData` carrying the `LatinSquareError`, which names the row or line.
+    pub fn from_csv(mut reader: impl Read) -> io::Result<LatinSquare> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text.replace('"', "").parse().map_err(|error: LatinSquareError| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Packs the square into bytes: the order as 4 bytes, little-endian, then every cell row by row in the
+    /// fewest bits that hold the symbols below the order, most significant bit first and zero-padded to
+    /// a whole byte, then the CRC-32 of everything before it as 4 bytes, little-endian. A square of order 16
+    /// takes 4 bits per cell, 136 bytes in all.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::latin_square::DecodeError;
+    ///
+    /// let square = LatinSquare::new_random(16, &mut rand::thread_rng()).unwrap();
+    /// let mut bytes = square.to_bytes();
+    /// assert_eq!(bytes.len(), 4 + 16 * 16 / 2 + 4);
+    /// assert_eq!(LatinSquare::from_bytes(&bytes), Ok(square));
+    ///
+    /// bytes[10] ^= 1;
+    /// assert!(matches!(LatinSquare::from_bytes(&bytes), Err(DecodeError::Checksum { .. })));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bits = bits_per_cell(self.size);
+        let mut bytes = Vec::with_capacity(8 + (self.size * self.size * bits).div_ceil(8));
+        bytes.extend_from_slice(&(self.size as u32).to_le_bytes());
+        let (mut buffer, mut filled) = (0_u64, 0);
+        for &symbol in self.square.iter().flatten() {
+            buffer = (buffer << bits) | symbol as u64;
+            filled += bits;
+            while filled >= 8 {
+                filled -= 8;
+                bytes.push((buffer >> filled) as u8);
+            }
+        }
+        if filled > 0 {
+            bytes.push((buffer << (8 - filled)) as u8);
+        }
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Unpacks a square packed by `to_bytes`, checking the length, the checksum, and that the rows form a
+    /// latin square.
+    pub fn from_bytes(bytes: &[u8]) -> Result<LatinSquare, DecodeError> {
+        if bytes.len() < 8 {
+            return Err(DecodeError::Length { expected: 8, found: bytes.len() })
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 4);
+        let order = u32::from_le_bytes([body[0], body[1], body[2], body[3]]) as usize;
+        let bits = bits_per_cell(order);
+        let expected = order.checked_mul(order)
+            .and_then(|cells| cells.checked_mul(bits))
+            .and_then(|cells| cells.div_ceil(8).checked_add(8))
+            .ok_or(DecodeError::Length { expected: usize::MAX, found: bytes.len() })?;
+        if bytes.len() != expected {
+            return Err(DecodeError::Length { expected, found: bytes.len() })
+        }
+        let stored = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let computed = crc32(body);
+        if stored != computed {
+            return Err(DecodeError::Checksum { stored, computed })
+        }
+        let mask = (1_u64 << bits) - 1;
+        let (mut buffer, mut filled) = (0_u64, 0);
+        let mut packed = body[4..].iter();
+        let mut cells = Vec::with_capacity(order * order);
+        while cells.len() < order * order {
+            while filled < bits {
+                let byte = packed.next().expect("ProgrammingError: the length was checked.");
+                buffer = (buffer << 8) | *byte as u64;
+                filled += 8;
+            }
+            filled -= bits;
+            cells.push(((buffer >> filled) & mask) as Symbol);
+        }
+        let rows: Vec<Vec<Symbol>> = cells.chunks(order.max(1)).map(|row| row.to_vec()).collect();
+        LatinSquare::try_from(rows).map_err(DecodeError::Invalid)
+    }
+
+    /// The symbol at the cell, or None outside the grid.
+    pub fn get(&self, row: usize, column: usize) -> Option<Symbol> {
+        self.square.get(row)?.get(column).copied()
+    }
+
+    /// Writes the symbol at the cell and marks the square dirty, as it may no longer be latin.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let mut square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// assert!(!square.is_dirty());
+    /// let symbol = square[(0, 0)];
+    /// square.set(0, 0, square[(0, 1)]);
+    /// assert!(square.is_dirty());
+    /// assert!(square.revalidate().is_err());
+    ///
+    /// square[(0, 0)] = symbol;
+    /// assert!(square.revalidate().is_ok());
+    /// assert!(!square.is_dirty());
+    /// assert_eq!(square.get(0, 0), Some(symbol));
+    /// assert_eq!(square.get(3, 0), None);
+    /// ```
+    ///
+    /// # Panics
+    /// If the cell is outside the grid.
+    pub fn set(&mut self, row: usize, column: usize, symbol: Symbol) {
+        self[(row, column)] = symbol;
+    }
+
+    /// Returns true if a cell was written through `set` or `IndexMut` since the square was built or last
+    /// passed `revalidate`. Writes to the `square` field directly are not tracked.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Validates the square like `validate`, and marks it clean if it passes.
+    pub fn revalidate(&mut self) -> Result<(), Vec<Violation>> {
+        self.validate()?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Returns true if the rows form a latin square: a non-empty square grid of the symbols 0..n, each once
+    /// in every row and column.
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Checks that the rows form a latin square, returning every violation otherwise. Shape violations come
+    /// alone, as the rest can't be checked without a square grid.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, latin_square::Violation};
+    ///
+    /// let mut square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// assert!(square.is_valid());
+    /// square.square[0][0] = 1;
+    /// square.square[2][2] = 5;
+    /// assert_eq!(square.validate().unwrap_err(), vec![
+    ///     Violation::OutOfRange { row: 2, column: 2, symbol: 5, order: 3 },
+    ///     Violation::RowRepeat { row: 0, symbol: 1, first: 0, second: 1 },
+    ///     Violation::ColumnRepeat { column: 0, symbol: 1, first: 0, second: 1 }
+    /// ]);
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let violations = grid_violations(&self.square);
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+
+    /// Jitters the square with the given number of random cycle switches, each swapping two rows on
+    /// a cycle of columns, drawn from `rng`. A switch of length two flips an intercalate. See the
+    /// `switching` module.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, Quasigroup};
+    ///
+    /// let mut square = IncidenceCube::new_cyclic(6).unwrap().as_latin_square();
+    /// square.perturb(3, &mut rand::thread_rng());
+    /// assert!(Quasigroup::new(square).is_some());
+    /// ```
+    pub fn perturb(&mut self, switches: usize, rng: &mut impl Rng) {
+        for _ in 0..switches {
+            switching::switch_random_cycle(self, rng);
+        }
+    }
+
+    /// Returns true if `other` can be obtained from this square by permuting its rows and its columns,
+    /// keeping the symbols as they are.
+    ///
+    /// Moving row 0 to some row of `other` fixes where every column has to go, as the symbols of a row
+    /// are distinct, so this tries n candidates at n^2 steps each.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// let mut shuffled = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// shuffled.square.swap(0, 3);
+    /// shuffled.square.iter_mut().for_each(|row| row.swap(1, 2));
+    /// assert!(square.is_rc_equivalent(&shuffled));
+    ///
+    /// // Renaming symbols is not allowed.
+    /// shuffled.square.iter_mut().flatten().filter(|symbol| **symbol < 2).for_each(|symbol| *symbol = 1 - *symbol);
+    /// assert!(!square.is_rc_equivalent(&shuffled));
+    /// ```
+    pub fn is_rc_equivalent(&self, other: &LatinSquare) -> bool {
+        if self.size != other.size || self.square.len() != other.square.len() {
+            return false
+        }
+        let Some(first) = self.square.first() else {
+            return true
+        };
+        let other_rows: HashSet<&Vec<Symbol>> = other.square.iter().collect();
+        other.square.iter().any(|target| {
+            // columns[c] is the column of `other` that column c of this square moves to.
+            let mut position = HashMap::new();
+            for (column, &symbol) in target.iter().enumerate() {
+                position.insert(symbol, column);
+            }
+            let Some(columns) = first.iter().map(|symbol| position.get(symbol).copied()).collect::<Option<Vec<usize>>>() else {
+                return false
+            };
+            let mut seen = HashSet::new();
+            self.square.iter().all(|row| {
+                let mut moved = vec![0; row.len()];
+                for (column, &symbol) in row.iter().enumerate() {
+                    moved[columns[column]] = symbol;
+                }
+                other_rows.contains(&moved) && seen.insert(moved)
+            })
+        })
+    }
+}
+
+impl Index<(usize, usize)> for LatinSquare {
+    type Output = Symbol;
+
+    /// The symbol at (row, column).
+    fn index(&self, (row, column): (usize, usize)) -> &Symbol {
+        &self.square[row][column]
+    }
+}
+
+impl IndexMut<(usize, usize)> for LatinSquare {
+    /// The symbol at (row, column), marking the square dirty.
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut Symbol {
+        self.dirty = true;
+        &mut self.square[row][column]
+    }
+}
+
+/// Compares the squares as shown, cell by cell, whatever the indices of their symbols.
+impl<T: PartialEq> PartialEq for LatinSquare<T> {
+    fn eq(&self, other: &LatinSquare<T>) -> bool {
+        self.square.len() == other.square.len() && self.square.iter().zip(&other.square).all(|(row, other_row)| {
+            row.len() == other_row.len()
+                && row.iter().zip(other_row).all(|(&index, &other_index)| self.symbols.get(index) == other.symbols.get(other_index))
+        })
+    }
+}
+
+impl<T: Eq> Eq for LatinSquare<T> {}
+
+/// Hashes the squares as shown, like `PartialEq` compares them.
+impl<T: Hash> Hash for LatinSquare<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.square.len().hash(state);
+        for row in &self.square {
+            row.len().hash(state);
+            for &index in row {
+                self.symbols.get(index).hash(state);
+            }
+        }
+    }
+}
+
+/// Shows the rows as symbols.
+impl<T: fmt::Debug> fmt::Debug for LatinSquare<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<Vec<&T>> = self.square.iter()
+            .map(|row| row.iter().filter_map(|&index| self.symbols.get(index)).collect())
+            .collect();
+        f.debug_struct("LatinSquare").field("size", &self.size).field("square", &rows).finish()
+    }
+}
+
+/// Serializes the square as its rows.
+#[cfg(feature = "serde")]
+impl Serialize for LatinSquare {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.square.serialize(serializer)
+    }
+}
+
+/// Deserializes rows, checking them like `TryFrom`.
+///
+/// ```
+/// use combinatorial_patterns::LatinSquare;
+/// use serde::de::{value, Deserialize, IntoDeserializer};
+///
+/// let rows = vec![vec![0, 1], vec![1, 0]];
+/// let square = LatinSquare::deserialize(IntoDeserializer::<value::Error>::into_deserializer(rows)).unwrap();
+/// assert_eq!(square.size(), 2);
+///
+/// let rows = vec![vec![0, 1], vec![0, 1]];
+/// let error = LatinSquare::deserialize(IntoDeserializer::<value::Error>::into_deserializer(rows)).err().unwrap();
+/// assert!(error.to_string().starts_with("column 0: symbol 0 repeats"));
+/// ```
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LatinSquare {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LatinSquare, D::Error> {
+        let rows = Vec::<Vec<Symbol>>::deserialize(deserializer)?;
+        LatinSquare::try_from(rows).map_err(de::Error::custom)
+    }
+}
+
+/// Writes a heading, then a row per line with the symbols right-aligned in columns as wide as the widest.
+/// The alternate form, `{:#}`, draws box borders around the cells instead.
+///
+/// ```
+/// use combinatorial_patterns::LatinSquare;
+///
+/// let square = LatinSquare::new_back_circulant(11).unwrap();
+/// let text = square.to_string();
+/// assert_eq!(text.lines().nth(2), Some(" 0  1  2  3  4  5  6  7  8  9 10"));
+/// assert_eq!(text.lines().nth(3), Some(" 1  2  3  4  5  6  7  8  9 10  0"));
+///
+/// let boxed = format!("{:#}", LatinSquare::new_back_circulant(2).unwrap());
+/// assert_eq!(boxed, "Latin square of size 2\n\n┌───┬───┐\n│ 0 │ 1 │\n├───┼───┤\n│ 1 │ 0 │\n└───┴───┘");
+/// assert_eq!(boxed.parse::<LatinSquare>().unwrap(), LatinSquare::new_back_circulant(2).unwrap());
+/// ```
+impl<T: fmt::Display> fmt::Display for LatinSquare<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = Alphabet::Custom(self.symbols.iter().map(|symbol| symbol.to_string()).collect());
+        write!(f, "{}", self.to_text(&DisplayOptions::new().with_alphabet(labels).with_boxed(f.alternate())))
+    }
+}
+
+/// A way the rows of a grid fail to form a latin square. Rows, columns, and symbols count from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// The grid has no rows.
+    Empty,
+    /// A row has `length` symbols where the grid has `expected` rows.
+    RowLength { row: usize, length: usize, expected: usize },
+    /// A symbol is not below the order.
+    OutOfRange { row: usize, column: usize, symbol: Symbol, order: usize },
+    /// A symbol appears in columns `first` and `second` of a row.
+    RowRepeat { row: usize, symbol: Symbol, first: usize, second: usize },
+    /// A symbol appears in rows `first` and `second` of a column.
+    ColumnRepeat { column: usize, symbol: Symbol, first: usize, second: usize }
+}
+
+impl Violation {
+    /// The violation with its symbols counted from the base rather than from 0.
+    pub fn in_base(self, base: SymbolBase) -> Violation {
+        let first = base.first();
+        match self {
+            Violation::OutOfRange { row, column, symbol, order } => {
+                Violation::OutOfRange { row, column, symbol: symbol.wrapping_add(first), order }
+            },
+            Violation::RowRepeat { row, symbol, first: earlier, second } => {
+                Violation::RowRepeat { row, symbol: symbol + first, first: earlier, second }
+            },
+            Violation::ColumnRepeat { column, symbol, first: earlier, second } => {
+                Violation::ColumnRepeat { column, symbol: symbol + first, first: earlier, second }
+            },
+            other => other
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Violation::Empty => write!(f, "shape: the grid is empty"),
+            Violation::RowLength { row, length, expected } => {
+                write!(f, "shape: row {} has {} symbols, expected {}", row, length, expected)
+            },
+            Violation::OutOfRange { row, column, symbol, order } => {
+                write!(f, "range: symbol {} at row {}, column {} is not below the order {}", symbol, row, column, order)
+            },
+            Violation::RowRepeat { row, symbol, first, second } => {
+                write!(f, "row {}: symbol {} repeats in columns {} and {}", row, symbol, first, second)
+            },
+            Violation::ColumnRepeat { column, symbol, first, second } => {
+                write!(f, "column {}: symbol {} repeats in rows {} and {}", column, symbol, first, second)
+            }
+        }
+    }
+}
+
+/// Why rows failed to form a latin square, from the first kind of violation found: the shape, then the
+/// symbol range, then the latin property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LatinSquareError {
+    /// There are no rows.
+    Empty,
+    /// A row has `length` symbols where there are `expected` rows.
+    NotSquare { row: usize, length: usize, expected: usize },
+    /// A symbol is not below the order.
+    OutOfRange { row: usize, column: usize, symbol: Symbol, order: usize },
+    /// Symbols repeat in rows or columns, with every repeat.
+    NotLatin(Vec<Violation>),
+    /// A field of the text, on the given line counting from 1, is not a number.
+    NotASymbol { line: usize, field: String }
+}
+
+impl LatinSquareError {
+    /// The error with its symbols counted from the base rather than from 0.
+    fn in_base(self, base: SymbolBase) -> LatinSquareError {
+        match self {
+            LatinSquareError::OutOfRange { row, column, symbol, order } => {
+                LatinSquareError::OutOfRange { row, column, symbol: symbol.wrapping_add(base.first()), order }
+            },
+            LatinSquareError::NotLatin(violations) => {
+                LatinSquareError::NotLatin(violations.into_iter().map(|violation| violation.in_base(base)).collect())
+            },
+            other => other
+        }
+    }
+}
+
+impl fmt::Display for LatinSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LatinSquareError::Empty => write!(f, "{}", Violation::Empty),
+            &LatinSquareError::NotSquare { row, length, expected } => {
+                write!(f, "{}", Violation::RowLength { row, length, expected })
+            },
+            &LatinSquareError::OutOfRange { row, column, symbol, order } => {
+                write!(f, "{}", Violation::OutOfRange { row, column, symbol, order })
+            },
+            LatinSquareError::NotLatin(violations) => {
+                let messages: Vec<String> = violations.iter().map(|violation| violation.to_string()).collect();
+                write!(f, "{}", messages.join("; "))
+            },
+            LatinSquareError::NotASymbol { line, field } => write!(f, "line {}: {:?} is not a symbol", line, field)
+        }
+    }
+}
+
+impl std::error::Error for LatinSquareError {}
+
+/// Checks the rows like `LatinSquare::validate` before taking them.
+///
+/// ```
+/// use combinatorial_patterns::LatinSquare;
+/// use combinatorial_patterns::latin_square::LatinSquareError;
+///
+/// let square = LatinSquare::try_from(vec![vec![0, 1], vec![1, 0]]).unwrap();
+/// assert_eq!(square.size(), 2);
+///
+/// let ragged = LatinSquare::try_from(vec![vec![0, 1], vec![1]]);
+/// assert_eq!(ragged.err(), Some(LatinSquareError::NotSquare { row: 1, length: 1, expected: 2 }));
+/// let repeated = LatinSquare::try_from(vec![vec![0, 1], vec![0, 1]]).err().unwrap();
+/// assert_eq!(repeated.to_string(), "column 0: symbol 0 repeats in rows 0 and 1; column 1: symbol 1 repeats in rows 0 and 1");
+/// ```
+impl TryFrom<Vec<Vec<Symbol>>> for LatinSquare {
+    type Error = LatinSquareError;
+
+    fn try_from(rows: Vec<Vec<Symbol>>) -> Result<LatinSquare, LatinSquareError> {
+        let violations = grid_violations(&rows);
+        match violations.first() {
+            None => Ok(LatinSquare::from_rows(rows)),
+            Some(Violation::Empty) => Err(LatinSquareError::Empty),
+            Some(&Violation::RowLength { row, length, expected }) => {
+                Err(LatinSquareError::NotSquare { row, length, expected })
+            },
+            Some(&Violation::OutOfRange { row, column, symbol, order }) => {
+                Err(LatinSquareError::OutOfRange { row, column, symbol, order })
+            },
+            Some(_) => Err(LatinSquareError::NotLatin(violations))
+        }
+    }
+}
+
+/// Parses a square written a row per line, with the symbols separated by whitespace or commas, as `Display`
+/// writes it. Blank lines, the `Latin square of size` heading, and box borders are skipped, the order is
+/// the number of rows, and the rows are checked like `TryFrom`.
+///
+/// ```
+/// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+/// use combinatorial_patterns::latin_square::LatinSquareError;
+///
+/// let square = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+/// let parsed: LatinSquare = square.to_string().parse().unwrap();
+/// assert_eq!(parsed.square, square.square);
+///
+/// let csv: LatinSquare = "0,1,2\n1, 2, 0\n2,0,1\n".parse().unwrap();
+/// assert_eq!(csv.size(), 3);
+/// assert_eq!(
+///     "0 1\n1 x".parse::<LatinSquare>().err(),
+///     Some(LatinSquareError::NotASymbol { line: 2, field: "x".to_string() })
+/// );
+/// ```
+impl FromStr for LatinSquare {
+    type Err = LatinSquareError;
+
+    fn from_str(text: &str) -> Result<LatinSquare, LatinSquareError> {
+        LatinSquare::parse_in_base(text, SymbolBase::Zero)
+    }
+}
+
+impl LatinSquare {
+    /// Builds a square from rows of symbols counted from the base, checking them like `TryFrom`. Errors give
+    /// the symbols in the base too.
+    ///
+    /// ```
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::latin_square::{SymbolBase, Violation, LatinSquareError};
+    ///
+    /// let square = LatinSquare::from_rows_in_base(vec![vec![1, 2], vec![2, 1]], SymbolBase::One).unwrap();
+    /// assert_eq!(square.square, [[0, 1], [1, 0]]);
+    /// assert_eq!(square.rows_in_base(SymbolBase::One), [[1, 2], [2, 1]]);
+    ///
+    /// let repeated = LatinSquare::from_rows_in_base(vec![vec![1, 2], vec![1, 2]], SymbolBase::One).err().unwrap();
+    /// let LatinSquareError::NotLatin(violations) = repeated else { panic!() };
+    /// assert_eq!(violations[0], Violation::ColumnRepeat { column: 0, symbol: 1, first: 0, second: 1 });
+    /// ```
+    pub fn from_rows_in_base(rows: Vec<Vec<Symbol>>, base: SymbolBase) -> Result<LatinSquare, LatinSquareError> {
+        let first = base.first();
+        // Symbols below the base wrap around past the order, and back in the errors.
+        let rows: Vec<Vec<Symbol>> = rows.into_iter().map(|row| row.into_iter().map(|symbol| symbol.wrapping_sub(first)).collect()).collect();
+        LatinSquare::try_from(rows).map_err(|error| error.in_base(base))
+    }
+
+    /// The rows, with the symbols counted from the base.
+    pub fn rows_in_base(&self, base: SymbolBase) -> Vec<Vec<Symbol>> {
+        self.square.iter().map(|row| row.iter().map(|&symbol| symbol + base.first()).collect()).collect()
     }
-    /// Returns one step higher of the current cube entry.
-    /// 
-    /// Direction is Improper -> Off -> On -> !panic
-    /// Panics if on entry is turned on, as this should only occur due to a programming error.
-    pub fn toggle_on(&self) -> CubeEntry {
-        match self {
-            CubeEntry::Off => CubeEntry:: On,
-            CubeEntry::Improper => CubeEntry::Off,
-            CubeEntry::On => panic!("ProgrammingError: cannot turn on cell that is already on.")
+
+    /// Parses a square like `FromStr`, with the symbols counted from the base.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::LatinSquare;
+    /// use combinatorial_patterns::latin_square::SymbolBase;
+    /// use combinatorial_patterns::render::DisplayOptions;
+    ///
+    /// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// let text = square.to_text(&DisplayOptions::new().with_base(SymbolBase::One));
+    /// assert_eq!(text.lines().nth(2), Some("1 2 3 4"));
+    /// assert_eq!(LatinSquare::parse_in_base(&text, SymbolBase::One).unwrap(), square);
+    /// assert!(LatinSquare::parse_in_base(&text, SymbolBase::Zero).is_err());
+    /// ```
+    pub fn parse_in_base(text: &str, base: SymbolBase) -> Result<LatinSquare, LatinSquareError> {
+        let mut rows = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("Latin square") || line.starts_with(['┌', '├', '└']) {
+                continue
+            }
+            let row = line.split(|c: char| c == ',' || c == '│' || c.is_whitespace())
+                .filter(|field| !field.is_empty())
+                .map(|field| field.parse().map_err(|_| LatinSquareError::NotASymbol { line: number + 1, field: field.to_string() }))
+                .collect::<Result<Vec<Symbol>, LatinSquareError>>()?;
+            rows.push(row);
         }
+        LatinSquare::from_rows_in_base(rows, base)
     }
+}
 
-    /// Helper to get cube entry as i32 value.
-    /// Mark T. Jacobson, Peter Matthews approaches usually calculate by adding/substracting.
-    /// This function allows the string output of a cube in a format more generally used with latin squares.
-    /// 
-    /// On = 1, Off = 0, Improper = -1.
-    #[allow(dead_code)]
-    pub fn as_int(&self) -> i32 {
+/// Why bytes failed to unpack into a latin square, see `LatinSquare::from_bytes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes are not as many as the order in the header needs.
+    Length { expected: usize, found: usize },
+    /// The checksum stored after the cells is not that of the bytes before it.
+    Checksum { stored: u32, computed: u32 },
+    /// The cells do not form a latin square.
+    Invalid(LatinSquareError)
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            CubeEntry::On => 1,
-            CubeEntry::Off => 0,
-            CubeEntry::Improper => -1
+            DecodeError::Length { expected, found } => write!(f, "expected {} bytes, found {}", expected, found),
+            DecodeError::Checksum { stored, computed } => {
+                write!(f, "the checksum is {:08x}, but the bytes give {:08x}", stored, computed)
+            },
+            DecodeError::Invalid(error) => write!(f, "{}", error)
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum SearchCoord {
-    X,
-    Y,
-    Z
+impl std::error::Error for DecodeError {}
+
+/// The bits needed for the symbols 0..order, at least 1.
+fn bits_per_cell(order: usize) -> usize {
+    (usize::BITS - order.saturating_sub(1).leading_zeros()).max(1) as usize
 }
 
-#[derive(Debug, Copy, Clone)]
-struct Coordinate {
-    x: usize,
-    y: usize,
-    z: usize,
+/// The CRC-32 of the bytes, with the reflected polynomial 0xEDB88320 of zlib and PNG.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
 }
 
-impl Coordinate {
-    /// Mutates the incidence cube's specified search coordinate by one.
-    /// Helper to allow incrementing an axis without knowing in advance which coordinate we want to increment.
-    pub fn increment(&mut self, coord: SearchCoord) {
-        match coord {
-            SearchCoord::X => self.x += 1,
-            SearchCoord::Y => self.y += 1,
-            SearchCoord::Z => self.z += 1
-        };
+/// The violations of the grid being a latin square, shape first, then range, then the rows, then the
+/// columns.
+pub(crate) fn grid_violations(grid: &[Vec<Symbol>]) -> Vec<Violation> {
+    let order = grid.len();
+    let mut found = Vec::new();
+    if order == 0 {
+        found.push(Violation::Empty);
+    }
+    for (row, symbols) in grid.iter().enumerate() {
+        if symbols.len() != order {
+            found.push(Violation::RowLength { row, length: symbols.len(), expected: order });
+        }
+    }
+    if !found.is_empty() {
+        return found
     }
 
-    /// Helper to find the value of the coordinate we are searching for.
-    /// Abstraction for operating withotu knowing what coordinate we are using in advance.
-    pub fn search_axis(&self, coord: SearchCoord) -> usize {
-        match coord {
-            SearchCoord::X => self.x,
-            SearchCoord::Y => self.y,
-            SearchCoord::Z => self.z
+    for (row, symbols) in grid.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            if symbol >= order {
+                found.push(Violation::OutOfRange { row, column, symbol, order });
+            }
         }
     }
+    for (row, symbols) in grid.iter().enumerate() {
+        found.extend(repeats(symbols).into_iter().map(|(symbol, first, second)| {
+            Violation::RowRepeat { row, symbol, first, second }
+        }));
+    }
+    for column in 0..order {
+        let symbols: Vec<Symbol> = grid.iter().map(|row| row[column]).collect();
+        found.extend(repeats(&symbols).into_iter().map(|(symbol, first, second)| {
+            Violation::ColumnRepeat { column, symbol, first, second }
+        }));
+    }
+    found
+}
 
-    /// Creaets a new coordinate with th specified coordinates. The search coordinate is initialized
-    ///     as Zero, as it will be iterated over to search for a specific value later.
-    /// the usize specified that matches the search coordinate will be ignored.
-    pub fn init_for_search(x: usize, y: usize, z: usize, search: SearchCoord) -> Coordinate {
-        match search {
-            SearchCoord::X => Coordinate {x: 0, y: y, z: z},
-            SearchCoord::Y => Coordinate {x: x, y: 0, z: z},
-            SearchCoord::Z => Coordinate {x: x, y: y, z: 0}
+/// Every (symbol, first index, later index) where a symbol appears again after its first appearance.
+pub(crate) fn repeats(symbols: &[usize]) -> Vec<(usize, usize, usize)> {
+    let mut first = HashMap::new();
+    let mut found = Vec::new();
+    for (index, &symbol) in symbols.iter().enumerate() {
+        match first.get(&symbol) {
+            Some(&earlier) => found.push((symbol, earlier, index)),
+            None => {
+                first.insert(symbol, index);
+            }
         }
     }
+    found
 }
 
-/// An n x n grid of n symbols, where each symbol appears in each row and column only once.
-/// 
-/// Can be used to create a latin square of any size.
-/// 
-/// To create a random valid latin square, simply call `LatinSquare::new_random(dimensions)`
-/// 
-/// An example rust main that would generate and output the resulting square:
+/// The uniform distribution on latin squares of one order, sampled by the Jacobson-Matthews shuffle of
+/// `LatinSquare::new_random`.
 ///
-/// ``` 
-/// fn _main() {
-///    println!("making cube...");
-///    let args: Vec<String> = env::args().collect();
-///    let size = args[1].parse().unwrap_or(4);
-///    println!("{}", LatinSquare::new_random(size));
-/// }
 /// ```
-pub struct LatinSquare {
-    size: usize,
-    pub square: Vec<Vec<Symbol>>
+/// use combinatorial_patterns::{LatinSquare, LatinSquareDistribution};
+/// use combinatorial_patterns::sudoku::Rules;
+/// use rand::Rng;
+///
+/// let mut rng = rand::thread_rng();
+/// let square: LatinSquare = rng.sample(LatinSquareDistribution::new(9).unwrap());
+/// assert!(Rules::latin(9).is_solution(&square));
+///
+/// let squares: Vec<LatinSquare> = rng.sample_iter(LatinSquareDistribution::new(4).unwrap()).take(3).collect();
+/// assert_eq!(squares.len(), 3);
+/// assert!(LatinSquareDistribution::new(0).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatinSquareDistribution {
+    pub order: usize
 }
 
-impl LatinSquare {
-    fn new_square(dimensions: usize, value_initializer: fn(usize, usize, usize) -> usize) -> LatinSquare {
-        let rows = (0..dimensions).map(|rownum| {
-            (0..dimensions).map(|colnum| {
-                value_initializer(dimensions, colnum, rownum)
-            }).collect::<Vec<usize>>()
-        }).collect::<Vec<Vec<usize>>>();
-        LatinSquare {
-            size: dimensions,
-            square: rows
+impl LatinSquareDistribution {
+    /// The distribution on latin squares of the given order. Fails for order 0.
+    pub fn new(order: usize) -> Result<LatinSquareDistribution, CombinatorialError> {
+        if order == 0 {
+            return Err(CombinatorialError::ZeroOrder)
         }
+        Ok(LatinSquareDistribution { order })
     }
+}
 
-    /// Creates a new latin square where each row is a 1-cell shift.
-    /// e.g. if `dimensions` is 3,
-    /// 
-    /// 1 2 3
-    /// 2 3 1
-    /// 3 1 2
-    /// 
-    /// Generally used as the starting point for a random latin square.
-    pub fn new_cyclic(dimensions: usize) -> LatinSquare {
-        LatinSquare::new_square(dimensions, |dimensions, colnum, rownum| {
-            ((colnum + rownum) % (dimensions - 1)) + 1
-        })
-    }
-
-    /// Creates a new randomized latin square using the Mark T. Jacobson, Peter Matthews approach.
-    /// 
-    /// TODO:: Add functionality here to add restrictions on structure/cyclcic nature.
-    pub fn new_random(dimensions: usize) -> LatinSquare {
-        let mut cube = IncidenceCube::new_cyclic(dimensions);
-        cube.shuffle();
+/// # Panics
+/// If the order is 0, for a distribution built without `LatinSquareDistribution::new`.
+impl Distribution<LatinSquare> for LatinSquareDistribution {
+    fn sample<R: Rng + ?Sized>(&self, mut rng: &mut R) -> LatinSquare {
+        let mut cube = IncidenceCube::new_cyclic(self.order).unwrap_or_else(|error| panic!("{}", error));
+        cube.shuffle_with_rng(&mut rng);
         cube.as_latin_square()
     }
+}
 
-    /// Creates a new latin square where every cell is 0.
-    /// This isn't a valid latin square.
-    /// In other words, just a Vec<Vec<usize>> of size `dimensions`, pre-populated with zeros.
-    pub fn new_empty(dimensions: usize) -> LatinSquare {
-        LatinSquare::new_square(dimensions, |_, _, _| 0)
+/// The entries of an incidence cube as a dense tensor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubeTensor {
+    /// The lengths of the row, column and symbol axes.
+    pub shape: [usize; 3],
+    /// The entries in row-major order: the entry (x, y, z) is at (x * shape[1] + y) * shape[2] + z.
+    pub data: Vec<i8>
+}
+
+impl CubeTensor {
+    /// The entry at row x, column y, symbol z.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> i8 {
+        self.data[(x * self.shape[1] + y) * self.shape[2] + z]
     }
 }
 
-impl fmt::Display for LatinSquare {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let rows: Vec<String> = self.square.iter().map(|row| {
-            row.iter().map(|symbol| symbol.to_string()).collect::<Vec<String>>().join("   ")
-        }).collect();
-        let square: String = rows.join("\n\n");
-        write!(f, "Latin square of size {}\n\n{}", self.size, square)
+/// How long `IncidenceCube::shuffle_with` walks, counted in moves from proper cubes.
+///
+/// The default of n^3 moves is the length Jacobson and Matthews ran their walk for. Their chain is proven
+/// to converge to the uniform distribution, but no bound on how fast it mixes is known, so n^3 is a
+/// convention that passes the tests of the `randomness` module; fewer moves trade uniformity for speed,
+/// and more give a margin for stronger statistical guarantees.
+///
+/// A burn-in on top of that length goes in `extra_proper_steps`: once the walk has made its moves and is
+/// back at a proper cube, it makes that many more moves, each from a proper cube and followed by the moves
+/// back to one. As every move is counted the same way, this walks as long as `moves` set to the sum would,
+/// but without having to work out n^3 to add to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShuffleConfig {
+    /// The moves to make, or None for n^3 at order n.
+    pub moves: Option<u64>,
+    /// Moves made after the cube is proper again at the end of `moves`, as a burn-in beyond the usual length.
+    pub extra_proper_steps: u64
+}
+
+impl ShuffleConfig {
+    /// n^3 moves, as `shuffle` makes.
+    pub fn new() -> ShuffleConfig {
+        ShuffleConfig::default()
+    }
+
+    /// Makes the given number of moves from proper cubes in place of n^3.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::latin_square::ShuffleConfig;
+    ///
+    /// assert_eq!(ShuffleConfig::new().proper_moves(8), 512);
+    /// // A burn-in of twice the usual length.
+    /// let config = ShuffleConfig::new().with_moves(2 * IncidenceCube::mixing_moves(8));
+    /// assert_eq!(config.proper_moves(8), 1024);
+    /// ```
+    pub fn with_moves(mut self, moves: u64) -> ShuffleConfig {
+        self.moves = Some(moves);
+        self
+    }
+
+    /// Makes the given number of moves from proper cubes once the usual ones are done.
+    ///
+    /// ```
+    /// use combinatorial_patterns::latin_square::ShuffleConfig;
+    ///
+    /// // n^3 moves and then as many again.
+    /// let config = ShuffleConfig::new().with_extra_proper_steps(512);
+    /// assert_eq!(config.proper_moves(8), 1024);
+    /// assert_eq!(config.with_moves(100).proper_moves(8), 612);
+    /// ```
+    pub fn with_extra_proper_steps(mut self, extra_proper_steps: u64) -> ShuffleConfig {
+        self.extra_proper_steps = extra_proper_steps;
+        self
+    }
+
+    /// The moves from proper cubes to make at the given order, the extra steps included.
+    pub fn proper_moves(&self, order: usize) -> u64 {
+        self.moves.unwrap_or_else(|| IncidenceCube::mixing_moves(order)).saturating_add(self.extra_proper_steps)
     }
 }
 
+/// Where a move of `IncidenceCube::apply_move` left the cube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveOutcome {
+    /// The cube is proper, the incidence cube of a latin square.
+    Proper,
+    /// The cube is improper, with the triple counting -1.
+    Improper(CubeCell)
+}
+
 /// A three-dimensional representation of a latin square.
 /// 
 /// the x and y axes are the same, where the enumeration of the possible values becomes the z axis.
@@ -197,6 +1554,7 @@ impl fmt::Display for LatinSquare {
 /// 
 /// call `IncidenceCube::new_cyclic` to create a new incidence cube with the dimensions you want.
 /// call `IncidenceCube::as_latin_square` to downgrade it to two dimensions, for general use and output.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct IncidenceCube {
     size: usize,
     // xpos, ypos, zpos
@@ -204,8 +1562,83 @@ pub struct IncidenceCube {
     improper_cell: Option<Coordinate>
 }
 
+/// Shows the entries as 1, 0, and -1, as `to_tensor` gives them, with the improper cell if there is one.
+///
+/// ```
+/// use combinatorial_patterns::IncidenceCube;
+///
+/// let cube = IncidenceCube::new_cyclic(2).unwrap();
+/// assert_eq!(cube.clone(), cube);
+/// assert_eq!(format!("{:?}", cube), "IncidenceCube { size: 2, improper_cell: None, entries: [[[1, 0], [0, 1]], [[0, 1], [1, 0]]] }");
+/// ```
+impl fmt::Debug for IncidenceCube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<Vec<Vec<i32>>> = self.cube.iter()
+            .map(|plane| plane.iter().map(|line| line.iter().map(CubeEntry::as_int).collect()).collect())
+            .collect();
+        let improper_cell = self.improper_cell.map(|cell| (cell.x, cell.y, cell.z));
+        f.debug_struct("IncidenceCube")
+            .field("size", &self.size)
+            .field("improper_cell", &improper_cell)
+            .field("entries", &entries)
+            .finish()
+    }
+}
+
+/// Serializes a proper cube as the rows of its latin square. Improper cubes, midway through a move, fail.
+#[cfg(feature = "serde")]
+impl Serialize for IncidenceCube {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.improper_cell.is_some() {
+            return Err(ser::Error::custom("an improper incidence cube has no latin square"))
+        }
+        self.as_latin_square().serialize(serializer)
+    }
+}
+
+/// Deserializes the rows of a latin square, checking them like `TryFrom`, as the cube of the square.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for IncidenceCube {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<IncidenceCube, D::Error> {
+        Ok(IncidenceCube::from_square(&LatinSquare::deserialize(deserializer)?))
+    }
+}
+
+/// The square of a proper cube, failing for an improper one, which stands for no latin square.
+///
+/// ```
+/// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+///
+/// let mut cube = IncidenceCube::new_cyclic(5).unwrap();
+/// cube.shuffle(&mut rand::thread_rng());
+/// assert_eq!(LatinSquare::try_from(&cube), Ok(cube.as_latin_square()));
+/// ```
+impl TryFrom<&IncidenceCube> for LatinSquare {
+    type Error = CombinatorialError;
+
+    fn try_from(cube: &IncidenceCube) -> Result<LatinSquare, CombinatorialError> {
+        if cube.improper_cell.is_some() {
+            return Err(CombinatorialError::ImproperCube)
+        }
+        Ok(cube.as_latin_square())
+    }
+}
+
 impl IncidenceCube {
-    pub fn new_cyclic(dimensions: usize) -> IncidenceCube {
+    /// Creates the incidence cube of the cyclic square of `LatinSquare::new_cyclic`, the usual start of the walk.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    ///
+    /// assert_eq!(IncidenceCube::new_cyclic(4).unwrap().as_latin_square(), LatinSquare::new_cyclic(4).unwrap());
+    /// assert!(IncidenceCube::new_cyclic(0).is_err());
+    /// ```
+    ///
+    /// Fails for order 0.
+    pub fn new_cyclic(dimensions: usize) -> Result<IncidenceCube, CombinatorialError> {
+        if dimensions == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
         // starting_square = LatinSquare::new_cyclic(dimensions);
         let coords: Vec<Vec<Vec<CubeEntry>>> = (0..dimensions).map(|rownum| {
             (0..dimensions).map(|colnum| {
@@ -220,11 +1653,94 @@ impl IncidenceCube {
                 }).collect()
             }).collect()
         }).collect();
-        IncidenceCube {
+        Ok(IncidenceCube {
            size: dimensions,
            cube: coords,
            improper_cell: None
-       }
+       })
+    }
+
+    /// The proper cube of a latin square.
+    pub(crate) fn from_square(square: &LatinSquare) -> IncidenceCube {
+        let cube = square.square.iter().map(|row| {
+            row.iter().map(|&set_symbol| {
+                (0..square.size).map(|symbolnum| if symbolnum == set_symbol { CubeEntry::On } else { CubeEntry::Off }).collect()
+            }).collect()
+        }).collect();
+        IncidenceCube {
+            size: square.size,
+            cube,
+            improper_cell: None
+        }
+    }
+
+    /// The bytes taken by the entries of a cube of the given size, not counting the outermost vector.
+    pub(crate) fn footprint(size: usize) -> u64 {
+        let size = size as u64;
+        let rows = size.saturating_mul(mem::size_of::<Vec<Vec<CubeEntry>>>() as u64);
+        let cells = size.saturating_mul(size).saturating_mul(mem::size_of::<Vec<CubeEntry>>() as u64);
+        let entries = size.saturating_pow(3).saturating_mul(mem::size_of::<CubeEntry>() as u64);
+        rows.saturating_add(cells).saturating_add(entries)
+    }
+
+    /// Puts the cube back to the cyclic one, as `new_cyclic` creates it, reusing its buffers.
+    pub(crate) fn reset_cyclic(&mut self) {
+        let size = self.size;
+        for (rownum, row) in self.cube.iter_mut().enumerate() {
+            for (colnum, col) in row.iter_mut().enumerate() {
+                let set_symbol = (colnum + rownum) % size;
+                for (symbolnum, entry) in col.iter_mut().enumerate() {
+                    *entry = if symbolnum == set_symbol { CubeEntry::On } else { CubeEntry::Off };
+                }
+            }
+        }
+        self.improper_cell = None;
+    }
+
+    /// Writes the 2-dimensional representation into a square of the same size, reusing its rows.
+    pub(crate) fn write_latin_square(&self, square: &mut LatinSquare) {
+        for (cells, row) in square.square.iter_mut().zip(&self.cube) {
+            for (cell, col) in cells.iter_mut().zip(row) {
+                if let Some(symbol) = col.iter().position(|entry| matches!(entry, CubeEntry::On)) {
+                    *cell = symbol;
+                }
+            }
+        }
+    }
+
+    /// The cube with its axes permuted into the roles of the conjugate, which is the incidence cube of the
+    /// conjugate square. Improper cubes are permuted too, their improper cell with them.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::latin_square::ConjugateKind;
+    ///
+    /// let mut cube = IncidenceCube::new_cyclic(6).unwrap();
+    /// cube.shuffle(&mut rand::thread_rng());
+    /// let square = cube.as_latin_square();
+    /// for kind in ConjugateKind::ALL {
+    ///     assert_eq!(cube.conjugate(kind).as_latin_square().square, square.conjugate(kind).square);
+    /// }
+    /// ```
+    pub fn conjugate(&self, kind: ConjugateKind) -> IncidenceCube {
+        let roles = kind.roles();
+        let size = self.size;
+        let cube = (0..size).map(|first| {
+            (0..size).map(|second| {
+                (0..size).map(|third| {
+                    let mut original = [0; 3];
+                    original[roles[0]] = first;
+                    original[roles[1]] = second;
+                    original[roles[2]] = third;
+                    self.cube[original[0]][original[1]][original[2]]
+                }).collect()
+            }).collect()
+        }).collect();
+        let improper_cell = self.improper_cell.map(|cell| {
+            let triple = [cell.x, cell.y, cell.z];
+            Coordinate { x: triple[roles[0]], y: triple[roles[1]], z: triple[roles[2]] }
+        });
+        IncidenceCube { size, cube, improper_cell }
     }
 
     /// Transform the incidence cube in to its 2-dimensional representation.
@@ -243,22 +1759,243 @@ impl IncidenceCube {
         square
     }
 
-    /// Shuffles the incidence cube at least cube.size ^ 3 times.
+    /// The entries of the cube as a tensor of shape (rows, columns, symbols), with On = 1, Off = 0 and
+    /// Improper = -1, for feeding its structure to numerical tools.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let tensor = IncidenceCube::new_cyclic(3).unwrap().to_tensor();
+    /// assert_eq!(tensor.shape, [3, 3, 3]);
+    /// assert_eq!(tensor.data.len(), 27);
+    /// // Cell (1, 2) of the cyclic square holds (1 + 2) mod 3.
+    /// assert_eq!(tensor.get(1, 2, 0), 1);
+    /// assert_eq!(tensor.get(1, 2, 1), 0);
+    /// ```
+    pub fn to_tensor(&self) -> CubeTensor {
+        let data = self.cube.iter().flatten().flatten().map(|entry| entry.as_int() as i8).collect();
+        CubeTensor { shape: [self.size; 3], data }
+    }
+
+    /// Shuffles the incidence cube at least cube.size ^ 3 times, see `ShuffleConfig` for why and for
+    /// shuffling longer or shorter with `shuffle_with`.
     /// Will continue to shuffle until the cube is proper.
     /// 
     /// Optionally, will also continue to shuffle until the cube has no cyclical cells.
     /// This option is only viable if the cube size is an even number.
     /// Checking for cyclic cells is very slow, especially for large cubes. Avoid using if performance matters.
-    pub fn shuffle(&mut self) {
-        for _ in 0..i32::pow(self.size as i32, 3) {
-            self.move_cell();
+    /// Every random choice is drawn from `rng`.
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.shuffle_with_rng(rng);
+    }
+
+    /// Shuffles for as many moves as the config asks, then on until the cube is proper, drawing every random
+    /// choice from `rng`. Returns the number of moves made, improper ones included.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::latin_square::ShuffleConfig;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let mut cube = IncidenceCube::new_cyclic(8).unwrap();
+    /// // A quick, less uniform square.
+    /// assert!(cube.shuffle_with(&ShuffleConfig::new().with_moves(50), &mut rng) >= 50);
+    /// assert!(cube.as_latin_square().is_valid());
+    /// // Twice the usual length.
+    /// assert!(cube.shuffle_with(&ShuffleConfig::new().with_moves(1024), &mut rng) >= 1024);
+    /// // The usual length with a burn-in after it.
+    /// assert!(cube.shuffle_with(&ShuffleConfig::new().with_extra_proper_steps(256), &mut rng) >= 768);
+    ///
+    /// // No moves leave the cube as it is.
+    /// let mut cyclic = IncidenceCube::new_cyclic(8).unwrap();
+    /// assert_eq!(cyclic.shuffle_with(&ShuffleConfig::new().with_moves(0), &mut rng), 0);
+    /// assert_eq!(cyclic, IncidenceCube::new_cyclic(8).unwrap());
+    /// ```
+    pub fn shuffle_with(&mut self, config: &ShuffleConfig, rng: &mut impl Rng) -> u64 {
+        self.advance(config.proper_moves(self.size), rng)
+    }
+
+    /// Shuffles like `shuffle_with`, calling `progress(done, total)` after every move from a proper cube,
+    /// with the number of such moves made and the number the config asks for. The walk then ends with the
+    /// moves back to a proper cube, if the last move left it improper. The callback runs n^3 times by
+    /// default, so one drawing a progress bar should redraw only when the percentage changes.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::latin_square::ShuffleConfig;
+    ///
+    /// let mut cube = IncidenceCube::new_cyclic(10).unwrap();
+    /// let mut checkpoints = Vec::new();
+    /// cube.shuffle_with_progress(&ShuffleConfig::new(), &mut rand::thread_rng(), |done, total| {
+    ///     if done * 10 % total == 0 {
+    ///         checkpoints.push(done * 100 / total);
+    ///     }
+    /// });
+    /// assert_eq!(checkpoints, [10, 20, 30, 40, 50, 60, 70, 80, 90, 100]);
+    /// ```
+    pub fn shuffle_with_progress(&mut self, config: &ShuffleConfig, rng: &mut impl Rng, progress: impl FnMut(u64, u64)) -> u64 {
+        self.advance_with_progress(config.proper_moves(self.size), rng, progress)
+    }
+
+    /// Shuffles like `shuffle`, drawing every random choice from a generator seeded with `seed`, so the same
+    /// seed always gives the same square.
+    pub fn shuffle_seeded(&mut self, seed: u64) {
+        self.shuffle_with_rng(&mut ChaCha8Rng::seed_from_u64(seed));
+    }
+
+    /// Shuffles like `shuffle`, drawing every random choice from the given generator, so a seeded
+    /// generator always gives the same square. Returns the number of moves made.
+    pub(crate) fn shuffle_with_rng(&mut self, rng: &mut impl Rng) -> u64 {
+        self.shuffle_with(&ShuffleConfig::new(), rng)
+    }
+
+    /// The number of moves from proper cubes that `shuffle` makes at the given order, n^3, saturating
+    /// rather than overflowing for orders past the cube root of `u64::MAX`. The walk itself is far from
+    /// that: at order 2000 the cube takes 8 GB, see `resources` for predicting what a walk will take.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// assert_eq!(IncidenceCube::mixing_moves(4), 64);
+    /// // Past where n^3 leaves i32.
+    /// assert_eq!(IncidenceCube::mixing_moves(2000), 8_000_000_000);
+    /// assert_eq!(IncidenceCube::mixing_moves(usize::MAX), u64::MAX);
+    /// ```
+    pub fn mixing_moves(order: usize) -> u64 {
+        u64::try_from(order).unwrap_or(u64::MAX).saturating_pow(3)
+    }
+
+    /// Makes the given number of moves from proper cubes, each followed by the moves back to a proper cube.
+    /// Returns the number of moves made, improper ones included.
+    ///
+    /// Only the moves from proper cubes count: the proper cubes visited form a chain of their own, which is
+    /// uniform in the limit. Counting the improper moves too, and taking the first proper cube after some
+    /// total, favours the squares that end long improper runs, and at order 4 gives squares with 12
+    /// intercalates a third as often as it should.
+    pub(crate) fn advance(&mut self, moves: u64, rng: &mut impl Rng) -> u64 {
+        self.advance_with_progress(moves, rng, |_, _| {})
+    }
+
+    /// Advances like `advance`, calling `progress` with the moves from proper cubes made so far and the
+    /// moves asked for after each of them.
+    fn advance_with_progress(&mut self, moves: u64, rng: &mut impl Rng, mut progress: impl FnMut(u64, u64)) -> u64 {
+        let mut remaining = moves;
+        let mut made = 0;
+        while let Some(step) = self.traced_move(&mut remaining, rng) {
+            made += 1;
+            if !step.from_improper {
+                progress(moves - remaining, moves);
+            }
         }
-        loop {
-            if self.improper_cell.is_none() {
-                break
+        made
+    }
+
+    /// Makes `moves` moves from proper cubes like `shuffle`, each followed by the moves back to a proper
+    /// cube, and returns a record of every move. See the `explain` module for narrating them.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    ///
+    /// let mut cube = IncidenceCube::new_cyclic(4).unwrap();
+    /// let steps = cube.shuffle_explained(3, &mut rand::thread_rng());
+    /// assert!(steps.len() >= 3);
+    /// assert!(steps.last().unwrap().improper.is_none());
+    /// ```
+    pub fn shuffle_explained(&mut self, moves: usize, rng: &mut impl Rng) -> Vec<Step> {
+        self.shuffle_trace(&ShuffleConfig::new().with_moves(moves as u64), rng).collect()
+    }
+
+    /// Shuffles like `shuffle_with` one move at a time, as an iterator yielding every move as it is made,
+    /// with the triples it added and removed. See `explain::ShuffleTrace`.
+    pub fn shuffle_trace<R: Rng>(&mut self, config: &ShuffleConfig, rng: R) -> ShuffleTrace<'_, R> {
+        let moves = config.proper_moves(self.size);
+        ShuffleTrace::new(self, moves, rng)
+    }
+
+    /// The order of the cube, i.e. the number of rows, columns, and symbols.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns true if no entry counts -1, so that the cube is that of a latin square.
+    pub fn is_proper(&self) -> bool {
+        self.improper_cell.is_none()
+    }
+
+    /// The triple counting -1, if the cube is improper.
+    pub fn improper_cell(&self) -> Option<CubeCell> {
+        self.improper_cell.map(|cell| cell.as_cube_cell())
+    }
+
+    /// Makes the move from `origin` that picks the row, column, and symbol of `target`, for driving the walk
+    /// by hand. From a proper cube the origin must be a triple not in the square, and from an improper one
+    /// the improper triple; the target must have On entries at (target row, origin column, origin symbol),
+    /// (origin row, target column, origin symbol), and (origin row, origin column, target symbol). Fails,
+    /// leaving the cube as it is, for any other move.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{CombinatorialError, IncidenceCube};
+    /// use combinatorial_patterns::explain::CubeCell;
+    /// use combinatorial_patterns::latin_square::MoveOutcome;
+    ///
+    /// // 0 1 2
+    /// // 1 2 0
+    /// // 2 0 1
+    /// let mut cube = IncidenceCube::new_cyclic(3).unwrap();
+    /// let origin = CubeCell { row: 0, column: 0, symbol: 1 };
+    /// // Column 0 has symbol 1 in row 1, row 0 has it in column 1, and cell (0, 0) holds symbol 0.
+    /// let target = CubeCell { row: 1, column: 1, symbol: 0 };
+    /// assert_eq!(cube.apply_move(origin, target), Ok(MoveOutcome::Improper(CubeCell { row: 1, column: 1, symbol: 0 })));
+    /// assert!(!cube.is_proper());
+    /// assert_eq!(cube.improper_cell(), Some(target));
+    ///
+    /// // Only the improper triple can start the next move.
+    /// let error = cube.apply_move(origin, target).unwrap_err();
+    /// assert_eq!(error, CombinatorialError::NotAnOrigin(origin));
+    /// let outcome = cube.apply_move(target, CubeCell { row: 2, column: 0, symbol: 2 }).unwrap();
+    /// assert_eq!(outcome, MoveOutcome::Proper);
+    /// assert!(cube.as_latin_square().is_valid());
+    /// ```
+    pub fn apply_move(&mut self, origin: CubeCell, target: CubeCell) -> Result<MoveOutcome, CombinatorialError> {
+        for cell in [origin, target] {
+            if cell.row >= self.size || cell.column >= self.size || cell.symbol >= self.size {
+                return Err(CombinatorialError::OutOfCube { x: cell.row, y: cell.column, z: cell.symbol, order: self.size })
             }
-            self.move_cell();
         }
+        let origin_coordinate = Coordinate { x: origin.row, y: origin.column, z: origin.symbol };
+        let valid_origin = match self.improper_cell {
+            Some(cell) => cell == origin_coordinate,
+            None => self.cube[origin.row][origin.column][origin.symbol] == CubeEntry::Off
+        };
+        if !valid_origin {
+            return Err(CombinatorialError::NotAnOrigin(origin))
+        }
+        let lines = [
+            self.cube[target.row][origin.column][origin.symbol],
+            self.cube[origin.row][target.column][origin.symbol],
+            self.cube[origin.row][origin.column][target.symbol]
+        ];
+        if lines.iter().any(|&entry| entry != CubeEntry::On) {
+            return Err(CombinatorialError::NotATarget(target))
+        }
+        let step = self.toggle_move(origin_coordinate, Coordinate { x: target.row, y: target.column, z: target.symbol });
+        Ok(match step.improper {
+            Some(cell) => MoveOutcome::Improper(cell),
+            None => MoveOutcome::Proper
+        })
+    }
+
+    /// The next move of a walk with `remaining` moves from proper cubes left, counting it down, or None
+    /// once none are left and the cube is proper.
+    pub(crate) fn traced_move(&mut self, remaining: &mut u64, rng: &mut impl Rng) -> Option<Step> {
+        // Below order 2 there is a single square and no Off entry to move from.
+        if self.size < 2 || (*remaining == 0 && self.improper_cell.is_none()) {
+            return None
+        }
+        if self.improper_cell.is_none() {
+            *remaining -= 1;
+        }
+        Some(self.move_cell(rng))
     }
 
     /// Moves a cell in the cube to another position. May resultin an improper cube.
@@ -267,21 +2004,27 @@ impl IncidenceCube {
     /// 
     /// Logical reasoning here is too complex for documentation, but can be further explored in
     /// "Generating Uniformly Distributed Latin Squares" by  Mark T. Jacobson, Peter Matthews.
-    fn move_cell(&mut self) {
-        let &mut zero_cell;
-        let (origin, use_first_occurence) = match &self.improper_cell {
+    fn move_cell(&mut self, rng: &mut impl Rng) -> Step {
+        let (origin, use_first_occurence) = match self.improper_cell {
             Some(cell) => (cell, None),
-            None => {
-                zero_cell = self.find_off_cell();
-                (&zero_cell, Some(true))
-            }
+            None => (self.find_off_cell(rng), Some(true))
         };
 
+        // The cubes built by the crate have an On entry along every axis, and two through an improper cell.
+        let moved = "ProgrammingError: a proper or improper cube has the On entries of a move.";
         let new = Coordinate {
-            x: self.pick_coordinate(0, origin.y, origin.z, SearchCoord::X, use_first_occurence),
-            y: self.pick_coordinate(origin.x, 0, origin.z, SearchCoord::Y, use_first_occurence),
-            z: self.pick_coordinate(origin.x, origin.y, 0, SearchCoord::Z, use_first_occurence)
+            x: self.pick_coordinate(0, origin.y, origin.z, SearchCoord::X, use_first_occurence, rng).expect(moved),
+            y: self.pick_coordinate(origin.x, 0, origin.z, SearchCoord::Y, use_first_occurence, rng).expect(moved),
+            z: self.pick_coordinate(origin.x, origin.y, 0, SearchCoord::Z, use_first_occurence, rng).expect(moved)
         };
+        self.toggle_move(origin, new)
+    }
+
+    /// Makes the move from the origin with the chosen row, column, and symbol of the new coordinate, which
+    /// must have On entries in the lines through the origin.
+    fn toggle_move(&mut self, origin: Coordinate, new: Coordinate) -> Step {
+        let moved = "ProgrammingError: a move toggles entries On, Off, or Improper in turn.";
+        let from_improper = self.improper_cell.is_some();
 
         // Switch new coords on
         for c in [
@@ -290,7 +2033,7 @@ impl IncidenceCube {
             Coordinate { x: new.x, y: new.y, z: origin.z }, // x2,y2,z1 -> x2,y1,z1
             Coordinate { x: new.x, y: origin.y, z: new.z } // x2,y1,z2 -> x2,y2,z2
         ] {
-            self.cube[c.x][c.y][c.z] = self.cube[c.x][c.y][c.z].toggle_on();
+            self.cube[c.x][c.y][c.z] = self.cube[c.x][c.y][c.z].toggle_on().expect(moved);
         }
 
         for c in [
@@ -299,7 +2042,7 @@ impl IncidenceCube {
             Coordinate { x: new.x, y: origin.y, z: origin.z },
             Coordinate { x: new.x, y: new.y, z: new.z }
         ] {
-            self.cube[c.x][c.y][c.z] = self.cube[c.x][c.y][c.z].toggle_off();
+            self.cube[c.x][c.y][c.z] = self.cube[c.x][c.y][c.z].toggle_off().expect(moved);
         }
 
         if let CubeEntry::Improper = self.cube[new.x][new.y][new.z] {
@@ -307,6 +2050,14 @@ impl IncidenceCube {
         } else {
             self.improper_cell = None;
         }
+        Step {
+            from_improper,
+            origin: origin.as_cube_cell(),
+            row: new.x,
+            column: new.y,
+            symbol: new.z,
+            improper: self.improper_cell.map(|cell| cell.as_cube_cell())
+        }
     }
 
     /// Returns all cyclical cube cells. That is:
@@ -315,6 +2066,7 @@ impl IncidenceCube {
     /// - x2, y2, z1
     /// - x1+n, y1, z2
     /// - x2+n, y2, z2
+    ///
     /// The cell is cylcical.
     /// 
     /// In a Latin Square representation, we might have:
@@ -330,8 +2082,8 @@ impl IncidenceCube {
     /// - (1, 1, 3)
     /// - (2, 2, 1)
     /// - (3, 1, 1)
+    ///
     /// Would be cyclical cells.
-
     #[allow(dead_code)]
     fn find_cyclic_cell(&self) -> Option<Vec<Coordinate>> {
         let mut cyclic_cells: Vec<Coordinate> = Vec::new();
@@ -354,22 +2106,22 @@ impl IncidenceCube {
     ///
     /// Danger: Will loop infinitely if there are no zero cells, and may be very slow if the cube is not
     ///     representative of an actual latin square.
-    fn find_off_cell(&self) -> Coordinate {
+    fn find_off_cell(&self, rng: &mut impl Rng) -> Coordinate {
         let mut x: usize;
         let mut y: usize;
         let mut z: usize;
         loop {
-            x = thread_rng().gen_range(0..self.size);
-            y = thread_rng().gen_range(0..self.size);
-            z = thread_rng().gen_range(0..self.size);
+            x = rng.gen_range(0..self.size);
+            y = rng.gen_range(0..self.size);
+            z = rng.gen_range(0..self.size);
             if let CubeEntry::Off = self.cube[x][y][z] {
                 break;
             }
         }
         Coordinate {
-            x: x,
-            y: y,
-            z: z
+            x,
+            y,
+            z
         }
     }
 
@@ -380,17 +2132,18 @@ impl IncidenceCube {
     /// 
     /// # Arguments
     /// * `search_pos` - A coordinate that will search along two of x, y, and z. The position of the third
-    ///     coordinate will be mutably incremented, so the value within the search_pos will be the same as the
-    ///     return value.
+    ///   coordinate will be mutably incremented, so the value within the search_pos will be the same as the
+    ///   return value.
     /// * `search_coord` - Whhich axis to increment. The value of this enum indicates which axis we
-    ///     are looking for, leaving the other two as originally passed.
+    ///   are looking for, leaving the other two as originally passed.
     fn find_on_cell_along_axis(&self, search_pos: &mut Coordinate, search_coord: SearchCoord) -> Option<usize> {
         loop {
+            if search_pos.search_axis(search_coord) >= self.size {
+                return None
+            }
             let cell = &self.cube[search_pos.x][search_pos.y][search_pos.z];
             if let CubeEntry::On = cell {
                 break
-            } else if search_pos.search_axis(search_coord) == self.size {
-                return None
             } else {
                 search_pos.increment(search_coord);
             }
@@ -407,30 +2160,58 @@ impl IncidenceCube {
     /// - `z` - The z position on which to start your search.
     /// - `search_coord` - The axis on which you are looking for an On value.
     /// - `take_first` - Allows for some degree of randomness. 
-    ///     If Some, will take the first if true or the second if false.
-    ///     If None, will take the first or second with a 50/50 probability.
+    ///   If Some, will take the first if true or the second if false.
+    ///   If None, will take the first or second with a 50/50 probability, drawn from `rng`.
+    /// - `rng` - The generator of the 50/50 choice.
+    ///
+    /// Fails if the coordinate is outside the cube, or the axis through it has no On entry to take.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{CombinatorialError, IncidenceCube};
+    /// use combinatorial_patterns::latin_square::SearchCoord;
+    ///
+    /// let cube = IncidenceCube::new_cyclic(3).unwrap();
+    /// let mut rng = rand::thread_rng();
+    /// // Row 1 holds symbol 2 in column 1.
+    /// assert_eq!(cube.pick_coordinate(1, 0, 2, SearchCoord::Y, Some(true), &mut rng), Ok(1));
+    /// // A proper cube has a single On entry along every axis.
+    /// assert_eq!(
+    ///     cube.pick_coordinate(1, 0, 2, SearchCoord::Y, Some(false), &mut rng),
+    ///     Err(CombinatorialError::NoOnEntry { x: 1, y: 0, z: 2, axis: SearchCoord::Y })
+    /// );
+    /// assert!(cube.pick_coordinate(3, 0, 0, SearchCoord::Y, Some(true), &mut rng).is_err());
+    /// ```
     pub fn pick_coordinate(
-        &self, 
+        &self,
         x: usize,
         y: usize,
         z: usize,
         search_coord: SearchCoord,
         take_first: Option<bool>,
-    ) -> usize {
+        rng: &mut impl Rng
+    ) -> Result<usize, CombinatorialError> {
+        if x >= self.size || y >= self.size || z >= self.size {
+            return Err(CombinatorialError::OutOfCube { x, y, z, order: self.size })
+        }
         let mut search_pos = Coordinate::init_for_search(x, y, z, search_coord);
 
         let take_first = take_first.unwrap_or_else(|| {
-            thread_rng().gen_bool(0.5)
+            rng.gen_bool(0.5)
         });
 
-        let first_result = &self.find_on_cell_along_axis(&mut search_pos, search_coord);
+        let not_found = CombinatorialError::NoOnEntry { x, y, z, axis: search_coord };
+        let first_result = self.find_on_cell_along_axis(&mut search_pos, search_coord);
         match (first_result, take_first) {
-            (Some(res), true) => *res,
-            (_, false) => {
+            (Some(res), true) => Ok(res),
+            (Some(_), false) => {
                 search_pos.increment(search_coord); // Prevent finding the same coordinate we just found.
-                *&self.find_on_cell_along_axis(&mut search_pos, search_coord).unwrap()
+                self.find_on_cell_along_axis(&mut search_pos, search_coord).ok_or(not_found)
             },
-            _ => panic!("Couldn't find 'On' point along cube axis x: {}, y: {}, z: {}", x, y, z)
+            (None, _) => Err(not_found)
         }
    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
 }
\ No newline at end of file
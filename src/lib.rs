@@ -1,6 +1,70 @@
 //! Utilities for combinatorial patterns.
 //! 
-//! Currently only serves to genrate latin squares.
+//! Currently serves to genrate latin squares, and to use them as quasigroups.
 pub mod latin_square;
+pub mod quasigroup;
+pub mod triple_system;
+pub mod matching;
+pub mod finite_field;
+pub mod mols;
+pub mod incomplete;
+pub mod bitset;
+pub mod partial;
+pub mod defining_set;
+pub mod species;
+pub mod permanent;
+pub mod estimate;
+pub mod rectangle;
+pub mod pattern;
+pub mod switching;
+pub mod margins;
+pub mod birkhoff;
+pub mod sdr;
+pub mod rook;
+pub mod queens;
+pub mod numbers;
+pub mod ranking;
+pub mod monte_carlo;
+pub mod explain;
+pub mod batch;
+pub mod audit;
+pub mod design;
+pub mod resolvable;
+pub mod row_column;
+pub mod scheduling;
+pub mod sudoku;
+pub mod hints;
+pub mod cryptography;
+pub mod diagnostics;
+pub mod factorization;
+pub mod starter;
+pub mod library;
+pub mod convergence;
+pub mod exact;
+pub mod testing;
+pub mod correction;
+pub mod weighted;
+pub mod benchmark;
+pub mod enumerate;
+pub mod constraints;
+pub mod relaxed;
+pub mod polya;
+pub mod covering;
+pub mod lotto;
+pub mod constant_weight;
+pub mod solver;
+pub mod minimal_clues;
+pub mod algebra_export;
+pub mod import;
+pub mod generator;
+pub mod resources;
+pub mod randomness;
+pub mod orderly;
+pub mod design_isomorphism;
+pub mod canonical;
+pub mod render;
+pub mod error;
 
-pub use crate::latin_square::{LatinSquare, IncidenceCube};
\ No newline at end of file
+pub use crate::latin_square::{LatinSquare, LatinSquareDistribution, IncidenceCube};
+pub use crate::quasigroup::Quasigroup;
+pub use crate::error::CombinatorialError;
\ No newline at end of file
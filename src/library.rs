@@ -0,0 +1,326 @@
+//! An embedded library of every reduced latin square up to order 6, and of the canonical squares of the
+//! main classes of order 7.
+//!
+//! A reduced square has its first row and first column in order, and every latin square is isotopic to
+//! one. The reduced squares are numbered in lexicographic order, reading each square row by row. Their
+//! search, through the completions of the first row and column, takes seconds for order 6, and the tables
+//! here were computed once and are only read; `species` runs through them to find the isotopy and main
+//! classes, and the functions here return its classes, found once and then cached.
+//!
+//! Order 7, with 16942080 reduced squares, is too large to embed. Its main classes are in the library,
+//! computed once by running through those squares, and numbered like those of `species`: a main class is
+//! represented by the smallest canonical square of its isotopy classes, and the classes are numbered in the
+//! order of their canonical squares.
+//!
+//! | order          | 1 | 2 | 3 | 4 | 5  | 6    | 7   |
+//! |----------------|---|---|---|---|----|------|-----|
+//! | reduced squares| 1 | 1 | 1 | 4 | 56 | 9408 |     |
+//! | isotopy classes| 1 | 1 | 1 | 2 | 2  | 22   | 564 |
+//! | main classes   | 1 | 1 | 1 | 2 | 2  | 12   | 147 |
+//!
+//! Sources:
+//!
+//! - [Small latin squares, quasigroups, and loops, Brendan D. McKay, Alison Meynert, Wendy Myrvold](https://doi.org/10.1002/jcd.20105)
+
+use std::sync::OnceLock;
+
+use crate::latin_square::LatinSquare;
+use crate::species;
+
+/// The largest order whose reduced squares are in the library.
+pub const MAX_REDUCED_ORDER: usize = 6;
+
+/// The largest order whose isotopy classes are in the library.
+pub const MAX_ISOTOPY_ORDER: usize = species::MAX_ORDER;
+
+/// The largest order whose main classes are in the library.
+pub const MAX_MAIN_ORDER: usize = 7;
+
+/// The reduced squares of every order up to `MAX_REDUCED_ORDER`, one per line, row by row, by order and then
+/// in lexicographic order.
+const REDUCED_SQUARES: &str = include_str!("library/reduced_squares.txt");
+
+/// The number of reduced squares of the given order, or None if the order is not in the library.
+///
+/// ```
+/// use combinatorial_patterns::library;
+///
+/// assert_eq!(library::reduced_square_count(6), Some(9408));
+/// assert_eq!(library::reduced_square_count(7), None);
+/// ```
+pub fn reduced_square_count(order: usize) -> Option<usize> {
+    Some(reduced_cells(order)?.count())
+}
+
+/// The reduced squares of the given order, in lexicographic order, or None if the order is not in the library.
+///
+/// ```
+/// use combinatorial_patterns::library;
+///
+/// let squares = library::reduced_squares(4).unwrap();
+/// assert_eq!(squares.len(), 4);
+/// assert!(squares.iter().all(|square| square.is_valid() && square.square[0] == [0, 1, 2, 3]));
+/// assert!(squares.windows(2).all(|pair| pair[0].square < pair[1].square));
+/// ```
+pub fn reduced_squares(order: usize) -> Option<Vec<LatinSquare>> {
+    Some(reduced_cells(order)?.map(|cells| as_square(order, cells)).collect())
+}
+
+/// The reduced square with the given index, or None if the order is not in the library or the index is past
+/// the last square.
+///
+/// ```
+/// use combinatorial_patterns::library;
+///
+/// assert_eq!(library::reduced_square(6, 0).unwrap().square[1], [1, 0, 3, 2, 5, 4]);
+/// assert_eq!(library::reduced_square(6, 9407).unwrap().square[1], [1, 5, 4, 2, 3, 0]);
+/// assert!(library::reduced_square(6, 9408).is_none());
+/// ```
+pub fn reduced_square(order: usize, index: usize) -> Option<LatinSquare> {
+    reduced_cells(order)?.nth(index).map(|cells| as_square(order, cells))
+}
+
+/// The number of isotopy classes of the given order, or None if the order is not in the library.
+///
+/// ```
+/// use combinatorial_patterns::{library, species};
+///
+/// assert_eq!(library::isotopy_class_count(6), Some(22));
+/// assert_eq!(library::isotopy_class_count(7), None);
+/// assert_eq!(library::isotopy_classes(5), species::isotopy_classes(5));
+/// ```
+pub fn isotopy_class_count(order: usize) -> Option<usize> {
+    Some(cached_isotopy_classes(order)?.len())
+}
+
+/// The canonical squares of the isotopy classes of the given order, or None if the order is not in the library.
+pub fn isotopy_classes(order: usize) -> Option<Vec<LatinSquare>> {
+    Some(cached_isotopy_classes(order)?.to_vec())
+}
+
+/// The canonical square of the isotopy class with the given index, or None if the order is not in the
+/// library or the index is past the last class.
+pub fn isotopy_class(order: usize, index: usize) -> Option<LatinSquare> {
+    cached_isotopy_classes(order)?.get(index).cloned()
+}
+
+/// The number of main classes of the given order, or None if the order is not in the library.
+///
+/// ```
+/// use combinatorial_patterns::{library, species, Quasigroup};
+///
+/// assert_eq!(library::main_class_count(7), Some(147));
+/// // The last main class of order 7 is the cyclic group.
+/// let cyclic = library::main_class(7, 146).unwrap();
+/// assert_eq!(cyclic.square[1], vec![1, 2, 3, 4, 5, 6, 0]);
+/// assert!(library::main_classes(7).unwrap().into_iter().all(|square| Quasigroup::new(square).is_some()));
+/// assert_eq!(library::main_classes(6), species::main_classes(6));
+/// ```
+pub fn main_class_count(order: usize) -> Option<usize> {
+    if order == MAX_MAIN_ORDER {
+        return Some(MAIN_CLASSES_7.len())
+    }
+    Some(cached_main_classes(order)?.len())
+}
+
+/// The canonical squares of the main classes of the given order, or None if the order is not in the library.
+pub fn main_classes(order: usize) -> Option<Vec<LatinSquare>> {
+    (0..main_class_count(order)?).map(|index| main_class(order, index)).collect()
+}
+
+/// The canonical square of the main class with the given index, or None if the order is not in the
+/// library or the index is past the last class.
+pub fn main_class(order: usize, index: usize) -> Option<LatinSquare> {
+    if order == MAX_MAIN_ORDER {
+        return MAIN_CLASSES_7.get(index).map(|cells| as_square(order, cells))
+    }
+    cached_main_classes(order)?.get(index).cloned()
+}
+
+/// The lines of the reduced squares of the given order.
+fn reduced_cells(order: usize) -> Option<impl Iterator<Item = &'static str>> {
+    if order == 0 || order > MAX_REDUCED_ORDER {
+        return None
+    }
+    Some(REDUCED_SQUARES.lines().filter(move |cells| cells.len() == order * order))
+}
+
+/// The isotopy classes of `species`, found on the first call for all orders at once.
+fn cached_isotopy_classes(order: usize) -> Option<&'static [LatinSquare]> {
+    static CLASSES: OnceLock<Vec<Vec<LatinSquare>>> = OnceLock::new();
+    if order == 0 || order > MAX_ISOTOPY_ORDER {
+        return None
+    }
+    let classes = CLASSES.get_or_init(|| {
+        (0..=MAX_ISOTOPY_ORDER).map(|order| species::isotopy_classes(order).expect("ProgrammingError: species has every order up to its maximum.")).collect()
+    });
+    Some(&classes[order])
+}
+
+/// The main classes of `species`, found on the first call for all orders at once.
+fn cached_main_classes(order: usize) -> Option<&'static [LatinSquare]> {
+    static CLASSES: OnceLock<Vec<Vec<LatinSquare>>> = OnceLock::new();
+    if order == 0 || order > species::MAX_ORDER {
+        return None
+    }
+    let classes = CLASSES.get_or_init(|| {
+        (0..=species::MAX_ORDER).map(|order| species::main_classes(order).expect("ProgrammingError: species has every order up to its maximum.")).collect()
+    });
+    Some(&classes[order])
+}
+
+fn as_square(order: usize, cells: &str) -> LatinSquare {
+    let symbols: Vec<usize> = cells.bytes().map(|digit| (digit - b'0') as usize).collect();
+    LatinSquare::from_rows(symbols.chunks(order).map(|row| row.to_vec()).collect())
+}
+
+/// The canonical squares of the main classes of order 7, row by row.
+const MAIN_CLASSES_7: &[&str] = &[
+    "0123456103256423016453456012426510356142306540321",
+    "0123456103256423016453456012426510356143206540231",
+    "0123456103256423016453456012426510356402316514320",
+    "0123456103256423016453456012426513056403216514203",
+    "0123456103256423016453456021426513056142036540312",
+    "0123456103256423016453456021426513056143026540213",
+    "0123456103256423016453456021426513056402136514302",
+    "0123456103256423016453456021426513056403126514203",
+    "0123456103256423016453456021456013256142036245310",
+    "0123456103256423016453456021456021356143026245130",
+    "0123456103256423016453456021456031256142036245130",
+    "0123456103256423016453456120426503156143026540213",
+    "0123456103256423046153256041456012356412306415302",
+    "0123456103256423046153256041456012356413026415230",
+    "0123456103256423046153256041456013256413206415203",
+    "0123456103256423046153256041456123056401236415302",
+    "0123456103256423046153256041456130256401236415230",
+    "0123456103256423046153256041456132056401326415203",
+    "0123456103256423046153256041461523054601236541302",
+    "0123456103256423046153256041461530254612306540123",
+    "0123456103256423046153256140456102356402316415302",
+    "0123456103256423046153256140456103256403216415203",
+    "0123456103256423046153456021426510356103426541230",
+    "0123456103256423046153456021426510356412306510342",
+    "0123456103256423046153456021456013256412036215340",
+    "0123456103256423046153456021456120356103426245130",
+    "0123456103256423046153456021456120356401326215340",
+    "0123456103256423046153456021456123056103426245103",
+    "0123456103256423046153456021461520352613406540132",
+    "0123456103256423046153456021461523052601436541302",
+    "0123456103256423046153456102426503156413206510243",
+    "0123456103256423046153456102456023156410236215340",
+    "0123456103256423046153456102456023156413206215043",
+    "0123456103256423046153456102456032156412306215043",
+    "0123456103256423046153456102456102356402316215340",
+    "0123456103256423046153456102456132056102436245031",
+    "0123456103256423046153456102456132056402316215043",
+    "0123456103256423046153456102461523052610436540321",
+    "0123456103256423046153456120461530252610436540231",
+    "0123456103256423046153456201456012356410326215340",
+    "0123456103256423046153456201456102356103426245130",
+    "0123456103256423046153456201456102356401326215340",
+    "0123456103256423046153456201461502352613406540132",
+    "0123456103256423046153456201461503252601436541320",
+    "0123456103256423046153456201461503252613406540123",
+    "0123456103256423046153456201461532052601436541032",
+    "0123456103256423046153465021425610356412306510342",
+    "0123456103256423046153465021425613056102436541302",
+    "0123456103256423046153465021425613056412036510342",
+    "0123456103256423046153465021451620356401326251340",
+    "0123456103256423046153465021451630256412306250143",
+    "0123456103256423046153465021465123052461036510342",
+    "0123456103256423046153465102451623056403216251043",
+    "0123456103256423046153465102465123052160436540321",
+    "0123456103256423046153465201451603256401236251340",
+    "0123456103256423046153465201451603256413206250143",
+    "0123456103256423046153465201451632056401326251043",
+    "0123456103256423046153465201465012352163406541032",
+    "0123456103256423046153465201465013252160436541320",
+    "0123456103256423046153465201465102352163406540132",
+    "0123456103256423046153465201465102352461306510342",
+    "0123456103256423046153546102461502352613406450231",
+    "0123456103256423046153546201461503254601236251340",
+    "0123456103256423046153546201461503254613206250143",
+    "0123456103256423046153546201461532052601436451032",
+    "0123456103256423046153546201465013252610436415320",
+    "0123456103256423046153546201465103252601436415320",
+    "0123456103256423046153645201451632052610436450132",
+    "0123456103256423106453456012426530156412306504123",
+    "0123456103256423106453456012456123056041236245301",
+    "0123456103256423106453456012460532152641306541203",
+    "0123456103256423106453456102460523152643106541023",
+    "0123456103256423106453465021425613056042136541302",
+    "0123456103256423146053256041456012356412306405312",
+    "0123456103256423146053256041456013256413206405213",
+    "0123456103256423146053256041456021356413206405132",
+    "0123456103256423146053256041456123056403126405123",
+    "0123456103256423146053256041456132056401326405213",
+    "0123456103256423146053256041456132056402136405132",
+    "0123456103256423146053256041460513254602136541320",
+    "0123456103256423146053256041460513254613206540213",
+    "0123456103256423146053256140456102356403126405231",
+    "0123456103256423146053456012456012356412306205341",
+    "0123456103256423146053456012456023156413206205143",
+    "0123456103256423146053456012456032156012436245130",
+    "0123456103256423146053456012456123056401236205341",
+    "0123456103256423146053456012456132056402316205143",
+    "0123456103256423146053456012460512352603416541230",
+    "0123456103256423146053456012460512352613406540231",
+    "0123456103256423146053456012460523152613406540123",
+    "0123456103256423146053456021460521352613406540132",
+    "0123456103256423146053456210456012356013426245031",
+    "0123456103256423146053456210460513252610436540321",
+    "0123456103256423146053465012425613056012436540321",
+    "0123456103256423146053465012450623156401236251340",
+    "0123456103256423146053465012450623156413206250143",
+    "0123456103256423146053465021450613256402136251340",
+    "0123456103256423146053465021450621356401326251340",
+    "0123456103256423146053465021450631256412306250143",
+    "0123456103256423146053465021465013252463106501243",
+    "0123456103256423146053465210450613256403216251043",
+    "0123456103256423146053465210450632156401326251043",
+    "0123456103256423146053465210465013252063416541023",
+    "0123456103256423146053465210465103252061436540321",
+    "0123456103256423146053506142465102354602316245310",
+    "0123456103256423146053506142465123054603216245013",
+    "0123456103256423146053506241465132054601326245013",
+    "0123456103256423146053546012460512354602316251340",
+    "0123456103256423456013256140460123554603126514023",
+    "0123456103256423456013256140461023554610236504312",
+    "0123456103256423456013406125456031256142306251043",
+    "0123456103256423456013406125456123056103426254013",
+    "0123456103256423456013406125465031252610436514230",
+    "0123456103256423456013406125465123052640136510342",
+    "0123456103256423456013406215456013256143206251043",
+    "0123456103256423456013406215456132056140326250143",
+    "0123456103256423456013406215465013252610436514320",
+    "0123456103256423456013406215465013252613406514023",
+    "0123456103256423456013416025465013252613406504213",
+    "0123456103256423456013416025465021352613406504132",
+    "0123456103256423456013416025465021352641306501342",
+    "0123456103256423456013416025465123052601436504312",
+    "0123456103256423456013456012456132056102436204135",
+    "0123456103256423456013456120461023552610436504312",
+    "0123456103256423456013456210426013556140236501342",
+    "0123456103256423456013456210461032552610436504132",
+    "0123456103256423456013460215450613256143206251043",
+    "0123456103256423456013604215425613054610236510342",
+    "0123456103256423456013604215451632052601436451032",
+    "0123456103256423456103416205456032156041326251043",
+    "0123456103256423456103460125451630256042316251043",
+    "0123456103256423456103604125451620352603416451032",
+    "0123456103256424056133546102465023152613406314025",
+    "0123456103256424056133546102465123052603416314025",
+    "0123456103256424056133546120436120556140326250341",
+    "0123456103256424056133564201431602556401326251340",
+    "0123456103256424056133614025456013253462016251340",
+    "0123456103256424056313546102436021556140236251340",
+    "0123456103256424056313546210465102353601426214305",
+    "0123456103256424056313560142431620556413206254013",
+    "0123456103256424156033506142436021556413206254031",
+    "0123456103456223106453506124426530156412306452013",
+    "0123456103456223106453506124465123054620136245301",
+    "0123456103456223406153562140425630156012346415023",
+    "0123456103456223460153465201451263056013246250143",
+    "0123456120456323456013416025456213056302146051342",
+    "0123456123456023456013456012456012356012346012345",
+];
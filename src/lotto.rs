@@ -0,0 +1,196 @@
+//! Lotto designs, the "wheels" of lottery players.
+//!
+//! A lotto design LD(n, k, p, t) is a set of tickets, k-subsets of the numbers 1..n, such that whichever p
+//! numbers are drawn, some ticket has at least t of them. A covering design C(n, k, t) has every t-subset in
+//! a ticket, so it is a lotto design for any draw of p >= t numbers, with some slack when p > t: tickets
+//! can be dropped as long as every draw still matches one.
+//!
+//! `LottoDesign::generate` takes the best covering that `CoveringDesign::best` finds and drops the tickets
+//! it can, in random order. Checking a design goes through all C(n, p) draws, so the numbers are limited to
+//! `MAX_NUMBERS`, and the draws to `MAX_DRAWS`. Internally the numbers are 0..n like the points of a design;
+//! `to_ticket_list` writes them from 1, as printed on a ticket.
+//!
+//! Sources:
+//!
+//! - Lotto designs, in Handbook of Combinatorial Designs, C. J. Colbourn, J. H. Dinitz (editors), 2nd edition (2007)
+//! - [Lottery wheeling, Wikipedia](https://en.wikipedia.org/wiki/Lottery_wheeling)
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::covering::{subsets_of, CoveringDesign};
+use crate::numbers;
+
+/// The most numbers a lotto design can have.
+pub const MAX_NUMBERS: usize = 64;
+
+/// The most draws checked when verifying a lotto design.
+pub const MAX_DRAWS: u128 = 10_000_000;
+
+/// The draws of p of the numbers 0..n that no ticket matches in t numbers, in lexicographic order. Returns
+/// None unless t <= p <= n and t <= k <= n, every ticket has k distinct numbers below n, n is at most
+/// `MAX_NUMBERS`, and there are at most `MAX_DRAWS` draws.
+///
+/// ```
+/// use combinatorial_patterns::lotto;
+///
+/// // Two tickets of 3 out of 6 numbers, with any 4 drawn: one ticket always has 2 of them.
+/// let tickets = vec![vec![0, 1, 2], vec![3, 4, 5]];
+/// assert!(lotto::missed_draws(6, 3, 4, 2, &tickets).unwrap().is_empty());
+/// // But not always 3.
+/// assert_eq!(lotto::missed_draws(6, 3, 4, 3, &tickets).unwrap()[0], vec![0, 1, 3, 4]);
+/// ```
+pub fn missed_draws(numbers: usize, ticket_size: usize, draw_size: usize, matches: usize, tickets: &[Vec<usize>]) -> Option<Vec<Vec<usize>>> {
+    let masks = checked_masks(numbers, ticket_size, draw_size, matches, tickets)?;
+    let all: Vec<usize> = (0..numbers).collect();
+    Some(subsets_of(&all, draw_size).into_iter().filter(|draw| {
+        let drawn = mask(draw);
+        !masks.iter().any(|&ticket| (ticket & drawn).count_ones() as usize >= matches)
+    }).collect())
+}
+
+/// A lotto design LD(n, k, p, t): tickets of k of the numbers 0..n, one of which matches at least t of any
+/// p drawn.
+///
+/// ```
+/// use combinatorial_patterns::lotto::LottoDesign;
+///
+/// let design = LottoDesign::generate(10, 5, 5, 3, 20_000, &mut rand::thread_rng()).unwrap();
+/// assert!(LottoDesign::new(10, 5, 5, 3, design.tickets().to_vec()).is_some());
+/// assert!(design.tickets().len() <= 20);
+/// assert_eq!(design.to_string().lines().next(), Some(format!("LD(10, 5, 5, 3) with {} tickets", design.tickets().len()).as_str()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LottoDesign {
+    numbers: usize,
+    ticket_size: usize,
+    draw_size: usize,
+    matches: usize,
+    tickets: Vec<Vec<usize>>
+}
+
+impl LottoDesign {
+    /// Checks the tickets the way `missed_draws` does, sorting each. Returns None if there are missed draws,
+    /// or the parameters or tickets are invalid.
+    pub fn new(numbers: usize, ticket_size: usize, draw_size: usize, matches: usize, tickets: Vec<Vec<usize>>) -> Option<LottoDesign> {
+        if !missed_draws(numbers, ticket_size, draw_size, matches, &tickets)?.is_empty() {
+            return None
+        }
+        let tickets = tickets.into_iter().map(|mut ticket| {
+            ticket.sort_unstable();
+            ticket
+        }).collect();
+        Some(LottoDesign { numbers, ticket_size, draw_size, matches, tickets })
+    }
+
+    /// The covering C(n, k, t) as a lotto design for draws of p numbers. Returns None unless p is at least t
+    /// and at most n, or if the parameters are beyond `MAX_NUMBERS`.
+    ///
+    /// ```
+    /// use combinatorial_patterns::covering::CoveringDesign;
+    /// use combinatorial_patterns::lotto::LottoDesign;
+    ///
+    /// let covering = CoveringDesign::greedy(7, 3, 2).unwrap();
+    /// assert_eq!(LottoDesign::from_covering(&covering, 4).unwrap().tickets().len(), 7);
+    /// ```
+    pub fn from_covering(covering: &CoveringDesign, draw_size: usize) -> Option<LottoDesign> {
+        let (numbers, ticket_size, matches) = (covering.points(), covering.block_size(), covering.strength());
+        checked_masks(numbers, ticket_size, draw_size, matches, covering.blocks())?;
+        Some(LottoDesign { numbers, ticket_size, draw_size, matches, tickets: covering.blocks().to_vec() })
+    }
+
+    /// A lotto design from the best covering found with `steps` moves per attempt, with tickets dropped in
+    /// random order while every draw is still matched, every choice drawn from `rng`. Returns None if the
+    /// parameters are invalid or beyond the limits.
+    pub fn generate(numbers: usize, ticket_size: usize, draw_size: usize, matches: usize, steps: usize, rng: &mut impl Rng) -> Option<LottoDesign> {
+        checked_masks(numbers, ticket_size, draw_size, matches, &[])?;
+        let covering = CoveringDesign::best(numbers, ticket_size, matches, steps, rng)?;
+        let mut design = LottoDesign::from_covering(&covering, draw_size)?;
+        let all: Vec<usize> = (0..numbers).collect();
+        let draws: Vec<u64> = subsets_of(&all, draw_size).iter().map(|draw| mask(draw)).collect();
+        let mut order: Vec<usize> = (0..design.tickets.len()).collect();
+        order.shuffle(rng);
+        let mut kept = vec![true; design.tickets.len()];
+        let masks: Vec<u64> = design.tickets.iter().map(|ticket| mask(ticket)).collect();
+        for index in order {
+            kept[index] = false;
+            let matched = draws.iter().all(|&drawn| {
+                masks.iter().zip(&kept).any(|(&ticket, &kept)| kept && (ticket & drawn).count_ones() as usize >= matches)
+            });
+            if !matched {
+                kept[index] = true;
+            }
+        }
+        design.tickets = design.tickets.into_iter().zip(kept).filter(|&(_, kept)| kept).map(|(ticket, _)| ticket).collect();
+        Some(design)
+    }
+
+    /// The number of numbers n.
+    pub fn numbers(&self) -> usize {
+        self.numbers
+    }
+
+    /// The numbers on a ticket, k.
+    pub fn ticket_size(&self) -> usize {
+        self.ticket_size
+    }
+
+    /// The numbers drawn, p.
+    pub fn draw_size(&self) -> usize {
+        self.draw_size
+    }
+
+    /// The numbers some ticket matches, t.
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+
+    /// The tickets, each sorted, with numbers from 0.
+    pub fn tickets(&self) -> &[Vec<usize>] {
+        &self.tickets
+    }
+
+    /// The tickets one per line, with numbers from 1 separated by spaces.
+    ///
+    /// ```
+    /// use combinatorial_patterns::lotto::LottoDesign;
+    ///
+    /// let design = LottoDesign::new(6, 3, 4, 2, vec![vec![0, 1, 2], vec![3, 4, 5]]).unwrap();
+    /// assert_eq!(design.to_ticket_list(), "1 2 3\n4 5 6\n");
+    /// ```
+    pub fn to_ticket_list(&self) -> String {
+        self.tickets.iter().map(|ticket| {
+            let numbers: Vec<String> = ticket.iter().map(|number| (number + 1).to_string()).collect();
+            numbers.join(" ") + "\n"
+        }).collect()
+    }
+}
+
+impl fmt::Display for LottoDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LD({}, {}, {}, {}) with {} tickets\n{}", self.numbers, self.ticket_size, self.draw_size, self.matches, self.tickets.len(), self.to_ticket_list().trim_end())
+    }
+}
+
+/// The tickets as bit masks, if the parameters and tickets are valid and within the limits.
+fn checked_masks(numbers: usize, ticket_size: usize, draw_size: usize, matches: usize, tickets: &[Vec<usize>]) -> Option<Vec<u64>> {
+    if !(matches <= draw_size && draw_size <= numbers && matches <= ticket_size && ticket_size <= numbers) || numbers > MAX_NUMBERS {
+        return None
+    }
+    if numbers::binomial(numbers, draw_size)? > MAX_DRAWS {
+        return None
+    }
+    tickets.iter().map(|ticket| {
+        if ticket.len() != ticket_size || ticket.iter().any(|&number| number >= numbers) {
+            return None
+        }
+        let ticket_mask = mask(ticket);
+        if ticket_mask.count_ones() as usize == ticket_size { Some(ticket_mask) } else { None }
+    }).collect()
+}
+
+fn mask(numbers: &[usize]) -> u64 {
+    numbers.iter().fold(0, |mask, &number| mask | (1 << number))
+}
@@ -1,3 +1,204 @@
-fn main() {
-    println!("Hello, world!");
+//! Command line tools for latin squares.
+//!
+//! `validate [--format text|csv|json] [--diagonal] [--symmetric] [--orthogonal FILE] [FILE]` reads a grid
+//! from FILE, or stdin if it is missing or `-`, and reports every violation of the latin property with its
+//! position. Text grids separate symbols by whitespace, and may start with the header line `Display` writes.
+//! CSV grids separate them by commas. JSON grids are an array of rows, on their own or as the first such
+//! array in an object, like `{"square": [[0, 1], [1, 0]]}`. Without `--format`, the format is guessed from
+//! the input. The other options also check that both diagonals hold every symbol, that the square equals its
+//! transpose, and that it is orthogonal to a second square.
+//!
+//! `batch --seed SEED --count COUNT --order ORDER` writes COUNT random squares as NDJSON records to stdout, one
+//! per line, each with the seed that regenerates it. See the `batch` module of the library.
+//!
+//! Exit codes are 0 when every check passes, 1 when some check fails, and 2 when the input or the arguments
+//! can't be read.
+
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use combinatorial_patterns::batch;
+use combinatorial_patterns::testing::{diagonal_violations, latin_violations, orthogonality_violations, symmetry_violations};
+
+const USAGE: &str = "Usage:
+    combinatorial_patterns validate [--format text|csv|json] [--diagonal] [--symmetric] [--orthogonal FILE] [FILE]
+    combinatorial_patterns batch --seed SEED --count COUNT --order ORDER";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(|command| command.as_str()) {
+        Some("validate") => validate(&args[1..]),
+        Some("batch") => generate_batch(&args[1..]),
+        _ => Err(USAGE.to_string())
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Csv,
+    Json
+}
+
+/// Runs the checks and prints what fails. Returns whether everything passed.
+fn validate(args: &[String]) -> Result<bool, String> {
+    let mut format = None;
+    let mut diagonal = false;
+    let mut symmetric = false;
+    let mut orthogonal = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = Some(match args.next().map(|format| format.as_str()) {
+                    Some("text") => Format::Text,
+                    Some("csv") => Format::Csv,
+                    Some("json") => Format::Json,
+                    _ => return Err(USAGE.to_string())
+                });
+            },
+            "--diagonal" => diagonal = true,
+            "--symmetric" => symmetric = true,
+            "--orthogonal" => orthogonal = Some(args.next().ok_or_else(|| USAGE.to_string())?.clone()),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_string())
+        }
+    }
+
+    let grid = parse(&read(path.as_deref())?, format)?;
+    let mut violations = latin_violations(&grid);
+    // The further checks only make sense for a square grid of symbols in range.
+    if violations.iter().all(|violation| !violation.starts_with("shape")) {
+        if diagonal {
+            violations.extend(diagonal_violations(&grid));
+        }
+        if symmetric {
+            violations.extend(symmetry_violations(&grid));
+        }
+        if let Some(other) = orthogonal {
+            let other_grid = parse(&read(Some(&other))?, format)?;
+            let other_violations = latin_violations(&other_grid);
+            if other_violations.is_empty() {
+                violations.extend(orthogonality_violations(&grid, &other_grid));
+            } else {
+                violations.extend(other_violations.into_iter().map(|violation| format!("{}: {}", other, violation)));
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        println!("valid latin square of order {}", grid.len());
+        return Ok(true)
+    }
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    Ok(false)
+}
+
+/// Writes a batch of seeded squares as NDJSON.
+fn generate_batch(args: &[String]) -> Result<bool, String> {
+    let (mut seed, mut count, mut order) = (None, None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| USAGE.to_string())?;
+        match arg.as_str() {
+            "--seed" => seed = Some(value.parse::<u64>().map_err(|_| format!("{:?} is not a seed", value))?),
+            "--count" => count = Some(value.parse::<u64>().map_err(|_| format!("{:?} is not a count", value))?),
+            "--order" => order = Some(value.parse::<usize>().map_err(|_| format!("{:?} is not an order", value))?),
+            _ => return Err(USAGE.to_string())
+        }
+    }
+    let (Some(seed), Some(count), Some(order)) = (seed, count, order) else {
+        return Err(USAGE.to_string())
+    };
+    if order < 2 {
+        return Err(format!("Random squares need an order of at least 2, not {}", order))
+    }
+    batch::write_ndjson(&mut io::stdout().lock(), seed, order, count).map_err(|error| format!("Can't write: {}", error))?;
+    Ok(true)
+}
+
+fn read(path: Option<&str>) -> Result<String, String> {
+    match path {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|error| format!("Can't read stdin: {}", error))?;
+            Ok(input)
+        },
+        Some(path) => fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))
+    }
+}
+
+/// Parses a grid of symbols, guessing the format when none is given.
+fn parse(input: &str, format: Option<Format>) -> Result<Vec<Vec<usize>>, String> {
+    let trimmed = input.trim_start();
+    let format = format.unwrap_or(if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        Format::Json
+    } else if input.contains(',') {
+        Format::Csv
+    } else {
+        Format::Text
+    });
+    match format {
+        Format::Text => parse_lines(input, |line| line.split_whitespace().collect()),
+        Format::Csv => parse_lines(input, |line| line.split(',').map(|field| field.trim()).collect()),
+        Format::Json => parse_json(input)
+    }
+}
+
+fn parse_lines(input: &str, split: impl Fn(&str) -> Vec<&str>) -> Result<Vec<Vec<usize>>, String> {
+    input.lines().enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with("Latin square"))
+        .map(|(number, line)| {
+            split(line).into_iter()
+                .map(|field| field.parse().map_err(|_| format!("line {}: {:?} is not a symbol", number + 1, field)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses the first array of arrays of numbers in the input.
+fn parse_json(input: &str) -> Result<Vec<Vec<usize>>, String> {
+    let characters: Vec<char> = input.chars().collect();
+    let is_space = |c: &char| c.is_whitespace();
+    let start = (0..characters.len())
+        .find(|&index| characters[index] == '[' && characters[index + 1..].iter().find(|c| !is_space(c)) == Some(&'['))
+        .ok_or_else(|| "JSON input has no array of rows".to_string())?;
+
+    let mut rows = Vec::new();
+    let mut row: Option<Vec<usize>> = None;
+    let mut number = String::new();
+    for &c in &characters[start + 1..] {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue
+        }
+        if !number.is_empty() {
+            let symbol = number.parse().map_err(|_| format!("JSON symbol {} is too large", number))?;
+            row.as_mut().ok_or_else(|| "JSON symbols must be inside rows".to_string())?.push(symbol);
+            number.clear();
+        }
+        match c {
+            '[' if row.is_none() => row = Some(Vec::new()),
+            ']' => match row.take() {
+                Some(finished) => rows.push(finished),
+                None => return Ok(rows)
+            },
+            ',' => {},
+            c if c.is_whitespace() => {},
+            c => return Err(format!("JSON grid has an unexpected {:?}", c))
+        }
+    }
+    Err("JSON grid is not closed".to_string())
 }
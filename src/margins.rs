@@ -0,0 +1,211 @@
+//! Random 0/1 and nonnegative integer matrices with prescribed row and column sums, i.e. contingency tables
+//! with fixed margins, by switch chain Markov chain Monte Carlo.
+//!
+//! A step of the chain picks two rows i, j and two columns k, l at random. For 0/1 matrices, if the 2 x 2
+//! submatrix reads 1 0 / 0 1 or 0 1 / 1 0, it is replaced by the other one; otherwise nothing happens.
+//! For integer matrices, one of the diagonals is raised by one and the other lowered by one, unless that
+//! would go negative. Both moves keep every margin, are their own reverse, and are picked with the same
+//! probability as their reverse, so the chains are symmetric and converge to the uniform distribution on
+//! the matrices with the given margins. This is the two-dimensional cousin of the Jacobson Matthews walk
+//! on the incidence cube, and like it needs a burn in before its states are close to uniform.
+//!
+//! Starting points come from Ryser's greedy construction for 0/1 matrices, which succeeds exactly when
+//! the Gale-Ryser condition holds, and from the northwest corner rule for integer matrices.
+//!
+//! Sources:
+//!
+//! - [Algebraic algorithms for sampling from conditional distributions, Persi Diaconis, Bernd Sturmfels](https://doi.org/10.1214/aos/1030563990)
+//! - [Gale-Ryser theorem, Wikipedia](https://en.wikipedia.org/wiki/Gale%E2%80%93Ryser_theorem)
+
+use rand::Rng;
+
+/// The switch chain on 0/1 matrices with fixed row and column sums.
+///
+/// ```
+/// use combinatorial_patterns::margins::BinaryMatrixChain;
+///
+/// let mut chain = BinaryMatrixChain::new(&[2, 1, 1], &[1, 1, 2]).unwrap();
+/// chain.run(100, &mut rand::thread_rng());
+/// let matrix = chain.matrix();
+/// assert_eq!(matrix[0].iter().filter(|&&cell| cell).count(), 2);
+/// assert_eq!(matrix.iter().filter(|row| row[2]).count(), 2);
+///
+/// // No 0/1 matrix has a row summing to 3 with only 2 columns.
+/// assert!(BinaryMatrixChain::new(&[3], &[2, 1]).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryMatrixChain {
+    matrix: Vec<Vec<bool>>
+}
+
+impl BinaryMatrixChain {
+    /// Starts the chain at a 0/1 matrix with the given margins, or returns None if there is none.
+    pub fn new(row_sums: &[usize], column_sums: &[usize]) -> Option<BinaryMatrixChain> {
+        let mut remaining = column_sums.to_vec();
+        let mut matrix = vec![vec![false; column_sums.len()]; row_sums.len()];
+        for (row, &sum) in matrix.iter_mut().zip(row_sums.iter()) {
+            // Ryser: fill the columns that still need the most ones.
+            let mut columns: Vec<usize> = (0..remaining.len()).collect();
+            columns.sort_by_key(|&column| std::cmp::Reverse(remaining[column]));
+            if sum > columns.len() {
+                return None
+            }
+            for &column in &columns[..sum] {
+                if remaining[column] == 0 {
+                    return None
+                }
+                remaining[column] -= 1;
+                row[column] = true;
+            }
+        }
+        if remaining.iter().any(|&left| left > 0) {
+            return None
+        }
+        Some(BinaryMatrixChain { matrix })
+    }
+
+    /// Starts the chain at the given matrix, whose margins it keeps.
+    pub fn from_matrix(matrix: Vec<Vec<bool>>) -> BinaryMatrixChain {
+        BinaryMatrixChain { matrix }
+    }
+
+    /// The current state of the chain.
+    pub fn matrix(&self) -> &[Vec<bool>] {
+        &self.matrix
+    }
+
+    /// Takes one step of the chain, drawn from `rng`. Returns true if the matrix changed.
+    pub fn step(&mut self, rng: &mut impl Rng) -> bool {
+        let Some([i, j, k, l]) = random_switch(&self.matrix, rng) else {
+            return false
+        };
+        let m = &mut self.matrix;
+        if m[i][k] == m[j][l] && m[i][l] == m[j][k] && m[i][k] != m[i][l] {
+            for (row, column) in [(i, k), (i, l), (j, k), (j, l)] {
+                m[row][column] = !m[row][column];
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Takes the given number of steps, e.g. as burn in.
+    pub fn run(&mut self, steps: usize, rng: &mut impl Rng) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    /// Takes `steps` steps and returns a copy of the matrix reached, so that repeated calls draw a thinned sample.
+    pub fn sample(&mut self, steps: usize, rng: &mut impl Rng) -> Vec<Vec<bool>> {
+        self.run(steps, rng);
+        self.matrix.clone()
+    }
+}
+
+/// The switch chain on nonnegative integer matrices with fixed row and column sums.
+///
+/// ```
+/// use combinatorial_patterns::margins::IntegerMatrixChain;
+///
+/// let mut chain = IntegerMatrixChain::new(&[3, 2], &[1, 4]).unwrap();
+/// let matrix = chain.sample(50, &mut rand::thread_rng());
+/// assert_eq!(matrix[0][0] + matrix[0][1], 3);
+/// assert_eq!(matrix[0][1] + matrix[1][1], 4);
+///
+/// // The margins must have the same total.
+/// assert!(IntegerMatrixChain::new(&[3, 2], &[1, 1]).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntegerMatrixChain {
+    matrix: Vec<Vec<usize>>
+}
+
+impl IntegerMatrixChain {
+    /// Starts the chain at an integer matrix with the given margins, or returns None if their totals differ.
+    pub fn new(row_sums: &[usize], column_sums: &[usize]) -> Option<IntegerMatrixChain> {
+        if row_sums.iter().sum::<usize>() != column_sums.iter().sum::<usize>() {
+            return None
+        }
+        // Northwest corner: fill each cell with as much as both its row and column still need.
+        let mut rows = row_sums.to_vec();
+        let mut columns = column_sums.to_vec();
+        let mut matrix = vec![vec![0; columns.len()]; rows.len()];
+        for (row, cells) in matrix.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                let amount = rows[row].min(columns[column]);
+                *cell = amount;
+                rows[row] -= amount;
+                columns[column] -= amount;
+            }
+        }
+        Some(IntegerMatrixChain { matrix })
+    }
+
+    /// Starts the chain at the given matrix, whose margins it keeps.
+    pub fn from_matrix(matrix: Vec<Vec<usize>>) -> IntegerMatrixChain {
+        IntegerMatrixChain { matrix }
+    }
+
+    /// The current state of the chain.
+    pub fn matrix(&self) -> &[Vec<usize>] {
+        &self.matrix
+    }
+
+    /// Takes one step of the chain, drawn from `rng`. Returns true if the matrix changed.
+    pub fn step(&mut self, rng: &mut impl Rng) -> bool {
+        let Some([i, j, k, l]) = random_switch(&self.matrix, rng) else {
+            return false
+        };
+        // Add one on (i, k), (j, l) and take one from (i, l), (j, k); swapping k and l gives the reverse move.
+        let m = &mut self.matrix;
+        if m[i][l] == 0 || m[j][k] == 0 {
+            return false
+        }
+        m[i][k] += 1;
+        m[j][l] += 1;
+        m[i][l] -= 1;
+        m[j][k] -= 1;
+        true
+    }
+
+    /// Takes the given number of steps, e.g. as burn in.
+    pub fn run(&mut self, steps: usize, rng: &mut impl Rng) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    /// Takes `steps` steps and returns a copy of the matrix reached, so that repeated calls draw a thinned sample.
+    pub fn sample(&mut self, steps: usize, rng: &mut impl Rng) -> Vec<Vec<usize>> {
+        self.run(steps, rng);
+        self.matrix.clone()
+    }
+}
+
+/// Returns a uniformly random 0/1 matrix with the given margins after `burn_in` steps of the switch chain,
+/// drawn from `rng`, or None if there is none.
+pub fn random_binary_matrix(row_sums: &[usize], column_sums: &[usize], burn_in: usize, rng: &mut impl Rng) -> Option<Vec<Vec<bool>>> {
+    BinaryMatrixChain::new(row_sums, column_sums).map(|mut chain| chain.sample(burn_in, rng))
+}
+
+/// Returns a uniformly random nonnegative integer matrix with the given margins after `burn_in` steps of the
+/// switch chain, drawn from `rng`, or None if the margins have different totals.
+pub fn random_integer_matrix(row_sums: &[usize], column_sums: &[usize], burn_in: usize, rng: &mut impl Rng) -> Option<Vec<Vec<usize>>> {
+    IntegerMatrixChain::new(row_sums, column_sums).map(|mut chain| chain.sample(burn_in, rng))
+}
+
+/// Picks two distinct rows i, j and two distinct columns k, l, if the matrix has them.
+fn random_switch<T>(matrix: &[Vec<T>], rng: &mut impl Rng) -> Option<[usize; 4]> {
+    let rows = matrix.len();
+    let columns = matrix.first().map(|row| row.len()).unwrap_or(0);
+    if rows < 2 || columns < 2 {
+        return None
+    }
+    let i = rng.gen_range(0..rows);
+    let j = (i + rng.gen_range(1..rows)) % rows;
+    let k = rng.gen_range(0..columns);
+    let l = (k + rng.gen_range(1..columns)) % columns;
+    Some([i, j, k, l])
+}
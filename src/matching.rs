@@ -0,0 +1,364 @@
+//! Bipartite matching, the engine behind completing latin rectangles and related constructions.
+//!
+//! Latin rectangles grow one row at a time by picking, for every column, a symbol the column hasn't
+//! used yet, with no symbol picked twice. That is a perfect matching between columns and symbols,
+//! and Hall's theorem guarantees one always exists.
+//!
+//! Matchings are grown with augmenting paths (Kuhn's algorithm). Augmenting never unmatches a vertex,
+//! so a matching can be grown from a chosen set of vertices first, which is needed for constructions
+//! that must saturate a particular subset of vertices.
+//!
+//! Random perfect matchings are drawn exactly for small graphs, one left vertex at a time, weighting each
+//! choice by the number of perfect matchings it leaves, a permanent. Larger graphs use the Jerrum Sinclair
+//! chain on perfect and near perfect matchings instead, see `MatchingChain`. Drawing matchings of the
+//! availability graph of a latin rectangle adds random rows to it, which grows random latin squares,
+//! though not uniformly distributed ones: squares with fewer completions along the way are favoured.
+//!
+//! Sources:
+//!
+//! - [Approximating the permanent, Mark Jerrum, Alistair Sinclair](https://doi.org/10.1137/0218077)
+
+use rand::Rng;
+
+use crate::permanent::{permanent, random_permutation};
+
+/// A bipartite graph, with `left` vertices numbered 0..left and `right` vertices numbered 0..right.
+///
+/// ```
+/// use combinatorial_patterns::matching::BipartiteGraph;
+///
+/// let mut graph = BipartiteGraph::new(3, 3);
+/// graph.add_edge(0, 0);
+/// graph.add_edge(0, 1);
+/// graph.add_edge(1, 0);
+/// graph.add_edge(2, 1);
+/// graph.add_edge(2, 2);
+///
+/// let matching = graph.maximum_matching();
+/// assert_eq!(matching.size(), 3);
+/// assert_eq!(matching.right_of(1), Some(0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BipartiteGraph {
+    left: usize,
+    right: usize,
+    adjacency: Vec<Vec<usize>>,
+    reverse_adjacency: Vec<Vec<usize>>
+}
+
+/// A matching in a `BipartiteGraph`, stored from both sides.
+#[derive(Debug, Clone)]
+pub struct Matching {
+    left_to_right: Vec<Option<usize>>,
+    right_to_left: Vec<Option<usize>>
+}
+
+impl BipartiteGraph {
+    /// Creates a graph with no edges.
+    pub fn new(left: usize, right: usize) -> BipartiteGraph {
+        BipartiteGraph {
+            left,
+            right,
+            adjacency: vec![Vec::new(); left],
+            reverse_adjacency: vec![Vec::new(); right]
+        }
+    }
+
+    /// Adds an edge between left vertex `u` and right vertex `v`.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.adjacency[u].push(v);
+        self.reverse_adjacency[v].push(u);
+    }
+
+    /// The number of left vertices.
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// The number of right vertices.
+    pub fn right(&self) -> usize {
+        self.right
+    }
+
+    /// The right vertices adjacent to left vertex `u`.
+    pub fn neighbours(&self, u: usize) -> &[usize] {
+        &self.adjacency[u]
+    }
+
+    /// Returns true if left vertex `u` and right vertex `v` are adjacent.
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.adjacency[u].contains(&v)
+    }
+
+    /// Returns a matching of maximum size.
+    pub fn maximum_matching(&self) -> Matching {
+        let mut matching = Matching::empty(self);
+        for u in 0..self.left {
+            matching.augment_from_left(self, u);
+        }
+        matching
+    }
+
+    /// The number of perfect matchings, as the permanent of the adjacency matrix. Panics if the two sides
+    /// have a different number of vertices.
+    pub fn count_perfect_matchings(&self) -> u128 {
+        permanent(&self.adjacency_matrix())
+    }
+
+    /// Returns a perfect matching drawn uniformly at random with `rng`, or None if there is none.
+    ///
+    /// Exact for up to `EXACT_SAMPLING_LIMIT` vertices on each side; above that, the result of `MatchingChain`
+    /// after `10 * n * edges` steps, which is close to uniform.
+    ///
+    /// ```
+    /// use combinatorial_patterns::matching::BipartiteGraph;
+    ///
+    /// let mut graph = BipartiteGraph::new(3, 3);
+    /// for u in 0..3 {
+    ///     for v in 0..3 {
+    ///         if u != v {
+    ///             graph.add_edge(u, v);
+    ///         }
+    ///     }
+    /// }
+    /// // The two derangements of 3 elements.
+    /// assert_eq!(graph.count_perfect_matchings(), 2);
+    /// let matching = graph.random_perfect_matching(&mut rand::thread_rng()).unwrap();
+    /// assert!((0..3).all(|u| matching.right_of(u) != Some(u)));
+    /// ```
+    pub fn random_perfect_matching(&self, rng: &mut impl Rng) -> Option<Matching> {
+        if self.left <= EXACT_SAMPLING_LIMIT {
+            return self.random_perfect_matching_exact(rng)
+        }
+        let steps = 10 * self.left * self.adjacency.iter().map(|neighbours| neighbours.len()).sum::<usize>();
+        MatchingChain::new(self).map(|mut chain| chain.sample(steps, rng))
+    }
+
+    /// Returns a perfect matching drawn exactly uniformly at random with `rng`, or None if there is none.
+    /// This takes O(n^3 * 2^n) steps, so it is only practical for small graphs.
+    pub fn random_perfect_matching_exact(&self, rng: &mut impl Rng) -> Option<Matching> {
+        if self.left != self.right {
+            return None
+        }
+        let (_, permutation) = random_permutation(&self.adjacency_matrix(), rng)?;
+        let mut matching = Matching::empty(self);
+        for (u, v) in permutation.into_iter().enumerate() {
+            matching.left_to_right[u] = Some(v);
+            matching.right_to_left[v] = Some(u);
+        }
+        Some(matching)
+    }
+
+    fn adjacency_matrix(&self) -> Vec<Vec<bool>> {
+        let mut matrix = vec![vec![false; self.right]; self.left];
+        for (u, neighbours) in self.adjacency.iter().enumerate() {
+            for &v in neighbours {
+                matrix[u][v] = true;
+            }
+        }
+        matrix
+    }
+
+    /// Returns a matching that covers every left and every right vertex, if there is one.
+    pub fn perfect_matching(&self) -> Option<Matching> {
+        if self.left != self.right {
+            return None
+        }
+        let matching = self.maximum_matching();
+        if matching.size() == self.left {
+            Some(matching)
+        } else {
+            None
+        }
+    }
+}
+
+/// The largest number of vertices per side for which `random_perfect_matching` samples exactly.
+pub const EXACT_SAMPLING_LIMIT: usize = 16;
+
+/// The Jerrum Sinclair chain on the perfect and near perfect matchings of a graph with the same number of
+/// vertices on both sides. A near perfect matching leaves one left and one right vertex unmatched.
+///
+/// Each step picks an edge (u, v) uniformly, and with probability one half does nothing. Otherwise, from a
+/// perfect matching containing the edge, it removes it. From a near perfect matching, it adds the edge if it
+/// joins the two unmatched vertices, or if only one end is unmatched, swaps it in for the matched edge at
+/// the other end. The chain is symmetric, so it converges to the uniform distribution on its states, and
+/// conditioned on being perfect, to the uniform distribution on perfect matchings.
+///
+/// ```
+/// use combinatorial_patterns::matching::{BipartiteGraph, MatchingChain};
+///
+/// let mut graph = BipartiteGraph::new(4, 4);
+/// for u in 0..4 {
+///     graph.add_edge(u, u);
+///     graph.add_edge(u, (u + 1) % 4);
+/// }
+/// let mut chain = MatchingChain::new(&graph).unwrap();
+/// let matching = chain.sample(200, &mut rand::thread_rng());
+/// assert_eq!(matching.size(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MatchingChain {
+    edges: Vec<(usize, usize)>,
+    matching: Matching,
+    /// The unmatched left and right vertex of a near perfect matching.
+    hole: Option<(usize, usize)>
+}
+
+impl MatchingChain {
+    /// Starts the chain at a perfect matching of the graph, or returns None if it has none.
+    pub fn new(graph: &BipartiteGraph) -> Option<MatchingChain> {
+        let matching = graph.perfect_matching()?;
+        let edges = graph.adjacency.iter().enumerate()
+            .flat_map(|(u, neighbours)| neighbours.iter().map(move |&v| (u, v)))
+            .collect();
+        Some(MatchingChain { edges, matching, hole: None })
+    }
+
+    /// The current matching, which may be near perfect.
+    pub fn matching(&self) -> &Matching {
+        &self.matching
+    }
+
+    /// Returns true if the current matching is perfect.
+    pub fn is_perfect(&self) -> bool {
+        self.hole.is_none()
+    }
+
+    /// Takes one step of the chain, drawn from `rng`.
+    pub fn step(&mut self, rng: &mut impl Rng) {
+        if self.edges.is_empty() || rng.gen_bool(0.5) {
+            return
+        }
+        let (u, v) = self.edges[rng.gen_range(0..self.edges.len())];
+        match self.hole {
+            None => {
+                if self.matching.left_to_right[u] == Some(v) {
+                    self.matching.left_to_right[u] = None;
+                    self.matching.right_to_left[v] = None;
+                    self.hole = Some((u, v));
+                }
+            },
+            Some((free_left, free_right)) => {
+                if u == free_left && v == free_right {
+                    self.matching.left_to_right[u] = Some(v);
+                    self.matching.right_to_left[v] = Some(u);
+                    self.hole = None;
+                } else if u == free_left {
+                    let previous = self.matching.right_to_left[v].expect("ProgrammingError: only one right vertex is unmatched.");
+                    self.matching.left_to_right[previous] = None;
+                    self.matching.left_to_right[u] = Some(v);
+                    self.matching.right_to_left[v] = Some(u);
+                    self.hole = Some((previous, free_right));
+                } else if v == free_right {
+                    let previous = self.matching.left_to_right[u].expect("ProgrammingError: only one left vertex is unmatched.");
+                    self.matching.right_to_left[previous] = None;
+                    self.matching.left_to_right[u] = Some(v);
+                    self.matching.right_to_left[v] = Some(u);
+                    self.hole = Some((free_left, previous));
+                }
+            }
+        }
+    }
+
+    /// Takes the given number of steps, then keeps going until the matching is perfect, and returns it.
+    pub fn sample(&mut self, steps: usize, rng: &mut impl Rng) -> Matching {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+        while !self.is_perfect() {
+            self.step(rng);
+        }
+        self.matching.clone()
+    }
+}
+
+impl Matching {
+    /// Creates a matching with no edges for `graph`.
+    pub fn empty(graph: &BipartiteGraph) -> Matching {
+        Matching {
+            left_to_right: vec![None; graph.left],
+            right_to_left: vec![None; graph.right]
+        }
+    }
+
+    /// The number of matched edges.
+    pub fn size(&self) -> usize {
+        self.left_to_right.iter().filter(|v| v.is_some()).count()
+    }
+
+    /// The right vertex matched to left vertex `u`.
+    pub fn right_of(&self, u: usize) -> Option<usize> {
+        self.left_to_right[u]
+    }
+
+    /// The left vertex matched to right vertex `v`.
+    pub fn left_of(&self, v: usize) -> Option<usize> {
+        self.right_to_left[v]
+    }
+
+    /// Matched (left, right) pairs, in order of the left vertex.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        self.left_to_right.iter().enumerate()
+            .filter_map(|(u, v)| v.map(|v| (u, v)))
+            .collect()
+    }
+
+    /// Tries to match left vertex `u` along an augmenting path. Vertices that were already matched stay matched.
+    /// Returns true if `u` is matched afterwards.
+    pub fn augment_from_left(&mut self, graph: &BipartiteGraph, u: usize) -> bool {
+        if self.left_to_right[u].is_some() {
+            return true
+        }
+        let mut visited = vec![false; graph.right];
+        self.search_from_left(graph, u, &mut visited)
+    }
+
+    /// Tries to match right vertex `v` along an augmenting path. Vertices that were already matched stay matched.
+    /// Returns true if `v` is matched afterwards.
+    pub fn augment_from_right(&mut self, graph: &BipartiteGraph, v: usize) -> bool {
+        if self.right_to_left[v].is_some() {
+            return true
+        }
+        let mut visited = vec![false; graph.left];
+        self.search_from_right(graph, v, &mut visited)
+    }
+
+    fn search_from_left(&mut self, graph: &BipartiteGraph, u: usize, visited: &mut [bool]) -> bool {
+        for &v in &graph.adjacency[u] {
+            if visited[v] {
+                continue
+            }
+            visited[v] = true;
+            let free = match self.right_to_left[v] {
+                None => true,
+                Some(other) => self.search_from_left(graph, other, visited)
+            };
+            if free {
+                self.left_to_right[u] = Some(v);
+                self.right_to_left[v] = Some(u);
+                return true
+            }
+        }
+        false
+    }
+
+    fn search_from_right(&mut self, graph: &BipartiteGraph, v: usize, visited: &mut [bool]) -> bool {
+        for &u in &graph.reverse_adjacency[v] {
+            if visited[u] {
+                continue
+            }
+            visited[u] = true;
+            let free = match self.left_to_right[u] {
+                None => true,
+                Some(other) => self.search_from_right(graph, other, visited)
+            };
+            if free {
+                self.left_to_right[u] = Some(v);
+                self.right_to_left[v] = Some(u);
+                return true
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,194 @@
+//! Puzzles with as few clues as possible for a given solution.
+//!
+//! The clue sets of a solution that keep it unique are closed upwards: adding a clue to a puzzle with a
+//! unique solution keeps it unique. So every puzzle shrinks by removing clues while the solution stays unique
+//! to one where no clue can go, but such a minimal puzzle need not be the smallest. `minimal_puzzle` does
+//! two things to get closer:
+//!
+//! - Restarts: the removal runs several times in different random orders, keeping the puzzle with fewest clues.
+//! - Exact search: on the engine of `enumerate`, it removes clues from the full grid in row-major order,
+//!   pruning as soon as the solution stops being unique, for a puzzle with one clue fewer than the best,
+//!   and then fewer again. When a search runs out without finding one, the best puzzle is a smallest one.
+//!   This is exponential, so it stops after a node limit, which small grids like 4 x 4 Sudoku do not reach.
+//!
+//! The report gives the symmetries of the clue layout too, the cells that are given rather than their
+//! symbols, which setters like to be symmetric.
+//!
+//! Sources:
+//!
+//! - There is no 16-clue Sudoku: solving the Sudoku minimum number of clues problem via hitting set
+//!   enumeration, G. McGuire, B. Tugemann, G. Civario, Experimental Mathematics 23 (2014)
+//! - [Mathematics of Sudoku, Wikipedia](https://en.wikipedia.org/wiki/Mathematics_of_Sudoku)
+
+use rand::Rng;
+
+use crate::enumerate::{self, Control, Node, Search, Status};
+use crate::latin_square::LatinSquare;
+use crate::partial::PartialLatinSquare;
+use crate::sudoku::{self, Puzzle, Rules};
+
+/// A symmetry of a clue layout, which maps given cells to given cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// A half turn about the centre.
+    HalfTurn,
+    /// A quarter turn about the centre.
+    QuarterTurn,
+    /// The reflection swapping the top and bottom rows.
+    Horizontal,
+    /// The reflection swapping the left and right columns.
+    Vertical,
+    /// The reflection in the main diagonal.
+    Diagonal,
+    /// The reflection in the anti-diagonal.
+    AntiDiagonal
+}
+
+impl Symmetry {
+    /// Every symmetry, in the order they are reported.
+    pub const ALL: [Symmetry; 6] = [
+        Symmetry::HalfTurn, Symmetry::QuarterTurn, Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Diagonal, Symmetry::AntiDiagonal
+    ];
+
+    /// The image of a cell of a grid of the given order.
+    pub fn apply(&self, order: usize, (row, column): (usize, usize)) -> (usize, usize) {
+        let last = order.saturating_sub(1);
+        match self {
+            Symmetry::HalfTurn => (last - row, last - column),
+            Symmetry::QuarterTurn => (column, last - row),
+            Symmetry::Horizontal => (last - row, column),
+            Symmetry::Vertical => (row, last - column),
+            Symmetry::Diagonal => (column, row),
+            Symmetry::AntiDiagonal => (last - column, last - row)
+        }
+    }
+}
+
+/// The symmetries of the layout of the filled cells, in the order of `Symmetry::ALL`.
+///
+/// ```
+/// use combinatorial_patterns::minimal_clues::{self, Symmetry};
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+///
+/// let mut clues = PartialLatinSquare::new(4);
+/// clues.set(0, 1, Some(0));
+/// clues.set(3, 2, Some(1));
+/// assert_eq!(minimal_clues::layout_symmetries(&clues), vec![Symmetry::HalfTurn]);
+/// ```
+pub fn layout_symmetries(clues: &PartialLatinSquare) -> Vec<Symmetry> {
+    let order = clues.order();
+    let filled = clues.filled_cells();
+    Symmetry::ALL.iter().copied().filter(|symmetry| {
+        filled.iter().all(|&(row, column, _)| {
+            let (row, column) = symmetry.apply(order, (row, column));
+            clues.get(row, column).is_some()
+        })
+    }).collect()
+}
+
+/// The puzzle found by `minimal_puzzle`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimalPuzzle {
+    pub puzzle: Puzzle,
+    /// The symmetries of the clue layout.
+    pub symmetries: Vec<Symmetry>,
+    /// Whether the exact search showed that no puzzle for the solution has fewer clues.
+    pub proven: bool
+}
+
+impl MinimalPuzzle {
+    /// The number of clues.
+    pub fn clue_count(&self) -> usize {
+        self.puzzle.clues().filled_count()
+    }
+}
+
+/// Searches for a puzzle with as few clues as possible whose unique solution is `solution` under the
+/// rules: the best of `restarts` removals in random orders drawn from `rng`, improved by an exact search of
+/// at most `node_limit` nodes. Returns None if the solution does not meet the rules.
+///
+/// ```
+/// use combinatorial_patterns::minimal_clues;
+/// use combinatorial_patterns::sudoku::{self, Rules};
+///
+/// // Every 4 x 4 Sudoku needs 4 clues, and the exact search shows it.
+/// let rules = Rules::sudoku(2);
+/// let mut rng = rand::thread_rng();
+/// let solution = sudoku::generate(&rules, &mut rng).unwrap().solve().unwrap();
+/// let found = minimal_clues::minimal_puzzle(&rules, &solution, 5, 1_000_000, &mut rng).unwrap();
+/// assert_eq!(found.clue_count(), 4);
+/// assert!(found.proven);
+/// assert!(found.puzzle.has_unique_solution());
+/// assert_eq!(found.puzzle.solve().unwrap().square, solution.square);
+/// ```
+pub fn minimal_puzzle(rules: &Rules, solution: &LatinSquare, restarts: usize, node_limit: u64, rng: &mut impl Rng) -> Option<MinimalPuzzle> {
+    if !rules.is_solution(solution) {
+        return None
+    }
+    let mut best = (0..restarts.max(1)).map(|_| sudoku::minimize(rules.clone(), solution, rng))
+        .min_by_key(|puzzle| puzzle.clues().filled_count())
+        .expect("ProgrammingError: at least one removal.");
+    let full = Puzzle::new(rules.clone(), PartialLatinSquare::from_square(solution))
+        .expect("ProgrammingError: a solution is a valid puzzle.");
+    let mut nodes_left = node_limit;
+    let proven = loop {
+        let Some(target) = best.clues().filled_count().checked_sub(1) else {
+            break true
+        };
+        let mut search = Removal { puzzle: full.clone(), solution, next: 0, target };
+        let mut found = None;
+        let outcome = enumerate::run(&mut search, nodes_left, |search| {
+            found = Some(search.puzzle.clone());
+            Control::Stop
+        });
+        nodes_left -= outcome.nodes;
+        match (found, outcome.status) {
+            (Some(puzzle), _) => best = puzzle,
+            (None, Status::Exhausted) => break true,
+            (None, _) => break false
+        }
+    };
+    let symmetries = layout_symmetries(best.clues());
+    Some(MinimalPuzzle { puzzle: best, symmetries, proven })
+}
+
+/// Removes clues in row-major order, down to `target` clues, while the solution stays unique.
+struct Removal<'a> {
+    puzzle: Puzzle,
+    solution: &'a LatinSquare,
+    /// The first cell, as `row * order + column`, that may still be removed.
+    next: usize,
+    target: usize
+}
+
+impl Search for Removal<'_> {
+    /// The removed cell, and the `next` before removing it.
+    type Choice = (usize, usize);
+
+    fn expand(&mut self) -> Node<(usize, usize)> {
+        let order = self.puzzle.rules().order();
+        let count = self.puzzle.clues().filled_count();
+        if count == self.target {
+            return Node::Solution
+        }
+        // Enough cells must be left after the one removed to get down to the target.
+        let needed = count - self.target;
+        Node::Branch((self.next..(order * order + 1).saturating_sub(needed)).map(|cell| (cell, self.next)).collect())
+    }
+
+    fn apply(&mut self, &(cell, _): &(usize, usize)) {
+        let order = self.puzzle.rules().order();
+        self.puzzle.clues_mut().set(cell / order, cell % order, None);
+        self.next = cell + 1;
+    }
+
+    fn undo(&mut self, &(cell, next): &(usize, usize)) {
+        let order = self.puzzle.rules().order();
+        self.puzzle.clues_mut().set(cell / order, cell % order, Some(self.solution.square[cell / order][cell % order]));
+        self.next = next;
+    }
+
+    fn is_viable(&self) -> bool {
+        self.puzzle.has_unique_solution()
+    }
+}
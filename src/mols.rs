@@ -0,0 +1,152 @@
+//! Mutually orthogonal latin squares (MOLS).
+//!
+//! Two latin squares of order n are orthogonal if, when superimposed, every ordered pair of symbols
+//! appears exactly once. A set of squares is mutually orthogonal if every two of them are.
+//! At most n - 1 MOLS of order n exist, and that bound is reached for every prime power n:
+//! over GF(q), the squares `L_t(x, y) = t * x + y` for nonzero t form a complete set.
+//!
+//! Direct products of MOLS are MOLS again, which gives MacNeish's construction for any order:
+//! as many MOLS as the smallest prime power factor of the order, minus one.
+//!
+//! Sources:
+//!
+//! - [Mutually orthogonal latin squares, Wikipedia](https://en.wikipedia.org/wiki/Mutually_orthogonal_Latin_squares)
+//! - [Das Problem der 36 Offiziere, H. F. MacNeish](https://doi.org/10.2307/1967613)
+
+use crate::error::CombinatorialError;
+use crate::finite_field::FiniteField;
+use crate::latin_square::LatinSquare;
+
+/// Returns the complete set of `order - 1` MOLS built from GF(order), or None if the order is not a prime power.
+///
+/// Row x, column y of the t-th square (counting from 1) holds `t * x + y`, computed in the field.
+///
+/// ```
+/// use combinatorial_patterns::mols;
+///
+/// let squares = mols::new_prime_power(4).unwrap();
+/// assert_eq!(squares.len(), 3);
+/// assert!(mols::are_mutually_orthogonal(&squares));
+/// ```
+pub fn new_prime_power(order: usize) -> Option<Vec<LatinSquare>> {
+    let field = FiniteField::new(order)?;
+    Some((1..order).map(|t| field_square(&field, t)).collect())
+}
+
+/// Returns a set of MOLS of the given order via MacNeish's construction.
+///
+/// The order is split into prime powers, each contributing its complete set of MOLS,
+/// and the direct products of those sets are taken. The result has one less square than the smallest
+/// prime power factor, e.g. 2 squares for order 12 = 4 * 3, and a single square when the order is 2 mod 4.
+/// Order 1 returns its single square.
+///
+/// ```
+/// use combinatorial_patterns::mols;
+///
+/// let squares = mols::new(12).unwrap();
+/// assert_eq!(squares.len(), 2);
+/// assert!(mols::are_mutually_orthogonal(&squares));
+/// assert!(mols::new(0).is_err());
+/// ```
+///
+/// Fails for order 0.
+pub fn new(order: usize) -> Result<Vec<LatinSquare>, CombinatorialError> {
+    if order == 0 {
+        return Err(CombinatorialError::ZeroOrder)
+    }
+    if order == 1 {
+        return Ok(vec![LatinSquare::from_rows(vec![vec![0]])])
+    }
+    let mut remaining = order;
+    let mut factors = Vec::new();
+    let mut divisor = 2;
+    while remaining > 1 {
+        let mut factor = 1;
+        while remaining.is_multiple_of(divisor) {
+            remaining /= divisor;
+            factor *= divisor;
+        }
+        if factor > 1 {
+            factors.push(factor);
+        }
+        divisor += 1;
+    }
+
+    let count = factors.iter().min().map(|smallest| smallest - 1).unwrap_or(1);
+    Ok(factors.iter().map(|&factor| {
+        let mut squares = new_prime_power(factor).expect("ProgrammingError: factor is not a prime power.");
+        squares.truncate(count);
+        squares
+    }).reduce(|product, squares| direct_product_mols(&product, &squares))
+        .expect("ProgrammingError: order above one has no prime factors."))
+}
+
+/// The t-th square of the complete set over `field`.
+fn field_square(field: &FiniteField, t: usize) -> LatinSquare {
+    let order = field.order();
+    LatinSquare::from_rows((0..order).map(|x| {
+        (0..order).map(|y| field.add(field.mul(t, x), y)).collect()
+    }).collect())
+}
+
+/// Returns true if `a` and `b` are orthogonal latin squares of the same order with symbols 0..n.
+pub fn are_orthogonal(a: &LatinSquare, b: &LatinSquare) -> bool {
+    let order = a.size();
+    if b.size() != order {
+        return false
+    }
+    let mut seen = vec![vec![false; order]; order];
+    for (row_a, row_b) in a.square.iter().zip(b.square.iter()) {
+        for (&symbol_a, &symbol_b) in row_a.iter().zip(row_b.iter()) {
+            if symbol_a >= order || symbol_b >= order || seen[symbol_a][symbol_b] {
+                return false
+            }
+            seen[symbol_a][symbol_b] = true;
+        }
+    }
+    true
+}
+
+/// Returns true if every two of the given squares are orthogonal.
+pub fn are_mutually_orthogonal(squares: &[LatinSquare]) -> bool {
+    squares.iter().enumerate().all(|(i, a)| {
+        squares[(i + 1)..].iter().all(|b| are_orthogonal(a, b))
+    })
+}
+
+/// The direct product of two latin squares, of order `a.size() * b.size()`.
+///
+/// Cell ((i1, i2), (j1, j2)) holds the pair (a\[i1\]\[j1\], b\[i2\]\[j2\]), where a pair (s, t) and
+/// a row or column (i1, i2) are numbered `s * b.size() + t` and `i1 * b.size() + i2`.
+pub fn direct_product(a: &LatinSquare, b: &LatinSquare) -> LatinSquare {
+    let inner = b.size();
+    let order = a.size() * inner;
+    LatinSquare::from_rows((0..order).map(|row| {
+        (0..order).map(|column| {
+            a.square[row / inner][column / inner] * inner + b.square[row % inner][column % inner]
+        }).collect()
+    }).collect())
+}
+
+/// Pairs up two sets of MOLS by direct product, keeping as many squares as the smaller set.
+pub fn direct_product_mols(a: &[LatinSquare], b: &[LatinSquare]) -> Vec<LatinSquare> {
+    a.iter().zip(b.iter()).map(|(a, b)| direct_product(a, b)).collect()
+}
+
+/// The orthogonal array OA(k + 2, n) of k MOLS of order n: a row (x, y, L_1(x, y), .., L_k(x, y)) for every
+/// cell (x, y). Any two columns hold every ordered pair of symbols in exactly one row, and conversely any
+/// such array gives MOLS.
+///
+/// ```
+/// use combinatorial_patterns::mols;
+///
+/// let array = mols::orthogonal_array(&mols::new_prime_power(3).unwrap());
+/// assert_eq!(array.len(), 9);
+/// assert_eq!(array[5], vec![1, 2, 0, 1]);
+/// ```
+pub fn orthogonal_array(squares: &[LatinSquare]) -> Vec<Vec<usize>> {
+    let order = squares.first().map(|square| square.size()).unwrap_or(0);
+    (0..order).flat_map(|x| (0..order).map(move |y| {
+        [x, y].into_iter().chain(squares.iter().map(|square| square.square[x][y])).collect()
+    })).collect()
+}
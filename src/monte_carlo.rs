@@ -0,0 +1,189 @@
+//! Monte Carlo estimates of the sizes of combinatorial sets, with confidence intervals.
+//!
+//! Rejection sampling draws from a set of known size and counts how often a draw has a property, such as
+//! a random latin square having a transversal or no intercalate. The fraction of hits is a `Proportion`,
+//! and multiplying it by the size of the set estimates how many elements have the property. Its confidence
+//! interval is the Wilson score interval, which stays within 0..1 and behaves for fractions near 0 or 1,
+//! where the normal approximation does not.
+//!
+//! Importance sampling draws elements with some known probability and records the weight 1 / probability
+//! for hits and 0 for misses. The mean weight estimates the size directly, as in the `estimate` module,
+//! and its interval is the normal one from the standard error.
+//!
+//! Random latin squares come from the Jacobson-Matthews chain, which is close to uniform but not exactly,
+//! so proportions of random squares are slightly biased in principle.
+//!
+//! Sources:
+//!
+//! - [Binomial proportion confidence interval, Wikipedia](https://en.wikipedia.org/wiki/Binomial_proportion_confidence_interval)
+//! - [An algorithm for computing the inverse normal cumulative distribution function, Peter J. Acklam](https://web.archive.org/web/20151030215612/http://home.online.no/~pjacklam/notes/invnorm/)
+
+use crate::error::CombinatorialError;
+use crate::estimate::Estimate;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+use rand::Rng;
+
+/// A range that holds the estimated quantity with the given confidence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    pub lower: f64,
+    pub upper: f64,
+    /// The confidence level, e.g. 0.95.
+    pub confidence: f64
+}
+
+impl ConfidenceInterval {
+    /// Returns true if the value is within the interval, ends included.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+
+    /// The upper minus the lower end.
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+
+    /// The interval with both ends multiplied by a nonnegative factor, e.g. the size of the sampled set.
+    pub fn scaled(&self, factor: f64) -> ConfidenceInterval {
+        ConfidenceInterval { lower: self.lower * factor, upper: self.upper * factor, confidence: self.confidence }
+    }
+}
+
+/// The fraction of samples that have some property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proportion {
+    pub successes: usize,
+    pub samples: usize
+}
+
+impl Proportion {
+    /// The fraction of successes.
+    pub fn value(&self) -> f64 {
+        self.successes as f64 / self.samples as f64
+    }
+
+    /// The standard error of the fraction, by the normal approximation.
+    pub fn standard_error(&self) -> f64 {
+        let value = self.value();
+        (value * (1.0 - value) / self.samples as f64).sqrt()
+    }
+
+    /// The fraction as an estimate of the mean of 0/1 weights.
+    pub fn as_estimate(&self) -> Estimate {
+        Estimate { value: self.value(), standard_error: self.standard_error(), samples: self.samples }
+    }
+
+    /// The Wilson score interval of the fraction at the given confidence. Panics unless the confidence is
+    /// strictly between 0 and 1.
+    pub fn confidence_interval(&self, confidence: f64) -> ConfidenceInterval {
+        let z = z_score(confidence);
+        let n = self.samples as f64;
+        let value = self.value();
+        let denominator = 1.0 + z * z / n;
+        let center = (value + z * z / (2.0 * n)) / denominator;
+        let spread = z / denominator * (value * (1.0 - value) / n + z * z / (4.0 * n * n)).sqrt();
+        ConfidenceInterval { lower: (center - spread).max(0.0), upper: (center + spread).min(1.0), confidence }
+    }
+}
+
+/// Estimates the fraction of draws with a property by calling `draw` `samples` times, each returning
+/// whether a fresh random element has it. Panics if there are no samples.
+///
+/// ```
+/// use combinatorial_patterns::monte_carlo;
+/// use rand::{Rng, SeedableRng};
+///
+/// // Points in the unit square that fall in the quarter circle.
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+/// let proportion = monte_carlo::rejection(10000, || {
+///     let (x, y): (f64, f64) = (rng.gen(), rng.gen());
+///     x * x + y * y < 1.0
+/// });
+/// let interval = proportion.confidence_interval(0.999);
+/// assert!(interval.contains(std::f64::consts::PI / 4.0));
+/// ```
+pub fn rejection(samples: usize, mut draw: impl FnMut() -> bool) -> Proportion {
+    if samples == 0 {
+        panic!("An estimate needs at least one sample.");
+    }
+    let successes = (0..samples).filter(|_| draw()).count();
+    Proportion { successes, samples }
+}
+
+/// Estimates the mean of the weights returned by calling `draw` `samples` times. With weights 1 / probability
+/// for elements of a set and 0 otherwise, that is the size of the set. Panics if there are no samples.
+///
+/// ```
+/// use combinatorial_patterns::monte_carlo;
+/// use rand::{Rng, SeedableRng};
+///
+/// // Sizes of random subsets of 0..10 with three elements, each subset drawn with probability 1 / 1024.
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+/// let estimate = monte_carlo::importance(20000, || {
+///     let subset: u32 = rng.gen_range(0..1024);
+///     if subset.count_ones() == 3 { 1024.0 } else { 0.0 }
+/// });
+/// assert!(estimate.confidence_interval(0.999).contains(120.0));
+/// ```
+pub fn importance(samples: usize, mut draw: impl FnMut() -> f64) -> Estimate {
+    if samples == 0 {
+        panic!("An estimate needs at least one sample.");
+    }
+    let weights: Vec<f64> = (0..samples).map(|_| draw()).collect();
+    Estimate::from_weights(&weights)
+}
+
+/// Estimates the fraction of latin squares of the given order with a property, from `samples` squares
+/// generated by the Jacobson-Matthews chain with `rng`. Fails for order 0, and panics if there are no samples.
+///
+/// ```
+/// use combinatorial_patterns::{monte_carlo, switching};
+///
+/// // Every latin square of order 4 has an intercalate.
+/// let proportion = monte_carlo::random_squares(4, 50, &mut rand::thread_rng(), |square| switching::intercalates(square).is_empty()).unwrap();
+/// assert_eq!(proportion.successes, 0);
+/// assert!(proportion.confidence_interval(0.95).upper < 0.1);
+/// ```
+pub fn random_squares(order: usize, samples: usize, rng: &mut impl Rng, mut predicate: impl FnMut(&LatinSquare) -> bool) -> Result<Proportion, CombinatorialError> {
+    let cyclic = IncidenceCube::new_cyclic(order)?;
+    Ok(rejection(samples, || {
+        let mut cube = cyclic.clone();
+        cube.shuffle(rng);
+        predicate(&cube.as_latin_square())
+    }))
+}
+
+/// The z with a standard normal variable within -z..z with the given probability. Panics unless the
+/// confidence is strictly between 0 and 1.
+pub(crate) fn z_score(confidence: f64) -> f64 {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        panic!("A confidence level is strictly between 0 and 1, not {}", confidence);
+    }
+    normal_quantile((1.0 + confidence) / 2.0)
+}
+
+/// The inverse of the standard normal distribution function, by Acklam's rational approximation, with a
+/// relative error below 1.2e-9.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    const LOW: f64 = 0.02425;
+
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail(p)
+    } else if p > 1.0 - LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
@@ -0,0 +1,379 @@
+//! Classical combinatorial numbers, computed exactly.
+//!
+//! Every function returns a u128, or None once the value no longer fits. With the `bigint` feature, each
+//! also has a `_big` variant returning a `num_bigint::BigUint`, which never overflows. They are computed by
+//! their recurrences, so a call takes polynomial time in its arguments.
+//!
+//! - `factorial(n)` and `binomial(n, k)`.
+//! - `derangements(n)`: permutations of n elements without fixed points, 1, 0, 1, 2, 9, 44, ..
+//! - `menage(n)`: ways to seat n men at a round table between their fixed seated wives, none next to his own,
+//!   1, 0, 0, 1, 2, 13, 80, .. Some sources set the value for a single couple to -1 instead of 0.
+//! - `eulerian(n, k)`: permutations of n elements with k ascents.
+//! - `lah(n, k)`: ways to split n elements into k nonempty ordered lists.
+//! - `stirling_first(n, k)`: permutations of n elements with k cycles, the unsigned Stirling numbers of the first kind.
+//! - `stirling_second(n, k)`: ways to split n elements into k nonempty sets.
+//! - `harmonic(n)`: the harmonic number 1 + 1/2 + .. + 1/n, as a reduced fraction. Its numerator before
+//!   reducing, over n!, is the Stirling number of the first kind for n + 1 elements and 2 cycles.
+//!
+//! Sources:
+//!
+//! - [Rencontres numbers, Wikipedia](https://en.wikipedia.org/wiki/Rencontres_numbers)
+//! - [Ménage problem, Wikipedia](https://en.wikipedia.org/wiki/M%C3%A9nage_problem)
+//! - [Eulerian number, Wikipedia](https://en.wikipedia.org/wiki/Eulerian_number)
+//! - [Lah number, Wikipedia](https://en.wikipedia.org/wiki/Lah_number)
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+/// Natural numbers with arithmetic that reports overflow, shared by the exact counts of the crate.
+pub(crate) trait Natural: Clone + PartialEq {
+    fn from_u128(value: u128) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    /// None when the result would be negative, as well as on overflow.
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    /// Quotient and remainder. Panics when dividing by zero.
+    fn div_rem(&self, other: &Self) -> (Self, Self);
+
+    fn is_zero(&self) -> bool {
+        *self == Self::from_u128(0)
+    }
+}
+
+impl Natural for u128 {
+    fn from_u128(value: u128) -> Self {
+        value
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u128::checked_add(*self, *other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        u128::checked_sub(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u128::checked_mul(*self, *other)
+    }
+
+    fn div_rem(&self, other: &Self) -> (Self, Self) {
+        (self / other, self % other)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Natural for BigUint {
+    fn from_u128(value: u128) -> Self {
+        BigUint::from(value)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if other > self {
+            None
+        } else {
+            Some(self - other)
+        }
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn div_rem(&self, other: &Self) -> (Self, Self) {
+        (self / other, self % other)
+    }
+}
+
+fn small<T: Natural>(value: usize) -> T {
+    T::from_u128(value as u128)
+}
+
+fn factorial_in<T: Natural>(n: usize) -> Option<T> {
+    (1..=n).try_fold(small(1), |product: T, i| product.checked_mul(&small(i)))
+}
+
+fn binomial_in<T: Natural>(n: usize, k: usize) -> Option<T> {
+    if k > n {
+        return Some(small(0))
+    }
+    // Each prefix product is itself a binomial coefficient, so the divisions are exact. Dividing by their
+    // common factor first keeps the intermediate product no larger than the result.
+    let k = k.min(n - k);
+    (0..k).try_fold(small(1), |product: T, i| {
+        let divisor = small::<T>(i + 1);
+        let common = gcd(&product, &divisor);
+        let rest = divisor.div_rem(&common).0;
+        product.div_rem(&common).0.checked_mul(&small::<T>(n - i).div_rem(&rest).0)
+    })
+}
+
+fn derangements_in<T: Natural>(n: usize) -> Option<T> {
+    let (mut previous, mut current): (T, T) = (small(1), small(0));
+    if n == 0 {
+        return Some(previous)
+    }
+    for i in 2..=n {
+        let next = small::<T>(i - 1).checked_mul(&previous.checked_add(&current)?)?;
+        previous = current;
+        current = next;
+    }
+    Some(current)
+}
+
+fn menage_in<T: Natural>(n: usize) -> Option<T> {
+    if n == 1 {
+        return Some(small(0))
+    }
+    // Touchard: sum over k of (-1)^k 2n / (2n - k) * C(2n - k, k) * (n - k)!, where the fraction times the
+    // binomial is C(2n - k, k) + C(2n - k - 1, k - 1). The signs are summed apart to stay nonnegative.
+    let mut positive: T = small(0);
+    let mut negative: T = small(0);
+    for k in 0..=n {
+        let ways = if k == 0 {
+            small(1)
+        } else {
+            binomial_in::<T>(2 * n - k, k)?.checked_add(&binomial_in(2 * n - k - 1, k - 1)?)?
+        };
+        let term = ways.checked_mul(&factorial_in(n - k)?)?;
+        if k.is_multiple_of(2) {
+            positive = positive.checked_add(&term)?;
+        } else {
+            negative = negative.checked_add(&term)?;
+        }
+    }
+    positive.checked_sub(&negative)
+}
+
+/// Fills a triangle row by row with `next(n, k, above_left, above)` giving entry (n + 1, k) from
+/// entries (n, k - 1) and (n, k), starting from entry (0, 0) = 1, and returns entry (n, k).
+fn triangle_in<T: Natural>(n: usize, k: usize, next: impl Fn(usize, usize, &T, &T) -> Option<T>) -> Option<T> {
+    if k > n {
+        return Some(small(0))
+    }
+    let mut row: Vec<T> = vec![small(1)];
+    for i in 0..n {
+        let mut following = Vec::with_capacity(i + 2);
+        for j in 0..=(i + 1) {
+            let zero = small(0);
+            let above_left = if j == 0 { &zero } else { &row[j - 1] };
+            let above = row.get(j).unwrap_or(&zero);
+            following.push(next(i, j, above_left, above)?);
+        }
+        row = following;
+    }
+    Some(row[k].clone())
+}
+
+fn eulerian_in<T: Natural>(n: usize, k: usize) -> Option<T> {
+    if n == 0 {
+        return Some(small(if k == 0 { 1 } else { 0 }))
+    }
+    // A(n + 1, k) = (k + 1) A(n, k) + (n + 1 - k) A(n, k - 1).
+    triangle_in(n, k, |n, k, above_left, above| {
+        small::<T>(k + 1).checked_mul(above)?.checked_add(&small::<T>(n + 1 - k).checked_mul(above_left)?)
+    })
+}
+
+fn lah_in<T: Natural>(n: usize, k: usize) -> Option<T> {
+    // L(n + 1, k) = (n + k) L(n, k) + L(n, k - 1).
+    triangle_in(n, k, |n, k, above_left, above| small::<T>(n + k).checked_mul(above)?.checked_add(above_left))
+}
+
+fn stirling_first_in<T: Natural>(n: usize, k: usize) -> Option<T> {
+    // c(n + 1, k) = n c(n, k) + c(n, k - 1).
+    triangle_in(n, k, |n, _, above_left, above| small::<T>(n).checked_mul(above)?.checked_add(above_left))
+}
+
+fn stirling_second_in<T: Natural>(n: usize, k: usize) -> Option<T> {
+    // S(n + 1, k) = k S(n, k) + S(n, k - 1).
+    triangle_in(n, k, |_, k, above_left, above| small::<T>(k).checked_mul(above)?.checked_add(above_left))
+}
+
+fn gcd<T: Natural>(a: &T, b: &T) -> T {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while !b.is_zero() {
+        let remainder = a.div_rem(&b).1;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+fn harmonic_in<T: Natural>(n: usize) -> Option<(T, T)> {
+    let mut numerator: T = small(0);
+    let mut denominator: T = small(1);
+    for i in 1..=n {
+        // numerator / denominator + 1 / i
+        let i = small::<T>(i);
+        numerator = numerator.checked_mul(&i)?.checked_add(&denominator)?;
+        denominator = denominator.checked_mul(&i)?;
+        let divisor = gcd(&numerator, &denominator);
+        numerator = numerator.div_rem(&divisor).0;
+        denominator = denominator.div_rem(&divisor).0;
+    }
+    Some((numerator, denominator))
+}
+
+/// n!, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!(numbers::factorial(5), Some(120));
+/// assert_eq!(numbers::factorial(34).is_some(), true);
+/// assert_eq!(numbers::factorial(35), None);
+/// ```
+pub fn factorial(n: usize) -> Option<u128> {
+    factorial_in(n)
+}
+
+/// The number of k element subsets of an n element set, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!(numbers::binomial(6, 2), Some(15));
+/// assert_eq!(numbers::binomial(2, 6), Some(0));
+/// assert!(numbers::binomial(130, 65).is_some());
+/// ```
+pub fn binomial(n: usize, k: usize) -> Option<u128> {
+    binomial_in(n, k)
+}
+
+/// The number of permutations of n elements without fixed points, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((0..7).map(|n| numbers::derangements(n).unwrap()).collect::<Vec<u128>>(), vec![1, 0, 1, 2, 9, 44, 265]);
+/// ```
+pub fn derangements(n: usize) -> Option<u128> {
+    derangements_in(n)
+}
+
+/// The ménage number for n couples, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((0..8).map(|n| numbers::menage(n).unwrap()).collect::<Vec<u128>>(), vec![1, 0, 0, 1, 2, 13, 80, 579]);
+/// ```
+pub fn menage(n: usize) -> Option<u128> {
+    menage_in(n)
+}
+
+/// The number of permutations of n elements with exactly k ascents, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((0..4).map(|k| numbers::eulerian(4, k).unwrap()).collect::<Vec<u128>>(), vec![1, 11, 11, 1]);
+/// ```
+pub fn eulerian(n: usize, k: usize) -> Option<u128> {
+    eulerian_in(n, k)
+}
+
+/// The unsigned Lah number: ways to split n elements into k nonempty ordered lists, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((1..5).map(|k| numbers::lah(4, k).unwrap()).collect::<Vec<u128>>(), vec![24, 36, 12, 1]);
+/// ```
+pub fn lah(n: usize, k: usize) -> Option<u128> {
+    lah_in(n, k)
+}
+
+/// The number of permutations of n elements with exactly k cycles, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((1..5).map(|k| numbers::stirling_first(4, k).unwrap()).collect::<Vec<u128>>(), vec![6, 11, 6, 1]);
+/// ```
+pub fn stirling_first(n: usize, k: usize) -> Option<u128> {
+    stirling_first_in(n, k)
+}
+
+/// The number of ways to split n elements into k nonempty sets, or None if it doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!((1..5).map(|k| numbers::stirling_second(4, k).unwrap()).collect::<Vec<u128>>(), vec![1, 7, 6, 1]);
+/// ```
+pub fn stirling_second(n: usize, k: usize) -> Option<u128> {
+    stirling_second_in(n, k)
+}
+
+/// The harmonic number 1 + 1/2 + .. + 1/n as a reduced (numerator, denominator), or None if an intermediate
+/// value doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::numbers;
+///
+/// assert_eq!(numbers::harmonic(4), Some((25, 12)));
+/// assert_eq!(numbers::harmonic(0), Some((0, 1)));
+/// ```
+pub fn harmonic(n: usize) -> Option<(u128, u128)> {
+    harmonic_in(n)
+}
+
+/// n!
+#[cfg(feature = "bigint")]
+pub fn factorial_big(n: usize) -> BigUint {
+    factorial_in(n).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The number of k element subsets of an n element set.
+#[cfg(feature = "bigint")]
+pub fn binomial_big(n: usize, k: usize) -> BigUint {
+    binomial_in(n, k).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The number of permutations of n elements without fixed points.
+#[cfg(feature = "bigint")]
+pub fn derangements_big(n: usize) -> BigUint {
+    derangements_in(n).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The ménage number for n couples.
+#[cfg(feature = "bigint")]
+pub fn menage_big(n: usize) -> BigUint {
+    menage_in(n).expect("ProgrammingError: ménage numbers are nonnegative.")
+}
+
+/// The number of permutations of n elements with exactly k ascents.
+#[cfg(feature = "bigint")]
+pub fn eulerian_big(n: usize, k: usize) -> BigUint {
+    eulerian_in(n, k).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The unsigned Lah number: ways to split n elements into k nonempty ordered lists.
+#[cfg(feature = "bigint")]
+pub fn lah_big(n: usize, k: usize) -> BigUint {
+    lah_in(n, k).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The number of permutations of n elements with exactly k cycles.
+#[cfg(feature = "bigint")]
+pub fn stirling_first_big(n: usize, k: usize) -> BigUint {
+    stirling_first_in(n, k).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The number of ways to split n elements into k nonempty sets.
+#[cfg(feature = "bigint")]
+pub fn stirling_second_big(n: usize, k: usize) -> BigUint {
+    stirling_second_in(n, k).expect("ProgrammingError: big integers don't overflow.")
+}
+
+/// The harmonic number 1 + 1/2 + .. + 1/n as a reduced (numerator, denominator).
+#[cfg(feature = "bigint")]
+pub fn harmonic_big(n: usize) -> (BigUint, BigUint) {
+    harmonic_in(n).expect("ProgrammingError: big integers don't overflow.")
+}
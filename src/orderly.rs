@@ -0,0 +1,335 @@
+//! Isomorph-free exhaustive enumeration by orderly generation.
+//!
+//! Plain backtracking finds every labelled object, n! (n - 1)! times as many latin squares as there are
+//! reduced ones, and far more than there are classes. Orderly generation runs the search of `enumerate` so
+//! that only the canonical object of each class is built: every partial object must be canonical, the
+//! smallest of its class in a fixed order, or the `is_canonical` hook prunes it with everything below it.
+//! This finds each class exactly once, without comparing objects with each other, as long as the parts
+//! are added in order, so that the part removed last from a canonical object leaves a canonical one.
+//!
+//! - Latin squares up to isotopy are built row by row, each row after the ones before it, and a latin
+//!   rectangle is canonical when its rows, read one after another, are the smallest of any rectangle
+//!   isotopic to it. Setting a row r to 0..n-1 by renaming the symbols turns another row s into a
+//!   permutation `π` of the columns, and reordering the columns by `σ` conjugates it to `σ^-1 π σ`. The
+//!   smallest conjugates of `π` depend only on its cycle type, so only the few `σ` that give the second row
+//!   of the rectangle are tried, rather than all n!, which makes order 7 a matter of seconds.
+//! - Steiner triple systems up to a group of permutations of the points, as a `PermutationGroup`, are built
+//!   block by block, each block covering the smallest pair not yet covered. The system is canonical when
+//!   no element of the group maps its sorted blocks to a smaller list. The group lists its elements, so
+//!   the full symmetric group is practical up to `MAX_POINTS` points.
+//!
+//! Every object returned is the canonical one of its class. For latin squares those are the reduced squares
+//! that `species` takes as canonical, in the same order.
+//!
+//! Sources:
+//!
+//! - Every one a winner, or how to avoid isomorphism search when cataloguing combinatorial configurations,
+//!   R. C. Read, Annals of Discrete Mathematics 2 (1978)
+//! - Isomorph-free exhaustive generation, B. D. McKay, Journal of Algorithms 26 (1998)
+//! - Classification algorithms for codes and designs, P. Kaski, P. R. J. Östergård (2006)
+
+use std::cmp::Ordering;
+
+use crate::enumerate::{self, Control, Node, Search};
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::polya::{Permutation, PermutationGroup};
+use crate::triple_system::{SteinerTripleSystem, Triple};
+
+/// The largest order whose latin squares can be enumerated up to isotopy.
+pub const MAX_ORDER: usize = 7;
+
+/// The largest number of points whose Steiner triple systems can be enumerated up to isomorphism, listing
+/// the symmetric group.
+pub const MAX_POINTS: usize = 9;
+
+/// The canonical squares of the isotopy classes of the given order, in increasing order, or None if the
+/// order is above `MAX_ORDER`.
+///
+/// ```
+/// use combinatorial_patterns::{orderly, species};
+///
+/// for order in 1..=5 {
+///     let classes: Vec<_> = orderly::isotopy_classes(order).unwrap().into_iter().map(|square| square.square).collect();
+///     let known: Vec<_> = species::isotopy_classes(order).unwrap().into_iter().map(|square| square.square).collect();
+///     assert_eq!(classes, known);
+/// }
+/// assert_eq!(orderly::isotopy_classes(6).unwrap().len(), 22);
+/// assert!(orderly::isotopy_classes(8).is_none());
+/// ```
+pub fn isotopy_classes(order: usize) -> Option<Vec<LatinSquare>> {
+    if order > MAX_ORDER {
+        return None
+    }
+    let mut search = Rectangles { order, rows: Vec::new() };
+    let mut classes = Vec::new();
+    enumerate::run(&mut search, u64::MAX, |search| {
+        classes.push(LatinSquare::from_rows(search.rows.clone()));
+        Control::Continue
+    });
+    Some(classes)
+}
+
+/// The Steiner triple systems on the given number of points up to isomorphism, each as the canonical system
+/// of its class, or None if there are more than `MAX_POINTS` points.
+///
+/// ```
+/// use combinatorial_patterns::orderly;
+///
+/// // The Fano plane is the only system on 7 points.
+/// assert_eq!(orderly::steiner_triple_systems(7).unwrap().len(), 1);
+/// assert!(orderly::steiner_triple_systems(8).unwrap().is_empty());
+/// ```
+pub fn steiner_triple_systems(points: usize) -> Option<Vec<SteinerTripleSystem>> {
+    if points > MAX_POINTS {
+        return None
+    }
+    Some(triple_systems_up_to(&PermutationGroup::symmetric(points)))
+}
+
+/// The Steiner triple systems on the points of the group, one from each orbit of the group, each the
+/// smallest of its orbit.
+///
+/// ```
+/// use combinatorial_patterns::orderly;
+/// use combinatorial_patterns::polya::PermutationGroup;
+///
+/// // 30 labelled Fano planes, which fall into 6 orbits under rotation of the points.
+/// let trivial = PermutationGroup::generated(7, &[]).unwrap();
+/// assert_eq!(orderly::triple_systems_up_to(&trivial).len(), 30);
+/// assert_eq!(orderly::triple_systems_up_to(&PermutationGroup::cyclic(7)).len(), 6);
+/// ```
+pub fn triple_systems_up_to(group: &PermutationGroup) -> Vec<SteinerTripleSystem> {
+    let points = group.degree();
+    if !matches!(points % 6, 1 | 3) {
+        return Vec::new()
+    }
+    let mut search = Triples { points, blocks: Vec::new(), covered: vec![vec![false; points]; points], group };
+    let mut systems = Vec::new();
+    enumerate::run(&mut search, u64::MAX, |search| {
+        systems.push(SteinerTripleSystem::from_blocks(points, search.blocks.clone()).expect("ProgrammingError: every pair is covered once."));
+        Control::Continue
+    });
+    systems
+}
+
+/// Latin rectangles built row by row, each row greater than the one before.
+struct Rectangles {
+    order: usize,
+    rows: Vec<Vec<Symbol>>
+}
+
+impl Search for Rectangles {
+    type Choice = Vec<Symbol>;
+
+    fn expand(&mut self) -> Node<Vec<Symbol>> {
+        if self.rows.len() == self.order {
+            return Node::Solution
+        }
+        let Some(last) = self.rows.last() else {
+            return Node::Branch(vec![(0..self.order).collect()])
+        };
+        let mut rows = Vec::new();
+        let mut row = Vec::with_capacity(self.order);
+        let mut used = vec![false; self.order];
+        extensions(&self.rows, &mut row, &mut used, &mut rows);
+        rows.retain(|row| row > last);
+        Node::Branch(rows)
+    }
+
+    fn apply(&mut self, row: &Vec<Symbol>) {
+        self.rows.push(row.clone());
+    }
+
+    fn undo(&mut self, _: &Vec<Symbol>) {
+        self.rows.pop();
+    }
+
+    fn is_canonical(&self) -> bool {
+        is_canonical_rectangle(&self.rows)
+    }
+}
+
+/// Collects every row that extends the rectangle, in increasing order.
+fn extensions(rows: &[Vec<Symbol>], row: &mut Vec<Symbol>, used: &mut [bool], found: &mut Vec<Vec<Symbol>>) {
+    let column = row.len();
+    if column == used.len() {
+        found.push(row.clone());
+        return
+    }
+    for symbol in 0..used.len() {
+        if used[symbol] || rows.iter().any(|other| other[column] == symbol) {
+            continue
+        }
+        used[symbol] = true;
+        row.push(symbol);
+        extensions(rows, row, used, found);
+        row.pop();
+        used[symbol] = false;
+    }
+}
+
+/// Returns true if no rectangle isotopic to the rows, which are in increasing order with the first one
+/// 0..n-1, is smaller.
+fn is_canonical_rectangle(rows: &[Vec<Symbol>]) -> bool {
+    if rows.len() < 2 {
+        return true
+    }
+    let order = rows[0].len();
+    let columns_of: Vec<Vec<usize>> = rows.iter().map(|row| inverse(row)).collect();
+    let second = &rows[1];
+    for (first, columns) in columns_of.iter().enumerate() {
+        // Row `first` set to 0..n-1 makes every row a permutation of the columns.
+        let permutations: Vec<Permutation> = rows.iter().map(|row| row.iter().map(|&symbol| columns[symbol]).collect()).collect();
+        for (_, permutation) in permutations.iter().enumerate().filter(|&(other, _)| other != first) {
+            match smallest_conjugate(permutation).cmp(second) {
+                Ordering::Less => return false,
+                Ordering::Greater => continue,
+                Ordering::Equal => {}
+            }
+            for order_of_columns in conjugators(permutation, second) {
+                let mut position = vec![0; order];
+                for (new, &old) in order_of_columns.iter().enumerate() {
+                    position[old] = new;
+                }
+                let mut image: Vec<Vec<Symbol>> = permutations.iter()
+                    .map(|permutation| order_of_columns.iter().map(|&old| position[permutation[old]]).collect())
+                    .collect();
+                image.sort_unstable();
+                if image.as_slice() < rows {
+                    return false
+                }
+            }
+        }
+    }
+    true
+}
+
+pub(crate) fn inverse(permutation: &[usize]) -> Permutation {
+    let mut inverse = vec![0; permutation.len()];
+    for (point, &image) in permutation.iter().enumerate() {
+        inverse[image] = point;
+    }
+    inverse
+}
+
+/// The smallest permutation conjugate to the given one: its cycles, shortest first, on consecutive points.
+pub(crate) fn smallest_conjugate(permutation: &[usize]) -> Permutation {
+    let mut lengths = Vec::new();
+    let mut seen = vec![false; permutation.len()];
+    for start in 0..permutation.len() {
+        let mut length = 0;
+        let mut point = start;
+        while !seen[point] {
+            seen[point] = true;
+            point = permutation[point];
+            length += 1;
+        }
+        if length > 0 {
+            lengths.push(length);
+        }
+    }
+    lengths.sort_unstable();
+    let mut smallest = Vec::with_capacity(permutation.len());
+    for length in lengths {
+        let start = smallest.len();
+        smallest.extend((start + 1..start + length).chain([start]));
+    }
+    smallest
+}
+
+/// Every `σ` with `σ^-1 π σ = target`, for a `target` conjugate to `π`.
+pub(crate) fn conjugators(permutation: &[usize], target: &[usize]) -> Vec<Permutation> {
+    let mut found = Vec::new();
+    let mut sigma = vec![None; permutation.len()];
+    let mut used = vec![false; permutation.len()];
+    extend_conjugator(permutation, target, &mut sigma, &mut used, &mut found);
+    found
+}
+
+/// Maps the cycle of the first unmapped point of `target` onto a cycle of `π`, in every way that fits.
+fn extend_conjugator(permutation: &[usize], target: &[usize], sigma: &mut [Option<usize>], used: &mut [bool], found: &mut Vec<Permutation>) {
+    let Some(start) = sigma.iter().position(Option::is_none) else {
+        found.push(sigma.iter().map(|point| point.expect("ProgrammingError: every point is mapped.")).collect());
+        return
+    };
+    for image in 0..permutation.len() {
+        if used[image] {
+            continue
+        }
+        // σ(target(j)) = π(σ(j)) along the cycle of `start`, which must close when the cycle of `image` does.
+        let mut mapped = Vec::new();
+        let (mut point, mut value) = (start, image);
+        let fits = loop {
+            if used[value] {
+                break false
+            }
+            sigma[point] = Some(value);
+            used[value] = true;
+            mapped.push(point);
+            point = target[point];
+            value = permutation[value];
+            if point == start || value == image {
+                break point == start && value == image
+            }
+        };
+        if fits {
+            extend_conjugator(permutation, target, sigma, used, found);
+        }
+        for &point in &mapped {
+            used[sigma[point].expect("ProgrammingError: the point was just mapped.")] = false;
+            sigma[point] = None;
+        }
+    }
+}
+
+/// Triple systems built block by block, each block covering the smallest uncovered pair, so the blocks
+/// come in increasing order.
+struct Triples<'a> {
+    points: usize,
+    blocks: Vec<Triple>,
+    covered: Vec<Vec<bool>>,
+    group: &'a PermutationGroup
+}
+
+impl Triples<'_> {
+    fn cover(&mut self, &[a, b, c]: &Triple, covered: bool) {
+        for (x, y) in [(a, b), (a, c), (b, c)] {
+            self.covered[x][y] = covered;
+            self.covered[y][x] = covered;
+        }
+    }
+}
+
+impl Search for Triples<'_> {
+    type Choice = Triple;
+
+    fn expand(&mut self) -> Node<Triple> {
+        let uncovered = (0..self.points).flat_map(|a| (a + 1..self.points).map(move |b| (a, b))).find(|&(a, b)| !self.covered[a][b]);
+        match uncovered {
+            None => Node::Solution,
+            Some((a, b)) => Node::Branch((b + 1..self.points).filter(|&c| !self.covered[a][c] && !self.covered[b][c]).map(|c| [a, b, c]).collect())
+        }
+    }
+
+    fn apply(&mut self, block: &Triple) {
+        self.cover(block, true);
+        self.blocks.push(*block);
+    }
+
+    fn undo(&mut self, block: &Triple) {
+        self.cover(block, false);
+        self.blocks.pop();
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.group.elements().iter().all(|element| {
+            let mut image: Vec<Triple> = self.blocks.iter().map(|block| {
+                let mut image = block.map(|point| element[point]);
+                image.sort_unstable();
+                image
+            }).collect();
+            image.sort_unstable();
+            image >= self.blocks
+        })
+    }
+}
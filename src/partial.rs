@@ -0,0 +1,284 @@
+//! Partial latin squares: n x n arrays where some cells are empty, and no symbol repeats in a row or column.
+//!
+//! Besides describing puzzles and patterns, a partial latin square is the natural input for completion:
+//! `complete`, `completions`, and `count_completions` run a backtracking search that always fills the
+//! empty cell with the fewest remaining candidates first. `count_completions_from` counts them in
+//! pieces, from one checkpoint to the next, for counts that take longer than one run.
+
+use std::fmt;
+
+use crate::bitset::BitSet;
+use crate::enumerate::{self, Checkpoint, Control, Node, Outcome, Search, Status};
+use crate::latin_square::{LatinSquare, Symbol};
+
+/// An n x n array of optional symbols 0..n.
+///
+/// ```
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+///
+/// let mut partial = PartialLatinSquare::new(3);
+/// partial.set(0, 0, Some(0));
+/// partial.set(1, 1, Some(0));
+/// assert_eq!(partial.count_completions(10), 2);
+///
+/// partial.set(0, 1, Some(1));
+/// assert_eq!(partial.count_completions(10), 1);
+/// assert_eq!(partial.complete().unwrap().square[2], vec![1, 2, 0]);
+///
+/// partial.set(2, 0, Some(0));
+/// assert!(!partial.is_consistent());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialLatinSquare {
+    order: usize,
+    cells: Vec<Vec<Option<Symbol>>>
+}
+
+impl PartialLatinSquare {
+    /// Creates a partial latin square of the given order with every cell empty.
+    pub fn new(order: usize) -> PartialLatinSquare {
+        PartialLatinSquare {
+            order,
+            cells: vec![vec![None; order]; order]
+        }
+    }
+
+    /// Creates a partial latin square with every cell of `square` filled in.
+    pub fn from_square(square: &LatinSquare) -> PartialLatinSquare {
+        PartialLatinSquare {
+            order: square.size(),
+            cells: square.square.iter().map(|row| row.iter().map(|&symbol| Some(symbol)).collect()).collect()
+        }
+    }
+
+    /// Creates a partial latin square keeping only the given (row, column) cells of `square`.
+    pub fn from_cells(square: &LatinSquare, cells: &[(usize, usize)]) -> PartialLatinSquare {
+        let mut partial = PartialLatinSquare::new(square.size());
+        for &(row, column) in cells {
+            partial.cells[row][column] = Some(square.square[row][column]);
+        }
+        partial
+    }
+
+    /// Creates a partial latin square from rows of optional symbols.
+    ///
+    /// Returns None if the rows don't form a square, or if the entries are not consistent.
+    pub fn from_rows(rows: Vec<Vec<Option<Symbol>>>) -> Option<PartialLatinSquare> {
+        let order = rows.len();
+        if rows.iter().any(|row| row.len() != order) {
+            return None
+        }
+        let partial = PartialLatinSquare { order, cells: rows };
+        if partial.is_consistent() {
+            Some(partial)
+        } else {
+            None
+        }
+    }
+
+    /// The order of the square.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The symbol at (row, column), if the cell is filled.
+    pub fn get(&self, row: usize, column: usize) -> Option<Symbol> {
+        self.cells[row][column]
+    }
+
+    /// Fills or empties the cell at (row, column). Does not check consistency, see `is_consistent`.
+    pub fn set(&mut self, row: usize, column: usize, symbol: Option<Symbol>) {
+        self.cells[row][column] = symbol;
+    }
+
+    /// All cells, row by row.
+    pub fn cells(&self) -> &[Vec<Option<Symbol>>] {
+        &self.cells
+    }
+
+    /// Every filled cell as (row, column, symbol), in row-major order.
+    pub fn filled_cells(&self) -> Vec<(usize, usize, Symbol)> {
+        self.cells.iter().enumerate().flat_map(|(row, cells)| {
+            cells.iter().enumerate().filter_map(move |(column, cell)| cell.map(|symbol| (row, column, symbol)))
+        }).collect()
+    }
+
+    /// The number of filled cells.
+    pub fn filled_count(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| cell.is_some()).count()
+    }
+
+    /// Returns true if every filled symbol is in 0..n and no symbol repeats in a row or column.
+    pub fn is_consistent(&self) -> bool {
+        let order = self.order;
+        let mut in_row = vec![vec![false; order]; order];
+        let mut in_column = vec![vec![false; order]; order];
+        for (row, column, symbol) in self.filled_cells() {
+            if symbol >= order || in_row[row][symbol] || in_column[column][symbol] {
+                return false
+            }
+            in_row[row][symbol] = true;
+            in_column[column][symbol] = true;
+        }
+        true
+    }
+
+    /// Returns some completion to a latin square, if there is one.
+    pub fn complete(&self) -> Option<LatinSquare> {
+        self.completions(1).pop()
+    }
+
+    /// Returns up to `limit` distinct completions to a latin square.
+    pub fn completions(&self, limit: usize) -> Vec<LatinSquare> {
+        let mut found = Vec::new();
+        if limit > 0 {
+            self.search(&mut |cells: &[Symbol]| {
+                let order = self.order;
+                found.push(LatinSquare::from_rows(cells.chunks(order.max(1)).take(order).map(|row| row.to_vec()).collect()));
+                found.len() < limit
+            });
+        }
+        found
+    }
+
+    /// Counts the completions to a latin square, stopping once `cap` have been found.
+    ///
+    /// A cap of 2 is enough to tell whether the completion is unique.
+    pub fn count_completions(&self, cap: usize) -> usize {
+        let mut count = 0;
+        if cap > 0 {
+            self.search(&mut |_: &[Symbol]| {
+                count += 1;
+                count < cap
+            });
+        }
+        count
+    }
+
+    /// Runs the completion search, handing every completion (as row-major cells) to `on_completion`
+    /// until it returns false.
+    fn search(&self, on_completion: &mut dyn FnMut(&[Symbol]) -> bool) {
+        let Some(mut state) = self.completion_state() else {
+            return
+        };
+        enumerate::run(&mut state, u64::MAX, |state| {
+            let filled: Vec<Symbol> = state.cells.iter().map(|cell| cell.unwrap()).collect();
+            if on_completion(&filled) { Control::Continue } else { Control::Stop }
+        });
+    }
+
+    /// Counts completions from a checkpoint for at most `node_limit` nodes, returning the checkpoint to go
+    /// on from unless the count is finished. The count in the outcome includes the checkpoint's.
+    ///
+    /// ```
+    /// use combinatorial_patterns::enumerate::Checkpoint;
+    /// use combinatorial_patterns::partial::PartialLatinSquare;
+    ///
+    /// // The 576 latin squares of order 4, counted 50 nodes at a time.
+    /// let empty = PartialLatinSquare::new(4);
+    /// let mut checkpoint = Checkpoint::start();
+    /// let total = loop {
+    ///     match empty.count_completions_from(&checkpoint, 50) {
+    ///         (_, Some(next)) => checkpoint = next,
+    ///         (outcome, None) => break outcome.solutions
+    ///     }
+    /// };
+    /// assert_eq!(total, 576);
+    /// ```
+    pub fn count_completions_from(&self, checkpoint: &Checkpoint, node_limit: u64) -> (Outcome, Option<Checkpoint>) {
+        match self.completion_state() {
+            Some(mut state) => enumerate::resume(&mut state, checkpoint, node_limit, u64::MAX, |_| Control::Continue, |_, _| {}),
+            None => (Outcome { status: Status::Exhausted, solutions: checkpoint.solutions, nodes: checkpoint.nodes }, None)
+        }
+    }
+
+    /// The state of a completion search with the filled cells placed, if they are consistent.
+    fn completion_state(&self) -> Option<Completion> {
+        if !self.is_consistent() {
+            return None
+        }
+        let order = self.order;
+        let mut state = Completion {
+            order,
+            cells: vec![None; order * order],
+            row_free: vec![BitSet::full(order); order],
+            column_free: vec![BitSet::full(order); order]
+        };
+        for (row, column, symbol) in self.filled_cells() {
+            state.place(row * order + column, symbol);
+        }
+        Some(state)
+    }
+}
+
+/// Backtracking state of a completion search.
+struct Completion {
+    order: usize,
+    cells: Vec<Option<Symbol>>,
+    row_free: Vec<BitSet>,
+    column_free: Vec<BitSet>
+}
+
+impl Completion {
+    fn place(&mut self, cell: usize, symbol: Symbol) {
+        self.cells[cell] = Some(symbol);
+        self.row_free[cell / self.order].remove(symbol);
+        self.column_free[cell % self.order].remove(symbol);
+    }
+
+    fn unplace(&mut self, cell: usize, symbol: Symbol) {
+        self.cells[cell] = None;
+        self.row_free[cell / self.order].insert(symbol);
+        self.column_free[cell % self.order].insert(symbol);
+    }
+
+    fn candidates(&self, cell: usize) -> BitSet {
+        self.row_free[cell / self.order].intersection(&self.column_free[cell % self.order])
+    }
+}
+
+impl Search for Completion {
+    type Choice = (usize, Symbol);
+
+    /// Branches on the empty cell with the fewest candidates.
+    fn expand(&mut self) -> Node<(usize, Symbol)> {
+        let mut best: Option<(usize, BitSet, usize)> = None;
+        for cell in (0..self.cells.len()).filter(|&cell| self.cells[cell].is_none()) {
+            let candidates = self.candidates(cell);
+            let count = candidates.count();
+            if count == 0 {
+                return Node::Branch(Vec::new())
+            }
+            if best.as_ref().is_none_or(|&(_, _, fewest)| count < fewest) {
+                best = Some((cell, candidates, count));
+                if count == 1 {
+                    break
+                }
+            }
+        }
+        match best {
+            None => Node::Solution,
+            Some((cell, candidates, _)) => Node::Branch(candidates.iter().map(|symbol| (cell, symbol)).collect())
+        }
+    }
+
+    fn apply(&mut self, &(cell, symbol): &(usize, Symbol)) {
+        self.place(cell, symbol);
+    }
+
+    fn undo(&mut self, &(cell, symbol): &(usize, Symbol)) {
+        self.unplace(cell, symbol);
+    }
+}
+
+impl fmt::Display for PartialLatinSquare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self.cells.iter().map(|row| {
+            row.iter().map(|cell| match cell {
+                Some(symbol) => symbol.to_string(),
+                None => ".".to_string()
+            }).collect::<Vec<String>>().join("   ")
+        }).collect();
+        write!(f, "Partial latin square of size {}\n\n{}", self.order, rows.join("\n\n"))
+    }
+}
@@ -0,0 +1,195 @@
+//! Searching a latin square for occurrences of a partial latin square, such as an intercalate or any
+//! other configuration that a design should avoid.
+//!
+//! A pattern of order m is placed on a square of order n >= m by sending its rows and columns to distinct
+//! rows and columns of the square, and occurs there when every filled cell of the pattern lands on the
+//! same symbol. The placements considered are either windows, consecutive rows and columns in the
+//! pattern's own order, or any rows and columns in any order.
+//!
+//! With rows and columns free, a filled cell of a pattern row fixes its column as soon as the row is
+//! placed, because a symbol appears only once in a row of the square. The search places the fullest rows
+//! first, and only chooses freely for columns that have no filled cells.
+
+use crate::latin_square::LatinSquare;
+use crate::partial::PartialLatinSquare;
+
+/// Which placements of a pattern are considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrangement {
+    /// Consecutive rows and columns, keeping their order: the pattern is moved, but not rearranged.
+    Window,
+    /// Any distinct rows and any distinct columns, in any order.
+    Permuted
+}
+
+/// A placement of a pattern: row i of the pattern goes to row `rows[i]` of the square, and
+/// column j to column `columns[j]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub rows: Vec<usize>,
+    pub columns: Vec<usize>
+}
+
+/// Returns every placement of `pattern` in `square` where the pattern occurs.
+///
+/// Rows and columns of the pattern without filled cells are placed too, so they multiply the placements found.
+///
+/// ```
+/// use combinatorial_patterns::{mols, IncidenceCube};
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::pattern::{self, Arrangement};
+///
+/// let intercalate = PartialLatinSquare::from_rows(vec![
+///     vec![Some(0), Some(1)],
+///     vec![Some(1), Some(0)]
+/// ]).unwrap();
+///
+/// // The table of the Klein four-group has two intercalates on the symbols 0 and 1, in its top left
+/// // and bottom right corners. Either of their rows can take the first row of the pattern.
+/// let order_two = IncidenceCube::new_cyclic(2).unwrap().as_latin_square();
+/// let klein = mols::direct_product(&order_two, &order_two);
+/// assert_eq!(pattern::find_pattern(&klein, &intercalate, Arrangement::Permuted).len(), 4);
+///
+/// let windows = pattern::find_pattern(&klein, &intercalate, Arrangement::Window);
+/// assert_eq!(windows.len(), 2);
+/// assert_eq!(windows[1].rows, vec![2, 3]);
+/// ```
+pub fn find_pattern(square: &LatinSquare, pattern: &PartialLatinSquare, arrangement: Arrangement) -> Vec<Placement> {
+    let order = square.size();
+    let size = pattern.order();
+    if size > order {
+        return Vec::new()
+    }
+    match arrangement {
+        Arrangement::Window => {
+            let mut found = Vec::new();
+            for top in 0..=(order - size) {
+                for left in 0..=(order - size) {
+                    let placement = Placement {
+                        rows: (top..top + size).collect(),
+                        columns: (left..left + size).collect()
+                    };
+                    if occurs(square, pattern, &placement) {
+                        found.push(placement);
+                    }
+                }
+            }
+            found
+        },
+        Arrangement::Permuted => {
+            // position[row][symbol] is the column holding the symbol in that row of the square.
+            let mut position = vec![vec![usize::MAX; order]; order];
+            for (row, symbols) in square.square.iter().enumerate() {
+                for (column, &symbol) in symbols.iter().enumerate() {
+                    if symbol < order {
+                        position[row][symbol] = column;
+                    }
+                }
+            }
+            let mut pattern_rows: Vec<usize> = (0..size).collect();
+            pattern_rows.sort_by_key(|&row| std::cmp::Reverse(pattern.cells()[row].iter().filter(|cell| cell.is_some()).count()));
+            let mut search = Search {
+                pattern,
+                position,
+                pattern_rows,
+                rows: vec![None; size],
+                columns: vec![None; size],
+                row_used: vec![false; order],
+                column_used: vec![false; order],
+                found: Vec::new()
+            };
+            search.place_row(0);
+            search.found
+        }
+    }
+}
+
+/// Returns true if every filled cell of `pattern` matches `square` under the placement.
+pub fn occurs(square: &LatinSquare, pattern: &PartialLatinSquare, placement: &Placement) -> bool {
+    pattern.filled_cells().into_iter().all(|(row, column, symbol)| {
+        square.square[placement.rows[row]][placement.columns[column]] == symbol
+    })
+}
+
+/// Backtracking state of the search for permuted placements.
+struct Search<'a> {
+    pattern: &'a PartialLatinSquare,
+    position: Vec<Vec<usize>>,
+    /// Pattern rows in the order they are placed.
+    pattern_rows: Vec<usize>,
+    rows: Vec<Option<usize>>,
+    columns: Vec<Option<usize>>,
+    row_used: Vec<bool>,
+    column_used: Vec<bool>,
+    found: Vec<Placement>
+}
+
+impl Search<'_> {
+    fn place_row(&mut self, index: usize) {
+        if index == self.pattern_rows.len() {
+            self.place_free_column(0);
+            return
+        }
+        let pattern_row = self.pattern_rows[index];
+        for row in 0..self.row_used.len() {
+            if self.row_used[row] {
+                continue
+            }
+            // Columns this row forces, undone when moving on to the next candidate.
+            let mut forced = Vec::new();
+            let mut fits = true;
+            for (column, cell) in self.pattern.cells()[pattern_row].iter().enumerate() {
+                let Some(symbol) = *cell else { continue };
+                let target = self.position[row].get(symbol).copied().unwrap_or(usize::MAX);
+                match self.columns[column] {
+                    Some(placed) if placed == target => {},
+                    Some(_) => fits = false,
+                    None if target == usize::MAX || self.column_used[target] => fits = false,
+                    None => {
+                        self.columns[column] = Some(target);
+                        self.column_used[target] = true;
+                        forced.push(column);
+                    }
+                }
+                if !fits {
+                    break
+                }
+            }
+            if fits {
+                self.rows[pattern_row] = Some(row);
+                self.row_used[row] = true;
+                self.place_row(index + 1);
+                self.row_used[row] = false;
+                self.rows[pattern_row] = None;
+            }
+            for column in forced {
+                let target = self.columns[column].take().expect("ProgrammingError: forced columns are placed.");
+                self.column_used[target] = false;
+            }
+        }
+    }
+
+    /// Places the pattern columns no filled cell has fixed, from `column` onwards, in every possible way.
+    fn place_free_column(&mut self, column: usize) {
+        if column == self.columns.len() {
+            self.found.push(Placement {
+                rows: self.rows.iter().map(|row| row.expect("ProgrammingError: every row is placed.")).collect(),
+                columns: self.columns.iter().map(|column| column.expect("ProgrammingError: every column is placed.")).collect()
+            });
+            return
+        }
+        if self.columns[column].is_some() {
+            self.place_free_column(column + 1);
+            return
+        }
+        for target in 0..self.column_used.len() {
+            if !self.column_used[target] {
+                self.columns[column] = Some(target);
+                self.column_used[target] = true;
+                self.place_free_column(column + 1);
+                self.column_used[target] = false;
+                self.columns[column] = None;
+            }
+        }
+    }
+}
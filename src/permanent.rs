@@ -0,0 +1,113 @@
+//! Permanents of 0/1 matrices.
+//!
+//! The permanent of an n x n matrix is the sum, over all permutations p, of the products
+//! `a[0][p(0)] * .. * a[n - 1][p(n - 1)]`. For a 0/1 matrix that is the number of perfect matchings of the
+//! bipartite graph it describes; in particular, the number of ways to add a row to a latin rectangle
+//! is the permanent of its availability matrix, with a 1 where a symbol is still free in a column.
+//!
+//! Ryser's formula takes O(2^n * n) steps, walking the subsets of columns in Gray code order.
+//!
+//! Drawing a uniformly random permutation that only uses the 1 entries of a matrix, i.e. a random perfect
+//! matching, goes one row at a time, choosing each column with probability proportional to the permanent
+//! of the minor it leaves.
+//!
+//! Sources:
+//!
+//! - [Computing the permanent, Wikipedia](https://en.wikipedia.org/wiki/Computing_the_permanent)
+
+use rand::Rng;
+
+/// The permanent of a square 0/1 matrix, given as rows. Panics if the matrix is not square.
+///
+/// The result is exact whenever it fits in a u128, which covers every 0/1 matrix with up to 34 rows,
+/// more than the running time allows in practice.
+///
+/// ```
+/// use combinatorial_patterns::permanent::permanent;
+///
+/// // The permanent of the all ones matrix counts permutations.
+/// assert_eq!(permanent(&vec![vec![true; 5]; 5]), 120);
+///
+/// // Forbidding the diagonal leaves the derangements.
+/// let derangements: Vec<Vec<bool>> = (0..5).map(|i| (0..5).map(|j| i != j).collect()).collect();
+/// assert_eq!(permanent(&derangements), 44);
+/// ```
+pub fn permanent(matrix: &[Vec<bool>]) -> u128 {
+    let n = matrix.len();
+    if let Some(row) = matrix.iter().find(|row| row.len() != n) {
+        panic!("Permanent of a non-square matrix: {} rows, but a row of length {}", n, row.len());
+    }
+    if n == 0 {
+        return 1
+    }
+
+    // perm(A) = sum over nonempty column sets S of (-1)^(n - |S|) * prod over rows of (row sum within S).
+    // Every intermediate value is taken mod 2^128, which leaves the final, nonnegative, result exact.
+    let mut row_sums = vec![0u128; n];
+    let mut total = 0u128;
+    let mut gray = 0u64;
+    for step in 1..(1u64 << n) {
+        let column = step.trailing_zeros() as usize;
+        gray ^= 1 << column;
+        let added = gray & (1 << column) != 0;
+        for (sum, row) in row_sums.iter_mut().zip(matrix.iter()) {
+            if row[column] {
+                if added {
+                    *sum += 1;
+                } else {
+                    *sum -= 1;
+                }
+            }
+        }
+        let product = row_sums.iter().fold(1u128, |product, &sum| product.wrapping_mul(sum));
+        if (n - gray.count_ones() as usize).is_multiple_of(2) {
+            total = total.wrapping_add(product);
+        } else {
+            total = total.wrapping_sub(product);
+        }
+    }
+    total
+}
+
+/// Draws a permutation uniformly among those only using true cells of the matrix, as `permutation[row] = column`,
+/// along with the number of such permutations. Returns None if there are none.
+pub(crate) fn random_permutation(matrix: &[Vec<bool>], rng: &mut impl Rng) -> Option<(u128, Vec<usize>)> {
+    let order = matrix.len();
+    let mut permutation = Vec::with_capacity(order);
+    // The columns of the remaining matrix, and the matrix itself, shrinking by a row each step.
+    let mut columns: Vec<usize> = (0..order).collect();
+    let mut remaining = matrix.to_vec();
+    let mut count = None;
+    while !remaining.is_empty() {
+        let options: Vec<(usize, u128)> = (0..columns.len())
+            .filter(|&index| remaining[0][index])
+            .map(|index| (index, permanent(&minor(&remaining, 0, index))))
+            .collect();
+        let total: u128 = options.iter().map(|&(_, ways)| ways).sum();
+        if total == 0 {
+            return None
+        }
+        count.get_or_insert(total);
+
+        let mut pick = rng.gen_range(0..total);
+        let &(index, _) = options.iter().find(|&&(_, ways)| {
+            if pick < ways {
+                true
+            } else {
+                pick -= ways;
+                false
+            }
+        }).expect("ProgrammingError: the pick is below the total.");
+        permutation.push(columns.remove(index));
+        remaining = minor(&remaining, 0, index);
+    }
+    Some((count.unwrap_or(1), permutation))
+}
+
+/// The matrix left after deleting the given row and column.
+fn minor(matrix: &[Vec<bool>], row: usize, column: usize) -> Vec<Vec<bool>> {
+    matrix.iter().enumerate()
+        .filter(|&(index, _)| index != row)
+        .map(|(_, cells)| cells.iter().enumerate().filter(|&(index, _)| index != column).map(|(_, &cell)| cell).collect())
+        .collect()
+}
@@ -0,0 +1,394 @@
+//! Counting up to symmetry with Burnside's lemma and Pólya's cycle index.
+//!
+//! Burnside's lemma counts the orbits of a group acting on a set as the average number of points fixed by
+//! an element. For colorings of n positions with k colors, a permutation fixes exactly the colorings that
+//! are constant on each of its cycles, k^c of them for a permutation with c cycles. Collecting the elements
+//! of the group by cycle type gives the cycle index Z(G) = (1/|G|) sum of x1^a1 x2^a2 .. xn^an, with a
+//! term for each element that has a_i cycles of length i, and the number of colorings up to the group is
+//! Z(G) at x_i = k.
+//!
+//! A `PermutationGroup` is generated by some permutations and lists all of its elements, so it is meant for
+//! small groups. The cycle indices of the cyclic, dihedral and symmetric groups also have closed forms,
+//! which don't list the elements: necklaces are colorings up to rotation, and bracelets up to rotation and
+//! reflection.
+//!
+//! Every count returns a u128, or None once it no longer fits. With the `bigint` feature, each also has a
+//! `_big` variant returning a `num_bigint::BigUint`.
+//!
+//! Sources:
+//!
+//! - Kombinatorische Anzahlbestimmungen für Gruppen, Graphen und chemische Verbindungen, G. Pólya, Acta Mathematica 68 (1937)
+//! - [Necklace (combinatorics), Wikipedia](https://en.wikipedia.org/wiki/Necklace_(combinatorics))
+//! - [Cycle index, Wikipedia](https://en.wikipedia.org/wiki/Cycle_index)
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+use crate::numbers::Natural;
+
+/// A permutation of 0..n, mapping i to `permutation[i]`.
+pub type Permutation = Vec<usize>;
+
+/// A group of permutations of 0..n, with all of its elements.
+///
+/// ```
+/// use combinatorial_patterns::polya::PermutationGroup;
+///
+/// // The rotations of a square's corners and the reflection across a diagonal.
+/// let group = PermutationGroup::generated(4, &[vec![1, 2, 3, 0], vec![0, 3, 2, 1]]).unwrap();
+/// assert_eq!(group.order(), 8);
+/// assert_eq!(group, PermutationGroup::dihedral(4));
+/// assert_eq!(group.cycle_index().to_string(), "(1/8)(x1^4 + 2 x1^2 x2 + 3 x2^2 + 2 x4)");
+/// assert!(PermutationGroup::generated(3, &[vec![0, 0, 1]]).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermutationGroup {
+    degree: usize,
+    /// Sorted, starting with the identity.
+    elements: Vec<Permutation>
+}
+
+impl PermutationGroup {
+    /// The group generated by the permutations of 0..degree. Returns None if one is not a permutation of
+    /// 0..degree.
+    pub fn generated(degree: usize, generators: &[Permutation]) -> Option<PermutationGroup> {
+        if generators.iter().any(|generator| !is_permutation(generator, degree)) {
+            return None
+        }
+        let identity: Permutation = (0..degree).collect();
+        let mut seen: HashSet<Permutation> = HashSet::from([identity.clone()]);
+        let mut queue = VecDeque::from([identity]);
+        while let Some(element) = queue.pop_front() {
+            for generator in generators {
+                let product: Permutation = element.iter().map(|&point| generator[point]).collect();
+                if seen.insert(product.clone()) {
+                    queue.push_back(product);
+                }
+            }
+        }
+        let mut elements: Vec<Permutation> = seen.into_iter().collect();
+        elements.sort_unstable();
+        Some(PermutationGroup { degree, elements })
+    }
+
+    /// The rotations of n positions in a cycle.
+    pub fn cyclic(n: usize) -> PermutationGroup {
+        PermutationGroup::generated(n, &[rotation(n)]).expect("ProgrammingError: a rotation is a permutation.")
+    }
+
+    /// The rotations and reflections of n positions in a cycle, of order 2n for n above 2.
+    pub fn dihedral(n: usize) -> PermutationGroup {
+        let reflection = (0..n).map(|point| (n - point) % n).collect();
+        PermutationGroup::generated(n, &[rotation(n), reflection]).expect("ProgrammingError: rotations and reflections are permutations.")
+    }
+
+    /// All n! permutations of n points.
+    pub fn symmetric(n: usize) -> PermutationGroup {
+        let mut generators = vec![rotation(n)];
+        if n > 1 {
+            let mut swap: Permutation = (0..n).collect();
+            swap.swap(0, 1);
+            generators.push(swap);
+        }
+        PermutationGroup::generated(n, &generators).expect("ProgrammingError: a rotation and a swap are permutations.")
+    }
+
+    /// The number of points permuted.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The number of elements.
+    pub fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// The elements, sorted, so the identity comes first.
+    pub fn elements(&self) -> &[Permutation] {
+        &self.elements
+    }
+
+    /// The cycle index, from the cycle types of the elements.
+    pub fn cycle_index(&self) -> CycleIndex {
+        let mut terms = BTreeMap::new();
+        for element in &self.elements {
+            *terms.entry(cycle_type(element)).or_insert(0) += 1;
+        }
+        CycleIndex::from_terms(self.degree, terms)
+    }
+
+    /// The number of orbits of the group acting on some set, by Burnside's lemma, given the number of
+    /// points each element fixes. Returns None if the sum doesn't fit in a u128.
+    ///
+    /// ```
+    /// use combinatorial_patterns::polya::PermutationGroup;
+    ///
+    /// // The 2-subsets of the corners of a square, up to its symmetries: sides and diagonals.
+    /// let group = PermutationGroup::dihedral(4);
+    /// let fixed = |element: &[usize]| {
+    ///     let mut count = 0;
+    ///     for a in 0..4 {
+    ///         for b in (a + 1)..4 {
+    ///             let (x, y) = (element[a].min(element[b]), element[a].max(element[b]));
+    ///             count += ((x, y) == (a, b)) as u128;
+    ///         }
+    ///     }
+    ///     count
+    /// };
+    /// assert_eq!(group.count_orbits(fixed), Some(2));
+    /// ```
+    pub fn count_orbits(&self, mut fixed: impl FnMut(&[usize]) -> u128) -> Option<u128> {
+        let total = self.elements.iter().try_fold(0u128, |total, element| total.checked_add(fixed(element)))?;
+        Some(total / self.elements.len() as u128)
+    }
+}
+
+/// A cycle index: the number of elements of each cycle type, and the order of the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleIndex {
+    degree: usize,
+    group_order: u128,
+    /// Each term as the numbers of cycles of lengths 1 to n, with the number of elements of that type,
+    /// the most fixed points first.
+    terms: Vec<(Vec<usize>, u128)>
+}
+
+impl CycleIndex {
+    fn from_terms(degree: usize, terms: BTreeMap<Vec<usize>, u128>) -> CycleIndex {
+        let group_order = terms.values().sum();
+        CycleIndex { degree, group_order, terms: terms.into_iter().rev().collect() }
+    }
+
+    /// The cycle index of the rotations of n positions: each d dividing n has phi(d) rotations with
+    /// n / d cycles of length d.
+    ///
+    /// ```
+    /// use combinatorial_patterns::polya::{CycleIndex, PermutationGroup};
+    ///
+    /// assert_eq!(CycleIndex::cyclic(6), PermutationGroup::cyclic(6).cycle_index());
+    /// assert_eq!(CycleIndex::cyclic(6).to_string(), "(1/6)(x1^6 + x2^3 + 2 x3^2 + 2 x6)");
+    /// ```
+    pub fn cyclic(n: usize) -> CycleIndex {
+        let mut terms = BTreeMap::new();
+        if n == 0 {
+            // The identity of no points.
+            terms.insert(Vec::new(), 1);
+        }
+        for length in (1..=n).filter(|&length| n.is_multiple_of(length)) {
+            *terms.entry(power(n, length, n / length)).or_insert(0) += totient(length) as u128;
+        }
+        CycleIndex::from_terms(n, terms)
+    }
+
+    /// The cycle index of the rotations and reflections of n positions, the dihedral group of order 2n,
+    /// whose elements coincide as permutations for n up to 2.
+    ///
+    /// ```
+    /// use combinatorial_patterns::polya::{CycleIndex, PermutationGroup};
+    ///
+    /// assert_eq!(CycleIndex::dihedral(5), PermutationGroup::dihedral(5).cycle_index());
+    /// assert_eq!(CycleIndex::dihedral(6), PermutationGroup::dihedral(6).cycle_index());
+    /// ```
+    pub fn dihedral(n: usize) -> CycleIndex {
+        let mut terms = BTreeMap::new();
+        for (cycle_type, count) in CycleIndex::cyclic(n).terms {
+            terms.insert(cycle_type, count);
+        }
+        let mut reflect = |cycle_type: Vec<usize>, count: usize| *terms.entry(cycle_type).or_insert(0) += count as u128;
+        if n == 0 {
+            return CycleIndex::from_terms(n, terms)
+        }
+        if !n.is_multiple_of(2) {
+            // Each reflection fixes one position and swaps the others in pairs.
+            let mut cycle_type = power(n, 2, n / 2);
+            cycle_type[0] = 1;
+            reflect(cycle_type, n);
+        } else {
+            // Half the reflections fix no position, and half fix two opposite ones.
+            reflect(power(n, 2, n / 2), n / 2);
+            let mut cycle_type = power(n, 2, n / 2 - 1);
+            cycle_type[0] += 2;
+            reflect(cycle_type, n / 2);
+        }
+        CycleIndex::from_terms(n, terms)
+    }
+
+    /// The cycle index of all permutations of n points, computed from the partitions of n, or None if n!
+    /// doesn't fit in a u128.
+    ///
+    /// ```
+    /// use combinatorial_patterns::polya::{CycleIndex, PermutationGroup};
+    ///
+    /// assert_eq!(CycleIndex::symmetric(4), Some(PermutationGroup::symmetric(4).cycle_index()));
+    /// assert_eq!(CycleIndex::symmetric(3).unwrap().to_string(), "(1/6)(x1^3 + 3 x1 x2 + 2 x3)");
+    /// assert!(CycleIndex::symmetric(40).is_none());
+    /// ```
+    pub fn symmetric(n: usize) -> Option<CycleIndex> {
+        let factorial = crate::numbers::factorial(n)?;
+        let mut terms = BTreeMap::new();
+        let mut cycle_type = vec![0; n];
+        partitions(n, n, &mut cycle_type, &mut |cycle_type| {
+            // n! / (product of i^a_i a_i!) permutations have the cycle type.
+            let centralizer: u128 = cycle_type.iter().enumerate().map(|(index, &count)| {
+                ((index + 1) as u128).pow(count as u32) * (1..=count as u128).product::<u128>()
+            }).product();
+            terms.insert(cycle_type.to_vec(), factorial / centralizer);
+        });
+        Some(CycleIndex::from_terms(n, terms))
+    }
+
+    /// The number of points permuted.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The order of the group.
+    pub fn group_order(&self) -> u128 {
+        self.group_order
+    }
+
+    /// The terms, as the numbers of cycles of lengths 1 to n and the number of elements with them.
+    pub fn terms(&self) -> &[(Vec<usize>, u128)] {
+        &self.terms
+    }
+
+    /// The number of colorings of the points with the given number of colors, up to the group, or None if
+    /// it doesn't fit in a u128.
+    ///
+    /// ```
+    /// use combinatorial_patterns::polya::PermutationGroup;
+    ///
+    /// // Colorings of the corners of a square with 3 colors, up to its symmetries.
+    /// assert_eq!(PermutationGroup::dihedral(4).cycle_index().count_colorings(3), Some(21));
+    /// ```
+    pub fn count_colorings(&self, colors: u128) -> Option<u128> {
+        self.count_colorings_as(colors)
+    }
+
+    /// The number of colorings of the points with the given number of colors, up to the group.
+    #[cfg(feature = "bigint")]
+    pub fn count_colorings_big(&self, colors: u128) -> BigUint {
+        self.count_colorings_as(colors).expect("ProgrammingError: big integers don't overflow.")
+    }
+
+    fn count_colorings_as<T: Natural>(&self, colors: u128) -> Option<T> {
+        let colors = T::from_u128(colors);
+        let mut total = T::from_u128(0);
+        for (cycle_type, count) in &self.terms {
+            let cycles: usize = cycle_type.iter().sum();
+            let fixed = (0..cycles).try_fold(T::from_u128(*count), |product, _| product.checked_mul(&colors))?;
+            total = total.checked_add(&fixed)?;
+        }
+        Some(total.div_rem(&T::from_u128(self.group_order)).0)
+    }
+}
+
+impl fmt::Display for CycleIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self.terms.iter().map(|(cycle_type, count)| {
+            let factors: Vec<String> = cycle_type.iter().enumerate().filter(|&(_, &cycles)| cycles > 0).map(|(index, &cycles)| {
+                if cycles == 1 { format!("x{}", index + 1) } else { format!("x{}^{}", index + 1, cycles) }
+            }).collect();
+            let monomial = if factors.is_empty() { "1".to_string() } else { factors.join(" ") };
+            if *count == 1 { monomial } else { format!("{} {}", count, monomial) }
+        }).collect();
+        write!(f, "(1/{})({})", self.group_order, terms.join(" + "))
+    }
+}
+
+/// The number of necklaces of n beads in k colors, colorings up to rotation, or None if it doesn't fit in
+/// a u128.
+///
+/// ```
+/// use combinatorial_patterns::polya;
+///
+/// assert_eq!((1..=6).map(|n| polya::necklaces(n, 2).unwrap()).collect::<Vec<u128>>(), vec![2, 3, 4, 6, 8, 14]);
+/// ```
+pub fn necklaces(n: usize, colors: u128) -> Option<u128> {
+    CycleIndex::cyclic(n).count_colorings(colors)
+}
+
+/// The number of bracelets of n beads in k colors, colorings up to rotation and reflection, or None if it
+/// doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::polya;
+///
+/// assert_eq!((1..=6).map(|n| polya::bracelets(n, 2).unwrap()).collect::<Vec<u128>>(), vec![2, 3, 4, 6, 8, 13]);
+/// ```
+pub fn bracelets(n: usize, colors: u128) -> Option<u128> {
+    CycleIndex::dihedral(n).count_colorings(colors)
+}
+
+/// The number of necklaces of n beads in k colors.
+#[cfg(feature = "bigint")]
+pub fn necklaces_big(n: usize, colors: u128) -> BigUint {
+    CycleIndex::cyclic(n).count_colorings_big(colors)
+}
+
+/// The number of bracelets of n beads in k colors.
+#[cfg(feature = "bigint")]
+pub fn bracelets_big(n: usize, colors: u128) -> BigUint {
+    CycleIndex::dihedral(n).count_colorings_big(colors)
+}
+
+fn is_permutation(permutation: &[usize], degree: usize) -> bool {
+    let mut seen = vec![false; degree];
+    permutation.len() == degree && permutation.iter().all(|&point| point < degree && !std::mem::replace(&mut seen[point], true))
+}
+
+fn rotation(n: usize) -> Permutation {
+    (0..n).map(|point| (point + 1) % n).collect()
+}
+
+/// The numbers of cycles of lengths 1 to n of the permutation.
+fn cycle_type(permutation: &[usize]) -> Vec<usize> {
+    let mut counts = vec![0; permutation.len()];
+    let mut seen = vec![false; permutation.len()];
+    for start in 0..permutation.len() {
+        let mut length = 0;
+        let mut point = start;
+        while !seen[point] {
+            seen[point] = true;
+            point = permutation[point];
+            length += 1;
+        }
+        if length > 0 {
+            counts[length - 1] += 1;
+        }
+    }
+    counts
+}
+
+/// The cycle type of degree n with `cycles` cycles of the given length and nothing else.
+fn power(n: usize, length: usize, cycles: usize) -> Vec<usize> {
+    let mut cycle_type = vec![0; n];
+    if cycles > 0 {
+        cycle_type[length - 1] = cycles;
+    }
+    cycle_type
+}
+
+fn totient(n: usize) -> usize {
+    (1..=n).filter(|&k| gcd(k, n) == 1).count()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Calls `visit` with the cycle type of every partition of `remaining`, into parts of at most `largest`.
+fn partitions(remaining: usize, largest: usize, cycle_type: &mut Vec<usize>, visit: &mut impl FnMut(&[usize])) {
+    if remaining == 0 {
+        visit(cycle_type);
+        return
+    }
+    for part in (1..=largest.min(remaining)).rev() {
+        cycle_type[part - 1] += 1;
+        partitions(remaining - part, part, cycle_type, visit);
+        cycle_type[part - 1] -= 1;
+    }
+}
@@ -0,0 +1,255 @@
+//! Quasigroups, the algebraic view of latin squares.
+//!
+//! A latin square of order n with symbols 0..n is exactly the multiplication table (Cayley table)
+//! of a quasigroup: `a * b` is the symbol in row `a`, column `b`. Since every row and every column
+//! is a permutation, the equations `a * x = b` and `y * a = b` always have unique solutions,
+//! which gives the left and right divisions.
+//!
+//! A quasigroup is semisymmetric if its table is closed under the cyclic conjugates, which permute the
+//! roles of row, column and symbol cyclically, and totally symmetric if it is closed under all six. The
+//! idempotent ones are exactly the Mendelsohn and Steiner triple systems (see [`crate::triple_system`]).
+//!
+//! Besides the raw operations, this module implements the string transformations of Markovski et al.,
+//! which are the building block of quasigroup based hash functions and ciphers (see [`hash`]), and checks
+//! for the standard quasigroup and loop identities (see [`identities`]).
+//!
+//! Sources:
+//!
+//! - [Quasigroup string processing, Smile Markovski, Danilo Gligoroski, Verica Bakeva](https://www.researchgate.net/publication/228981388_Quasigroup_string_processing_Part_1)
+
+pub mod hash;
+pub mod identities;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::triple_system::{MendelsohnTripleSystem, SteinerTripleSystem};
+use rand::Rng;
+
+/// A finite quasigroup on the symbols 0..order, backed by its Cayley table.
+///
+/// Build one from any latin square whose symbols are 0..n with `Quasigroup::new`,
+/// or generate a random one with `Quasigroup::new_random`.
+///
+/// ```
+/// use combinatorial_patterns::{IncidenceCube, Quasigroup};
+///
+/// // The cyclic cube gives the additive group of integers mod 4.
+/// let quasigroup = Quasigroup::new(IncidenceCube::new_cyclic(4).unwrap().as_latin_square()).unwrap();
+/// assert_eq!(quasigroup.op(3, 2), 1);
+/// assert_eq!(quasigroup.left_division(3, 1), 2);
+///
+/// let leader = 1;
+/// let encoded = quasigroup.e_transformation(leader, &[0, 2, 3, 3]);
+/// assert_eq!(quasigroup.d_transformation(leader, &encoded), vec![0, 2, 3, 3]);
+/// assert_eq!(quasigroup.clone(), quasigroup);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quasigroup {
+    order: usize,
+    table: Vec<Vec<Symbol>>,
+    // left_divisions[a][b] = x such that a * x = b
+    left_divisions: Vec<Vec<Symbol>>,
+    // right_divisions[a][b] = y such that y * a = b
+    right_divisions: Vec<Vec<Symbol>>
+}
+
+impl Quasigroup {
+    /// Creates a quasigroup using `square` as its Cayley table.
+    ///
+    /// Returns None if the square is not a latin square over the symbols 0..n,
+    /// as is the case for `LatinSquare::new_empty`.
+    pub fn new(square: LatinSquare) -> Option<Quasigroup> {
+        let order = square.size();
+        let table = square.square;
+        let mut left_divisions = vec![vec![order; order]; order];
+        let mut right_divisions = vec![vec![order; order]; order];
+
+        if table.len() != order || table.iter().any(|row| row.len() != order) {
+            return None
+        }
+        for (a, row) in table.iter().enumerate() {
+            for (b, &product) in row.iter().enumerate() {
+                // A symbol seen twice in a row or column leaves a division taken already.
+                if product >= order || left_divisions[a][product] != order || right_divisions[b][product] != order {
+                    return None
+                }
+                left_divisions[a][product] = b;
+                right_divisions[b][product] = a;
+            }
+        }
+
+        Some(Quasigroup {
+            order,
+            table,
+            left_divisions,
+            right_divisions
+        })
+    }
+
+    /// Creates a quasigroup from a random latin square of the given order, drawn from `rng`. Fails for order 0.
+    pub fn new_random(order: usize, rng: &mut impl Rng) -> Result<Quasigroup, CombinatorialError> {
+        let square = LatinSquare::new_random(order, rng)?;
+        Ok(Quasigroup::new(square).expect("ProgrammingError: generated latin square is not a quasigroup table."))
+    }
+
+    /// Creates an idempotent quasigroup of the given order, i.e. one where `x * x = x` for every x.
+    ///
+    /// Odd orders use `x * y = (x + y) / 2 mod n`. Even orders prolong the odd order below them
+    /// along the transversal `(i, i + 1)`, which keeps the diagonal in place.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{CombinatorialError, Quasigroup};
+    ///
+    /// for order in [1, 3, 4, 7, 10] {
+    ///     assert!(Quasigroup::new_idempotent(order).unwrap().is_idempotent());
+    /// }
+    /// assert_eq!(Quasigroup::new_idempotent(2).err(), Some(CombinatorialError::NoneOfOrder(2)));
+    /// assert_eq!(Quasigroup::new_idempotent(0).err(), Some(CombinatorialError::ZeroOrder));
+    /// ```
+    ///
+    /// Fails for order 0, and for order 2, the only order with no idempotent quasigroup.
+    pub fn new_idempotent(order: usize) -> Result<Quasigroup, CombinatorialError> {
+        if order == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        if order == 2 {
+            return Err(CombinatorialError::NoneOfOrder(order))
+        }
+        let odd_order = if order.is_multiple_of(2) { order.saturating_sub(1) } else { order };
+        // (n + 1) / 2 is the inverse of 2 modulo an odd n.
+        let half = odd_order.div_ceil(2);
+        let mut rows: Vec<Vec<Symbol>> = (0..odd_order).map(|x| {
+            (0..odd_order).map(|y| ((x + y) * half) % odd_order).collect()
+        }).collect();
+
+        if order != odd_order {
+            // Prolongation: every transversal cell moves its symbol to the new row and column,
+            // and takes the new symbol instead.
+            let new_symbol = odd_order;
+            let mut new_row = vec![new_symbol; order];
+            for (i, row) in rows.iter_mut().enumerate() {
+                let j = (i + 1) % odd_order;
+                let moved = row[j];
+                row[j] = new_symbol;
+                row.push(moved);
+                new_row[j] = moved;
+            }
+            rows.push(new_row);
+        }
+        Ok(Quasigroup::new(LatinSquare::from_rows(rows))
+            .expect("ProgrammingError: the prolonged table is an idempotent quasigroup."))
+    }
+
+    /// Returns true if `x * x = x` for every element.
+    pub fn is_idempotent(&self) -> bool {
+        (0..self.order).all(|x| self.op(x, x) == x)
+    }
+
+    /// Returns true if `x * y = y * x` for all elements.
+    pub fn is_commutative(&self) -> bool {
+        (0..self.order).all(|x| (0..x).all(|y| self.op(x, y) == self.op(y, x)))
+    }
+
+    /// Returns true if `y * (x * y) = x` for all elements, i.e. the table is closed under the cyclic
+    /// conjugates: with `x * y = z` come `y * z = x` and `z * x = y`.
+    pub fn is_semisymmetric(&self) -> bool {
+        (0..self.order).all(|x| (0..self.order).all(|y| self.op(y, self.op(x, y)) == x))
+    }
+
+    /// Returns true if the quasigroup is commutative and semisymmetric, i.e. the table is closed under all six conjugates.
+    pub fn is_totally_symmetric(&self) -> bool {
+        self.is_commutative() && self.is_semisymmetric()
+    }
+
+    /// Creates the totally symmetric quasigroup `x * y = -(x + y) mod n`, which exists for every order.
+    ///
+    /// ```
+    /// use combinatorial_patterns::Quasigroup;
+    ///
+    /// for order in 1..8 {
+    ///     assert!(Quasigroup::new_totally_symmetric(order).unwrap().is_totally_symmetric());
+    /// }
+    /// assert!(Quasigroup::new_totally_symmetric(0).is_err());
+    /// ```
+    ///
+    /// Fails for order 0.
+    pub fn new_totally_symmetric(order: usize) -> Result<Quasigroup, CombinatorialError> {
+        if order == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        let rows = (0..order).map(|x| (0..order).map(|y| (2 * order - x - y) % order).collect()).collect();
+        Ok(Quasigroup::new(LatinSquare::from_rows(rows))
+            .expect("ProgrammingError: -(x + y) is a quasigroup operation."))
+    }
+
+    /// Creates an idempotent totally symmetric quasigroup, the Steiner quasigroup of a Steiner triple system.
+    ///
+    /// Returns None unless order ≡ 1 or 3 (mod 6), the orders of Steiner triple systems.
+    pub fn new_steiner(order: usize) -> Option<Quasigroup> {
+        SteinerTripleSystem::new(order).map(|system| system.as_quasigroup())
+    }
+
+    /// Creates an idempotent semisymmetric quasigroup, from a Mendelsohn triple system.
+    ///
+    /// Returns None unless order ≡ 0 or 1 (mod 3) and order != 6, the orders of Mendelsohn triple systems.
+    ///
+    /// ```
+    /// use combinatorial_patterns::Quasigroup;
+    ///
+    /// let quasigroup = Quasigroup::new_idempotent_semisymmetric(10).unwrap();
+    /// assert!(quasigroup.is_semisymmetric() && !quasigroup.is_commutative());
+    /// assert!(Quasigroup::new_idempotent_semisymmetric(6).is_none());
+    /// ```
+    pub fn new_idempotent_semisymmetric(order: usize) -> Option<Quasigroup> {
+        MendelsohnTripleSystem::new(order).map(|system| system.as_quasigroup())
+    }
+
+    /// The number of elements in the quasigroup.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns `a * b`.
+    pub fn op(&self, a: Symbol, b: Symbol) -> Symbol {
+        self.table[a][b]
+    }
+
+    /// Returns `a \ b`, the unique x such that `a * x = b`.
+    pub fn left_division(&self, a: Symbol, b: Symbol) -> Symbol {
+        self.left_divisions[a][b]
+    }
+
+    /// Returns `b / a`, the unique y such that `y * a = b`.
+    pub fn right_division(&self, b: Symbol, a: Symbol) -> Symbol {
+        self.right_divisions[a][b]
+    }
+
+    /// Applies the e-transformation with the given leader to `input`.
+    ///
+    /// Each output symbol is the previous output (or the leader, for the first symbol)
+    /// multiplied by the current input symbol: `b1 = l * a1`, `bi = b(i-1) * ai`.
+    pub fn e_transformation(&self, leader: Symbol, input: &[Symbol]) -> Vec<Symbol> {
+        let mut previous = leader;
+        input.iter().map(|&symbol| {
+            previous = self.op(previous, symbol);
+            previous
+        }).collect()
+    }
+
+    /// Applies the d-transformation with the given leader to `input`, undoing `e_transformation`.
+    ///
+    /// `a1 = l \ b1`, `ai = b(i-1) \ bi`.
+    pub fn d_transformation(&self, leader: Symbol, input: &[Symbol]) -> Vec<Symbol> {
+        let mut previous = leader;
+        input.iter().map(|&symbol| {
+            let decoded = self.left_division(previous, symbol);
+            previous = symbol;
+            decoded
+        }).collect()
+    }
+
+    /// Returns the Cayley table of the quasigroup as a latin square.
+    pub fn as_latin_square(&self) -> LatinSquare {
+        LatinSquare::from_rows(self.table.clone())
+    }
+}
@@ -0,0 +1,118 @@
+//! A quasigroup based hash (compression) function built from iterated e-transformations.
+//!
+//! The message is read as a string of quasigroup symbols, then pushed through a chain of
+//! e-transformations, one per leader. Since every transformation only needs its previous output
+//! symbol, the whole chain can be computed in a single streaming pass, keeping one symbol of state
+//! per leader. The digest is the final state of the chain.
+//!
+//! Bytes are split into base-n digits, where n is the order of the quasigroup, so any quasigroup
+//! of order 2 or more can be used. The message length is absorbed on `finalize` so that messages
+//! differing only by trailing zero bytes do not collide trivially.
+//!
+//! This is a demonstration of quasigroup string processing, and has not been vetted for
+//! cryptographic use.
+//!
+//! ```
+//! use combinatorial_patterns::Quasigroup;
+//! use combinatorial_patterns::quasigroup::hash::{QuasigroupHash, digest};
+//!
+//! let quasigroup = Quasigroup::new_random(16, &mut rand::thread_rng()).unwrap();
+//! let leaders = [3, 14, 1, 5, 9, 2, 6, 5];
+//!
+//! let mut hasher = QuasigroupHash::new(&quasigroup, &leaders);
+//! hasher.update(b"latin ");
+//! hasher.update(b"squares");
+//! let streamed = hasher.finalize();
+//!
+//! assert_eq!(streamed.len(), leaders.len());
+//! assert_eq!(streamed, digest(&quasigroup, &leaders, b"latin squares"));
+//! ```
+
+use crate::latin_square::Symbol;
+use crate::quasigroup::Quasigroup;
+
+/// Streaming state of the hash. Feed data with `update`, then call `finalize` for the digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuasigroupHash<'a> {
+    quasigroup: &'a Quasigroup,
+    // The last output symbol of each e-transformation in the chain, starting as the leaders.
+    state: Vec<Symbol>,
+    digits_per_byte: usize,
+    length: u64
+}
+
+impl<'a> QuasigroupHash<'a> {
+    /// Creates a new hasher over `quasigroup`, with one e-transformation per leader.
+    ///
+    /// The digest has as many symbols as there are leaders.
+    /// Panics if there are no leaders, if a leader is not a symbol of the quasigroup,
+    /// or if the quasigroup has fewer than two elements.
+    pub fn new(quasigroup: &'a Quasigroup, leaders: &[Symbol]) -> QuasigroupHash<'a> {
+        let order = quasigroup.order();
+        if order < 2 {
+            panic!("Quasigroup hash needs a quasigroup of order 2 or more, got {}", order);
+        }
+        if leaders.is_empty() {
+            panic!("Quasigroup hash needs at least one leader.");
+        }
+        if let Some(leader) = leaders.iter().find(|&&leader| leader >= order) {
+            panic!("Leader {} is not a symbol of a quasigroup of order {}", leader, order);
+        }
+
+        let mut digits_per_byte = 1;
+        let mut capacity = order;
+        while capacity < 256 {
+            capacity *= order;
+            digits_per_byte += 1;
+        }
+
+        QuasigroupHash {
+            quasigroup,
+            state: leaders.to_vec(),
+            digits_per_byte,
+            length: 0
+        }
+    }
+
+    /// Absorbs `data` into the hash state.
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.absorb_byte(byte);
+        }
+        self.length += data.len() as u64;
+    }
+
+    /// Absorbs the message length and returns the digest, one symbol per leader.
+    pub fn finalize(mut self) -> Vec<Symbol> {
+        for byte in self.length.to_le_bytes() {
+            self.absorb_byte(byte);
+        }
+        self.state
+    }
+
+    fn absorb_byte(&mut self, byte: u8) {
+        let order = self.quasigroup.order();
+        let mut remaining = byte as usize;
+        for _ in 0..self.digits_per_byte {
+            self.absorb_symbol(remaining % order);
+            remaining /= order;
+        }
+    }
+
+    /// Pushes one symbol through every e-transformation of the chain.
+    /// The output of each transformation is the input of the next.
+    fn absorb_symbol(&mut self, symbol: Symbol) {
+        let mut carry = symbol;
+        for previous in self.state.iter_mut() {
+            *previous = self.quasigroup.op(*previous, carry);
+            carry = *previous;
+        }
+    }
+}
+
+/// Hashes `data` in one call. Equivalent to a single `update` followed by `finalize`.
+pub fn digest(quasigroup: &Quasigroup, leaders: &[Symbol], data: &[u8]) -> Vec<Symbol> {
+    let mut hasher = QuasigroupHash::new(quasigroup, leaders);
+    hasher.update(data);
+    hasher.finalize()
+}
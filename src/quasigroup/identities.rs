@@ -0,0 +1,183 @@
+//! Checking the standard identities of quasigroups and loops on a Cayley table.
+//!
+//! An identity holds if both sides agree for every choice of the variables, so checking one with k
+//! variables looks at all n^k choices, stopping at the first that fails, which is returned as a
+//! counterexample. The identities here are the ones that pick out the classical varieties:
+//!
+//! - Steiner quasigroups, `x * x = x`, `x * y = y * x` and `x * (x * y) = y`, are those of Steiner triple
+//!   systems; unipotent ones, `x * x = y * y`, have a constant diagonal, as the Steiner loops of the same
+//!   systems do.
+//! - Bol loops satisfy the left Bol identity `x * (y * (x * z)) = (x * (y * x)) * z`, or its mirror image,
+//!   and Moufang loops both, which for loops is `(x * y) * (z * x) = (x * (y * z)) * x`. Groups are the
+//!   associative ones.
+//! - Medial, or entropic, quasigroups, `(x * y) * (z * w) = (x * z) * (y * w)`, are by the Toyoda-Bruck
+//!   theorem the affine ones, `x * y = f(x) + g(y) + c` over an abelian group.
+//!
+//! Sources:
+//!
+//! - Quasigroups and loops: introduction, H. O. Pflugfelder
+//! - A survey of binary systems, R. H. Bruck
+//! - [Quasigroup, Wikipedia](https://en.wikipedia.org/wiki/Quasigroup)
+
+use std::fmt;
+
+use crate::latin_square::Symbol;
+use crate::quasigroup::Quasigroup;
+
+/// An identity, or a set of identities, that a quasigroup may satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Identity {
+    /// `x * x = x`.
+    Idempotent,
+    /// `x * x = y * y`.
+    Unipotent,
+    /// `x * y = y * x`.
+    Commutative,
+    /// `(x * y) * z = x * (y * z)`.
+    Associative,
+    /// `x * (y * x) = (x * y) * x`.
+    Flexible,
+    /// `x * x = x`, `x * y = y * x` and `x * (x * y) = y`.
+    Steiner,
+    /// `x * (y * (x * z)) = (x * (y * x)) * z`.
+    LeftBol,
+    /// `((z * x) * y) * x = z * ((x * y) * x)`.
+    RightBol,
+    /// `(x * y) * (z * x) = (x * (y * z)) * x`.
+    Moufang,
+    /// `(x * y) * (z * w) = (x * z) * (y * w)`.
+    Medial
+}
+
+impl Identity {
+    /// Every identity, in the order they are reported.
+    pub const ALL: [Identity; 10] = [
+        Identity::Idempotent, Identity::Unipotent, Identity::Commutative, Identity::Associative, Identity::Flexible,
+        Identity::Steiner, Identity::LeftBol, Identity::RightBol, Identity::Moufang, Identity::Medial
+    ];
+
+    /// The number of variables.
+    pub fn variables(&self) -> usize {
+        match self {
+            Identity::Idempotent => 1,
+            Identity::Unipotent | Identity::Commutative | Identity::Flexible | Identity::Steiner => 2,
+            Identity::Associative | Identity::LeftBol | Identity::RightBol | Identity::Moufang => 3,
+            Identity::Medial => 4
+        }
+    }
+
+    /// Returns true if the identity holds for the given values of the variables.
+    fn holds_at(&self, quasigroup: &Quasigroup, values: &[Symbol]) -> bool {
+        let op = |a, b| quasigroup.op(a, b);
+        match *self {
+            Identity::Idempotent => op(values[0], values[0]) == values[0],
+            Identity::Unipotent => op(values[0], values[0]) == op(values[1], values[1]),
+            Identity::Commutative => op(values[0], values[1]) == op(values[1], values[0]),
+            Identity::Associative => {
+                let (x, y, z) = (values[0], values[1], values[2]);
+                op(op(x, y), z) == op(x, op(y, z))
+            },
+            Identity::Flexible => {
+                let (x, y) = (values[0], values[1]);
+                op(x, op(y, x)) == op(op(x, y), x)
+            },
+            Identity::Steiner => {
+                let (x, y) = (values[0], values[1]);
+                op(x, x) == x && op(x, y) == op(y, x) && op(x, op(x, y)) == y
+            },
+            Identity::LeftBol => {
+                let (x, y, z) = (values[0], values[1], values[2]);
+                op(x, op(y, op(x, z))) == op(op(x, op(y, x)), z)
+            },
+            Identity::RightBol => {
+                let (x, y, z) = (values[0], values[1], values[2]);
+                op(op(op(z, x), y), x) == op(z, op(op(x, y), x))
+            },
+            Identity::Moufang => {
+                let (x, y, z) = (values[0], values[1], values[2]);
+                op(op(x, y), op(z, x)) == op(op(x, op(y, z)), x)
+            },
+            Identity::Medial => {
+                let (x, y, z, w) = (values[0], values[1], values[2], values[3]);
+                op(op(x, y), op(z, w)) == op(op(x, z), op(y, w))
+            }
+        }
+    }
+
+    /// The first values of the variables, in lexicographic order, for which the identity fails, or None if
+    /// it holds.
+    ///
+    /// ```
+    /// use combinatorial_patterns::Quasigroup;
+    /// use combinatorial_patterns::quasigroup::identities::Identity;
+    ///
+    /// // x * y = -(x + y) mod 3 is medial, but not associative.
+    /// let quasigroup = Quasigroup::new_totally_symmetric(3).unwrap();
+    /// assert_eq!(Identity::Medial.counterexample(&quasigroup), None);
+    /// assert_eq!(Identity::Associative.counterexample(&quasigroup), Some(vec![0, 0, 1]));
+    /// ```
+    pub fn counterexample(&self, quasigroup: &Quasigroup) -> Option<Vec<Symbol>> {
+        let order = quasigroup.order();
+        let mut values = vec![0; self.variables()];
+        if order == 0 {
+            return None
+        }
+        loop {
+            if !self.holds_at(quasigroup, &values) {
+                return Some(values)
+            }
+            // The next values in lexicographic order, or the end once they wrap around.
+            let position = values.iter().rposition(|&value| value + 1 < order)?;
+            values[position] += 1;
+            for value in &mut values[position + 1..] {
+                *value = 0;
+            }
+        }
+    }
+
+    /// Returns true if the identity holds for all values of the variables.
+    pub fn holds(&self, quasigroup: &Quasigroup) -> bool {
+        self.counterexample(quasigroup).is_none()
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Identity::Idempotent => "idempotent",
+            Identity::Unipotent => "unipotent",
+            Identity::Commutative => "commutative",
+            Identity::Associative => "associative",
+            Identity::Flexible => "flexible",
+            Identity::Steiner => "Steiner",
+            Identity::LeftBol => "left Bol",
+            Identity::RightBol => "right Bol",
+            Identity::Moufang => "Moufang",
+            Identity::Medial => "medial"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The identities of `Identity::ALL` that hold in the quasigroup.
+///
+/// ```
+/// use combinatorial_patterns::{IncidenceCube, Quasigroup};
+/// use combinatorial_patterns::quasigroup::identities::{self, Identity};
+///
+/// // The Steiner quasigroup of the Fano plane.
+/// let steiner = Quasigroup::new_steiner(7).unwrap();
+/// let found = identities::satisfied(&steiner);
+/// assert!(found.contains(&Identity::Steiner) && found.contains(&Identity::Idempotent));
+/// assert!(!found.contains(&Identity::Associative));
+///
+/// // A group satisfies every loop identity.
+/// let group = Quasigroup::new(IncidenceCube::new_cyclic(4).unwrap().as_latin_square()).unwrap();
+/// assert_eq!(identities::satisfied(&group), vec![
+///     Identity::Commutative, Identity::Associative, Identity::Flexible, Identity::LeftBol, Identity::RightBol,
+///     Identity::Moufang, Identity::Medial
+/// ]);
+/// ```
+pub fn satisfied(quasigroup: &Quasigroup) -> Vec<Identity> {
+    Identity::ALL.iter().copied().filter(|identity| identity.holds(quasigroup)).collect()
+}
@@ -0,0 +1,241 @@
+//! The n-queens problem: placing n queens on an n x n board so that no two share a row, column, or diagonal.
+//!
+//! A solution is given as the column of the queen of each row, so it is a permutation of 0..n. Solutions
+//! are counted and listed by backtracking over the rows, keeping the attacked columns and diagonals as bit
+//! masks, which limits those to n <= 64. Counting uses the mirror symmetry of the board, placing the first
+//! queen on the left half only.
+//!
+//! Random solutions are found by min-conflicts: start from a random permutation, and keep moving a
+//! queen that is attacked to a column of its row with the fewest attacks, restarting if that gets stuck.
+//! Each move takes O(n) steps, and a few times n moves usually suffice, which handles boards with a few
+//! thousand rows. The solutions found are not uniformly distributed.
+//!
+//! Sources:
+//!
+//! - [Eight queens puzzle, Wikipedia](https://en.wikipedia.org/wiki/Eight_queens_puzzle)
+//! - [Minimizing conflicts: a heuristic repair method, Steven Minton, Mark D. Johnston, Andrew B. Philips, Philip Laird](https://doi.org/10.1016/0004-3702(92)90007-K)
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Returns true if `columns` places one queen per row with no two attacking each other.
+///
+/// ```
+/// use combinatorial_patterns::queens;
+///
+/// assert!(queens::is_solution(&[1, 3, 0, 2]));
+/// assert!(!queens::is_solution(&[0, 2, 1, 3]));
+/// ```
+pub fn is_solution(columns: &[usize]) -> bool {
+    let n = columns.len();
+    let mut column_used = vec![false; n];
+    let mut diagonal_used = vec![false; 2 * n];
+    let mut anti_diagonal_used = vec![false; 2 * n];
+    for (row, &column) in columns.iter().enumerate() {
+        if column >= n || column_used[column] || diagonal_used[row + n - column] || anti_diagonal_used[row + column] {
+            return false
+        }
+        column_used[column] = true;
+        diagonal_used[row + n - column] = true;
+        anti_diagonal_used[row + column] = true;
+    }
+    true
+}
+
+/// The number of solutions for an n x n board. Panics above n = 64.
+///
+/// ```
+/// use combinatorial_patterns::queens;
+///
+/// assert_eq!(queens::count_solutions(8), 92);
+/// assert_eq!(queens::count_solutions(3), 0);
+/// ```
+pub fn count_solutions(n: usize) -> u128 {
+    let full = full_mask(n);
+    if n == 0 {
+        return 1
+    }
+    // Mirroring the board swaps first queens left and right of the middle.
+    let half = n / 2;
+    let mut total = 0;
+    for column in 0..half {
+        let bit = 1 << column;
+        total += 2 * count_from(full, bit, (bit << 1) & full, bit >> 1);
+    }
+    if !n.is_multiple_of(2) {
+        let bit = 1 << half;
+        total += count_from(full, bit, (bit << 1) & full, bit >> 1);
+    }
+    total
+}
+
+fn count_from(full: u64, columns: u64, left: u64, right: u64) -> u128 {
+    if columns == full {
+        return 1
+    }
+    let mut candidates = full & !(columns | left | right);
+    let mut count = 0;
+    while candidates != 0 {
+        let bit = candidates & candidates.wrapping_neg();
+        candidates &= !bit;
+        count += count_from(full, columns | bit, ((left | bit) << 1) & full, (right | bit) >> 1);
+    }
+    count
+}
+
+/// Returns an iterator over every solution for an n x n board, in lexicographic order. Panics above n = 64.
+///
+/// ```
+/// use combinatorial_patterns::queens;
+///
+/// let solutions: Vec<Vec<usize>> = queens::solutions(4).collect();
+/// assert_eq!(solutions, vec![vec![1, 3, 0, 2], vec![2, 0, 3, 1]]);
+/// ```
+pub fn solutions(n: usize) -> Solutions {
+    let full = full_mask(n);
+    Solutions {
+        n,
+        full,
+        placed: Vec::with_capacity(n),
+        candidates: vec![full],
+        attacked: vec![(0, 0, 0)],
+        done: false
+    }
+}
+
+/// Iterator over the solutions of the n-queens problem, see `solutions`.
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    n: usize,
+    full: u64,
+    placed: Vec<usize>,
+    /// Per row, the columns still to try. One entry more than queens placed.
+    candidates: Vec<u64>,
+    /// Per row, the attacked (columns, left diagonals, right diagonals), shifted to that row.
+    attacked: Vec<(u64, u64, u64)>,
+    done: bool
+}
+
+impl Solutions {
+    fn backtrack(&mut self) {
+        self.candidates.pop();
+        self.attacked.pop();
+        self.placed.pop();
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        while !self.done {
+            let row = self.placed.len();
+            if row == self.n {
+                let found = self.placed.clone();
+                if self.n == 0 {
+                    self.done = true;
+                } else {
+                    self.backtrack();
+                }
+                return Some(found)
+            }
+            let candidates = self.candidates[row];
+            if candidates == 0 {
+                if row == 0 {
+                    self.done = true;
+                } else {
+                    self.backtrack();
+                }
+                continue
+            }
+            let bit = candidates & candidates.wrapping_neg();
+            self.candidates[row] &= !bit;
+            let (columns, left, right) = self.attacked[row];
+            let next = (columns | bit, ((left | bit) << 1) & self.full, (right | bit) >> 1);
+            self.placed.push(bit.trailing_zeros() as usize);
+            self.candidates.push(self.full & !(next.0 | next.1 | next.2));
+            self.attacked.push(next);
+        }
+        None
+    }
+}
+
+/// Returns a random solution for an n x n board by min-conflicts with `rng`, or None for n = 2 and n = 3,
+/// which have none.
+///
+/// ```
+/// use combinatorial_patterns::queens;
+///
+/// let mut rng = rand::thread_rng();
+/// let solution = queens::random_solution(200, &mut rng).unwrap();
+/// assert!(queens::is_solution(&solution));
+/// assert!(queens::random_solution(3, &mut rng).is_none());
+/// ```
+pub fn random_solution(n: usize, rng: &mut impl Rng) -> Option<Vec<usize>> {
+    if n == 2 || n == 3 {
+        return None
+    }
+    loop {
+        if let Some(solution) = min_conflicts(n, 50 * n.max(10), rng) {
+            return Some(solution)
+        }
+    }
+}
+
+/// One run of min-conflicts from a random permutation, giving up after `steps` moves.
+fn min_conflicts(n: usize, steps: usize, rng: &mut impl Rng) -> Option<Vec<usize>> {
+    let mut columns: Vec<usize> = (0..n).collect();
+    columns.shuffle(rng);
+    let mut column_count = vec![0usize; n];
+    let mut diagonal_count = vec![0usize; 2 * n];
+    let mut anti_diagonal_count = vec![0usize; 2 * n];
+    for (row, &column) in columns.iter().enumerate() {
+        column_count[column] += 1;
+        diagonal_count[row + n - column] += 1;
+        anti_diagonal_count[row + column] += 1;
+    }
+    // Attacks on a queen at (row, column), not counting the queen itself if it stands there.
+    let attacks = |counts: (&[usize], &[usize], &[usize]), row: usize, column: usize, own: bool| {
+        let (column_count, diagonal_count, anti_diagonal_count) = counts;
+        let total = column_count[column] + diagonal_count[row + n - column] + anti_diagonal_count[row + column];
+        if own { total - 3 } else { total }
+    };
+
+    for _ in 0..steps {
+        let counts = (&column_count[..], &diagonal_count[..], &anti_diagonal_count[..]);
+        let conflicted: Vec<usize> = (0..n).filter(|&row| attacks(counts, row, columns[row], true) > 0).collect();
+        let Some(&row) = conflicted.choose(rng) else {
+            return Some(columns)
+        };
+        let current = columns[row];
+        let mut best = Vec::new();
+        let mut fewest = usize::MAX;
+        for column in 0..n {
+            let count = attacks(counts, row, column, column == current);
+            if count < fewest {
+                fewest = count;
+                best.clear();
+            }
+            if count == fewest {
+                best.push(column);
+            }
+        }
+        let column = *best.choose(rng).expect("ProgrammingError: a row has columns.");
+        column_count[current] -= 1;
+        diagonal_count[row + n - current] -= 1;
+        anti_diagonal_count[row + current] -= 1;
+        columns[row] = column;
+        column_count[column] += 1;
+        diagonal_count[row + n - column] += 1;
+        anti_diagonal_count[row + column] += 1;
+    }
+    None
+}
+
+fn full_mask(n: usize) -> u64 {
+    match n {
+        0..=63 => (1 << n) - 1,
+        64 => u64::MAX,
+        _ => panic!("Bit mask backtracking for n-queens is limited to n = 64, not {}", n)
+    }
+}
@@ -0,0 +1,263 @@
+//! A battery of empirical tests of the randomness of generated latin squares.
+//!
+//! One statistic can look uniform while others do not, so the battery runs several over a stream of squares
+//! of one order, each from a fresh shuffle as `batch::square` draws them, except for the serial test:
+//!
+//! - Cell-symbol marginals: how often each cell holds each symbol, against 1 / n. The counts have every
+//!   line sum fixed, so under uniformity their chi-square statistic is (n / (n - 1))^2 times a chi-square
+//!   with (n - 1)^3 degrees of freedom, by the symmetry of the uniform distribution under isotopy.
+//! - Pairwise cells: the joint symbols of cell (0, 0) with a cell in its row, one in its column, and one in
+//!   neither, against their exact distributions. Two cells in a line hold any two distinct symbols equally
+//!   often; two cells in no common line hold the same symbol with probability 1 / (n (n - 1)).
+//! - Serial correlation: one cube walks on, n^3 moves between samples, and consecutive squares should agree
+//!   in n cells on average, as independent ones do. The agreements of overlapping pairs are uncorrelated
+//!   under independence, so their mean is tested against n with a normal approximation.
+//! - Intercalates: the mean number of intercalates against its exact value, from all reduced squares of
+//!   the order, which is known for orders up to `EXACT_MOMENTS_ORDER` and left out above it.
+//!
+//! Each test gives a p-value, and passes if it is at least the significance divided by the number of tests,
+//! so the whole battery fails a perfect generator with probability at most the significance. The chi-square
+//! approximations want expected counts of at least 5, so use at least 5 n^2 samples.
+//!
+//! The battery is what showed that the walk has to count its moves from proper cubes only, see
+//! `IncidenceCube::shuffle`. It still fails at order 2, where every move swaps the two squares, so n^3 moves
+//! always end on the cyclic one.
+//!
+//! Sources:
+//!
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+//! - The distribution of chi-square, E. B. Wilson, M. M. Hilferty, Proceedings of the National Academy of Sciences 17 (1931)
+//! - Numerical recipes, W. H. Press, S. A. Teukolsky, W. T. Vetterling, B. P. Flannery, section 6.2
+
+use std::fmt;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::generator::Generator;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+use crate::partial::PartialLatinSquare;
+use crate::switching;
+
+/// The largest order whose intercalate moments are computed exactly, from its reduced squares.
+pub const EXACT_MOMENTS_ORDER: usize = 6;
+
+/// The result of one test of the battery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub name: String,
+    pub statistic: f64,
+    pub p_value: f64,
+    pub passed: bool
+}
+
+/// The results of the battery.
+///
+/// ```
+/// use combinatorial_patterns::randomness;
+///
+/// let report = randomness::run_battery(4, 400, 7, 0.001).unwrap();
+/// assert_eq!(report.tests.len(), 6);
+/// assert!(report.passed(), "{}", report);
+/// assert!(report.to_string().starts_with("Randomness tests of order 4"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessReport {
+    pub order: usize,
+    pub samples: usize,
+    /// The probability of failing a perfect generator.
+    pub significance: f64,
+    pub tests: Vec<TestResult>
+}
+
+impl RandomnessReport {
+    /// Returns true if every test passed.
+    pub fn passed(&self) -> bool {
+        self.tests.iter().all(|test| test.passed)
+    }
+}
+
+impl fmt::Display for RandomnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Randomness tests of order {}: {} samples, significance {}", self.order, self.samples, self.significance)?;
+        for test in &self.tests {
+            writeln!(f, "{}: statistic {:.3}, p-value {:.4}, {}", test.name, test.statistic, test.p_value, if test.passed { "passed" } else { "FAILED" })?;
+        }
+        write!(f, "{}", if self.passed() { "Passed" } else { "Failed" })
+    }
+}
+
+/// Runs every test on `samples` squares of the given order, drawn from the seed. Returns None below order 2
+/// or 2 samples.
+pub fn run_battery(order: usize, samples: usize, seed: u64, significance: f64) -> Option<RandomnessReport> {
+    if order < 2 || samples < 2 {
+        return None
+    }
+    let mut generator = Generator::from_seed(order, seed).expect("ProgrammingError: the order is at least 2.");
+    let squares: Vec<LatinSquare> = (0..samples).map(|_| LatinSquare::from_rows(generator.next_square().square.clone())).collect();
+    let mut tests = vec![("cell-symbol marginals".to_string(), marginals(&squares))];
+    let last = order - 1;
+    for (row, column) in [(0, last), (last, 0), (last, last)] {
+        tests.push((format!("cells (0, 0) and ({}, {})", row, column), pair(&squares, (row, column))));
+    }
+    tests.push(("serial agreement".to_string(), serial(order, samples, seed)));
+    if let Some((mean, variance)) = intercalate_moments(order) {
+        tests.push(("intercalate mean".to_string(), intercalates(&squares, mean, variance)));
+    }
+    let level = significance / tests.len() as f64;
+    let tests = tests.into_iter().map(|(name, (statistic, p_value))| TestResult { name, statistic, p_value, passed: p_value >= level }).collect();
+    Some(RandomnessReport { order, samples, significance, tests })
+}
+
+/// The exact mean and variance of the number of intercalates of a uniformly random square, for orders up
+/// to `EXACT_MOMENTS_ORDER`.
+///
+/// Isotopy keeps the number of intercalates, and every reduced square is isotopic to as many squares as
+/// any other, so the moments over the reduced squares are those over all squares.
+///
+/// ```
+/// use combinatorial_patterns::randomness;
+///
+/// // Of the 4 reduced squares of order 4, the square of the Klein group has 12 intercalates and the
+/// // others 4.
+/// assert_eq!(randomness::intercalate_moments(4), Some((6.0, 12.0)));
+/// assert_eq!(randomness::intercalate_moments(7), None);
+/// ```
+pub fn intercalate_moments(order: usize) -> Option<(f64, f64)> {
+    if order == 0 || order > EXACT_MOMENTS_ORDER {
+        return None
+    }
+    let mut reduced = PartialLatinSquare::new(order);
+    for index in 0..order {
+        reduced.set(0, index, Some(index));
+        reduced.set(index, 0, Some(index));
+    }
+    let counts: Vec<f64> = reduced.completions(usize::MAX).iter().map(|square| switching::intercalates(square).len() as f64).collect();
+    let mean = counts.iter().sum::<f64>() / counts.len() as f64;
+    let variance = counts.iter().map(|count| (count - mean).powi(2)).sum::<f64>() / counts.len() as f64;
+    Some((mean, variance))
+}
+
+/// The scaled chi-square statistic of the cell-symbol counts, and its p-value.
+fn marginals(squares: &[LatinSquare]) -> (f64, f64) {
+    let order = squares[0].size();
+    let mut counts = vec![0u64; order * order * order];
+    for square in squares {
+        for (row, symbols) in square.square.iter().enumerate() {
+            for (column, &symbol) in symbols.iter().enumerate() {
+                counts[(row * order + column) * order + symbol] += 1;
+            }
+        }
+    }
+    let expected = squares.len() as f64 / order as f64;
+    let chi_square: f64 = counts.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum();
+    let scale = (order as f64 / (order - 1) as f64).powi(2);
+    let statistic = chi_square / scale;
+    (statistic, chi_square_upper_tail(statistic, (order - 1).pow(3) as f64))
+}
+
+/// The chi-square statistic of the joint symbols of cell (0, 0) and the other cell, and its p-value.
+fn pair(squares: &[LatinSquare], (row, column): (usize, usize)) -> (f64, f64) {
+    let order = squares[0].size();
+    let mut observed = vec![0u64; order * order];
+    for square in squares {
+        observed[square.square[0][0] * order + square.square[row][column]] += 1;
+    }
+    let n = order as f64;
+    let shares_line = row == 0 || column == 0;
+    let probabilities: Vec<f64> = (0..order * order).map(|pair| {
+        match (pair / order == pair % order, shares_line) {
+            (true, true) => 0.0,
+            (false, true) => 1.0 / (n * (n - 1.0)),
+            (true, false) => 1.0 / (n * (n - 1.0)),
+            (false, false) => (n - 2.0) / (n * (n - 1.0) * (n - 1.0))
+        }
+    }).collect();
+    goodness_of_fit(&observed, &probabilities)
+}
+
+/// The normal score of the mean agreement of consecutive squares of one walk, and its p-value.
+fn serial(order: usize, samples: usize, seed: u64) -> (f64, f64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut cube = IncidenceCube::new_cyclic(order).expect("ProgrammingError: the battery runs from order 2.");
+    let moves = IncidenceCube::mixing_moves(order);
+    cube.advance(moves, &mut rng);
+    let mut previous = cube.as_latin_square();
+    let agreements: Vec<f64> = (1..samples).map(|_| {
+        cube.advance(moves, &mut rng);
+        let next = cube.as_latin_square();
+        let agreement = previous.square.iter().flatten().zip(next.square.iter().flatten()).filter(|(a, b)| a == b).count();
+        previous = next;
+        agreement as f64
+    }).collect();
+    let (mean, variance) = mean_and_variance(&agreements);
+    normal_test(mean, order as f64, variance / agreements.len() as f64)
+}
+
+/// The normal score of the mean number of intercalates, and its p-value.
+fn intercalates(squares: &[LatinSquare], mean: f64, variance: f64) -> (f64, f64) {
+    let counts: Vec<f64> = squares.iter().map(|square| switching::intercalates(square).len() as f64).collect();
+    let (observed, _) = mean_and_variance(&counts);
+    normal_test(observed, mean, variance / counts.len() as f64)
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() as f64 - 1.0).max(1.0);
+    (mean, variance)
+}
+
+/// The score of `observed` against `expected` with the given variance, and its two-sided p-value. A zero
+/// variance passes only an exact match.
+fn normal_test(observed: f64, expected: f64, variance: f64) -> (f64, f64) {
+    if variance <= 0.0 {
+        return if observed == expected { (0.0, 1.0) } else { (f64::INFINITY, 0.0) }
+    }
+    let score = (observed - expected) / variance.sqrt();
+    (score, (2.0 * normal_upper_tail(score.abs())).min(1.0))
+}
+
+/// Pearson's chi-square statistic of the counts against the probabilities, and its p-value. A count in a
+/// category of probability 0 fails outright.
+fn goodness_of_fit(observed: &[u64], probabilities: &[f64]) -> (f64, f64) {
+    let total: u64 = observed.iter().sum();
+    let mut statistic = 0.0;
+    let mut categories = 0;
+    for (&count, &probability) in observed.iter().zip(probabilities) {
+        if probability == 0.0 {
+            if count > 0 {
+                return (f64::INFINITY, 0.0)
+            }
+            continue
+        }
+        let expected = total as f64 * probability;
+        statistic += (count as f64 - expected).powi(2) / expected;
+        categories += 1;
+    }
+    (statistic, chi_square_upper_tail(statistic, (categories - 1) as f64))
+}
+
+/// The probability that a chi-square variable with the degrees of freedom exceeds `statistic`, by the
+/// Wilson-Hilferty cube root approximation.
+fn chi_square_upper_tail(statistic: f64, degrees: f64) -> f64 {
+    if degrees <= 0.0 {
+        return 1.0
+    }
+    let spread = 2.0 / (9.0 * degrees);
+    let score = ((statistic / degrees).cbrt() - (1.0 - spread)) / spread.sqrt();
+    normal_upper_tail(score)
+}
+
+/// The probability that a standard normal variable exceeds `score`.
+fn normal_upper_tail(score: f64) -> f64 {
+    0.5 * complementary_error_function(score / std::f64::consts::SQRT_2)
+}
+
+/// erfc by the Chebyshev fit of Numerical Recipes, with a relative error below 1.2e-7.
+fn complementary_error_function(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let coefficients = [-1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806, 0.27886807, -1.13520398, 1.48851587, -0.82215223, 0.17087277];
+    let polynomial = coefficients.iter().rev().fold(0.0, |sum, coefficient| sum * t + coefficient);
+    let value = t * (-x * x + polynomial).exp();
+    if x >= 0.0 { value } else { 2.0 - value }
+}
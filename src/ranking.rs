@@ -0,0 +1,159 @@
+//! Ranking and unranking combinations and permutations: numbering them 0, 1, 2, .. and going back and forth.
+//!
+//! Combinations use the combinatorial number system. A k-combination c_1 < c_2 < .. < c_k of 0, 1, 2, ..
+//! has the rank C(c_1, 1) + C(c_2, 2) + .. + C(c_k, k), which numbers the k-combinations of 0..n with
+//! 0..C(n, k) in colexicographic order, for every n at once. Unranking picks the elements greedily from the
+//! largest down, each time the largest c with C(c, i) not above the remaining rank.
+//!
+//! Permutations use the factorial number system. The digit of position i is how many of the elements after
+//! it are smaller, the Lehmer code, so the ranks number the permutations of 0..n with 0..n! in lexicographic
+//! order. Ranks fit in a u128 up to n = 34, which is the limit for permutations here.
+//!
+//! Together these give random access into the sequences of combinations and permutations, and uniform
+//! samples from a uniform rank.
+//!
+//! Sources:
+//!
+//! - [Combinatorial number system, Wikipedia](https://en.wikipedia.org/wiki/Combinatorial_number_system)
+//! - [Factorial number system, Wikipedia](https://en.wikipedia.org/wiki/Factorial_number_system)
+
+use crate::numbers::binomial;
+
+/// The largest n for which the ranks of all permutations of 0..n fit in a u128.
+pub const MAX_PERMUTATION_LENGTH: usize = 34;
+
+/// The rank of a strictly increasing combination in the combinatorial number system, or None if it is not
+/// strictly increasing or its rank doesn't fit in a u128.
+///
+/// ```
+/// use combinatorial_patterns::ranking;
+///
+/// // The 2-combinations of 0..4 in colexicographic order.
+/// let combinations = [[0, 1], [0, 2], [1, 2], [0, 3], [1, 3], [2, 3]];
+/// for (rank, combination) in combinations.iter().enumerate() {
+///     assert_eq!(ranking::combination_rank(combination), Some(rank as u128));
+///     assert_eq!(ranking::combination_unrank(4, 2, rank as u128), Some(combination.to_vec()));
+/// }
+/// assert_eq!(ranking::combination_rank(&[2, 1]), None);
+/// ```
+pub fn combination_rank(combination: &[usize]) -> Option<u128> {
+    if combination.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return None
+    }
+    combination.iter().enumerate()
+        .try_fold(0u128, |rank, (index, &element)| rank.checked_add(binomial(element, index + 1)?))
+}
+
+/// The k-combination of 0..n with the given rank in the combinatorial number system, or None if the rank is
+/// at least C(n, k). Takes O(n + k) steps.
+pub fn combination_unrank(n: usize, k: usize, rank: u128) -> Option<Vec<usize>> {
+    if k > n || rank >= binomial(n, k)? {
+        return None
+    }
+    let mut combination = vec![0; k];
+    let mut remaining = rank;
+    let mut candidate = n;
+    for size in (1..=k).rev() {
+        // The largest candidate below the previous element with C(candidate, size) <= remaining.
+        // C(candidate, size) is below C(n, k) here, so it fits.
+        candidate -= 1;
+        let mut ways = binomial(candidate, size).expect("ProgrammingError: smaller binomials fit.");
+        while ways > remaining {
+            // C(c - 1, size) = C(c, size) (c - size) / c.
+            ways = ways / candidate as u128 * (candidate - size) as u128
+                + ways % candidate as u128 * (candidate - size) as u128 / candidate as u128;
+            candidate -= 1;
+        }
+        combination[size - 1] = candidate;
+        remaining -= ways;
+    }
+    Some(combination)
+}
+
+/// The factorial number system digits of a value with n digits, most significant first, where the digit
+/// at position i is below n - i. Returns None if the value is at least n!.
+///
+/// ```
+/// use combinatorial_patterns::ranking;
+///
+/// // 463 = 3 * 5! + 4 * 4! + 1 * 3! + 0 * 2! + 1 * 1! + 0 * 0!
+/// assert_eq!(ranking::to_factoradic(463, 6), Some(vec![3, 4, 1, 0, 1, 0]));
+/// assert_eq!(ranking::from_factoradic(&[3, 4, 1, 0, 1, 0]), Some(463));
+/// assert_eq!(ranking::to_factoradic(720, 6), None);
+/// ```
+pub fn to_factoradic(value: u128, n: usize) -> Option<Vec<usize>> {
+    let mut digits = vec![0; n];
+    let mut remaining = value;
+    for radix in 1..=n {
+        digits[n - radix] = (remaining % radix as u128) as usize;
+        remaining /= radix as u128;
+    }
+    if remaining != 0 {
+        return None
+    }
+    Some(digits)
+}
+
+/// The value of factorial number system digits, most significant first, or None if some digit at position
+/// i is not below n - i or the value doesn't fit in a u128.
+pub fn from_factoradic(digits: &[usize]) -> Option<u128> {
+    let n = digits.len();
+    digits.iter().enumerate().try_fold(0u128, |value, (position, &digit)| {
+        let radix = n - position;
+        if digit >= radix {
+            return None
+        }
+        value.checked_mul(radix as u128)?.checked_add(digit as u128)
+    })
+}
+
+/// The lexicographic rank of a permutation of 0..n, or None if it is not a permutation or n is above
+/// `MAX_PERMUTATION_LENGTH`. Takes O(n) steps.
+///
+/// ```
+/// use combinatorial_patterns::ranking;
+///
+/// assert_eq!(ranking::permutation_rank(&[0, 1, 2]), Some(0));
+/// assert_eq!(ranking::permutation_rank(&[1, 0, 2]), Some(2));
+/// assert_eq!(ranking::permutation_rank(&[2, 1, 0]), Some(5));
+/// assert_eq!(ranking::permutation_unrank(3, 2), Some(vec![1, 0, 2]));
+/// assert_eq!(ranking::permutation_rank(&[0, 0, 1]), None);
+/// ```
+pub fn permutation_rank(permutation: &[usize]) -> Option<u128> {
+    let n = permutation.len();
+    if n > MAX_PERMUTATION_LENGTH {
+        return None
+    }
+    // Elements not yet seen, as a bit mask, so the smaller ones are counted in one step.
+    let mut unseen: u64 = (1 << n) - 1;
+    let mut digits = Vec::with_capacity(n);
+    for &element in permutation {
+        if element >= n || unseen & (1 << element) == 0 {
+            return None
+        }
+        digits.push((unseen & ((1 << element) - 1)).count_ones() as usize);
+        unseen &= !(1 << element);
+    }
+    from_factoradic(&digits)
+}
+
+/// The permutation of 0..n with the given lexicographic rank, or None if the rank is at least n! or n is above
+/// `MAX_PERMUTATION_LENGTH`.
+pub fn permutation_unrank(n: usize, rank: u128) -> Option<Vec<usize>> {
+    if n > MAX_PERMUTATION_LENGTH {
+        return None
+    }
+    let digits = to_factoradic(rank, n)?;
+    let mut unused: u64 = (1 << n) - 1;
+    let permutation = digits.iter().map(|&digit| {
+        // The digit-th smallest unused element.
+        let mut remaining = unused;
+        for _ in 0..digit {
+            remaining &= remaining - 1;
+        }
+        let element = remaining.trailing_zeros() as usize;
+        unused &= !(1 << element);
+        element
+    }).collect();
+    Some(permutation)
+}
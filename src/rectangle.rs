@@ -0,0 +1,231 @@
+//! Latin rectangles: k x n arrays of the symbols 0..n where no symbol repeats in a row or column.
+//!
+//! Every latin rectangle with fewer than n rows can be extended by another row, and so completed to a
+//! latin square. The number of ways to add the next row is the permanent of the availability matrix,
+//! which has a 1 where a symbol does not appear in a column yet.
+//!
+//! Counting all completions adds rows one at a time, and remembers the count for every availability
+//! matrix already seen. Reordering the columns of a rectangle doesn't change its counts, so rectangles that
+//! differ only in that share their count; in particular all first rows do. The last two rows are not
+//! enumerated: with n - 2 rows in place, every extension has exactly one completion. This is practical
+//! up to order 7 or so when starting from few rows.
+//!
+//! With the `bigint` feature, counts can also be returned as a `num_bigint::BigUint`, which never overflows.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+use rand::Rng;
+
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::matching::BipartiteGraph;
+use crate::numbers::Natural;
+use crate::permanent::permanent;
+
+/// A latin rectangle with n columns and symbols 0..n, and up to n rows.
+///
+/// ```
+/// use combinatorial_patterns::rectangle::LatinRectangle;
+///
+/// let mut rectangle = LatinRectangle::new(4);
+/// assert_eq!(rectangle.count_extensions(), 24);
+/// assert_eq!(rectangle.count_completions(), Some(576));
+///
+/// assert!(rectangle.push_row(vec![0, 1, 2, 3]));
+/// // The derangements of 4 symbols.
+/// assert_eq!(rectangle.count_extensions(), 9);
+/// assert_eq!(rectangle.count_completions(), Some(24));
+///
+/// // Repeats 0 in the first column.
+/// assert!(!rectangle.push_row(vec![0, 2, 3, 1]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatinRectangle {
+    order: usize,
+    rows: Vec<Vec<Symbol>>
+}
+
+impl LatinRectangle {
+    /// Creates a latin rectangle with n columns and no rows yet.
+    pub fn new(order: usize) -> LatinRectangle {
+        LatinRectangle {
+            order,
+            rows: Vec::new()
+        }
+    }
+
+    /// Creates a latin rectangle from rows of symbols 0..order.
+    ///
+    /// Returns None if there are more rows than the order, or the rows don't form a latin rectangle.
+    pub fn from_rows(order: usize, rows: Vec<Vec<Symbol>>) -> Option<LatinRectangle> {
+        let mut rectangle = LatinRectangle::new(order);
+        for row in rows {
+            if !rectangle.push_row(row) {
+                return None
+            }
+        }
+        Some(rectangle)
+    }
+
+    /// Creates a latin rectangle from the first rows of a square.
+    pub fn from_square(square: &LatinSquare, rows: usize) -> LatinRectangle {
+        LatinRectangle {
+            order: square.size(),
+            rows: square.square.iter().take(rows).cloned().collect()
+        }
+    }
+
+    /// The number of columns, which is also the number of symbols.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The rows of the rectangle.
+    pub fn rows(&self) -> &[Vec<Symbol>] {
+        &self.rows
+    }
+
+    /// Returns true if the rectangle has as many rows as columns, i.e. is a latin square.
+    pub fn is_complete(&self) -> bool {
+        self.rows.len() == self.order
+    }
+
+    /// The rectangle as a latin square, if it is complete.
+    pub fn as_latin_square(&self) -> Option<LatinSquare> {
+        if self.is_complete() {
+            Some(LatinSquare::from_rows(self.rows.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Adds a row at the bottom, if it keeps the rectangle latin. Returns false, leaving the rectangle
+    /// unchanged, if it doesn't or the rectangle is already complete.
+    pub fn push_row(&mut self, row: Vec<Symbol>) -> bool {
+        if self.is_complete() || row.len() != self.order {
+            return false
+        }
+        let mut in_row = vec![false; self.order];
+        for (column, &symbol) in row.iter().enumerate() {
+            if symbol >= self.order || in_row[symbol] || self.rows.iter().any(|other| other[column] == symbol) {
+                return false
+            }
+            in_row[symbol] = true;
+        }
+        self.rows.push(row);
+        true
+    }
+
+    /// Adds a row drawn uniformly with `rng` among the possible next rows, see `BipartiteGraph::random_perfect_matching`.
+    /// Returns false if the rectangle is already complete.
+    ///
+    /// Repeating this until complete gives a random latin square, though not a uniformly distributed one.
+    ///
+    /// ```
+    /// use combinatorial_patterns::Quasigroup;
+    /// use combinatorial_patterns::rectangle::LatinRectangle;
+    ///
+    /// let mut rectangle = LatinRectangle::new(6);
+    /// while rectangle.push_random_row(&mut rand::thread_rng()) {}
+    /// assert!(Quasigroup::new(rectangle.as_latin_square().unwrap()).is_some());
+    /// ```
+    pub fn push_random_row(&mut self, rng: &mut impl Rng) -> bool {
+        if self.is_complete() {
+            return false
+        }
+        let mut graph = BipartiteGraph::new(self.order, self.order);
+        for (column, free) in self.availability_matrix().iter().enumerate() {
+            for symbol in (0..self.order).filter(|&symbol| free[symbol]) {
+                graph.add_edge(column, symbol);
+            }
+        }
+        let matching = graph.random_perfect_matching(rng).expect("ProgrammingError: a latin rectangle always has a next row.");
+        self.rows.push((0..self.order).map(|column| matching.right_of(column).expect("ProgrammingError: perfect matchings cover every column.")).collect());
+        true
+    }
+
+    /// `matrix[column][symbol]` is true if the symbol does not appear in the column yet.
+    pub fn availability_matrix(&self) -> Vec<Vec<bool>> {
+        let mut matrix = vec![vec![true; self.order]; self.order];
+        for row in &self.rows {
+            for (column, &symbol) in row.iter().enumerate() {
+                matrix[column][symbol] = false;
+            }
+        }
+        matrix
+    }
+
+    /// The number of ways to add the next row, which is 1 for a complete rectangle.
+    pub fn count_extensions(&self) -> u128 {
+        if self.is_complete() {
+            return 1
+        }
+        permanent(&self.availability_matrix())
+    }
+
+    /// The number of ways to complete the rectangle to a latin square, or None if it doesn't fit in a u128.
+    /// Panics above order 128.
+    pub fn count_completions(&self) -> Option<u128> {
+        self.count_completions_as()
+    }
+
+    /// The number of ways to complete the rectangle to a latin square. Panics above order 128.
+    #[cfg(feature = "bigint")]
+    pub fn count_completions_big(&self) -> BigUint {
+        self.count_completions_as().expect("ProgrammingError: big integer sums don't overflow.")
+    }
+
+    fn count_completions_as<T: Natural>(&self) -> Option<T> {
+        if self.rows.len() + 2 >= self.order {
+            return Some(T::from_u128(self.count_extensions()))
+        }
+        if self.order > 128 {
+            panic!("Counting completions of latin rectangles is limited to order 128, not {}", self.order);
+        }
+        let free: Vec<u128> = self.availability_matrix().iter()
+            .map(|column| column.iter().enumerate().filter(|&(_, &free)| free).map(|(symbol, _)| 1 << symbol).sum())
+            .collect();
+        completions(self.order, free, self.rows.len(), &mut HashMap::new())
+    }
+}
+
+/// Counts the completions of a rectangle with `filled` rows, given as the bit masks of the free symbols
+/// of each column. Counts are remembered in `seen` by the sorted masks.
+fn completions<T: Natural>(order: usize, free: Vec<u128>, filled: usize, seen: &mut HashMap<Vec<u128>, T>) -> Option<T> {
+    if filled + 2 >= order {
+        let matrix: Vec<Vec<bool>> = free.iter().map(|&mask| (0..order).map(|symbol| mask & (1 << symbol) != 0).collect()).collect();
+        return Some(T::from_u128(permanent(&matrix)))
+    }
+    let mut key = free.clone();
+    key.sort_unstable();
+    if let Some(count) = seen.get(&key) {
+        return Some(count.clone())
+    }
+
+    let mut rows = Vec::new();
+    next_rows(&free, 0, 0, &mut Vec::new(), &mut rows);
+    let mut total = T::from_u128(0);
+    for row in rows {
+        let next = free.iter().zip(row.iter()).map(|(&mask, &symbol)| mask & !(1 << symbol)).collect();
+        total = total.checked_add(&completions(order, next, filled + 1, seen)?)?;
+    }
+    seen.insert(key, total.clone());
+    Some(total)
+}
+
+/// Collects every row that picks a distinct free symbol in each column, from `column` onwards.
+fn next_rows(free: &[u128], column: usize, used: u128, row: &mut Vec<Symbol>, rows: &mut Vec<Vec<Symbol>>) {
+    if column == free.len() {
+        rows.push(row.clone());
+        return
+    }
+    let mut options = free[column] & !used;
+    while options != 0 {
+        let symbol = options.trailing_zeros() as usize;
+        options &= options - 1;
+        row.push(symbol);
+        next_rows(free, column + 1, used | (1 << symbol), row, rows);
+        row.pop();
+    }
+}
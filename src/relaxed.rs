@@ -0,0 +1,260 @@
+//! Row-latin and column-latin squares: n x n arrays of the symbols 0..n where only the rows, or only the
+//! columns, are permutations.
+//!
+//! They relax a latin square to one direction, as when every worker does every task once but two may do
+//! the same task at once. A random row-latin square is just n independent random permutations, which makes
+//! it an easy starting point for repair. The defect measures how far it is from latin: the sum, over the
+//! columns, of the symbols missing from the column. Each missing symbol needs at least one cell changed, so
+//! the defect is a lower bound on the number of cells to change, and it is 0 exactly for latin squares.
+//!
+//! `repair` is a local search that swaps two cells of a row, keeping the rows permutations, until the
+//! defect reaches 0. A swap that lowers the defect is always taken, and one that keeps it the same often
+//! enough to wander off plateaus.
+//!
+//! Sources:
+//!
+//! - Latin squares and their applications, J. Dénes, A. D. Keedwell
+//! - Minimizing conflicts: a heuristic repair method for constraint satisfaction and scheduling problems,
+//!   S. Minton, M. D. Johnston, A. B. Philips, P. Laird, Artificial Intelligence 58 (1992)
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{LatinSquare, Symbol};
+
+/// An n x n array whose rows are permutations of 0..n.
+///
+/// ```
+/// use combinatorial_patterns::relaxed::RowLatinSquare;
+///
+/// let square = RowLatinSquare::from_rows(vec![vec![0, 1, 2], vec![0, 2, 1], vec![2, 0, 1]]).unwrap();
+/// // Column 0 misses 1 and column 2 misses 0.
+/// assert_eq!(square.defects(), vec![1, 0, 1]);
+/// assert_eq!(square.defect(), 2);
+/// assert!(square.to_latin_square().is_none());
+/// assert!(RowLatinSquare::from_rows(vec![vec![0, 0], vec![1, 0]]).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowLatinSquare {
+    rows: Vec<Vec<Symbol>>
+}
+
+impl RowLatinSquare {
+    /// Creates a row-latin square from its rows. Returns None unless there are n rows, each a permutation
+    /// of 0..n.
+    pub fn from_rows(rows: Vec<Vec<Symbol>>) -> Option<RowLatinSquare> {
+        if rows.iter().all(|row| is_permutation(row, rows.len())) {
+            Some(RowLatinSquare { rows })
+        } else {
+            None
+        }
+    }
+
+    /// The rows of a latin square.
+    pub fn from_square(square: &LatinSquare) -> RowLatinSquare {
+        RowLatinSquare { rows: square.square.clone() }
+    }
+
+    /// A row-latin square with every row an independent uniformly random permutation, drawn from `rng`.
+    /// Fails for order 0.
+    pub fn new_random(order: usize, rng: &mut impl Rng) -> Result<RowLatinSquare, CombinatorialError> {
+        if order == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        Ok(RowLatinSquare {
+            rows: (0..order).map(|_| {
+                let mut row: Vec<Symbol> = (0..order).collect();
+                row.shuffle(rng);
+                row
+            }).collect()
+        })
+    }
+
+    /// The order n.
+    pub fn order(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The rows.
+    pub fn rows(&self) -> &[Vec<Symbol>] {
+        &self.rows
+    }
+
+    /// The number of symbols missing from each column.
+    pub fn defects(&self) -> Vec<usize> {
+        let order = self.order();
+        (0..order).map(|column| missing(self.rows.iter().map(|row| row[column]), order)).collect()
+    }
+
+    /// The distance to latin: the symbols missing from the columns, in total.
+    pub fn defect(&self) -> usize {
+        self.defects().iter().sum()
+    }
+
+    /// Returns true if the columns are permutations too.
+    pub fn is_latin(&self) -> bool {
+        self.defect() == 0
+    }
+
+    /// The latin square, if the columns are permutations too.
+    pub fn to_latin_square(&self) -> Option<LatinSquare> {
+        if !self.is_latin() {
+            return None
+        }
+        Some(LatinSquare::from_rows(self.rows.clone()))
+    }
+
+    /// The transpose, whose columns are the rows of this one.
+    pub fn transpose(&self) -> ColumnLatinSquare {
+        ColumnLatinSquare { rows: transposed(&self.rows) }
+    }
+
+    /// Swaps random pairs of cells within rows, drawn from `rng`, at most `max_swaps` times, to bring the
+    /// defect down to 0. Returns true if the square became latin.
+    ///
+    /// ```
+    /// use combinatorial_patterns::relaxed::RowLatinSquare;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let mut square = RowLatinSquare::new_random(6, &mut rng).unwrap();
+    /// assert!(square.repair(1_000_000, &mut rng));
+    /// assert!(square.to_latin_square().is_some());
+    /// ```
+    pub fn repair(&mut self, max_swaps: usize, rng: &mut impl Rng) -> bool {
+        let order = self.order();
+        // counts[column][symbol] is the number of times the symbol is in the column.
+        let mut counts = vec![vec![0usize; order]; order];
+        for row in &self.rows {
+            for (column, &symbol) in row.iter().enumerate() {
+                counts[column][symbol] += 1;
+            }
+        }
+        let mut defect = self.defect();
+        for _ in 0..max_swaps {
+            if defect == 0 {
+                return true
+            }
+            let row = rng.gen_range(0..order);
+            let (a, b) = (rng.gen_range(0..order), rng.gen_range(0..order));
+            let (x, y) = (self.rows[row][a], self.rows[row][b]);
+            if a == b {
+                continue
+            }
+            // Moving x out of column a, and y into it, and the other way round in column b.
+            let change = |counts: &Vec<Vec<usize>>, column: usize, out: Symbol, into: Symbol| -> isize {
+                (counts[column][out] == 1) as isize - (counts[column][into] == 0) as isize
+            };
+            let delta = change(&counts, a, x, y) + change(&counts, b, y, x);
+            if delta < 0 || (delta == 0 && rng.gen_bool(0.5)) {
+                counts[a][x] -= 1;
+                counts[a][y] += 1;
+                counts[b][y] -= 1;
+                counts[b][x] += 1;
+                self.rows[row].swap(a, b);
+                defect = (defect as isize + delta) as usize;
+            }
+        }
+        defect == 0
+    }
+}
+
+/// An n x n array whose columns are permutations of 0..n, the transpose of a row-latin square.
+///
+/// ```
+/// use combinatorial_patterns::relaxed::ColumnLatinSquare;
+///
+/// let square = ColumnLatinSquare::new_random(5, &mut rand::thread_rng()).unwrap();
+/// assert_eq!(square.transpose().defect(), square.defect());
+/// assert_eq!(square.defects().len(), 5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnLatinSquare {
+    rows: Vec<Vec<Symbol>>
+}
+
+impl ColumnLatinSquare {
+    /// Creates a column-latin square from its rows. Returns None unless there are n rows of n symbols, and
+    /// every column is a permutation of 0..n.
+    pub fn from_rows(rows: Vec<Vec<Symbol>>) -> Option<ColumnLatinSquare> {
+        let order = rows.len();
+        if rows.iter().any(|row| row.len() != order) {
+            return None
+        }
+        Some(RowLatinSquare::from_rows(transposed(&rows))?.transpose())
+    }
+
+    /// The columns of a latin square.
+    pub fn from_square(square: &LatinSquare) -> ColumnLatinSquare {
+        ColumnLatinSquare { rows: square.square.clone() }
+    }
+
+    /// A column-latin square with every column an independent uniformly random permutation, drawn from
+    /// `rng`. Fails for order 0.
+    pub fn new_random(order: usize, rng: &mut impl Rng) -> Result<ColumnLatinSquare, CombinatorialError> {
+        Ok(RowLatinSquare::new_random(order, rng)?.transpose())
+    }
+
+    /// The order n.
+    pub fn order(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The rows.
+    pub fn rows(&self) -> &[Vec<Symbol>] {
+        &self.rows
+    }
+
+    /// The number of symbols missing from each row.
+    pub fn defects(&self) -> Vec<usize> {
+        self.rows.iter().map(|row| missing(row.iter().copied(), self.order())).collect()
+    }
+
+    /// The distance to latin: the symbols missing from the rows, in total.
+    pub fn defect(&self) -> usize {
+        self.defects().iter().sum()
+    }
+
+    /// Returns true if the rows are permutations too.
+    pub fn is_latin(&self) -> bool {
+        self.defect() == 0
+    }
+
+    /// The latin square, if the rows are permutations too.
+    pub fn to_latin_square(&self) -> Option<LatinSquare> {
+        if !self.is_latin() {
+            return None
+        }
+        Some(LatinSquare::from_rows(self.rows.clone()))
+    }
+
+    /// The transpose, whose rows are the columns of this one.
+    pub fn transpose(&self) -> RowLatinSquare {
+        RowLatinSquare { rows: transposed(&self.rows) }
+    }
+
+    /// Swaps pairs of cells within columns, as `RowLatinSquare::repair` does within rows.
+    pub fn repair(&mut self, max_swaps: usize, rng: &mut impl Rng) -> bool {
+        let mut transpose = self.transpose();
+        let repaired = transpose.repair(max_swaps, rng);
+        *self = transpose.transpose();
+        repaired
+    }
+}
+
+fn is_permutation(symbols: &[Symbol], order: usize) -> bool {
+    symbols.len() == order && missing(symbols.iter().copied(), order) == 0
+}
+
+/// The number of symbols 0..order that are not among the given ones.
+fn missing(symbols: impl Iterator<Item = Symbol>, order: usize) -> usize {
+    let mut seen = vec![false; order];
+    for symbol in symbols.filter(|&symbol| symbol < order) {
+        seen[symbol] = true;
+    }
+    seen.iter().filter(|&&seen| !seen).count()
+}
+
+fn transposed(rows: &[Vec<Symbol>]) -> Vec<Vec<Symbol>> {
+    (0..rows.len()).map(|column| rows.iter().map(|row| row[column]).collect()).collect()
+}
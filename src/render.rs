@@ -0,0 +1,585 @@
+//! Rendering latin squares for documents.
+//!
+//! - LaTeX: a `tabular` environment for text, or an `array` for math mode, optionally with rules around
+//!   every cell, controlled by `LatexOptions`.
+//! - HTML: a `table` of class `latin-square`, optionally with a class `symbol-k` on every cell of symbol k,
+//!   so a style sheet can colour the symbols, controlled by `HtmlOptions`.
+//! - Markdown: a pipe table, as GitHub renders it, with an empty header row, since tables need one.
+//! - Plain text, as `Display` writes it: the labels right-aligned in columns, optionally inside Unicode box
+//!   borders, after a header giving the order. `DisplayOptions` changes the separator and drops the header,
+//!   for tools that parse the output.
+//! - PNG, with the `png` feature: every cell a square of the colour of its symbol in a `ColorMap`. The
+//!   encoder is written out here, storing the pixels uncompressed, so the feature needs no dependencies.
+//! - SVG: the same coloured cells as scalable vector graphics, optionally labelled, controlled by
+//!   `SvgOptions`. Labels are black or white, whichever stands out more from the colour of the cell.
+//!
+//! The symbols are written through an `Alphabet`: as they are, counted from 1, as letters, or as any list
+//! of labels. Labels are written as given, so custom labels must already be escaped for the target format.
+//!
+//! Sources:
+//!
+//! - LaTeX: A Document Preparation System, L. Lamport (1994), the tabular and array environments
+//! - [GitHub Flavored Markdown Spec, tables](https://github.github.com/gfm/#tables-extension-)
+//! - [Portable Network Graphics (PNG) Specification, W3C](https://www.w3.org/TR/png/)
+//! - [Scalable Vector Graphics (SVG) 1.1, W3C](https://www.w3.org/TR/SVG11/)
+//! - [ZLIB Compressed Data Format Specification version 3.3, RFC 1950](https://www.rfc-editor.org/rfc/rfc1950)
+
+#[cfg(feature = "png")]
+use std::fs;
+#[cfg(feature = "png")]
+use std::io;
+#[cfg(feature = "png")]
+use std::path::Path;
+
+#[cfg(feature = "png")]
+use crate::error::CombinatorialError;
+#[cfg(feature = "png")]
+use crate::latin_square::crc32;
+use crate::latin_square::{LatinSquare, Symbol, SymbolBase};
+
+/// How the symbols of a square are written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Alphabet {
+    /// The symbols as they are, from 0.
+    #[default]
+    Numbers,
+    /// The symbols counted from 1.
+    NumbersFromOne,
+    /// The letters A to Z, then AA, AB, and so on, as spreadsheets name their columns.
+    Letters,
+    /// The label at the index of every symbol, or the symbol itself past the end of the list.
+    Custom(Vec<String>)
+}
+
+impl Alphabet {
+    /// The label of the symbol.
+    ///
+    /// ```
+    /// use combinatorial_patterns::render::Alphabet;
+    ///
+    /// assert_eq!(Alphabet::NumbersFromOne.label(0), "1");
+    /// assert_eq!(Alphabet::Letters.label(2), "C");
+    /// assert_eq!(Alphabet::Letters.label(27), "AB");
+    /// assert_eq!(Alphabet::Custom(vec!["x".to_string()]).label(0), "x");
+    /// ```
+    pub fn label(&self, symbol: Symbol) -> String {
+        match self {
+            Alphabet::Numbers => symbol.to_string(),
+            Alphabet::NumbersFromOne => (symbol + 1).to_string(),
+            Alphabet::Letters => {
+                let mut letters = Vec::new();
+                let mut rest = symbol + 1;
+                while rest > 0 {
+                    rest -= 1;
+                    letters.push((b'A' + (rest % 26) as u8) as char);
+                    rest /= 26;
+                }
+                letters.iter().rev().collect()
+            },
+            Alphabet::Custom(labels) => labels.get(symbol).cloned().unwrap_or_else(|| symbol.to_string())
+        }
+    }
+}
+
+/// How the symbols of a square are coloured, as red, green, and blue.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ColorMap {
+    /// Hues evenly spaced around the colour wheel, from red.
+    #[default]
+    Hues,
+    /// Greys from black for symbol 0 to white for the last symbol.
+    Greys,
+    /// The colour at the index of every symbol, repeating the list for symbols past its end.
+    Custom(Vec<[u8; 3]>)
+}
+
+impl ColorMap {
+    /// The colour of the symbol in a square of the given order.
+    ///
+    /// ```
+    /// use combinatorial_patterns::render::ColorMap;
+    ///
+    /// assert_eq!(ColorMap::Hues.color(0, 6), [242, 85, 85]);
+    /// assert_eq!(ColorMap::Greys.color(4, 5), [255, 255, 255]);
+    /// assert_eq!(ColorMap::Custom(vec![[1, 2, 3], [4, 5, 6]]).color(2, 3), [1, 2, 3]);
+    /// ```
+    pub fn color(&self, symbol: Symbol, order: usize) -> [u8; 3] {
+        match self {
+            ColorMap::Hues => {
+                let hue = 6.0 * symbol as f64 / order.max(1) as f64;
+                let (value, low) = (0.95, 0.95 * (1.0 - 0.65));
+                let rising = low + (value - low) * hue.fract();
+                let falling = value - (value - low) * hue.fract();
+                let [red, green, blue] = match hue as usize % 6 {
+                    0 => [value, rising, low],
+                    1 => [falling, value, low],
+                    2 => [low, value, rising],
+                    3 => [low, falling, value],
+                    4 => [rising, low, value],
+                    _ => [value, low, falling]
+                };
+                [red, green, blue].map(|channel: f64| (channel * 255.0).round() as u8)
+            },
+            ColorMap::Greys => {
+                let grey = (255 * symbol / order.saturating_sub(1).max(1)).min(255) as u8;
+                [grey; 3]
+            },
+            ColorMap::Custom(colors) if colors.is_empty() => [0; 3],
+            ColorMap::Custom(colors) => colors[symbol % colors.len()]
+        }
+    }
+}
+
+/// How `LatinSquare::to_svg` draws a square.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// The side of every cell, in user units.
+    pub cell_size: usize,
+    pub color_map: ColorMap,
+    /// The symbol written in every cell, through the alphabet, or no labels.
+    pub labels: Option<Alphabet>
+}
+
+impl SvgOptions {
+    /// Cells of 32 units coloured by hue, labelled with numbers from 0.
+    pub fn new() -> SvgOptions {
+        SvgOptions { cell_size: 32, color_map: ColorMap::Hues, labels: Some(Alphabet::Numbers) }
+    }
+
+    pub fn with_cell_size(mut self, cell_size: usize) -> SvgOptions {
+        self.cell_size = cell_size.max(1);
+        self
+    }
+
+    pub fn with_color_map(mut self, color_map: ColorMap) -> SvgOptions {
+        self.color_map = color_map;
+        self
+    }
+
+    pub fn with_labels(mut self, labels: Option<Alphabet>) -> SvgOptions {
+        self.labels = labels;
+        self
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions::new()
+    }
+}
+
+/// The LaTeX environment a square is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LatexEnvironment {
+    /// `tabular`, for running text.
+    #[default]
+    Tabular,
+    /// `array`, inside math mode.
+    Array
+}
+
+/// How `LatinSquare::to_text` writes a square. The defaults give what `Display` writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOptions {
+    pub alphabet: Alphabet,
+    /// Written between the cells of a row, unless boxed.
+    pub separator: String,
+    /// A first line "Latin square of size n" and an empty line after it.
+    pub header: bool,
+    /// Unicode box borders around every cell, as `{:#}` draws them.
+    pub boxed: bool
+}
+
+impl DisplayOptions {
+    /// Numbers from 0 separated by spaces, after the header, without borders.
+    pub fn new() -> DisplayOptions {
+        DisplayOptions { alphabet: Alphabet::Numbers, separator: " ".to_string(), header: true, boxed: false }
+    }
+
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> DisplayOptions {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Numbers counted from the base, replacing the alphabet.
+    pub fn with_base(mut self, base: SymbolBase) -> DisplayOptions {
+        self.alphabet = base.alphabet();
+        self
+    }
+
+    pub fn with_separator(mut self, separator: &str) -> DisplayOptions {
+        self.separator = separator.to_string();
+        self
+    }
+
+    pub fn with_header(mut self, header: bool) -> DisplayOptions {
+        self.header = header;
+        self
+    }
+
+    pub fn with_boxed(mut self, boxed: bool) -> DisplayOptions {
+        self.boxed = boxed;
+        self
+    }
+}
+
+impl Default for DisplayOptions {
+    fn default() -> DisplayOptions {
+        DisplayOptions::new()
+    }
+}
+
+/// How `LatinSquare::to_latex` writes a square.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LatexOptions {
+    pub alphabet: Alphabet,
+    pub environment: LatexEnvironment,
+    /// Rules around every cell, with `\hline` between the rows and `|` between the columns.
+    pub borders: bool
+}
+
+impl LatexOptions {
+    /// Numbers from 0 in a `tabular`, without borders.
+    pub fn new() -> LatexOptions {
+        LatexOptions::default()
+    }
+
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> LatexOptions {
+        self.alphabet = alphabet;
+        self
+    }
+
+    pub fn with_environment(mut self, environment: LatexEnvironment) -> LatexOptions {
+        self.environment = environment;
+        self
+    }
+
+    pub fn with_borders(mut self, borders: bool) -> LatexOptions {
+        self.borders = borders;
+        self
+    }
+}
+
+/// How `LatinSquare::to_html` writes a square.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HtmlOptions {
+    pub alphabet: Alphabet,
+    /// A class `symbol-k` on every cell of symbol k.
+    pub symbol_classes: bool
+}
+
+impl HtmlOptions {
+    /// Numbers from 0, without classes on the cells.
+    pub fn new() -> HtmlOptions {
+        HtmlOptions::default()
+    }
+
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> HtmlOptions {
+        self.alphabet = alphabet;
+        self
+    }
+
+    pub fn with_symbol_classes(mut self, symbol_classes: bool) -> HtmlOptions {
+        self.symbol_classes = symbol_classes;
+        self
+    }
+}
+
+impl<T> LatinSquare<T> {
+    /// The square as plain text, a row per line, without a newline at the end. The alphabet labels the
+    /// indices of the symbols.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::render::{Alphabet, DisplayOptions};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// assert_eq!(square.to_text(&DisplayOptions::new()), square.to_string());
+    ///
+    /// let options = DisplayOptions::new().with_alphabet(Alphabet::NumbersFromOne).with_separator(",").with_header(false);
+    /// assert_eq!(square.to_text(&options), "1,2,3\n2,3,1\n3,1,2");
+    /// let options = DisplayOptions::new().with_alphabet(Alphabet::Letters).with_header(false);
+    /// assert_eq!(square.to_text(&options).lines().next(), Some("A B C"));
+    /// ```
+    pub fn to_text(&self, options: &DisplayOptions) -> String {
+        let labels: Vec<Vec<String>> = self.square.iter()
+            .map(|row| row.iter().map(|&symbol| options.alphabet.label(symbol)).collect())
+            .collect();
+        let body = grid(&labels, &options.separator, options.boxed);
+        if options.header {
+            format!("Latin square of size {}\n\n{}", self.size(), body)
+        } else {
+            body
+        }
+    }
+}
+
+impl LatinSquare {
+    /// The square as a LaTeX table, a row per line, ending with a newline.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::render::{Alphabet, LatexEnvironment, LatexOptions};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// assert_eq!(
+    ///     square.to_latex(&LatexOptions::new()),
+    ///     "\\begin{tabular}{ccc}\n0 & 1 & 2 \\\\\n1 & 2 & 0 \\\\\n2 & 0 & 1\n\\end{tabular}\n"
+    /// );
+    ///
+    /// let options = LatexOptions::new().with_alphabet(Alphabet::Letters).with_environment(LatexEnvironment::Array).with_borders(true);
+    /// assert_eq!(square.to_latex(&options).lines().take(4).collect::<Vec<_>>(), [
+    ///     "\\begin{array}{|c|c|c|}",
+    ///     "\\hline",
+    ///     "A & B & C \\\\",
+    ///     "\\hline"
+    /// ]);
+    /// ```
+    pub fn to_latex(&self, options: &LatexOptions) -> String {
+        let environment = match options.environment {
+            LatexEnvironment::Tabular => "tabular",
+            LatexEnvironment::Array => "array"
+        };
+        let columns = if options.borders {
+            format!("|{}", "c|".repeat(self.size()))
+        } else {
+            "c".repeat(self.size())
+        };
+        let mut latex = format!("\\begin{{{}}}{{{}}}\n", environment, columns);
+        if options.borders {
+            latex.push_str("\\hline\n");
+        }
+        for (index, row) in self.rows().enumerate() {
+            let labels: Vec<String> = row.iter().map(|&symbol| options.alphabet.label(symbol)).collect();
+            latex.push_str(&labels.join(" & "));
+            if options.borders {
+                latex.push_str(" \\\\\n\\hline\n");
+            } else if index + 1 < self.size() {
+                latex.push_str(" \\\\\n");
+            } else {
+                latex.push('\n');
+            }
+        }
+        latex.push_str(&format!("\\end{{{}}}\n", environment));
+        latex
+    }
+
+    /// The square as an HTML table, a row per line, ending with a newline.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::render::{Alphabet, HtmlOptions};
+    ///
+    /// let square = IncidenceCube::new_cyclic(2).unwrap().as_latin_square();
+    /// assert_eq!(
+    ///     square.to_html(&HtmlOptions::new()),
+    ///     "<table class=\"latin-square\">\n<tr><td>0</td><td>1</td></tr>\n<tr><td>1</td><td>0</td></tr>\n</table>\n"
+    /// );
+    /// let options = HtmlOptions::new().with_alphabet(Alphabet::Letters).with_symbol_classes(true);
+    /// assert!(square.to_html(&options).contains("<tr><td class=\"symbol-1\">B</td><td class=\"symbol-0\">A</td></tr>"));
+    /// ```
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let mut html = String::from("<table class=\"latin-square\">\n");
+        for row in self.rows() {
+            html.push_str("<tr>");
+            for &symbol in row {
+                let label = options.alphabet.label(symbol);
+                if options.symbol_classes {
+                    html.push_str(&format!("<td class=\"symbol-{}\">{}</td>", symbol, label));
+                } else {
+                    html.push_str(&format!("<td>{}</td>", label));
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+
+    /// The square as a Markdown table, a row per line, ending with a newline.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::render::Alphabet;
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// assert_eq!(
+    ///     square.to_markdown(&Alphabet::NumbersFromOne),
+    ///     "|   |   |   |\n|---|---|---|\n| 1 | 2 | 3 |\n| 2 | 3 | 1 |\n| 3 | 1 | 2 |\n"
+    /// );
+    /// ```
+    pub fn to_markdown(&self, alphabet: &Alphabet) -> String {
+        let labels: Vec<Vec<String>> = self.rows().map(|row| row.iter().map(|&symbol| alphabet.label(symbol)).collect()).collect();
+        let width = labels.iter().flatten().map(|label| label.chars().count()).max().unwrap_or(1).max(1);
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let mut markdown = line(vec![" ".repeat(width); self.size()]);
+        markdown.push_str(&format!("|{}\n", format!("{}|", "-".repeat(width + 2)).repeat(self.size())));
+        for row in labels {
+            markdown.push_str(&line(row.into_iter().map(|label| format!("{:>width$}", label, width = width)).collect()));
+        }
+        markdown
+    }
+
+    /// The square as an SVG image of coloured cells, a line per cell, ending with a newline.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::render::{Alphabet, ColorMap, SvgOptions};
+    ///
+    /// let square = IncidenceCube::new_cyclic(3).unwrap().as_latin_square();
+    /// let options = SvgOptions::new().with_cell_size(10).with_color_map(ColorMap::Greys).with_labels(Some(Alphabet::Letters));
+    /// let svg = square.to_svg(&options);
+    /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"30\" viewBox=\"0 0 30 30\">"));
+    /// assert!(svg.contains("<rect x=\"10\" y=\"0\" width=\"10\" height=\"10\" fill=\"#7f7f7f\"/>"));
+    /// assert!(svg.contains(">C</text>"));
+    /// assert_eq!(svg.matches("<rect").count(), 9);
+    ///
+    /// assert!(!square.to_svg(&SvgOptions::new().with_labels(None)).contains("<text"));
+    /// ```
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let cell = options.cell_size;
+        let side = self.size() * cell;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+            side
+        );
+        for (row, symbols) in self.rows().enumerate() {
+            for (column, &symbol) in symbols.iter().enumerate() {
+                let [red, green, blue] = options.color_map.color(symbol, self.size());
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>\n",
+                    column * cell, row * cell, cell, cell, red, green, blue
+                ));
+                if let Some(alphabet) = &options.labels {
+                    // Relative luminance, by the weights of ITU-R BT.709.
+                    let luminance = 0.2126 * red as f64 + 0.7152 * green as f64 + 0.0722 * blue as f64;
+                    let ink = if luminance > 140.0 { "#000000" } else { "#ffffff" };
+                    svg.push_str(&format!(
+                        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                        column * cell + cell / 2, row * cell + cell / 2, cell / 2, ink, alphabet.label(symbol)
+                    ));
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The square as a PNG image, every cell a square of `cell_size` pixels, at least 1, in the colour of
+    /// its symbol. Fails for order 0, which PNG has no empty image for.
+    ///
+    /// ```
+    /// use combinatorial_patterns::{IncidenceCube, LatinSquare};
+    /// use combinatorial_patterns::render::ColorMap;
+    ///
+    /// let square = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+    /// let png = square.to_png(4, &ColorMap::Hues).unwrap();
+    /// assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    /// // The header holds the width and the height, 5 cells of 4 pixels.
+    /// assert_eq!(&png[16..24], &[0, 0, 0, 20, 0, 0, 0, 20]);
+    /// assert!(png.ends_with(b"IEND\xaeB`\x82"));
+    ///
+    /// // Cells of 0 pixels are drawn as 1.
+    /// assert_eq!(&square.to_png(0, &ColorMap::Hues).unwrap()[16..24], &[0, 0, 0, 5, 0, 0, 0, 5]);
+    /// assert!(LatinSquare::new_empty(0).to_png(4, &ColorMap::Hues).is_err());
+    /// ```
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, cell_size: usize, palette: &ColorMap) -> Result<Vec<u8>, CombinatorialError> {
+        if self.size() == 0 {
+            return Err(CombinatorialError::ZeroOrder)
+        }
+        let cell_size = cell_size.max(1);
+        let side = self.size() * cell_size;
+        let colors: Vec<[u8; 3]> = (0..self.size()).map(|symbol| palette.color(symbol, self.size())).collect();
+        let mut pixels = Vec::with_capacity(side * (3 * side + 1));
+        for row in self.rows() {
+            let mut line = vec![0];
+            for &symbol in row {
+                for _ in 0..cell_size {
+                    line.extend_from_slice(&colors[symbol]);
+                }
+            }
+            for _ in 0..cell_size {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(side as u32).to_be_bytes());
+        header.extend_from_slice(&(side as u32).to_be_bytes());
+        // 8 bits per channel, RGB, the only compression and filter methods, no interlacing.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        push_chunk(&mut png, b"IHDR", &header);
+        push_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+        push_chunk(&mut png, b"IEND", &[]);
+        Ok(png)
+    }
+
+    /// Writes the square as a PNG image to the file at `path`, see `to_png`. Fails with `InvalidInput` for
+    /// order 0.
+    #[cfg(feature = "png")]
+    pub fn render_png(&self, path: impl AsRef<Path>, cell_size: usize, palette: &ColorMap) -> io::Result<()> {
+        let png = self.to_png(cell_size, palette).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        fs::write(path, png)
+    }
+}
+
+/// The labels right-aligned in columns as wide as the widest label, a row per line without a final newline,
+/// either separated by `separator` or inside box borders.
+pub(crate) fn grid(labels: &[Vec<String>], separator: &str, boxed: bool) -> String {
+    let width = labels.iter().flatten().map(|label| label.chars().count()).max().unwrap_or(0);
+    let rows: Vec<String> = labels.iter().map(|row| {
+        let cells: Vec<String> = row.iter().map(|label| format!("{:>width$}", label, width = width)).collect();
+        if boxed { format!("│ {} │", cells.join(" │ ")) } else { cells.join(separator) }
+    }).collect();
+    if !boxed {
+        return rows.join("\n")
+    }
+    let columns = labels.first().map_or(0, Vec::len);
+    let rule = |left: &str, middle: &str, right: &str| {
+        format!("{}{}{}", left, vec!["─".repeat(width + 2); columns].join(middle), right)
+    };
+    let mut lines = vec![rule("┌", "┬", "┐")];
+    for (index, row) in rows.into_iter().enumerate() {
+        if index > 0 {
+            lines.push(rule("├", "┼", "┤"));
+        }
+        lines.push(row);
+    }
+    lines.push(rule("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+/// Appends a PNG chunk: its length, its type, its data, and the CRC-32 of the type and the data.
+#[cfg(feature = "png")]
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let checksum = crc32(&png[start..]);
+    png.extend_from_slice(&checksum.to_be_bytes());
+}
+
+/// The bytes as a zlib stream of stored, uncompressed, deflate blocks.
+#[cfg(feature = "png")]
+fn zlib_stored(bytes: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if bytes.is_empty() { vec![&[]] } else { bytes.chunks(u16::MAX as usize).collect() };
+    for (index, block) in blocks.iter().enumerate() {
+        stream.push(u8::from(index + 1 == blocks.len()));
+        let length = block.len() as u16;
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    // The Adler-32 of the bytes.
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    stream
+}
@@ -0,0 +1,338 @@
+//! Resolvable incomplete block designs for field trials: lattices and alpha(0,1) designs.
+//!
+//! With many treatments, complete blocks get too large to be uniform, so each replicate of the treatments
+//! is split into smaller blocks of k plots. The design is resolvable because every replicate is a full set
+//! of treatments, and it is written as replicates of blocks of treatments. Designs where any two treatments
+//! share a block at most once are (0,1) designs, which spread the comparisons evenly.
+//!
+//! - Square lattices have k^2 treatments laid out in a k x k array. The first replicate blocks by its rows,
+//!   the second by its columns, and each further one by the symbols of a latin square, which needs MOLS:
+//!   up to k + 1 replicates for prime powers k.
+//! - Rectangular lattices have k(k + 1) treatments: the cells of a (k + 1) x (k + 1) array off a common
+//!   transversal of a set of MOLS, blocked the same way. Removing the transversal takes one cell from each
+//!   line, so every block has k plots.
+//! - Alpha(0,1) designs have s * k treatments in s blocks of k per replicate. A k x r generating array
+//!   over the integers mod s puts treatment i * s + (a[i][j] + b) mod s in plot i of block b of replicate j.
+//!   Pairs of treatments meet at most once when, for every two rows of the array, the differences of their
+//!   entries are distinct across the columns. The array is found by backtracking.
+//!
+//! The efficiency factor of a design is the harmonic mean of the canonical efficiency factors, the nonzero
+//! eigenvalues of I - N N^T / (r k) for the treatment by block incidence matrix N. It measures the variance of
+//! treatment comparisons relative to a complete block design, so 1 is best, and is compared against the
+//! upper bound (v - 1)(r - 1) / ((v - 1)(r - 1) + r(b - 1)) for resolvable designs with b blocks per
+//! replicate, which square lattices attain.
+//!
+//! Sources:
+//!
+//! - [A new class of resolvable incomplete block designs, H. D. Patterson, E. R. Williams](https://doi.org/10.1093/biomet/63.1.83)
+//! - [Lattice design, Wikipedia](https://en.wikipedia.org/wiki/Lattice_design)
+
+use crate::design::{LongFormat, LongTable};
+use crate::latin_square::LatinSquare;
+use crate::mols;
+
+/// Steps the search for an alpha array may take before giving up.
+const ALPHA_SEARCH_LIMIT: usize = 1_000_000;
+
+/// A resolvable design: replicates of blocks of treatments 0..v, every replicate holding each treatment once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvableDesign {
+    treatments: usize,
+    block_size: usize,
+    replicates: Vec<Vec<Vec<usize>>>
+}
+
+impl ResolvableDesign {
+    /// The number of treatments v.
+    pub fn treatments(&self) -> usize {
+        self.treatments
+    }
+
+    /// The number of plots k in each block.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// The replicates, each a list of blocks, each a list of treatments.
+    pub fn replicates(&self) -> &[Vec<Vec<usize>>] {
+        &self.replicates
+    }
+
+    /// How often each pair of treatments shares a block.
+    pub fn concurrences(&self) -> Vec<Vec<usize>> {
+        let mut counts = vec![vec![0; self.treatments]; self.treatments];
+        for block in self.replicates.iter().flatten() {
+            for &a in block {
+                for &b in block {
+                    counts[a][b] += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// The smallest and largest number of blocks shared by two distinct treatments.
+    pub fn concurrence_range(&self) -> (usize, usize) {
+        let counts = self.concurrences();
+        let off_diagonal = (0..self.treatments).flat_map(|a| ((a + 1)..self.treatments).map(move |b| (a, b)));
+        off_diagonal.fold((usize::MAX, 0), |(low, high), (a, b)| (low.min(counts[a][b]), high.max(counts[a][b])))
+    }
+
+    /// The efficiency factor: the harmonic mean of the canonical efficiency factors. It is 0 for designs
+    /// that don't connect every pair of treatments.
+    ///
+    /// It uses that I - N N^T / (r k) + J / v has the same eigenvalues, except a 1 for the all ones vector,
+    /// so the sum of the inverse efficiency factors is the trace of its inverse minus 1.
+    pub fn efficiency_factor(&self) -> f64 {
+        let v = self.treatments;
+        if v < 2 {
+            return 1.0
+        }
+        let scale = (self.replicates.len() * self.block_size) as f64;
+        let counts = self.concurrences();
+        let matrix: Vec<Vec<f64>> = (0..v).map(|a| (0..v).map(|b| {
+            let identity = if a == b { 1.0 } else { 0.0 };
+            identity - counts[a][b] as f64 / scale + 1.0 / v as f64
+        }).collect()).collect();
+        match inverse_trace(matrix) {
+            Some(trace) => (v - 1) as f64 / (trace - 1.0),
+            None => 0.0
+        }
+    }
+
+    /// The upper bound on the efficiency factor of resolvable designs with the same numbers of treatments,
+    /// replicates, and blocks per replicate.
+    pub fn efficiency_upper_bound(&self) -> f64 {
+        let v = self.treatments as f64;
+        let r = self.replicates.len() as f64;
+        let blocks = self.replicates.first().map(|replicate| replicate.len()).unwrap_or(1) as f64;
+        if r < 2.0 {
+            return 1.0
+        }
+        (v - 1.0) * (r - 1.0) / ((v - 1.0) * (r - 1.0) + r * (blocks - 1.0))
+    }
+}
+
+/// Records of (replicate, block, unit, treatment), all numbered from 1 as is usual in field trials.
+///
+/// ```
+/// use combinatorial_patterns::resolvable;
+/// use combinatorial_patterns::design::LongFormat;
+///
+/// let table = resolvable::square_lattice(3, 2).unwrap().to_long_format();
+/// assert_eq!(table.header, vec!["Replicate", "Block", "Unit", "Treatment"]);
+/// assert_eq!(table.records[0], vec!["1", "1", "1", "1"]);
+/// assert_eq!(table.records.len(), 18);
+/// ```
+impl LongFormat for ResolvableDesign {
+    fn to_long_format(&self) -> LongTable {
+        let mut records = Vec::new();
+        for (replicate, blocks) in self.replicates.iter().enumerate() {
+            for (block, treatments) in blocks.iter().enumerate() {
+                for (unit, &treatment) in treatments.iter().enumerate() {
+                    records.push([replicate, block, unit, treatment].iter().map(|index| (index + 1).to_string()).collect());
+                }
+            }
+        }
+        LongTable {
+            header: ["Replicate", "Block", "Unit", "Treatment"].iter().map(|name| name.to_string()).collect(),
+            records
+        }
+    }
+}
+
+/// The square lattice for k^2 treatments in blocks of k, with the given number of replicates. Treatment
+/// k * row + column sits at that cell of the array. Returns None for fewer than 1 or more than 2 + m replicates,
+/// when `mols::new(k)` gives m squares; a complete set for prime powers allows up to k + 1.
+///
+/// ```
+/// use combinatorial_patterns::resolvable;
+///
+/// let simple = resolvable::square_lattice(3, 2).unwrap();
+/// assert_eq!(simple.concurrence_range(), (0, 1));
+/// assert!((simple.efficiency_factor() - 2.0 / 3.0).abs() < 1e-9);
+///
+/// // With every replicate, each pair of treatments meets exactly once.
+/// let balanced = resolvable::square_lattice(3, 4).unwrap();
+/// assert_eq!(balanced.concurrence_range(), (1, 1));
+/// assert!((balanced.efficiency_factor() - balanced.efficiency_upper_bound()).abs() < 1e-9);
+/// ```
+pub fn square_lattice(k: usize, replicates: usize) -> Option<ResolvableDesign> {
+    let squares = if replicates > 2 { mols::new(k).unwrap_or_default() } else { Vec::new() };
+    if k == 0 || replicates == 0 || replicates > 2 + squares.len() {
+        return None
+    }
+    let cells: Vec<(usize, usize)> = (0..k).flat_map(|row| (0..k).map(move |column| (row, column))).collect();
+    Some(ResolvableDesign {
+        treatments: k * k,
+        block_size: k,
+        replicates: line_replicates(k, &cells, &squares, replicates)
+    })
+}
+
+/// The rectangular lattice for k(k + 1) treatments in blocks of k, with the given number of replicates.
+/// Treatments number the cells of the (k + 1) x (k + 1) array off a transversal row by row, and the first two
+/// replicates block them by rows and by columns. For one or two replicates the transversal is the diagonal, so
+/// every k works. Further replicates come from the squares `mols::new(k + 1)` gives, the last of them giving
+/// the transversal, so with m squares there can be up to 1 + m; a complete set for prime powers k + 1 allows
+/// up to k + 1. Returns None for no replicates or more than that.
+///
+/// ```
+/// use combinatorial_patterns::resolvable;
+///
+/// let design = resolvable::rectangular_lattice(3, 3).unwrap();
+/// assert_eq!(design.treatments(), 12);
+/// assert_eq!(design.replicates()[0].len(), 4);
+/// assert_eq!(design.concurrence_range(), (0, 1));
+///
+/// // There are no two orthogonal squares of order 6, but the simple lattice needs none.
+/// let simple = resolvable::rectangular_lattice(5, 2).unwrap();
+/// assert_eq!(simple.treatments(), 30);
+/// assert_eq!(simple.concurrence_range(), (0, 1));
+/// assert!(resolvable::rectangular_lattice(5, 3).is_none());
+/// ```
+pub fn rectangular_lattice(k: usize, replicates: usize) -> Option<ResolvableDesign> {
+    let q = k + 1;
+    if k == 0 || replicates == 0 {
+        return None
+    }
+    let array = (0..q).flat_map(|row| (0..q).map(move |column| (row, column)));
+    if replicates <= 2 {
+        let cells: Vec<(usize, usize)> = array.filter(|&(row, column)| row != column).collect();
+        return Some(ResolvableDesign {
+            treatments: k * q,
+            block_size: k,
+            replicates: line_replicates(q, &cells, &[], replicates)
+        })
+    }
+    let squares = mols::new(q).unwrap_or_default();
+    if replicates > 1 + squares.len() {
+        return None
+    }
+    // The last square's cells with symbol 0 are a transversal of the others, by orthogonality.
+    let (transversal, others) = squares.split_last().expect("ProgrammingError: there are at least two squares.");
+    let cells: Vec<(usize, usize)> = array.filter(|&(row, column)| transversal.square[row][column] != 0).collect();
+    Some(ResolvableDesign {
+        treatments: k * q,
+        block_size: k,
+        replicates: line_replicates(q, &cells, others, replicates)
+    })
+}
+
+/// Blocks the treatments on the given cells of an order x order array by rows, columns, and then the symbols
+/// of each square, for the given number of replicates. Treatment t is `cells[t]`.
+fn line_replicates(order: usize, cells: &[(usize, usize)], squares: &[LatinSquare], replicates: usize) -> Vec<Vec<Vec<usize>>> {
+    let lines = |line: &dyn Fn(usize, usize) -> usize| {
+        let mut blocks = vec![Vec::new(); order];
+        for (treatment, &(row, column)) in cells.iter().enumerate() {
+            blocks[line(row, column)].push(treatment);
+        }
+        blocks
+    };
+    let mut result = vec![lines(&|row, _| row)];
+    if replicates > 1 {
+        result.push(lines(&|_, column| column));
+    }
+    for square in squares.iter().take(replicates.saturating_sub(2)) {
+        result.push(lines(&|row, column| square.square[row][column]));
+    }
+    result
+}
+
+/// An alpha(0,1) design for s * k treatments in s blocks of k per replicate, with the given number of
+/// replicates, or None if no generating array was found. That needs k <= s and r <= s, and the search gives up
+/// after a fixed number of steps on hard cases.
+///
+/// ```
+/// use combinatorial_patterns::resolvable;
+///
+/// // 24 treatments in blocks of 4, three replicates.
+/// let design = resolvable::alpha(6, 4, 3).unwrap();
+/// assert_eq!(design.treatments(), 24);
+/// assert_eq!(design.concurrence_range(), (0, 1));
+/// assert!(design.efficiency_factor() <= design.efficiency_upper_bound() + 1e-9);
+/// assert!(resolvable::alpha(3, 4, 2).is_none());
+/// ```
+pub fn alpha(s: usize, k: usize, replicates: usize) -> Option<ResolvableDesign> {
+    if s == 0 || k == 0 || replicates == 0 || k > s || replicates > s {
+        return None
+    }
+    let array = alpha_array(s, k, replicates)?;
+    let design = (0..replicates).map(|column| {
+        (0..s).map(|block| (0..k).map(|row| row * s + (array[row][column] + block) % s).collect()).collect()
+    }).collect();
+    Some(ResolvableDesign { treatments: s * k, block_size: k, replicates: design })
+}
+
+/// A k x r array mod s with zeros in its first row and column, where every two rows have distinct
+/// differences across the columns.
+fn alpha_array(s: usize, k: usize, replicates: usize) -> Option<Vec<Vec<usize>>> {
+    let mut array = vec![vec![0; replicates]; k];
+    // used[a][b][d]: rows a < b already differ by d in some column. The first column gives 0.
+    let mut used = vec![vec![vec![false; s]; k]; k];
+    for (a, pairs) in used.iter_mut().enumerate() {
+        for differences in pairs.iter_mut().skip(a + 1) {
+            differences[0] = true;
+        }
+    }
+    let free: Vec<(usize, usize)> = (1..k).flat_map(|row| (1..replicates).map(move |column| (row, column))).collect();
+    let mut steps = 0;
+    fill(&mut array, &mut used, &free, 0, s, &mut steps).then_some(array)
+}
+
+fn fill(array: &mut [Vec<usize>], used: &mut [Vec<Vec<bool>>], free: &[(usize, usize)], index: usize, s: usize, steps: &mut usize) -> bool {
+    let Some(&(row, column)) = free.get(index) else {
+        return true
+    };
+    for value in 0..s {
+        *steps += 1;
+        if *steps > ALPHA_SEARCH_LIMIT {
+            return false
+        }
+        let differences: Vec<usize> = (0..row).map(|other| (value + s - array[other][column]) % s).collect();
+        if differences.iter().enumerate().any(|(other, &difference)| used[other][row][difference]) {
+            continue
+        }
+        array[row][column] = value;
+        for (other, &difference) in differences.iter().enumerate() {
+            used[other][row][difference] = true;
+        }
+        if fill(array, used, free, index + 1, s, steps) {
+            return true
+        }
+        for (other, &difference) in differences.iter().enumerate() {
+            used[other][row][difference] = false;
+        }
+    }
+    false
+}
+
+/// The trace of the inverse of a symmetric matrix, by Gauss-Jordan elimination with partial pivoting, or
+/// None if it is singular.
+fn inverse_trace(mut matrix: Vec<Vec<f64>>) -> Option<f64> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n).map(|row| (0..n).map(|column| if row == column { 1.0 } else { 0.0 }).collect()).collect();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let scale = matrix[column][column];
+        for entry in 0..n {
+            matrix[column][entry] /= scale;
+            inverse[column][entry] /= scale;
+        }
+        for row in 0..n {
+            if row != column && matrix[row][column] != 0.0 {
+                let factor = matrix[row][column];
+                for entry in 0..n {
+                    matrix[row][entry] -= factor * matrix[column][entry];
+                    inverse[row][entry] -= factor * inverse[column][entry];
+                }
+            }
+        }
+    }
+    Some((0..n).map(|index| inverse[index][index]).sum())
+}
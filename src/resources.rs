@@ -0,0 +1,132 @@
+//! Predicting the memory and time that generating random latin squares will take, before starting.
+//!
+//! The Jacobson-Matthews walk of `IncidenceCube` keeps the whole n x n x n cube in memory, a byte per
+//! entry with a vector for every cell, and makes n^3 moves, each of which scans lines of the cube, so a
+//! square of order n takes about n^3 bytes and n^4 steps. At order 5000 that is 125 GB, which would send
+//! most machines into swap long before the walk ends.
+//!
+//! The memory is exact, the entries of the cube and of the square it is written to. The time is an order of
+//! magnitude: quick runs at a small order and at twice that order measure the cost of a move on this machine,
+//! a fixed part and a part that grows with the order, which are then scaled to n^3 moves. For large orders,
+//! where the cube no longer fits in the caches, the real time is longer.
+//!
+//! Sources:
+//!
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::latin_square::{IncidenceCube, Symbol};
+
+/// The order of the first calibration run, unless configured otherwise.
+pub const CALIBRATION_ORDER: usize = 12;
+
+/// What is to be generated, and how to calibrate the estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceConfig {
+    /// The number of squares to generate, one after another.
+    pub squares: u64,
+    /// The order of the first calibration run, the second being of twice the order; larger orders
+    /// calibrate better but take longer.
+    pub calibration_order: usize,
+    /// The shortest time of each calibration run, repeating the walk until it is reached.
+    pub calibration_time: Duration
+}
+
+impl ResourceConfig {
+    /// A config for one square, calibrated at orders `CALIBRATION_ORDER` and twice that, for 10 ms each.
+    pub fn new() -> ResourceConfig {
+        ResourceConfig { squares: 1, calibration_order: CALIBRATION_ORDER, calibration_time: Duration::from_millis(10) }
+    }
+
+    pub fn with_squares(mut self, squares: u64) -> ResourceConfig {
+        self.squares = squares;
+        self
+    }
+
+    pub fn with_calibration_order(mut self, order: usize) -> ResourceConfig {
+        self.calibration_order = order.max(2);
+        self
+    }
+
+    pub fn with_calibration_time(mut self, time: Duration) -> ResourceConfig {
+        self.calibration_time = time;
+        self
+    }
+}
+
+impl Default for ResourceConfig {
+    fn default() -> ResourceConfig {
+        ResourceConfig::new()
+    }
+}
+
+/// The predicted cost of generating squares of one order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceEstimate {
+    pub order: usize,
+    /// The bytes in use at once, for the cube and the square written from it.
+    pub memory_bytes: u64,
+    /// The predicted time for all the squares, in seconds.
+    pub seconds: f64
+}
+
+impl ResourceEstimate {
+    /// The predicted time.
+    pub fn time(&self) -> Duration {
+        Duration::try_from_secs_f64(self.seconds).unwrap_or(Duration::MAX)
+    }
+
+    /// The power of ten nearest the predicted time in seconds: 0 for seconds, 4 for hours, 6 for weeks.
+    pub fn time_magnitude(&self) -> i32 {
+        self.seconds.max(f64::MIN_POSITIVE).log10().round() as i32
+    }
+
+    /// Returns true if the memory needed is at most `bytes`.
+    pub fn fits_in(&self, bytes: u64) -> bool {
+        self.memory_bytes <= bytes
+    }
+}
+
+/// Predicts the memory and time of generating squares of the given order, after a short calibration run.
+///
+/// ```
+/// use combinatorial_patterns::resources::{self, ResourceConfig};
+///
+/// let small = resources::estimate_resources(20, &ResourceConfig::new());
+/// let huge = resources::estimate_resources(5000, &ResourceConfig::new());
+/// assert!(huge.memory_bytes > 125_000_000_000);
+/// assert!(!huge.fits_in(16 << 30));
+/// // Time grows as n^3 or faster.
+/// assert!(huge.seconds > small.seconds * 1e7);
+/// assert!(huge.time_magnitude() > small.time_magnitude());
+/// ```
+pub fn estimate_resources(order: usize, config: &ResourceConfig) -> ResourceEstimate {
+    let square = (order as u64).saturating_mul(mem::size_of::<Vec<Symbol>>() as u64)
+        .saturating_add((order as u64).saturating_pow(2).saturating_mul(mem::size_of::<Symbol>() as u64));
+    let memory_bytes = IncidenceCube::footprint(order).saturating_add(square);
+    // A move scans lines of n entries, so it costs a fixed time plus a time per entry.
+    let small = config.calibration_order;
+    let (at_small, at_large) = (move_time(small, config.calibration_time), move_time(2 * small, config.calibration_time));
+    let per_entry = ((at_large - at_small) / small as f64).max(0.0);
+    let fixed = (at_small - per_entry * small as f64).max(0.0);
+    let seconds = (order as f64).powi(3) * (fixed + per_entry * order as f64) * config.squares as f64;
+    ResourceEstimate { order, memory_bytes, seconds }
+}
+
+/// The time in seconds of one move at the given order on this machine, timed for at least `time`.
+fn move_time(order: usize, time: Duration) -> f64 {
+    let mut cube = IncidenceCube::new_cyclic(order.max(2)).expect("ProgrammingError: the order is at least 2.");
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut moves = 0;
+    let start = Instant::now();
+    while moves == 0 || start.elapsed() < time {
+        cube.reset_cyclic();
+        moves += cube.shuffle_with_rng(&mut rng);
+    }
+    start.elapsed().as_secs_f64() / moves as f64
+}
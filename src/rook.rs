@@ -0,0 +1,197 @@
+//! Rook polynomials: counting placements of non-attacking rooks on boards with forbidden cells.
+//!
+//! A board is a grid where some cells are allowed. Placing k rooks on allowed cells, no two in the same row
+//! or column, can be done in r_k ways, and the rook polynomial is r_0 + r_1 x + r_2 x^2 + ... With the cells
+//! where a symbol is still free in a column allowed, a placement of n rooks is a next row of a latin
+//! rectangle, so the top coefficient is the permanent counted by `LatinRectangle::count_extensions`.
+//!
+//! Rook polynomials count permutations avoiding forbidden positions by inclusion and exclusion: with
+//! the forbidden cells of an n x n board as its allowed cells, there are sum over k of (-1)^k r_k (n - k)!
+//! permutations touching none of them. Forbidding i -> i gives the derangements, forbidding i -> i and
+//! i -> i + 1 the ménage numbers.
+//!
+//! The coefficients are found row by row, keeping a count for every set of columns used so far, which
+//! takes O(rows * 2^columns * columns) steps with the shorter side of the board as its columns.
+//!
+//! Sources:
+//!
+//! - [Rook polynomial, Wikipedia](https://en.wikipedia.org/wiki/Rook_polynomial)
+
+use std::collections::HashMap;
+
+/// A board of rows x columns cells, each allowed or forbidden.
+///
+/// ```
+/// use combinatorial_patterns::rook::Board;
+///
+/// // A 3 x 3 board without its diagonal.
+/// let mut board = Board::new(3, 3);
+/// for i in 0..3 {
+///     board.forbid(i, i);
+/// }
+/// assert_eq!(board.rook_polynomial(), vec![1, 6, 9, 2]);
+/// assert_eq!(board.placements(3), vec![vec![(0, 1), (1, 2), (2, 0)], vec![(0, 2), (1, 0), (2, 1)]]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    rows: usize,
+    columns: usize,
+    allowed: Vec<Vec<bool>>
+}
+
+impl Board {
+    /// Creates a board where every cell is allowed.
+    pub fn new(rows: usize, columns: usize) -> Board {
+        Board {
+            rows,
+            columns,
+            allowed: vec![vec![true; columns]; rows]
+        }
+    }
+
+    /// Creates a board where only the given (row, column) cells are allowed.
+    pub fn from_cells(rows: usize, columns: usize, cells: &[(usize, usize)]) -> Board {
+        let mut board = Board {
+            rows,
+            columns,
+            allowed: vec![vec![false; columns]; rows]
+        };
+        for &(row, column) in cells {
+            board.allow(row, column);
+        }
+        board
+    }
+
+    /// Creates a board from rows of allowed cells. Returns None if the rows have different lengths.
+    pub fn from_matrix(allowed: Vec<Vec<bool>>) -> Option<Board> {
+        let columns = allowed.first().map(|row| row.len()).unwrap_or(0);
+        if allowed.iter().any(|row| row.len() != columns) {
+            return None
+        }
+        Some(Board { rows: allowed.len(), columns, allowed })
+    }
+
+    /// The number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns true if a rook may stand at (row, column).
+    pub fn is_allowed(&self, row: usize, column: usize) -> bool {
+        self.allowed[row][column]
+    }
+
+    /// Allows the cell at (row, column).
+    pub fn allow(&mut self, row: usize, column: usize) {
+        self.allowed[row][column] = true;
+    }
+
+    /// Forbids the cell at (row, column).
+    pub fn forbid(&mut self, row: usize, column: usize) {
+        self.allowed[row][column] = false;
+    }
+
+    /// The coefficients r_0, r_1, .. of the rook polynomial, up to the largest k with r_k > 0.
+    /// Panics if both sides of the board are longer than 128.
+    pub fn rook_polynomial(&self) -> Vec<u128> {
+        // Use the shorter side as the columns whose sets are tracked.
+        let (rows, columns) = if self.columns <= self.rows { (self.rows, self.columns) } else { (self.columns, self.rows) };
+        if columns > 128 {
+            panic!("Rook polynomials are limited to boards with a side of at most 128, not {} x {}", self.rows, self.columns);
+        }
+        let allowed = |row: usize, column: usize| if self.columns <= self.rows { self.allowed[row][column] } else { self.allowed[column][row] };
+
+        let mut counts: HashMap<u128, u128> = HashMap::new();
+        counts.insert(0, 1);
+        for row in 0..rows {
+            let mut next = counts.clone();
+            for (&used, &count) in &counts {
+                for column in (0..columns).filter(|&column| used & (1 << column) == 0 && allowed(row, column)) {
+                    *next.entry(used | (1 << column)).or_insert(0) += count;
+                }
+            }
+            counts = next;
+        }
+
+        let mut coefficients = vec![0; columns + 1];
+        for (used, count) in counts {
+            coefficients[used.count_ones() as usize] += count;
+        }
+        while coefficients.len() > 1 && coefficients.last() == Some(&0) {
+            coefficients.pop();
+        }
+        coefficients
+    }
+
+    /// The number of ways to place `rooks` non-attacking rooks on allowed cells.
+    pub fn count_placements(&self, rooks: usize) -> u128 {
+        self.rook_polynomial().get(rooks).copied().unwrap_or(0)
+    }
+
+    /// Every placement of `rooks` non-attacking rooks on allowed cells, as (row, column) cells in row order.
+    /// The placements are listed in lexicographic order.
+    pub fn placements(&self, rooks: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut found = Vec::new();
+        let mut used = vec![false; self.columns];
+        self.place(0, rooks, &mut used, &mut Vec::new(), &mut found);
+        found
+    }
+
+    fn place(&self, row: usize, rooks: usize, used: &mut [bool], placed: &mut Vec<(usize, usize)>, found: &mut Vec<Vec<(usize, usize)>>) {
+        if placed.len() == rooks {
+            found.push(placed.clone());
+            return
+        }
+        // Not enough rows left for the remaining rooks.
+        if self.rows - row < rooks - placed.len() {
+            return
+        }
+        for column in 0..self.columns {
+            if !used[column] && self.allowed[row][column] {
+                used[column] = true;
+                placed.push((row, column));
+                self.place(row + 1, rooks, used, placed, found);
+                placed.pop();
+                used[column] = false;
+            }
+        }
+        self.place(row + 1, rooks, used, placed, found);
+    }
+
+    /// For a square board, the number of permutations p with no (i, p(i)) among the allowed cells of the board,
+    /// counted by inclusion and exclusion over its rook polynomial. Returns None for boards that are not square.
+    ///
+    /// ```
+    /// use combinatorial_patterns::rook::Board;
+    ///
+    /// // The ménage problem for 5 couples: seat the men so that none sits next to his wife.
+    /// let n = 5;
+    /// let forbidden: Vec<(usize, usize)> = (0..n).flat_map(|i| [(i, i), (i, (i + 1) % n)]).collect();
+    /// assert_eq!(Board::from_cells(n, n, &forbidden).count_avoiding_permutations(), Some(13));
+    /// ```
+    pub fn count_avoiding_permutations(&self) -> Option<u128> {
+        if self.rows != self.columns {
+            return None
+        }
+        let n = self.rows;
+        let mut factorial = vec![1u128; n + 1];
+        for i in 1..=n {
+            factorial[i] = factorial[i - 1] * i as u128;
+        }
+        let mut total: i128 = 0;
+        for (k, &ways) in self.rook_polynomial().iter().enumerate() {
+            let term = (ways * factorial[n - k]) as i128;
+            if k.is_multiple_of(2) {
+                total += term;
+            } else {
+                total -= term;
+            }
+        }
+        Some(total as u128)
+    }
+}
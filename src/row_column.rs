@@ -0,0 +1,145 @@
+//! Nested row-column designs: replicates that each block their plots two ways, by rows and by columns, with
+//! every treatment once in every row and column of each replicate.
+//!
+//! A replicate is an n x n array of cells, each cell a block of k plots. With k = 1 it is a latin square
+//! on n treatments. Trojan squares take k mutually orthogonal latin squares of order n for nk treatments in
+//! k groups: cell (i, j) holds treatment n * g + L_g(i, j) of each group g. Every row and column then holds
+//! every treatment once, treatments of the same group never share a cell, and treatments of different groups
+//! share exactly one cell, by orthogonality. Replicates of latin squares are taken from a set of MOLS, so that
+//! any two replicates are orthogonal too.
+//!
+//! Sources:
+//!
+//! - [Trojan square and incomplete Trojan square designs for crop research, R. N. Edmondson](https://doi.org/10.1017/S0021859698005887)
+
+use crate::design::{LongFormat, LongTable};
+use crate::latin_square::LatinSquare;
+use crate::mols;
+
+/// Replicates of n x n arrays of cells, each cell a block of k plots of treatments 0..v.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowColumnDesign {
+    treatments: usize,
+    /// `replicates[replicate][row][column]` are the treatments of the plots of that cell.
+    replicates: Vec<Vec<Vec<Vec<usize>>>>
+}
+
+impl RowColumnDesign {
+    /// The number of treatments v.
+    pub fn treatments(&self) -> usize {
+        self.treatments
+    }
+
+    /// The replicates, as rows of cells, each cell the treatments of its plots.
+    pub fn replicates(&self) -> &[Vec<Vec<Vec<usize>>>] {
+        &self.replicates
+    }
+
+    /// Returns true if every row and every column of every replicate holds every treatment exactly once.
+    pub fn is_balanced(&self) -> bool {
+        let once = |treatments: &mut dyn Iterator<Item = usize>| {
+            let mut seen = vec![false; self.treatments];
+            let mut count = 0;
+            for treatment in treatments {
+                if treatment >= self.treatments || seen[treatment] {
+                    return false
+                }
+                seen[treatment] = true;
+                count += 1;
+            }
+            count == self.treatments
+        };
+        self.replicates.iter().all(|rows| {
+            let order = rows.len();
+            (0..order).all(|row| once(&mut rows[row].iter().flatten().copied()))
+                && (0..order).all(|column| once(&mut rows.iter().flat_map(|cells| cells[column].iter().copied())))
+        })
+    }
+
+    /// How often each pair of treatments shares a cell, over all replicates.
+    pub fn concurrences(&self) -> Vec<Vec<usize>> {
+        let mut counts = vec![vec![0; self.treatments]; self.treatments];
+        for cell in self.replicates.iter().flatten().flatten() {
+            for &a in cell {
+                for &b in cell {
+                    counts[a][b] += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Records of (replicate, row, column, plot, treatment), all numbered from 1 as is usual in field trials.
+impl LongFormat for RowColumnDesign {
+    fn to_long_format(&self) -> LongTable {
+        let mut records = Vec::new();
+        for (replicate, rows) in self.replicates.iter().enumerate() {
+            for (row, cells) in rows.iter().enumerate() {
+                for (column, plots) in cells.iter().enumerate() {
+                    for (plot, &treatment) in plots.iter().enumerate() {
+                        records.push([replicate, row, column, plot, treatment].iter().map(|index| (index + 1).to_string()).collect());
+                    }
+                }
+            }
+        }
+        LongTable {
+            header: ["Replicate", "Row", "Column", "Plot", "Treatment"].iter().map(|name| name.to_string()).collect(),
+            records
+        }
+    }
+}
+
+/// The Trojan square of order n with k plots per cell, for nk treatments in one replicate. Returns None
+/// unless 1 <= k <= m, when `mols::new(n)` gives m squares; prime powers n allow up to n - 1.
+///
+/// ```
+/// use combinatorial_patterns::row_column;
+///
+/// // 3 groups of 4 treatments in a 4 x 4 array of cells with 3 plots each.
+/// let design = row_column::trojan_square(4, 3).unwrap();
+/// assert_eq!(design.treatments(), 12);
+/// assert!(design.is_balanced());
+/// let concurrences = design.concurrences();
+/// assert_eq!((concurrences[0][1], concurrences[0][4]), (0, 1));
+/// ```
+pub fn trojan_square(n: usize, k: usize) -> Option<RowColumnDesign> {
+    let squares = mols::new(n).unwrap_or_default();
+    if n == 0 || k == 0 || k > squares.len() {
+        return None
+    }
+    let squares = &squares[..k];
+    let rows = (0..n).map(|row| (0..n).map(|column| {
+        squares.iter().enumerate().map(|(group, square)| n * group + square.square[row][column]).collect()
+    }).collect()).collect();
+    Some(RowColumnDesign { treatments: n * k, replicates: vec![rows] })
+}
+
+/// Replicates of n x n latin squares for n treatments, one plot per cell, taken from a set of MOLS so that
+/// any two replicates are orthogonal. Returns None unless 1 <= replicates <= m, when `mols::new(n)` gives m squares.
+///
+/// ```
+/// use combinatorial_patterns::row_column;
+/// use combinatorial_patterns::design::LongFormat;
+///
+/// let design = row_column::latin_square_replicates(5, 3).unwrap();
+/// assert!(design.is_balanced());
+/// assert_eq!(design.to_long_format().records.len(), 75);
+/// ```
+pub fn latin_square_replicates(n: usize, replicates: usize) -> Option<RowColumnDesign> {
+    let squares = mols::new(n).unwrap_or_default();
+    if n == 0 || replicates == 0 || replicates > squares.len() {
+        return None
+    }
+    Some(from_squares(&squares[..replicates]))
+}
+
+/// A design with one replicate per latin square, one plot per cell. The squares should have the same order.
+pub fn from_squares(squares: &[LatinSquare]) -> RowColumnDesign {
+    RowColumnDesign {
+        treatments: squares.first().map(|square| square.size()).unwrap_or(0),
+        replicates: squares.iter().map(|square| {
+            square.square.iter().map(|row| row.iter().map(|&symbol| vec![symbol]).collect()).collect()
+        }).collect()
+    }
+}
@@ -0,0 +1,872 @@
+//! Scheduling: exam timetables and rotating staff rosters.
+//!
+//! An exam timetable puts every exam in a timeslot and a room, such that no two exams with a common student
+//! share a timeslot, and every exam in a timeslot gets its own room with enough seats. Timeslots are colours of
+//! the conflict graph between exams. Rooms are a matching between the exams of a timeslot and the rooms
+//! that fit them, found by augmenting paths, so a timeslot works when that matching covers all its exams.
+//!
+//! The timeslots start from a greedy colouring, most conflicted exams first, and are repaired by
+//! min-conflicts local search: a random exam that has a conflict, or whose timeslot is short of rooms, moves to
+//! the timeslot where it causes the fewest problems, and sometimes to a random one, to get out of local minima.
+//!
+//! A rotating roster gives n staff a shift in each of n periods. All staff follow one cyclic pattern of
+//! shifts, staff s starting at position s of it, so staff s works position (s + p) mod n in period p: the
+//! positions form a cyclic latin square. Every staff member works the whole pattern over a cycle and each
+//! period has every position once, so each shift type is worked by the same number of staff in every period.
+//! Constraints between consecutive periods, such as no early shift after a night, only depend on the pattern,
+//! read cyclically, and the pattern is found by branch and bound over arrangements of the shift types.
+//!
+//! A round-robin tournament is a 1-factorization of the complete graph on the teams: each round is a perfect
+//! matching, and the rounds partition the games. The canonical 1-factorization fixes team n - 1 and turns
+//! the others around a circle, pairing teams r + i and r - i (mod n - 1) in round r. A break is a team playing
+//! at home, or away, in two consecutive rounds. With the home team of the fixed game alternating by round,
+//! and the other games' home team alternating by i, a single round-robin has n - 2 breaks, the fewest
+//! possible. The double round-robin mirrors it, repeating the rounds with home and away swapped, for 3n - 6
+//! breaks, the fewest for mirrored schedules. An odd number of teams gets a bye in place of the fixed team,
+//! leaving no breaks in a single round-robin and n - 2 in a double one. Venue constraints are met, where
+//! possible, by branch and bound over which team takes which place in the canonical schedule.
+//!
+//! Sources:
+//!
+//! - [Graph coloring, Wikipedia](https://en.wikipedia.org/wiki/Graph_coloring)
+//! - [A survey of search methodologies and automated system development for examination timetabling, R. Qu, E. K. Burke, B. McCollum, L. T. G. Merlot, S. Y. Lee](https://doi.org/10.1007/s10951-008-0077-5)
+//! - [Scheduling in sports, D. de Werra](https://doi.org/10.1016/S0304-0208(08)73235-3)
+//! - Cyclic rostering, in Staff scheduling and rostering: a review of applications, methods and models, A. T. Ernst, H. Jiang, M. Krishnamoorthy, D. Sier
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::design::{aligned, csv, LongFormat, LongTable};
+use crate::latin_square::LatinSquare;
+use crate::matching::BipartiteGraph;
+
+/// The chance that a local search move goes to a random timeslot instead of the best one.
+const RANDOM_MOVE_PROBABILITY: f64 = 0.1;
+
+/// The number of partial patterns the roster search tries before it settles for the best pattern so far.
+pub const ROSTER_SEARCH_LIMIT: usize = 1_000_000;
+
+/// The number of partial assignments of teams the league search tries before it settles for the best so far.
+pub const LEAGUE_SEARCH_LIMIT: usize = 1_000_000;
+
+/// An exam with the number of students taking it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exam {
+    pub name: String,
+    pub students: usize
+}
+
+/// A room with its number of seats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub capacity: usize
+}
+
+/// Exams to place in timeslots and rooms, with the pairs of exams that share students.
+///
+/// ```
+/// use combinatorial_patterns::scheduling::{Exam, ExamProblem, Room};
+///
+/// let exams = ["Algebra", "Biology", "Chemistry", "Drama"].iter()
+///     .map(|name| Exam { name: name.to_string(), students: 30 })
+///     .collect();
+/// let rooms = vec![Room { name: "Hall".to_string(), capacity: 100 }, Room { name: "Lab".to_string(), capacity: 40 }];
+/// // Two students, one taking Algebra, Biology and Chemistry, the other Chemistry and Drama.
+/// let problem = ExamProblem::from_enrolments(exams, rooms, 2, &[vec![0, 1, 2], vec![2, 3]]);
+/// // Three exams that conflict pairwise need three timeslots.
+/// let mut rng = rand::thread_rng();
+/// assert!(problem.solve(1000, &mut rng).is_none());
+///
+/// let problem = ExamProblem::from_enrolments(problem.exams().to_vec(), problem.rooms().to_vec(), 3, &[vec![0, 1, 2], vec![2, 3]]);
+/// let timetable = problem.solve(1000, &mut rng).unwrap();
+/// assert!(problem.is_valid(&timetable));
+/// assert_ne!(timetable.slot[2], timetable.slot[3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExamProblem {
+    exams: Vec<Exam>,
+    rooms: Vec<Room>,
+    slots: usize,
+    conflicts: Vec<Vec<bool>>
+}
+
+impl ExamProblem {
+    /// A problem without conflicts yet.
+    pub fn new(exams: Vec<Exam>, rooms: Vec<Room>, slots: usize) -> ExamProblem {
+        let count = exams.len();
+        ExamProblem { exams, rooms, slots, conflicts: vec![vec![false; count]; count] }
+    }
+
+    /// A problem where exams conflict if some student takes both. Each enrolment lists the exams of one student.
+    pub fn from_enrolments(exams: Vec<Exam>, rooms: Vec<Room>, slots: usize, enrolments: &[Vec<usize>]) -> ExamProblem {
+        let mut problem = ExamProblem::new(exams, rooms, slots);
+        for taken in enrolments {
+            for &a in taken {
+                for &b in taken {
+                    if a != b {
+                        problem.add_conflict(a, b);
+                    }
+                }
+            }
+        }
+        problem
+    }
+
+    /// Forbids two exams from sharing a timeslot.
+    pub fn add_conflict(&mut self, a: usize, b: usize) {
+        self.conflicts[a][b] = true;
+        self.conflicts[b][a] = true;
+    }
+
+    /// The exams.
+    pub fn exams(&self) -> &[Exam] {
+        &self.exams
+    }
+
+    /// The rooms.
+    pub fn rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    /// The number of timeslots.
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Returns true if no conflicting exams share a timeslot, and every exam has its own room with enough
+    /// seats for its timeslot.
+    pub fn is_valid(&self, timetable: &Timetable) -> bool {
+        let count = self.exams.len();
+        timetable.slot.len() == count && timetable.room.len() == count
+            && timetable.slot.iter().all(|&slot| slot < self.slots)
+            && (0..count).all(|exam| timetable.room[exam] < self.rooms.len() && self.exams[exam].students <= self.rooms[timetable.room[exam]].capacity)
+            && (0..count).all(|a| ((a + 1)..count).all(|b| {
+                timetable.slot[a] != timetable.slot[b] || (!self.conflicts[a][b] && timetable.room[a] != timetable.room[b])
+            }))
+    }
+
+    /// Searches for a timetable with at most `steps` local search moves, drawn from `rng`. Returns None if
+    /// none was found, which includes problems without any.
+    pub fn solve(&self, steps: usize, rng: &mut impl Rng) -> Option<Timetable> {
+        let count = self.exams.len();
+        if self.slots == 0 {
+            return if count == 0 { Some(self.timetable(Vec::new())) } else { None }
+        }
+        let mut slot = self.greedy_slots();
+        let mut cost = self.cost(&slot);
+
+        for _ in 0..steps {
+            if cost == 0 {
+                break
+            }
+            let troubled: Vec<usize> = (0..count).filter(|&exam| self.is_troubled(&slot, exam)).collect();
+            let &exam = troubled.choose(rng).expect("ProgrammingError: a positive cost has a troubled exam.");
+            let current = slot[exam];
+            let target = if rng.gen_bool(RANDOM_MOVE_PROBABILITY) {
+                rng.gen_range(0..self.slots)
+            } else {
+                let mut best = Vec::new();
+                let mut lowest = usize::MAX;
+                for candidate in 0..self.slots {
+                    slot[exam] = candidate;
+                    let candidate_cost = self.cost(&slot);
+                    if candidate_cost < lowest {
+                        lowest = candidate_cost;
+                        best.clear();
+                    }
+                    if candidate_cost == lowest {
+                        best.push(candidate);
+                    }
+                }
+                *best.choose(rng).expect("ProgrammingError: there is at least one timeslot.")
+            };
+            slot[exam] = target;
+            if target != current {
+                cost = self.cost(&slot);
+            }
+        }
+        (cost == 0).then(|| self.timetable(slot))
+    }
+
+    /// Colours exams greedily, most conflicts first, each with the timeslot of fewest conflicts so far.
+    fn greedy_slots(&self) -> Vec<usize> {
+        let count = self.exams.len();
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by_key(|&exam| std::cmp::Reverse(self.conflicts[exam].iter().filter(|&&conflict| conflict).count()));
+        let mut slot = vec![usize::MAX; count];
+        for exam in order {
+            let clashes = |candidate: usize| (0..count).filter(|&other| slot[other] == candidate && self.conflicts[exam][other]).count();
+            let loads = |candidate: usize| slot.iter().filter(|&&other| other == candidate).count();
+            slot[exam] = (0..self.slots).min_by_key(|&candidate| (clashes(candidate), loads(candidate))).unwrap_or(0);
+        }
+        slot
+    }
+
+    /// Conflicting pairs sharing a timeslot, plus the exams left without a room in each timeslot.
+    fn cost(&self, slot: &[usize]) -> usize {
+        let count = self.exams.len();
+        let clashes = (0..count).map(|a| ((a + 1)..count).filter(|&b| slot[a] == slot[b] && self.conflicts[a][b]).count()).sum::<usize>();
+        let shortage = (0..self.slots).map(|candidate| {
+            let exams: Vec<usize> = (0..count).filter(|&exam| slot[exam] == candidate).collect();
+            exams.len() - self.room_matching(&exams).iter().flatten().count()
+        }).sum::<usize>();
+        clashes + shortage
+    }
+
+    fn is_troubled(&self, slot: &[usize], exam: usize) -> bool {
+        let together: Vec<usize> = (0..self.exams.len()).filter(|&other| slot[other] == slot[exam]).collect();
+        together.iter().any(|&other| self.conflicts[exam][other])
+            || self.room_matching(&together).iter().any(|room| room.is_none())
+    }
+
+    /// The room of each of the given exams by a maximum matching, None for those left without one.
+    fn room_matching(&self, exams: &[usize]) -> Vec<Option<usize>> {
+        let mut graph = BipartiteGraph::new(exams.len(), self.rooms.len());
+        for (index, &exam) in exams.iter().enumerate() {
+            for (room, details) in self.rooms.iter().enumerate() {
+                if self.exams[exam].students <= details.capacity {
+                    graph.add_edge(index, room);
+                }
+            }
+        }
+        let matching = graph.maximum_matching();
+        (0..exams.len()).map(|index| matching.right_of(index)).collect()
+    }
+
+    fn timetable(&self, slot: Vec<usize>) -> Timetable {
+        let mut room = vec![0; self.exams.len()];
+        for candidate in 0..self.slots {
+            let exams: Vec<usize> = (0..self.exams.len()).filter(|&exam| slot[exam] == candidate).collect();
+            for (&exam, assigned) in exams.iter().zip(self.room_matching(&exams)) {
+                room[exam] = assigned.expect("ProgrammingError: a timetable without cost has a room for every exam.");
+            }
+        }
+        Timetable {
+            slot,
+            room,
+            exam_names: self.exams.iter().map(|exam| exam.name.clone()).collect(),
+            room_names: self.rooms.iter().map(|room| room.name.clone()).collect(),
+            slots: self.slots
+        }
+    }
+}
+
+/// A timeslot and room for every exam. `Display` writes a table per timeslot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timetable {
+    /// The timeslot of each exam.
+    pub slot: Vec<usize>,
+    /// The room of each exam.
+    pub room: Vec<usize>,
+    exam_names: Vec<String>,
+    room_names: Vec<String>,
+    slots: usize
+}
+
+impl Timetable {
+    /// The (exam, room) pairs of a timeslot, by room.
+    pub fn slot_table(&self, slot: usize) -> Vec<(usize, usize)> {
+        let mut table: Vec<(usize, usize)> = (0..self.slot.len())
+            .filter(|&exam| self.slot[exam] == slot)
+            .map(|exam| (exam, self.room[exam]))
+            .collect();
+        table.sort_by_key(|&(_, room)| room);
+        table
+    }
+}
+
+impl fmt::Display for Timetable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.room_names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+        for slot in 0..self.slots {
+            if slot > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "Timeslot {}", slot + 1)?;
+            for (exam, room) in self.slot_table(slot) {
+                writeln!(f, "    {:width$}  {}", self.room_names[room], self.exam_names[exam], width = width)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A shift type and the number of staff working it in every period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+    pub name: String,
+    pub staff: usize
+}
+
+/// Shift types for a rotating roster, with constraints on the shifts of consecutive periods. The number of
+/// staff, and of periods in a cycle, is the total staff of the shift types.
+///
+/// ```
+/// use combinatorial_patterns::scheduling::{RosterProblem, Shift};
+///
+/// let shift = |name: &str, staff| Shift { name: name.to_string(), staff };
+/// let mut problem = RosterProblem::new(vec![shift("Early", 2), shift("Late", 2), shift("Night", 2), shift("Off", 1)]);
+/// problem.forbid_transition(2, 0);
+/// problem.forbid_transition(2, 1);
+/// problem.forbid_transition(1, 0);
+/// problem.limit_run(2, 2);
+///
+/// let roster = problem.solve().unwrap();
+/// assert!(problem.violations(roster.pattern()).is_empty());
+/// // Every period has two staff on nights.
+/// assert!((0..7).all(|period| (0..7).filter(|&staff| roster.shift(staff, period) == "Night").count() == 2));
+///
+/// // With one early and one night shift, a night always comes before an early one in the next cycle.
+/// let mut problem = RosterProblem::new(vec![shift("Early", 1), shift("Night", 1)]);
+/// problem.forbid_transition(1, 0);
+/// let conflicts = problem.solve().unwrap_err();
+/// assert_eq!(conflicts.violations.len(), 1);
+/// assert_eq!(conflicts.to_string(), "periods 2 to 1: Night followed by Early");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RosterProblem {
+    shifts: Vec<Shift>,
+    forbidden: Vec<Vec<bool>>,
+    run_limits: Vec<Option<usize>>
+}
+
+impl RosterProblem {
+    /// A problem without constraints yet.
+    pub fn new(shifts: Vec<Shift>) -> RosterProblem {
+        let count = shifts.len();
+        RosterProblem { shifts, forbidden: vec![vec![false; count]; count], run_limits: vec![None; count] }
+    }
+
+    /// Forbids shift `to` in the period right after shift `from`.
+    pub fn forbid_transition(&mut self, from: usize, to: usize) {
+        self.forbidden[from][to] = true;
+    }
+
+    /// Allows at most `limit` consecutive periods of a shift.
+    pub fn limit_run(&mut self, shift: usize, limit: usize) {
+        self.run_limits[shift] = Some(limit);
+    }
+
+    /// The shift types.
+    pub fn shifts(&self) -> &[Shift] {
+        &self.shifts
+    }
+
+    /// The number of staff, which is also the number of periods in a cycle.
+    pub fn order(&self) -> usize {
+        self.shifts.iter().map(|shift| shift.staff).sum()
+    }
+
+    /// The constraints a cyclic pattern of shift indices breaks, with periods counted along the pattern.
+    pub fn violations(&self, pattern: &[usize]) -> Vec<RosterViolation> {
+        let n = pattern.len();
+        let name = |shift: usize| self.shifts[shift].name.clone();
+        let mut violations = Vec::new();
+        for period in 0..n {
+            let (from, to) = (pattern[period], pattern[(period + 1) % n]);
+            if self.forbidden[from][to] {
+                violations.push(RosterViolation::ForbiddenTransition { period, next: (period + 1) % n, from: name(from), to: name(to) });
+            }
+        }
+        for (start, length) in cyclic_runs(pattern) {
+            let shift = pattern[start];
+            if let Some(limit) = self.run_limits[shift] {
+                if length > limit {
+                    violations.push(RosterViolation::RunTooLong { shift: name(shift), start, length, limit });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Finds a roster that meets all constraints. Otherwise returns the roster with the fewest violations
+    /// found, counting each period past a run limit, together with its violations.
+    pub fn solve(&self) -> Result<Roster, RosterConflicts> {
+        let n = self.order();
+        let mut search = RosterSearch {
+            problem: self,
+            remaining: self.shifts.iter().map(|shift| shift.staff).collect(),
+            pattern: Vec::with_capacity(n),
+            order: n,
+            best: None,
+            nodes: 0
+        };
+        // Rotating a pattern only renumbers the staff, so it can start with the first shift type that has staff.
+        if let Some(first) = self.shifts.iter().position(|shift| shift.staff > 0) {
+            search.remaining[first] -= 1;
+            search.pattern.push(first);
+            search.extend(0, 1);
+        } else {
+            search.best = Some((0, Vec::new()));
+        }
+        let (_, pattern) = search.best.expect("ProgrammingError: the search completes at least one pattern.");
+        let violations = self.violations(&pattern);
+        let roster = self.roster(pattern);
+        if violations.is_empty() {
+            Ok(roster)
+        } else {
+            Err(RosterConflicts { roster, violations })
+        }
+    }
+
+    /// Forbidden transitions plus the periods past a run limit.
+    fn cost(&self, pattern: &[usize]) -> usize {
+        self.violations(pattern).iter().map(|violation| match violation {
+            RosterViolation::ForbiddenTransition { .. } => 1,
+            RosterViolation::RunTooLong { length, limit, .. } => length - limit
+        }).sum()
+    }
+
+    fn roster(&self, pattern: Vec<usize>) -> Roster {
+        Roster { names: self.shifts.iter().map(|shift| shift.name.clone()).collect(), pattern }
+    }
+}
+
+/// The (start, length) of the maximal runs of equal shifts in a cyclic pattern. A pattern of one shift is one run.
+fn cyclic_runs(pattern: &[usize]) -> Vec<(usize, usize)> {
+    let n = pattern.len();
+    let Some(offset) = (0..n).find(|&index| pattern[index] != pattern[(index + n - 1) % n]) else {
+        return if n == 0 { Vec::new() } else { vec![(0, n)] }
+    };
+    let mut runs = Vec::new();
+    let mut start = offset;
+    for step in 1..=n {
+        let index = (offset + step) % n;
+        if step == n || pattern[index] != pattern[start] {
+            runs.push((start, (index + n - start) % n));
+            start = index;
+        }
+    }
+    runs
+}
+
+/// Branch and bound over patterns, by a cost of the pattern so far that never exceeds the cost of its completions.
+struct RosterSearch<'a> {
+    problem: &'a RosterProblem,
+    remaining: Vec<usize>,
+    pattern: Vec<usize>,
+    order: usize,
+    best: Option<(usize, Vec<usize>)>,
+    nodes: usize
+}
+
+impl RosterSearch<'_> {
+    fn bound(&self) -> usize {
+        self.best.as_ref().map(|(cost, _)| *cost).unwrap_or(usize::MAX)
+    }
+
+    fn extend(&mut self, cost: usize, run: usize) {
+        self.nodes += 1;
+        if self.pattern.len() == self.order {
+            let total = self.problem.cost(&self.pattern);
+            if total < self.bound() {
+                self.best = Some((total, self.pattern.clone()));
+            }
+            return
+        }
+        let previous = *self.pattern.last().expect("ProgrammingError: the pattern starts with a shift.");
+        for shift in 0..self.remaining.len() {
+            if self.remaining[shift] == 0 || self.bound() == 0 || (self.nodes > ROSTER_SEARCH_LIMIT && self.best.is_some()) {
+                continue
+            }
+            let next_run = if shift == previous { run + 1 } else { 1 };
+            let added = usize::from(self.problem.forbidden[previous][shift])
+                + usize::from(self.problem.run_limits[shift].is_some_and(|limit| next_run > limit));
+            if cost + added >= self.bound() {
+                continue
+            }
+            self.remaining[shift] -= 1;
+            self.pattern.push(shift);
+            self.extend(cost + added, next_run);
+            self.pattern.pop();
+            self.remaining[shift] += 1;
+        }
+    }
+}
+
+/// A constraint broken by a roster pattern. Periods are numbered along the pattern, that is the periods of
+/// the first staff member, from 0; `Display` numbers them from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RosterViolation {
+    /// Shift `to` in period `next`, after shift `from` in `period`. The next period of the last is the first.
+    ForbiddenTransition { period: usize, next: usize, from: String, to: String },
+    /// A run of `length` periods of a shift from `start`, longer than its limit.
+    RunTooLong { shift: String, start: usize, length: usize, limit: usize }
+}
+
+impl fmt::Display for RosterViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RosterViolation::ForbiddenTransition { period, next, from, to } => {
+                write!(f, "periods {} to {}: {} followed by {}", period + 1, next + 1, from, to)
+            },
+            RosterViolation::RunTooLong { shift, start, length, limit } => {
+                write!(f, "period {}: {} periods of {} in a row, at most {} allowed", start + 1, length, shift, limit)
+            }
+        }
+    }
+}
+
+/// The roster closest to the constraints when none meets them all, with the constraints it breaks.
+#[derive(Debug, Clone)]
+pub struct RosterConflicts {
+    pub roster: Roster,
+    pub violations: Vec<RosterViolation>
+}
+
+impl fmt::Display for RosterConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.violations.iter().map(|violation| violation.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// A rotating roster of n staff over n periods. `Display` writes a table with a row per staff member.
+#[derive(Debug, Clone)]
+pub struct Roster {
+    names: Vec<String>,
+    pattern: Vec<usize>
+}
+
+impl Roster {
+    /// The cyclic pattern of shift indices that all staff follow.
+    pub fn pattern(&self) -> &[usize] {
+        &self.pattern
+    }
+
+    /// The cyclic latin square of the position in the pattern that each staff member works in each period.
+    pub fn square(&self) -> LatinSquare {
+        let n = self.pattern.len();
+        LatinSquare::from_rows((0..n).map(|staff| (0..n).map(|period| (staff + period) % n).collect()).collect())
+    }
+
+    /// The name of the shift a staff member works in a period.
+    pub fn shift(&self, staff: usize, period: usize) -> &str {
+        &self.names[self.pattern[(staff + period) % self.pattern.len()]]
+    }
+
+    /// The roster as CSV, laid out like the table `Display` writes.
+    pub fn to_csv(&self) -> String {
+        csv(&self.table())
+    }
+
+    fn table(&self) -> Vec<Vec<String>> {
+        let n = self.pattern.len();
+        let header = std::iter::once("Staff".to_string()).chain((1..=n).map(|period| format!("Period {}", period)));
+        let mut table = vec![header.collect()];
+        for staff in 0..n {
+            let shifts = (0..n).map(|period| self.shift(staff, period).to_string());
+            table.push(std::iter::once((staff + 1).to_string()).chain(shifts).collect());
+        }
+        table
+    }
+}
+
+impl fmt::Display for Roster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = self.table();
+        let borrowed: Vec<Vec<&str>> = table.iter().map(|row| row.iter().map(|cell| cell.as_str()).collect()).collect();
+        write!(f, "{}", aligned(&borrowed))
+    }
+}
+
+/// Teams for a round-robin league, with the rounds in which their venues are unavailable and the teams that
+/// share a venue, so that they can't both play at home in the same round. Rounds count from 0 over all legs.
+///
+/// ```
+/// use combinatorial_patterns::scheduling::LeagueProblem;
+/// use combinatorial_patterns::design::LongFormat;
+///
+/// let teams = ["Albion", "Rovers", "United", "City", "Athletic", "Wanderers"];
+/// let mut problem = LeagueProblem::new(teams.iter().map(|team| team.to_string()).collect());
+/// problem.share_venue(1, 2);
+/// problem.venue_unavailable(0, 0);
+///
+/// let league = problem.double_round_robin().unwrap();
+/// assert_eq!(league.rounds().len(), 10);
+/// // 3n - 6 breaks, the fewest for a mirrored double round-robin.
+/// assert_eq!(league.total_breaks(), 12);
+/// assert_eq!(league.home_away(0)[0], Some(false));
+/// assert!(problem.violations(&league).is_empty());
+/// // Rovers and United share a venue, so one is always away when the other is at home.
+/// assert!((0..10).all(|round| league.home_away(1)[round] != league.home_away(2)[round]));
+/// assert_eq!(league.to_long_format().to_csv().lines().next(), Some("Round,Home,Away"));
+///
+/// // Five teams, each with a bye in one round of each leg.
+/// let league = LeagueProblem::new(teams[..5].iter().map(|team| team.to_string()).collect()).single_round_robin().unwrap();
+/// assert_eq!((league.rounds().len(), league.total_breaks()), (5, 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LeagueProblem {
+    teams: Vec<String>,
+    unavailable: Vec<Vec<usize>>,
+    shared: Vec<(usize, usize)>
+}
+
+impl LeagueProblem {
+    /// A problem without venue constraints yet.
+    pub fn new(teams: Vec<String>) -> LeagueProblem {
+        let count = teams.len();
+        LeagueProblem { teams, unavailable: vec![Vec::new(); count], shared: Vec::new() }
+    }
+
+    /// Forbids a team from playing at home in a round.
+    pub fn venue_unavailable(&mut self, team: usize, round: usize) {
+        self.unavailable[team].push(round);
+    }
+
+    /// Forbids two teams that share a venue from both playing at home in the same round.
+    pub fn share_venue(&mut self, a: usize, b: usize) {
+        self.shared.push((a, b));
+    }
+
+    /// The teams.
+    pub fn teams(&self) -> &[String] {
+        &self.teams
+    }
+
+    /// Schedules every pair of teams once. Returns the schedule with the fewest violations found, with its
+    /// violations, if none meets all venue constraints.
+    pub fn single_round_robin(&self) -> Result<League, LeagueConflicts> {
+        self.solve(1)
+    }
+
+    /// Schedules every pair of teams twice, once at each venue, mirroring the first leg in the second.
+    pub fn double_round_robin(&self) -> Result<League, LeagueConflicts> {
+        self.solve(2)
+    }
+
+    /// The constraints a league breaks.
+    pub fn violations(&self, league: &League) -> Vec<LeagueViolation> {
+        let home: Vec<Vec<bool>> = (0..self.teams.len())
+            .map(|team| league.home_away(team).iter().map(|&venue| venue == Some(true)).collect())
+            .collect();
+        let mut violations = Vec::new();
+        for (team, rounds) in self.unavailable.iter().enumerate() {
+            for &round in rounds {
+                if home[team].get(round) == Some(&true) {
+                    violations.push(LeagueViolation::VenueUnavailable { team: self.teams[team].clone(), round });
+                }
+            }
+        }
+        for &(a, b) in &self.shared {
+            for (round, (&a_home, &b_home)) in home[a].iter().zip(&home[b]).enumerate() {
+                if a_home && b_home {
+                    violations.push(LeagueViolation::SharedVenue { first: self.teams[a].clone(), second: self.teams[b].clone(), round });
+                }
+            }
+        }
+        violations
+    }
+
+    fn solve(&self, legs: usize) -> Result<League, LeagueConflicts> {
+        let count = self.teams.len();
+        let places = count + count % 2;
+        let schedule = canonical_schedule(places, legs);
+        // Places at home in each round; with an odd number of teams the fixed place is the bye.
+        let home: Vec<Vec<bool>> = (0..places).map(|place| schedule.iter().map(|games| {
+            games.iter().any(|&(host, guest)| host == place && guest < count)
+        }).collect()).collect();
+        let mut search = LeagueSearch { problem: self, home, team_of: vec![None; count], position: vec![usize::MAX; count], best: None, nodes: 0 };
+        search.extend(0, 0);
+        let (_, position) = search.best.expect("ProgrammingError: the search completes at least one assignment.");
+        let league = League {
+            teams: self.teams.clone(),
+            rounds: schedule.iter().map(|games| games.iter()
+                .filter(|&&(host, guest)| host < count && guest < count)
+                .map(|&(host, guest)| (position[host], position[guest]))
+                .collect()).collect()
+        };
+        let violations = self.violations(&league);
+        if violations.is_empty() {
+            Ok(league)
+        } else {
+            Err(LeagueConflicts { league, violations })
+        }
+    }
+}
+
+/// The rounds of (home, away) places of the mirrored canonical 1-factorization of an even number of places.
+fn canonical_schedule(places: usize, legs: usize) -> Vec<Vec<(usize, usize)>> {
+    if places < 2 {
+        return Vec::new()
+    }
+    let turning = places - 1;
+    let first: Vec<Vec<(usize, usize)>> = (0..turning).map(|round| {
+        let fixed = if round % 2 == 0 { (turning, round) } else { (round, turning) };
+        let others = (1..(places / 2)).map(|i| {
+            let (a, b) = ((round + i) % turning, (round + turning - i) % turning);
+            if i % 2 == 1 { (a, b) } else { (b, a) }
+        });
+        std::iter::once(fixed).chain(others).collect()
+    }).collect();
+    (0..legs).flat_map(|leg| first.iter().map(move |games| {
+        games.iter().map(|&(home, away)| if leg % 2 == 0 { (home, away) } else { (away, home) }).collect()
+    })).collect()
+}
+
+/// Branch and bound over the places of the teams, in team order, counting the violations so far.
+struct LeagueSearch<'a> {
+    problem: &'a LeagueProblem,
+    home: Vec<Vec<bool>>,
+    /// The team in each place of the canonical schedule, for the places below the number of teams.
+    team_of: Vec<Option<usize>>,
+    /// `position[place]` is the team in that place once all teams have places.
+    position: Vec<usize>,
+    best: Option<(usize, Vec<usize>)>,
+    nodes: usize
+}
+
+impl LeagueSearch<'_> {
+    fn bound(&self) -> usize {
+        self.best.as_ref().map(|(cost, _)| *cost).unwrap_or(usize::MAX)
+    }
+
+    fn extend(&mut self, team: usize, cost: usize) {
+        self.nodes += 1;
+        let count = self.team_of.len();
+        if team == count {
+            for (place, assigned) in self.team_of.iter().enumerate() {
+                self.position[place] = assigned.expect("ProgrammingError: every place has a team.");
+            }
+            self.best = Some((cost, self.position.clone()));
+            return
+        }
+        for place in 0..count {
+            if self.team_of[place].is_some() || self.bound() == 0 || (self.nodes > LEAGUE_SEARCH_LIMIT && self.best.is_some()) {
+                continue
+            }
+            let home = &self.home[place];
+            let unavailable = self.problem.unavailable[team].iter().filter(|&&round| home.get(round) == Some(&true)).count();
+            let shared = self.problem.shared.iter().filter_map(|&(a, b)| {
+                let other = if a == team { b } else if b == team { a } else { return None };
+                let other_place = self.team_of.iter().position(|&assigned| assigned == Some(other))?;
+                Some((0..home.len()).filter(|&round| home[round] && self.home[other_place][round]).count())
+            }).sum::<usize>();
+            if cost + unavailable + shared >= self.bound() {
+                continue
+            }
+            self.team_of[place] = Some(team);
+            self.extend(team + 1, cost + unavailable + shared);
+            self.team_of[place] = None;
+        }
+    }
+}
+
+/// A venue constraint broken by a league schedule. Rounds count from 0; `Display` numbers them from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeagueViolation {
+    /// A team at home in a round when its venue is unavailable.
+    VenueUnavailable { team: String, round: usize },
+    /// Two teams sharing a venue, both at home in a round.
+    SharedVenue { first: String, second: String, round: usize }
+}
+
+impl fmt::Display for LeagueViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeagueViolation::VenueUnavailable { team, round } => {
+                write!(f, "round {}: {} at home while its venue is unavailable", round + 1, team)
+            },
+            LeagueViolation::SharedVenue { first, second, round } => {
+                write!(f, "round {}: {} and {} both at home at their shared venue", round + 1, first, second)
+            }
+        }
+    }
+}
+
+/// The league closest to the venue constraints when none meets them all, with the constraints it breaks.
+#[derive(Debug, Clone)]
+pub struct LeagueConflicts {
+    pub league: League,
+    pub violations: Vec<LeagueViolation>
+}
+
+impl fmt::Display for LeagueConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.violations.iter().map(|violation| violation.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Rounds of games between teams. `Display` writes the games of each round, home team first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct League {
+    teams: Vec<String>,
+    rounds: Vec<Vec<(usize, usize)>>
+}
+
+impl League {
+    /// The teams.
+    pub fn teams(&self) -> &[String] {
+        &self.teams
+    }
+
+    /// The (home, away) games of each round.
+    pub fn rounds(&self) -> &[Vec<(usize, usize)>] {
+        &self.rounds
+    }
+
+    /// Where a team plays in each round: Some(true) at home, Some(false) away, None for a bye.
+    pub fn home_away(&self, team: usize) -> Vec<Option<bool>> {
+        self.rounds.iter().map(|games| games.iter().find_map(|&(home, away)| {
+            if home == team { Some(true) } else if away == team { Some(false) } else { None }
+        })).collect()
+    }
+
+    /// The number of consecutive rounds in which a team plays at home twice, or away twice.
+    pub fn breaks(&self, team: usize) -> usize {
+        self.home_away(team).windows(2).filter(|pair| pair[0].is_some() && pair[0] == pair[1]).count()
+    }
+
+    /// The breaks of all teams.
+    pub fn total_breaks(&self) -> usize {
+        (0..self.teams.len()).map(|team| self.breaks(team)).sum()
+    }
+
+    /// A table of H for home, A for away and - for a bye, with a row per team and a column per round.
+    pub fn home_away_table(&self) -> String {
+        let header = std::iter::once("Team".to_string()).chain((1..=self.rounds.len()).map(|round| round.to_string()));
+        let mut table: Vec<Vec<String>> = vec![header.collect()];
+        for (team, name) in self.teams.iter().enumerate() {
+            let venues = self.home_away(team).into_iter().map(|venue| match venue {
+                Some(true) => "H".to_string(),
+                Some(false) => "A".to_string(),
+                None => "-".to_string()
+            });
+            table.push(std::iter::once(name.clone()).chain(venues).collect());
+        }
+        let borrowed: Vec<Vec<&str>> = table.iter().map(|row| row.iter().map(|cell| cell.as_str()).collect()).collect();
+        aligned(&borrowed)
+    }
+}
+
+/// Records of (round, home team, away team), with rounds numbered from 1, for importing into league software.
+impl LongFormat for League {
+    fn to_long_format(&self) -> LongTable {
+        LongTable {
+            header: ["Round", "Home", "Away"].iter().map(|name| name.to_string()).collect(),
+            records: self.rounds.iter().enumerate().flat_map(|(round, games)| games.iter().map(move |&(home, away)| {
+                vec![(round + 1).to_string(), self.teams[home].clone(), self.teams[away].clone()]
+            })).collect()
+        }
+    }
+}
+
+impl fmt::Display for League {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (round, games) in self.rounds.iter().enumerate() {
+            if round > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "Round {}", round + 1)?;
+            for &(home, away) in games {
+                writeln!(f, "    {} - {}", self.teams[home], self.teams[away])?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+//! Systems of distinct representatives (SDRs): picking one element from each of a family of sets, with no
+//! element picked twice.
+//!
+//! That is a matching between the sets and the elements which covers every set. Hall's theorem says one
+//! exists unless some k of the sets have fewer than k elements between them. When there is no SDR, a
+//! maximum matching leaves some set uncovered, and the sets reachable from it by alternating paths are
+//! such a violation of Hall's condition: their union is exactly the elements matched to all but one of them.
+//!
+//! Sources:
+//!
+//! - [Hall's marriage theorem, Wikipedia](https://en.wikipedia.org/wiki/Hall%27s_marriage_theorem)
+
+use crate::bitset::BitSet;
+use crate::matching::{BipartiteGraph, Matching};
+
+/// Sets that together have fewer elements than there are sets, and so have no SDR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HallViolation {
+    /// Indices of the sets, in increasing order.
+    pub sets: Vec<usize>,
+    /// Every element of those sets, in increasing order.
+    pub union: Vec<usize>
+}
+
+/// Returns an SDR of the sets, as the representative of each set, if there is one.
+///
+/// ```
+/// use combinatorial_patterns::bitset::BitSet;
+/// use combinatorial_patterns::sdr;
+///
+/// let sets = vec![BitSet::from_values(4, &[0, 1]), BitSet::from_values(4, &[0]), BitSet::from_values(4, &[1, 2, 3])];
+/// assert_eq!(sdr::sdr(&sets), Some(vec![1, 0, 2]));
+///
+/// // Three sets within two elements.
+/// let sets = vec![BitSet::from_values(4, &[0, 1]), BitSet::from_values(4, &[0]), BitSet::from_values(4, &[0, 1]), BitSet::from_values(4, &[3])];
+/// assert_eq!(sdr::sdr(&sets), None);
+/// let violation = sdr::hall_violation(&sets).unwrap();
+/// assert_eq!(violation.sets, vec![0, 1, 2]);
+/// assert_eq!(violation.union, vec![0, 1]);
+/// ```
+pub fn sdr(sets: &[BitSet]) -> Option<Vec<usize>> {
+    let (_, matching) = maximum_matching(sets);
+    (0..sets.len()).map(|set| matching.right_of(set)).collect()
+}
+
+/// Returns sets violating Hall's condition, if the sets have no SDR.
+pub fn hall_violation(sets: &[BitSet]) -> Option<HallViolation> {
+    let (graph, matching) = maximum_matching(sets);
+    let uncovered = (0..sets.len()).find(|&set| matching.right_of(set).is_none())?;
+
+    // Alternate from the uncovered set: any element of a reached set, then the set that element is matched to.
+    let mut reached_sets = vec![false; sets.len()];
+    let mut reached_elements = vec![false; graph.right()];
+    reached_sets[uncovered] = true;
+    let mut queue = vec![uncovered];
+    while let Some(set) = queue.pop() {
+        for &element in graph.neighbours(set) {
+            if reached_elements[element] {
+                continue
+            }
+            reached_elements[element] = true;
+            let owner = matching.left_of(element).expect("ProgrammingError: an unmatched element would augment the matching.");
+            if !reached_sets[owner] {
+                reached_sets[owner] = true;
+                queue.push(owner);
+            }
+        }
+    }
+    Some(HallViolation {
+        sets: (0..sets.len()).filter(|&set| reached_sets[set]).collect(),
+        union: (0..graph.right()).filter(|&element| reached_elements[element]).collect()
+    })
+}
+
+/// Returns an iterator over every SDR of the sets, with the representatives of earlier sets varying slowest.
+///
+/// There can be very many SDRs, so this is meant for small families.
+///
+/// ```
+/// use combinatorial_patterns::bitset::BitSet;
+/// use combinatorial_patterns::sdr;
+///
+/// let sets = vec![BitSet::from_values(3, &[0, 1]), BitSet::from_values(3, &[1, 2]), BitSet::from_values(3, &[0, 2])];
+/// let all: Vec<Vec<usize>> = sdr::sdrs(&sets).collect();
+/// assert_eq!(all, vec![vec![0, 1, 2], vec![1, 2, 0]]);
+/// ```
+pub fn sdrs(sets: &[BitSet]) -> Sdrs {
+    let capacity = sets.iter().map(|set| set.capacity()).max().unwrap_or(0);
+    Sdrs {
+        options: sets.iter().map(|set| set.iter().collect()).collect(),
+        cursor: vec![0; sets.len()],
+        chosen: Vec::with_capacity(sets.len()),
+        used: vec![false; capacity],
+        done: false
+    }
+}
+
+/// Iterator over the SDRs of a family of sets, see `sdrs`.
+#[derive(Debug, Clone)]
+pub struct Sdrs {
+    options: Vec<Vec<usize>>,
+    /// Per set, the index of the next option to try.
+    cursor: Vec<usize>,
+    chosen: Vec<usize>,
+    used: Vec<bool>,
+    done: bool
+}
+
+impl Sdrs {
+    fn pop(&mut self) {
+        let element = self.chosen.pop().expect("ProgrammingError: popped an empty choice.");
+        self.used[element] = false;
+    }
+}
+
+impl Iterator for Sdrs {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None
+        }
+        loop {
+            let level = self.chosen.len();
+            if level == self.options.len() {
+                let found = self.chosen.clone();
+                if level == 0 {
+                    self.done = true;
+                } else {
+                    self.pop();
+                }
+                return Some(found)
+            }
+            let next = (self.cursor[level]..self.options[level].len()).find(|&index| !self.used[self.options[level][index]]);
+            match next {
+                Some(index) => {
+                    let element = self.options[level][index];
+                    self.cursor[level] = index + 1;
+                    self.used[element] = true;
+                    self.chosen.push(element);
+                    if level + 1 < self.cursor.len() {
+                        self.cursor[level + 1] = 0;
+                    }
+                },
+                None => {
+                    if level == 0 {
+                        self.done = true;
+                        return None
+                    }
+                    self.pop();
+                }
+            }
+        }
+    }
+}
+
+fn maximum_matching(sets: &[BitSet]) -> (BipartiteGraph, Matching) {
+    let capacity = sets.iter().map(|set| set.capacity()).max().unwrap_or(0);
+    let mut graph = BipartiteGraph::new(sets.len(), capacity);
+    for (index, set) in sets.iter().enumerate() {
+        for element in set.iter() {
+            graph.add_edge(index, element);
+        }
+    }
+    let matching = graph.maximum_matching();
+    (graph, matching)
+}
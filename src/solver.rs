@@ -0,0 +1,102 @@
+//! Solving puzzles on their own, without generating them.
+//!
+//! A puzzle here is anything with some cells given and a search for the latin squares that agree with
+//! them: a bare `PartialLatinSquare`, whose solutions are its completions, or a Sudoku `Puzzle` under any
+//! of its rules. `solve`, `count_solutions` and `has_unique_solution` take either, so a puzzle produced
+//! elsewhere only needs to be read into one of them. Counting stops at the cap, and uniqueness only looks
+//! for a second solution, so both stay cheap on well-posed puzzles.
+//!
+//! Sources:
+//!
+//! - [Solving every Sudoku puzzle, P. Norvig](https://norvig.com/sudoku.html)
+//! - [Mathematics of Sudoku, Wikipedia](https://en.wikipedia.org/wiki/Mathematics_of_Sudoku)
+
+use crate::latin_square::LatinSquare;
+use crate::partial::PartialLatinSquare;
+use crate::sudoku::{Puzzle, Rules};
+
+/// Puzzles whose solutions are latin squares agreeing with some given cells.
+pub trait Solvable {
+    /// Up to `limit` distinct solutions.
+    fn solutions(&self, limit: usize) -> Vec<LatinSquare>;
+
+    /// Returns true if the square is a solution: it keeps the given cells and meets every constraint.
+    fn is_solution(&self, square: &LatinSquare) -> bool;
+}
+
+/// The completions of a partial latin square.
+impl Solvable for PartialLatinSquare {
+    fn solutions(&self, limit: usize) -> Vec<LatinSquare> {
+        self.completions(limit)
+    }
+
+    fn is_solution(&self, square: &LatinSquare) -> bool {
+        Rules::latin(self.order()).is_solution(square) && agrees(self, square)
+    }
+}
+
+/// The solutions of a Sudoku or variant under its rules.
+impl Solvable for Puzzle {
+    fn solutions(&self, limit: usize) -> Vec<LatinSquare> {
+        Puzzle::solutions(self, limit)
+    }
+
+    fn is_solution(&self, square: &LatinSquare) -> bool {
+        self.rules().is_solution(square) && agrees(self.clues(), square)
+    }
+}
+
+/// Some solution of the puzzle, if there is one.
+///
+/// ```
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::solver::{self, Solvable};
+///
+/// let puzzle = PartialLatinSquare::from_rows(vec![
+///     vec![Some(0), None, None],
+///     vec![None, Some(0), None],
+///     vec![None, None, None]
+/// ]).unwrap();
+/// let solution = solver::solve(&puzzle).unwrap();
+/// assert!(puzzle.is_solution(&solution));
+/// assert_eq!(solver::count_solutions(&puzzle, 10), 2);
+/// assert!(!solver::has_unique_solution(&puzzle));
+/// ```
+pub fn solve<P: Solvable + ?Sized>(puzzle: &P) -> Option<LatinSquare> {
+    puzzle.solutions(1).pop()
+}
+
+/// Counts the solutions of the puzzle, stopping once `cap` have been found.
+pub fn count_solutions<P: Solvable + ?Sized>(puzzle: &P, cap: usize) -> usize {
+    puzzle.solutions(cap).len()
+}
+
+/// Returns true if the puzzle has exactly one solution.
+///
+/// ```
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::solver;
+/// use combinatorial_patterns::sudoku::{self, Puzzle, Rules};
+///
+/// let puzzle = sudoku::generate(&Rules::sudoku(3), &mut rand::thread_rng()).unwrap();
+/// assert!(solver::has_unique_solution(&puzzle));
+///
+/// // Clues read from elsewhere, as a 4 x 4 Sudoku.
+/// let clues = PartialLatinSquare::from_rows(vec![
+///     vec![None, None, Some(0), None],
+///     vec![None, Some(2), None, Some(1)],
+///     vec![Some(1), Some(3), None, None],
+///     vec![None, None, None, None]
+/// ]).unwrap();
+/// let puzzle = Puzzle::new(Rules::sudoku(2), clues.clone()).unwrap();
+/// assert!(puzzle.has_unique_solution());
+/// // The same clues have more than one completion as a plain latin square.
+/// assert!(!solver::has_unique_solution(&clues));
+/// ```
+pub fn has_unique_solution<P: Solvable + ?Sized>(puzzle: &P) -> bool {
+    count_solutions(puzzle, 2) == 1
+}
+
+fn agrees(clues: &PartialLatinSquare, square: &LatinSquare) -> bool {
+    clues.filled_cells().into_iter().all(|(row, column, symbol)| square.square[row][column] == symbol)
+}
@@ -0,0 +1,225 @@
+//! Isotopy classes and main classes (species) of latin squares of small orders.
+//!
+//! Two latin squares are isotopic when one becomes the other by permuting its rows, its columns, and its
+//! symbols. They are in the same main class when that is possible after also taking a conjugate, i.e.
+//! after permuting the roles of rows, columns, and symbols. The number of classes of each order is
+//!
+//! | order          | 1 | 2 | 3 | 4 | 5 | 6  |
+//! |----------------|---|---|---|---|---|----|
+//! | isotopy classes| 1 | 1 | 1 | 2 | 2 | 22 |
+//! | main classes   | 1 | 1 | 1 | 2 | 2 | 12 |
+//!
+//! Every class is represented by its canonical square: the lexicographically smallest reduced square
+//! (first row and first column in order) of the class, reading the square row by row. The classes are
+//! numbered in the order of their canonical squares.
+//!
+//! The classes are found by running through the reduced squares of the order, which `library` embeds. The
+//! reduced squares isotopic to a square are obtained by picking which row goes first and how to order the
+//! columns; the symbols and the other rows then follow from the first row and column being in order. A
+//! class found this way marks all of its reduced squares at once, so no square is looked at twice. Order 7,
+//! with 16942080 reduced squares, is out of reach for this and not supported; `library` has its main
+//! classes, and `orderly` enumerates its classes without the reduced squares.
+//!
+//! Sources:
+//!
+//! - [Small latin squares, quasigroups, and loops, Brendan D. McKay, Alison Meynert, Wendy Myrvold](https://doi.org/10.1002/jcd.20105)
+
+use std::collections::{HashMap, HashSet};
+
+use crate::canonical::{self, Equivalence, Fingerprint};
+use crate::latin_square::{conjugate_rows, ConjugateKind, LatinSquare, Symbol};
+use crate::library;
+use crate::partial::PartialLatinSquare;
+
+/// The largest order that classes can be enumerated for.
+pub const MAX_ORDER: usize = 6;
+
+/// Returns the canonical squares of the isotopy classes of the given order, or None if the order is above `MAX_ORDER`.
+///
+/// ```
+/// use combinatorial_patterns::species;
+///
+/// assert_eq!(species::isotopy_classes(4).unwrap().len(), 2);
+/// assert_eq!(species::isotopy_classes(5).unwrap().len(), 2);
+///
+/// // The first class of order 4 is that of the Klein four-group, with an intercalate in the corner.
+/// let klein = &species::isotopy_classes(4).unwrap()[0];
+/// assert_eq!(klein.square[1], vec![1, 0, 3, 2]);
+/// ```
+pub fn isotopy_classes(order: usize) -> Option<Vec<LatinSquare>> {
+    if order > MAX_ORDER {
+        return None
+    }
+    let mut seen: HashSet<Vec<Symbol>> = HashSet::new();
+    let mut classes = Vec::new();
+    for square in reduced_squares(order) {
+        let cells: Vec<Symbol> = square.square.concat();
+        if seen.contains(&cells) {
+            continue
+        }
+        let isotopes = reduced_isotopes(&square.square);
+        let canonical = isotopes.iter().min().expect("ProgrammingError: a square is always isotopic to itself.").clone();
+        seen.extend(isotopes);
+        classes.push(canonical);
+    }
+    classes.sort_unstable();
+    Some(classes.into_iter().map(|cells| as_square(order, &cells)).collect())
+}
+
+/// Returns the canonical squares of the main classes of the given order, or None if the order is above `MAX_ORDER`.
+///
+/// A main class is represented by the smallest canonical square of the isotopy classes it joins.
+///
+/// ```
+/// use combinatorial_patterns::species;
+///
+/// let main_classes = species::main_classes(6).unwrap();
+/// assert_eq!(main_classes.len(), 12);
+/// assert_eq!(species::isotopy_classes(6).unwrap().len(), 22);
+/// ```
+pub fn main_classes(order: usize) -> Option<Vec<LatinSquare>> {
+    let isotopy_classes = isotopy_classes(order)?;
+    let mut classes: Vec<Vec<Symbol>> = isotopy_classes.iter()
+        .map(|square| {
+            ConjugateKind::ALL.iter()
+                .map(|&kind| isotopy_canonical_cells(&conjugate_rows(&square.square, kind)))
+                .min()
+                .expect("ProgrammingError: there are six conjugates.")
+        })
+        .collect();
+    classes.sort_unstable();
+    classes.dedup();
+    Some(classes.into_iter().map(|cells| as_square(order, &cells)).collect())
+}
+
+/// Returns the canonical square of the isotopy class with the given index, numbering the classes of the order
+/// from 0. Returns None if the order is above `MAX_ORDER` or the index is past the last class.
+///
+/// This enumerates every class of the order, so call `isotopy_classes` when going through several.
+pub fn isotopy_class(order: usize, index: usize) -> Option<LatinSquare> {
+    isotopy_classes(order)?.into_iter().nth(index)
+}
+
+/// Returns the canonical square of the main class with the given index, numbering the classes of the order
+/// from 0. Returns None if the order is above `MAX_ORDER` or the index is past the last class.
+///
+/// ```
+/// use combinatorial_patterns::species;
+///
+/// // The first main class of order 5 is not a group, and has intercalates. The second is the cyclic group.
+/// assert_eq!(species::main_class(5, 0).unwrap().square[1], vec![1, 0, 3, 4, 2]);
+/// assert_eq!(species::main_class(5, 1).unwrap().square[1], vec![1, 2, 3, 4, 0]);
+/// assert!(species::main_class(5, 2).is_none());
+/// ```
+pub fn main_class(order: usize, index: usize) -> Option<LatinSquare> {
+    main_classes(order)?.into_iter().nth(index)
+}
+
+/// The canonical square of the isotopy class of `square`, which may have any order.
+///
+/// This tries every first row and every column order, n * n! isotopes, so it is only practical for small orders.
+/// The `canonical` module finds another canonical form, not the smallest, in far less time.
+pub fn isotopy_canonical_form(square: &LatinSquare) -> LatinSquare {
+    as_square(square.size(), &isotopy_canonical_cells(&square.square))
+}
+
+/// Sorts squares of one order into isotopy classes, returning the first square of each class, in order of
+/// appearance, with the number of squares in the class.
+///
+/// Squares are compared by their fingerprints from `canonical::fingerprint`, which are far quicker to find
+/// than the smallest canonical squares of this module, so this works for squares of any order.
+///
+/// ```
+/// use combinatorial_patterns::{species, IncidenceCube, LatinSquare};
+///
+/// let mut squares = vec![IncidenceCube::new_cyclic(4).unwrap().as_latin_square(), LatinSquare::new_abelian_group(&[2, 2]).unwrap()];
+/// squares.extend((0..30).map(|_| LatinSquare::new_random(4, &mut rand::thread_rng()).unwrap()));
+/// let classes = species::dedup_by_isotopy(&squares);
+/// assert_eq!(classes.len(), 2);
+/// assert_eq!(classes.iter().map(|(_, count)| count).sum::<usize>(), 32);
+///
+/// // A square of order 20 and the same square with its rows moved.
+/// let square = LatinSquare::new_random(20, &mut rand::thread_rng()).unwrap();
+/// let mut rows = square.square.clone();
+/// rows.rotate_left(3);
+/// assert_eq!(species::dedup_by_isotopy(&[square.clone(), LatinSquare::try_from(rows).unwrap()]), vec![(square, 2)]);
+/// ```
+pub fn dedup_by_isotopy(squares: &[LatinSquare]) -> Vec<(LatinSquare, usize)> {
+    // The index into `classes` of each fingerprint seen.
+    let mut seen: HashMap<Fingerprint, usize> = HashMap::new();
+    let mut classes: Vec<(&LatinSquare, usize)> = Vec::new();
+    for square in squares {
+        let class = *seen.entry(canonical::fingerprint(square, Equivalence::Isotopy)).or_insert_with(|| {
+            classes.push((square, 0));
+            classes.len() - 1
+        });
+        classes[class].1 += 1;
+    }
+    classes.into_iter().map(|(square, count)| (square.clone(), count)).collect()
+}
+
+fn isotopy_canonical_cells(rows: &[Vec<Symbol>]) -> Vec<Symbol> {
+    reduced_isotopes(rows).into_iter().min().unwrap_or_default()
+}
+
+/// Every reduced square isotopic to `rows`, as row-major cells, possibly with repeats.
+///
+/// With `first` as the row moved to the top and `columns[j]` the column moved to position j, renaming
+/// the symbols so that the top row reads 0..n, and sorting the rows by their first symbol, gives a reduced square.
+fn reduced_isotopes(rows: &[Vec<Symbol>]) -> Vec<Vec<Symbol>> {
+    let order = rows.len();
+    if order == 0 {
+        return vec![Vec::new()]
+    }
+    let mut isotopes = Vec::new();
+    let mut rename = vec![0; order];
+    for columns in permutations(order) {
+        for first in rows {
+            for (position, &column) in columns.iter().enumerate() {
+                rename[first[column]] = position;
+            }
+            let mut cells = vec![0; order * order];
+            for row in rows {
+                let top = rename[row[columns[0]]];
+                for (position, &column) in columns.iter().enumerate() {
+                    cells[top * order + position] = rename[row[column]];
+                }
+            }
+            isotopes.push(cells);
+        }
+    }
+    isotopes
+}
+
+/// Every permutation of 0..n, in lexicographic order.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    let mut current: Vec<usize> = (0..n).collect();
+    let mut all = vec![current.clone()];
+    loop {
+        let Some(pivot) = (1..n).rev().find(|&i| current[i - 1] < current[i]).map(|i| i - 1) else {
+            return all
+        };
+        let successor = (pivot + 1..n).rev().find(|&i| current[i] > current[pivot])
+            .expect("ProgrammingError: the pivot has a larger element after it.");
+        current.swap(pivot, successor);
+        current[pivot + 1..].reverse();
+        all.push(current.clone());
+    }
+}
+
+/// Every reduced latin square of the given order, from the library when it has them.
+fn reduced_squares(order: usize) -> Vec<LatinSquare> {
+    if let Some(squares) = library::reduced_squares(order) {
+        return squares
+    }
+    let mut partial = PartialLatinSquare::new(order);
+    for i in 0..order {
+        partial.set(0, i, Some(i));
+        partial.set(i, 0, Some(i));
+    }
+    partial.completions(usize::MAX)
+}
+
+fn as_square(order: usize, cells: &[Symbol]) -> LatinSquare {
+    LatinSquare::from_rows(cells.chunks(order.max(1)).take(order).map(|row| row.to_vec()).collect())
+}
@@ -0,0 +1,580 @@
+//! Starters and adders in Z_n, and the cyclic Room squares, Howell movements and whist designs built from them.
+//!
+//! A starter in Z_n, for odd n, partitions the nonzero elements into (n - 1) / 2 pairs {x, y} whose
+//! differences ±(x - y) are every nonzero element once. With 0 paired to a point at infinity, its n
+//! translates are a 1-factorization of K_(n + 1). A starter is strong if the sums x + y are distinct and
+//! nonzero, and skew if the sums ±(x + y) are every nonzero element once.
+//!
+//! An adder for a starter assigns distinct nonzero elements a_i to its pairs {x_i, y_i} such that the
+//! pairs {x_i + a_i, y_i + a_i} again partition the nonzero elements. Every strong starter has the adder
+//! a_i = -(x_i + y_i). A starter and adder give a Room square of side n: an n x n array whose filled cells
+//! hold the pairs of n + 1 symbols, each pair once, with every symbol once in every row and column. Cell
+//! (g, g) holds {g, infinity} and cell (g, g - a_i) holds {x_i + g, y_i + g}, so the rows and the columns
+//! are two orthogonal 1-factorizations. The Room square of a skew starter is skew: of the cells (i, j) and
+//! (j, i) off the diagonal, exactly one is filled.
+//!
+//! Room squares are the largest Howell designs H(s, 2n), s x s arrays of pairs of 2n symbols with every
+//! symbol once in every row and column and every pair at most once, where n <= s <= 2n - 1. Read as a
+//! bridge movement, the rows are rounds, the columns are sets of boards, and the two pairs of players in
+//! a cell meet at a table; in a complete Howell movement every two pairs meet once, and every pair plays
+//! every set of boards.
+//!
+//! A whist design Wh(v) schedules games of two partnerships among v players, v = 4n or 4n + 1, so that
+//! every two players are partners once and opponents twice. A Z-cyclic design takes the translates of one
+//! initial round over Z_v, or over Z_(v - 1) plus infinity, and the partnerships of that round are a
+//! starter. The search here picks the partnerships and the games at once, checking differences as it goes.
+//!
+//! Strong starters in Z_n exist for odd n > 5 except 9; skew starters need n coprime to 6.
+//!
+//! Sources:
+//!
+//! - [Room square, Wikipedia](https://en.wikipedia.org/wiki/Room_square)
+//! - The existence of Room squares, W. D. Wallis, Aequationes Mathematicae 9 (1973)
+//! - Combinatorial designs: constructions and analysis, D. R. Stinson, chapter 6
+//! - Combinatorial designs and tournaments, I. Anderson, chapter 5
+
+use std::fmt;
+
+use crate::design::aligned;
+use crate::enumerate::{self, Control, Node, Search};
+
+/// The number of pairs, or games, the searches for starters and whist designs try before they give up.
+pub const STARTER_SEARCH_LIMIT: usize = 10_000_000;
+
+/// The kinds of starters a search can ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StarterKind {
+    Any,
+    /// Sums of pairs distinct and nonzero.
+    Strong,
+    /// Sums of pairs distinct, nonzero, and not negatives of each other.
+    Skew
+}
+
+/// A starter in Z_n: pairs of nonzero elements whose differences are every nonzero element up to sign.
+///
+/// ```
+/// use combinatorial_patterns::starter::{Starter, StarterKind};
+///
+/// let patterned = Starter::patterned(7).unwrap();
+/// assert!(!patterned.is_strong());
+///
+/// let strong = Starter::search(7, StarterKind::Strong).unwrap();
+/// assert!(strong.is_strong());
+/// assert!(strong.is_adder(&strong.strong_adder().unwrap()));
+/// assert!(Starter::search(9, StarterKind::Strong).is_none());
+/// assert!(Starter::search(11, StarterKind::Skew).unwrap().is_skew());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Starter {
+    modulus: usize,
+    pairs: Vec<(usize, usize)>
+}
+
+impl Starter {
+    /// A starter from its pairs. Returns None unless the modulus is odd and the pairs form a starter.
+    pub fn new(modulus: usize, pairs: Vec<(usize, usize)>) -> Option<Starter> {
+        if modulus.is_multiple_of(2) || pairs.len() != modulus / 2 {
+            return None
+        }
+        let mut used = vec![false; modulus];
+        let mut differences = vec![false; modulus];
+        used[0] = true;
+        for &(x, y) in &pairs {
+            if x >= modulus || y >= modulus || used[x] || used[y] || x == y {
+                return None
+            }
+            used[x] = true;
+            used[y] = true;
+            let difference = (modulus + x - y) % modulus;
+            if differences[difference] {
+                return None
+            }
+            differences[difference] = true;
+            differences[modulus - difference] = true;
+        }
+        Some(Starter { modulus, pairs })
+    }
+
+    /// The patterned starter {x, -x}, for any odd modulus. Returns None for an even one.
+    pub fn patterned(modulus: usize) -> Option<Starter> {
+        if modulus.is_multiple_of(2) {
+            return None
+        }
+        Some(Starter { modulus, pairs: (1..=(modulus / 2)).map(|x| (x, modulus - x)).collect() })
+    }
+
+    /// The first starter of a kind found by a search of at most `STARTER_SEARCH_LIMIT` pairs.
+    pub fn search(modulus: usize, kind: StarterKind) -> Option<Starter> {
+        Starter::find(modulus, kind, |_| true)
+    }
+
+    /// The first starter of a kind that `accept` accepts, found by a search of at most `STARTER_SEARCH_LIMIT`
+    /// pairs. Starters are tried with the smallest unpaired element paired first, to its smallest possible partner.
+    pub fn find(modulus: usize, kind: StarterKind, mut accept: impl FnMut(&Starter) -> bool) -> Option<Starter> {
+        if modulus.is_multiple_of(2) {
+            return None
+        }
+        let mut search = StarterSearch {
+            kind,
+            starter: Starter { modulus, pairs: Vec::new() },
+            used: vec![false; modulus],
+            differences: vec![false; modulus],
+            sums: vec![false; modulus]
+        };
+        search.used[0] = true;
+        let mut found = None;
+        enumerate::run(&mut search, STARTER_SEARCH_LIMIT as u64, |search| {
+            if accept(&search.starter) {
+                found = Some(search.starter.clone());
+                Control::Stop
+            } else {
+                Control::Continue
+            }
+        });
+        found
+    }
+
+    /// The modulus n.
+    pub fn modulus(&self) -> usize {
+        self.modulus
+    }
+
+    /// The pairs.
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    /// Returns true if the sums of the pairs are distinct and nonzero.
+    pub fn is_strong(&self) -> bool {
+        let mut sums = vec![false; self.modulus];
+        sums[0] = true;
+        self.pairs.iter().all(|&(x, y)| !std::mem::replace(&mut sums[(x + y) % self.modulus], true))
+    }
+
+    /// Returns true if the sums of the pairs and their negatives are every nonzero element once.
+    pub fn is_skew(&self) -> bool {
+        let n = self.modulus;
+        let mut sums = vec![false; n];
+        sums[0] = true;
+        for &(x, y) in &self.pairs {
+            let sum = (x + y) % n;
+            if sums[sum] || sums[n - sum] {
+                return false
+            }
+            sums[sum] = true;
+            sums[n - sum] = true;
+        }
+        true
+    }
+
+    /// The adder -(x + y) of a strong starter. Returns None if the starter is not strong.
+    pub fn strong_adder(&self) -> Option<Vec<usize>> {
+        if !self.is_strong() {
+            return None
+        }
+        Some(self.pairs.iter().map(|&(x, y)| (2 * self.modulus - x - y) % self.modulus).collect())
+    }
+
+    /// Returns true if the elements, one per pair, are an adder for the starter.
+    pub fn is_adder(&self, adder: &[usize]) -> bool {
+        let n = self.modulus;
+        if adder.len() != self.pairs.len() || adder.iter().any(|&a| a == 0 || a >= n) {
+            return false
+        }
+        let mut seen = vec![false; n];
+        if adder.iter().any(|&a| std::mem::replace(&mut seen[a], true)) {
+            return false
+        }
+        let mut covered = vec![false; n];
+        covered[0] = true;
+        self.pairs.iter().zip(adder).all(|(&(x, y), &a)| {
+            !std::mem::replace(&mut covered[(x + a) % n], true) && !std::mem::replace(&mut covered[(y + a) % n], true)
+        })
+    }
+}
+
+/// Backtracking state of a starter search.
+struct StarterSearch {
+    kind: StarterKind,
+    starter: Starter,
+    used: Vec<bool>,
+    differences: Vec<bool>,
+    sums: Vec<bool>
+}
+
+impl Search for StarterSearch {
+    type Choice = (usize, usize);
+
+    /// Pairs the smallest unpaired element with each partner that keeps the differences, and the sums the
+    /// kind restricts, distinct.
+    fn expand(&mut self) -> Node<(usize, usize)> {
+        let n = self.starter.modulus;
+        let Some(x) = (1..n).find(|&x| !self.used[x]) else {
+            return Node::Solution
+        };
+        Node::Branch(((x + 1)..n).filter(|&y| {
+            let sum = (x + y) % n;
+            let sum_taken = match self.kind {
+                StarterKind::Any => false,
+                StarterKind::Strong => sum == 0 || self.sums[sum],
+                StarterKind::Skew => sum == 0 || self.sums[sum] || self.sums[n - sum]
+            };
+            !(self.used[y] || self.differences[y - x] || sum_taken)
+        }).map(|y| (x, y)).collect())
+    }
+
+    fn apply(&mut self, &(x, y): &(usize, usize)) {
+        let n = self.starter.modulus;
+        self.used[x] = true;
+        self.used[y] = true;
+        self.differences[y - x] = true;
+        self.differences[n - (y - x)] = true;
+        self.sums[(x + y) % n] = true;
+        self.starter.pairs.push((x, y));
+    }
+
+    fn undo(&mut self, &(x, y): &(usize, usize)) {
+        let n = self.starter.modulus;
+        self.starter.pairs.pop();
+        self.sums[(x + y) % n] = false;
+        self.differences[y - x] = false;
+        self.differences[n - (y - x)] = false;
+        self.used[y] = false;
+        self.used[x] = false;
+    }
+}
+
+/// A Howell design H(s, 2n): an s x s array of empty cells and pairs of the symbols 0..2n, with every
+/// symbol once in every row and column and every pair at most once.
+///
+/// ```
+/// use combinatorial_patterns::starter::HowellDesign;
+///
+/// let room = HowellDesign::room_square(7).unwrap();
+/// assert!(room.is_room_square());
+/// assert_eq!(room.get(2, 2), Some((2, 7)));
+///
+/// // Every round of the movement seats 4 tables, and the pairs meet at every table once.
+/// let rounds = room.rounds();
+/// assert_eq!(rounds.len(), 7);
+/// assert!(rounds.iter().all(|tables| tables.len() == 4));
+/// assert!(HowellDesign::room_square(5).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HowellDesign {
+    symbols: usize,
+    cells: Vec<Vec<Option<(usize, usize)>>>
+}
+
+impl HowellDesign {
+    /// A Howell design from its cells. Returns None unless the array is square, 2n symbols fit its side s as
+    /// n <= s <= 2n - 1, and the cells satisfy the conditions.
+    pub fn new(symbols: usize, cells: Vec<Vec<Option<(usize, usize)>>>) -> Option<HowellDesign> {
+        let side = cells.len();
+        if symbols % 2 == 1 || side < symbols / 2 || side + 1 > symbols || cells.iter().any(|row| row.len() != side) {
+            return None
+        }
+        let mut paired = vec![vec![false; symbols]; symbols];
+        let mut in_row = vec![vec![0; symbols]; side];
+        let mut in_column = vec![vec![0; symbols]; side];
+        for (row, cells) in cells.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                let Some((a, b)) = *cell else { continue };
+                if a >= symbols || b >= symbols || a == b || paired[a][b] {
+                    return None
+                }
+                paired[a][b] = true;
+                paired[b][a] = true;
+                for symbol in [a, b] {
+                    in_row[row][symbol] += 1;
+                    in_column[column][symbol] += 1;
+                }
+            }
+        }
+        if in_row.iter().chain(&in_column).any(|counts| counts.iter().any(|&count| count != 1)) {
+            return None
+        }
+        Some(HowellDesign { symbols, cells })
+    }
+
+    /// The Room square of a starter and adder, with infinity numbered n. Returns None if the adder is not
+    /// an adder for the starter.
+    pub fn from_starter(starter: &Starter, adder: &[usize]) -> Option<HowellDesign> {
+        if !starter.is_adder(adder) {
+            return None
+        }
+        let n = starter.modulus;
+        let mut cells = vec![vec![None; n]; n];
+        for g in 0..n {
+            cells[g][g] = Some((g, n));
+            for (&(x, y), &a) in starter.pairs.iter().zip(adder) {
+                cells[g][(g + n - a) % n] = Some(((x + g) % n, (y + g) % n));
+            }
+        }
+        Some(HowellDesign { symbols: n + 1, cells })
+    }
+
+    /// A cyclic Room square of odd side, from the first strong starter a search finds, skew if the search
+    /// finds a skew starter. Returns None for even sides and when no strong starter of Z_side is found,
+    /// which is the case for 3, 5 and 9.
+    pub fn room_square(side: usize) -> Option<HowellDesign> {
+        let starter = Starter::search(side, StarterKind::Skew).or_else(|| Starter::search(side, StarterKind::Strong))?;
+        HowellDesign::from_starter(&starter, &starter.strong_adder()?)
+    }
+
+    /// The side s.
+    pub fn side(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The number of symbols 2n.
+    pub fn symbols(&self) -> usize {
+        self.symbols
+    }
+
+    /// The cells, by row.
+    pub fn cells(&self) -> &[Vec<Option<(usize, usize)>>] {
+        &self.cells
+    }
+
+    /// The pair in a cell, if it is filled.
+    pub fn get(&self, row: usize, column: usize) -> Option<(usize, usize)> {
+        self.cells[row][column]
+    }
+
+    /// Returns true if the design is a Room square, s = 2n - 1, so that every pair is in a cell.
+    pub fn is_room_square(&self) -> bool {
+        self.side() + 1 == self.symbols
+    }
+
+    /// Returns true if, of every two cells (i, j) and (j, i) off the diagonal, exactly one is filled.
+    pub fn is_skew(&self) -> bool {
+        (0..self.side()).all(|i| (0..i).all(|j| self.cells[i][j].is_some() != self.cells[j][i].is_some()))
+    }
+
+    /// The design as a bridge movement: for every round, a row, the tables as (pair, pair, set of boards).
+    pub fn rounds(&self) -> Vec<Vec<(usize, usize, usize)>> {
+        self.cells.iter().map(|row| {
+            row.iter().enumerate().filter_map(|(boards, cell)| cell.map(|(a, b)| (a, b, boards))).collect()
+        }).collect()
+    }
+}
+
+impl fmt::Display for HowellDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<Vec<String>> = self.cells.iter()
+            .map(|row| row.iter().map(|cell| cell.map(|(a, b)| format!("{},{}", a, b)).unwrap_or_else(|| String::from("."))).collect())
+            .collect();
+        let table: Vec<Vec<&str>> = cells.iter().map(|row| row.iter().map(String::as_str).collect()).collect();
+        write!(f, "{}", aligned(&table))
+    }
+}
+
+/// A game of whist: players 0 and 1 partner against players 2 and 3.
+pub type Game = [usize; 4];
+
+/// A whist design: rounds of games in which every two players are partners once and opponents twice.
+///
+/// ```
+/// use combinatorial_patterns::starter::WhistDesign;
+///
+/// let design = WhistDesign::cyclic(8).unwrap();
+/// assert_eq!(design.rounds().len(), 7);
+/// assert!(design.rounds().iter().all(|games| games.len() == 2));
+/// assert!(WhistDesign::new(8, design.rounds().to_vec()).is_some());
+///
+/// // With 4n + 1 players, one sits out every round.
+/// assert_eq!(WhistDesign::cyclic(13).unwrap().rounds().len(), 13);
+/// assert!(WhistDesign::cyclic(6).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhistDesign {
+    players: usize,
+    rounds: Vec<Vec<Game>>
+}
+
+impl WhistDesign {
+    /// A whist design from its rounds. Returns None unless there are 4n or 4n + 1 players, 4n - 1 or 4n + 1
+    /// rounds of n games with no player twice in a round, and every two players are partners once and
+    /// opponents twice.
+    pub fn new(players: usize, rounds: Vec<Vec<Game>>) -> Option<WhistDesign> {
+        if players < 4 || players % 4 > 1 {
+            return None
+        }
+        let expected = if players.is_multiple_of(4) { players - 1 } else { players };
+        if rounds.len() != expected || rounds.iter().any(|games| games.len() != players / 4) {
+            return None
+        }
+        let mut partners = vec![vec![0; players]; players];
+        let mut opponents = vec![vec![0; players]; players];
+        for games in &rounds {
+            let mut seated = vec![false; players];
+            for game in games {
+                if game.iter().any(|&player| player >= players || std::mem::replace(&mut seated[player], true)) {
+                    return None
+                }
+                for (a, b) in [(game[0], game[1]), (game[2], game[3])] {
+                    partners[a][b] += 1;
+                    partners[b][a] += 1;
+                }
+                for (a, b) in [(game[0], game[2]), (game[0], game[3]), (game[1], game[2]), (game[1], game[3])] {
+                    opponents[a][b] += 1;
+                    opponents[b][a] += 1;
+                }
+            }
+        }
+        let balanced = (0..players).all(|a| (0..players).filter(|&b| b != a).all(|b| partners[a][b] == 1 && opponents[a][b] == 2));
+        if !balanced {
+            return None
+        }
+        Some(WhistDesign { players, rounds })
+    }
+
+    /// A Z-cyclic whist design: the translates of an initial round over Z_v when v = 4n + 1, in which
+    /// player 0 sits out, or over Z_(v - 1) plus infinity, numbered v - 1, when v = 4n. Returns None unless
+    /// v is 4n or 4n + 1 with n > 0, when there is no Z-cyclic design, as for 9 players, or when the search of
+    /// at most `STARTER_SEARCH_LIMIT` games gives up, which it does beyond about 24 players.
+    pub fn cyclic(players: usize) -> Option<WhistDesign> {
+        if players < 4 || players % 4 > 1 {
+            return None
+        }
+        let infinite = players.is_multiple_of(4);
+        let modulus = if infinite { players - 1 } else { players };
+        let mut search = WhistSearch {
+            modulus,
+            games: Vec::new(),
+            seated: vec![false; players],
+            partner_differences: vec![false; modulus],
+            opponent_differences: vec![0; modulus],
+            nodes: 0
+        };
+        search.seated[0] = true;
+        if !search.extend(infinite) {
+            return None
+        }
+        let rounds = (0..modulus).map(|shift| {
+            search.games.iter().map(|game| game.map(|player| if player == modulus { player } else { (player + shift) % modulus })).collect()
+        }).collect();
+        WhistDesign::new(players, rounds)
+    }
+
+    /// The number of players.
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    /// The games of every round.
+    pub fn rounds(&self) -> &[Vec<Game>] {
+        &self.rounds
+    }
+}
+
+impl fmt::Display for WhistDesign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, games) in self.rounds.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "Round {}", index + 1)?;
+            for game in games {
+                write!(f, "\n    {} & {} - {} & {}", game[0], game[1], game[2], game[3])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Backtracking state of the search for the initial round of a Z-cyclic whist design.
+struct WhistSearch {
+    modulus: usize,
+    games: Vec<Game>,
+    seated: Vec<bool>,
+    /// Partner differences used, in both signs.
+    partner_differences: Vec<bool>,
+    /// How often each difference occurs between opponents.
+    opponent_differences: Vec<usize>,
+    nodes: usize
+}
+
+impl WhistSearch {
+    /// Seats the rest of the initial round. With infinity, the first game partners 0 with it.
+    fn extend(&mut self, infinite: bool) -> bool {
+        let n = self.modulus;
+        let (a, partners_of_a): (usize, Vec<usize>) = if infinite && self.games.is_empty() {
+            (0, vec![n])
+        } else {
+            let Some(a) = (1..n).find(|&player| !self.seated[player]) else {
+                return true
+            };
+            (a, ((a + 1)..n).filter(|&b| !self.seated[b] && !self.partner_differences[b - a]).collect())
+        };
+        self.seated[a] = true;
+        for b in partners_of_a {
+            self.set_partners(a, b, true);
+            for c in 1..n {
+                for d in (c + 1)..n {
+                    if self.seated[c] || self.seated[d] || self.partner_differences[d - c] {
+                        continue
+                    }
+                    self.nodes += 1;
+                    if self.nodes > STARTER_SEARCH_LIMIT {
+                        return false
+                    }
+                    let game = [a, b, c, d];
+                    if !self.set_opponents(&game, true) {
+                        continue
+                    }
+                    self.seated[c] = true;
+                    self.set_partners(c, d, true);
+                    self.games.push(game);
+                    if self.extend(infinite) {
+                        return true
+                    }
+                    self.games.pop();
+                    self.set_partners(c, d, false);
+                    self.seated[c] = false;
+                    self.set_opponents(&game, false);
+                }
+            }
+            self.set_partners(a, b, false);
+        }
+        self.seated[a] = infinite && a == 0;
+        false
+    }
+
+    /// Seats b as the partner of a, or unseats it, marking the difference of the partnership.
+    fn set_partners(&mut self, a: usize, b: usize, value: bool) {
+        self.seated[b] = value;
+        if b == self.modulus {
+            return
+        }
+        let difference = (self.modulus + b - a) % self.modulus;
+        self.partner_differences[difference] = value;
+        self.partner_differences[self.modulus - difference] = value;
+    }
+    /// Adds or removes the opponent differences of a game. Adding fails, changing nothing, if a difference
+    /// would occur more than twice.
+    fn set_opponents(&mut self, game: &Game, add: bool) -> bool {
+        let n = self.modulus;
+        let differences: Vec<usize> = [(game[0], game[2]), (game[0], game[3]), (game[1], game[2]), (game[1], game[3])].iter()
+            .filter(|&&(x, y)| x != n && y != n)
+            .flat_map(|&(x, y)| [(n + x - y) % n, (n + y - x) % n])
+            .collect();
+        if add {
+            let mut counts = self.opponent_differences.clone();
+            for &difference in &differences {
+                counts[difference] += 1;
+                if counts[difference] > 2 {
+                    return false
+                }
+            }
+            self.opponent_differences = counts;
+        } else {
+            for &difference in &differences {
+                self.opponent_differences[difference] -= 1;
+            }
+        }
+        true
+    }
+}
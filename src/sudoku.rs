@@ -0,0 +1,583 @@
+//! Sudoku and its variants, as latin squares with extra constraints.
+//!
+//! A Sudoku with r x c boxes is a latin square of order n = r * c in which each of the n boxes, r rows by c
+//! columns, also holds every symbol once: the classic 9 x 9 has 3 x 3 boxes, and 6 x 6 grids have 2 x 3.
+//! Sudoku-X adds the same constraint on the two main diagonals. A Killer Sudoku adds cages: groups of cells
+//! without repeated symbols, whose digits add up to a given sum. As in printed puzzles, digits count from 1,
+//! so symbol s counts as s + 1 in a cage sum.
+//!
+//! All of these are `Rules`: units, which are groups of cells that hold distinct symbols, plus cages. The
+//! solver keeps the candidate symbols of each cell and propagates constraints until nothing changes: a symbol
+//! placed in a cell leaves the rest of its units, a symbol with one possible cell in a unit goes there, and a
+//! cage keeps only the candidates that some choice of distinct digits for the other cells adds up to. The rest
+//! is backtracking on a cell with the fewest candidates. The generator solves the empty grid in random order
+//! and then removes clues in random order while the solution stays unique. For Killer puzzles it first
+//! splits the solution into random connected cages.
+//!
+//! Sources:
+//!
+//! - [Sudoku, Wikipedia](https://en.wikipedia.org/wiki/Sudoku)
+//! - [Killer sudoku, Wikipedia](https://en.wikipedia.org/wiki/Killer_sudoku)
+//! - [Solving every Sudoku puzzle, P. Norvig](https://norvig.com/sudoku.html)
+
+use std::fmt;
+
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::bitset::BitSet;
+use crate::error::CombinatorialError;
+use crate::latin_square::{LatinSquare, Symbol};
+use crate::partial::PartialLatinSquare;
+
+/// The shape of the boxes of a Sudoku, `rows` by `columns` cells, which tile a grid of order
+/// `rows * columns` with `columns` bands of `rows` boxes each. Boxes are numbered row by row.
+///
+/// ```
+/// use combinatorial_patterns::sudoku::BoxShape;
+///
+/// let shape = BoxShape::new(2, 3);
+/// assert_eq!(shape.order(), 6);
+/// assert_eq!(shape.box_of(3, 4), 3);
+/// assert_eq!(shape.cells(3), vec![(2, 3), (2, 4), (2, 5), (3, 3), (3, 4), (3, 5)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoxShape {
+    pub rows: usize,
+    pub columns: usize
+}
+
+impl BoxShape {
+    /// Boxes of `rows` by `columns` cells.
+    pub fn new(rows: usize, columns: usize) -> BoxShape {
+        BoxShape { rows, columns }
+    }
+
+    /// The order of the grid the boxes tile.
+    pub fn order(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// The index of the box holding a cell.
+    pub fn box_of(&self, row: usize, column: usize) -> usize {
+        (row / self.rows) * self.rows + column / self.columns
+    }
+
+    /// The (row, column) cells of a box, row by row.
+    pub fn cells(&self, index: usize) -> Vec<(usize, usize)> {
+        let (top, left) = ((index / self.rows) * self.rows, (index % self.rows) * self.columns);
+        (0..self.order()).map(|cell| (top + cell / self.columns, left + cell % self.columns)).collect()
+    }
+}
+
+/// Cells whose digits are distinct and add up to `sum`, where symbol s counts as digit s + 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    /// The (row, column) cells of the cage.
+    pub cells: Vec<(usize, usize)>,
+    pub sum: usize
+}
+
+/// The constraints on a grid: units of cells that hold distinct symbols, and cages.
+///
+/// ```
+/// use combinatorial_patterns::sudoku::{BoxShape, Cage, Rules};
+///
+/// let rules = Rules::sudoku(2).with_diagonals();
+/// assert_eq!(rules.order(), 4);
+/// // 4 rows, 4 columns, 4 boxes and 2 diagonals.
+/// assert_eq!(rules.units().len(), 14);
+///
+/// let rules = Rules::with_boxes(BoxShape::new(3, 4));
+/// assert_eq!((rules.order(), rules.box_size()), (12, None));
+/// assert_eq!(rules.units().len(), 36);
+///
+/// let rules = rules.with_cages(vec![Cage { cells: vec![(0, 0), (0, 1)], sum: 3 }]).unwrap();
+/// assert_eq!(rules.cages().len(), 1);
+/// assert!(Rules::sudoku(2).with_cages(vec![Cage { cells: vec![(0, 0), (0, 0)], sum: 3 }]).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    order: usize,
+    boxes: Option<BoxShape>,
+    diagonals: bool,
+    cages: Vec<Cage>
+}
+
+impl Rules {
+    /// Latin squares of the given order: distinct symbols in every row and column.
+    pub fn latin(order: usize) -> Rules {
+        Rules { order, boxes: None, diagonals: false, cages: Vec::new() }
+    }
+
+    /// Sudoku with square boxes of the given size, on squares of order `box_size * box_size`.
+    pub fn sudoku(box_size: usize) -> Rules {
+        Rules::with_boxes(BoxShape::new(box_size, box_size))
+    }
+
+    /// Sudoku with boxes of the given shape, on squares of order `shape.order()`.
+    pub fn with_boxes(shape: BoxShape) -> Rules {
+        Rules { order: shape.order(), boxes: Some(shape), diagonals: false, cages: Vec::new() }
+    }
+
+    /// The same rules, with distinct symbols on both main diagonals too, as in Sudoku-X.
+    pub fn with_diagonals(self) -> Rules {
+        Rules { diagonals: true, ..self }
+    }
+
+    /// The same rules with the given cages added. Returns None if a cage has a cell outside the grid, or
+    /// more cells than symbols, or if a cell is in more than one cage.
+    pub fn with_cages(self, cages: Vec<Cage>) -> Option<Rules> {
+        let order = self.order;
+        let mut caged = vec![vec![false; order]; order];
+        for cage in self.cages.iter().chain(&cages) {
+            if cage.cells.len() > order {
+                return None
+            }
+            for &(row, column) in &cage.cells {
+                if row >= order || column >= order || caged[row][column] {
+                    return None
+                }
+                caged[row][column] = true;
+            }
+        }
+        let mut rules = self;
+        rules.cages.extend(cages);
+        Some(rules)
+    }
+
+    /// The order of the grid.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The size of the boxes, if the rules have square boxes.
+    pub fn box_size(&self) -> Option<usize> {
+        self.boxes.filter(|shape| shape.rows == shape.columns).map(|shape| shape.rows)
+    }
+
+    /// The shape of the boxes, if the rules have boxes.
+    pub fn box_shape(&self) -> Option<BoxShape> {
+        self.boxes
+    }
+
+    /// Returns true if the main diagonals hold distinct symbols.
+    pub fn diagonals(&self) -> bool {
+        self.diagonals
+    }
+
+    /// The cages.
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// The groups of cells that hold distinct symbols, each cell as `row * order + column`: rows, columns,
+    /// boxes, diagonals and cages, in that order.
+    pub fn units(&self) -> Vec<Vec<usize>> {
+        let n = self.order;
+        let mut units: Vec<Vec<usize>> = (0..n).map(|row| (0..n).map(|column| row * n + column).collect()).collect();
+        units.extend((0..n).map(|column| (0..n).map(|row| row * n + column).collect()));
+        if let Some(shape) = self.boxes {
+            units.extend((0..n).map(|index| shape.cells(index).into_iter().map(|(row, column)| row * n + column).collect()));
+        }
+        if self.diagonals {
+            units.push((0..n).map(|index| index * n + index).collect());
+            units.push((0..n).map(|index| index * n + n - 1 - index).collect());
+        }
+        units.extend(self.cages.iter().map(|cage| cage.cells.iter().map(|&(row, column)| row * n + column).collect()));
+        units
+    }
+
+    /// Returns true if the square meets every constraint.
+    pub fn is_solution(&self, square: &LatinSquare) -> bool {
+        let n = self.order;
+        let symbol = |cell: usize| square.square[cell / n][cell % n];
+        square.size() == n
+            && self.units().iter().all(|unit| {
+                let mut seen = BitSet::new(n);
+                unit.iter().all(|&cell| {
+                    let fresh = symbol(cell) < n && !seen.contains(symbol(cell));
+                    if fresh {
+                        seen.insert(symbol(cell));
+                    }
+                    fresh
+                })
+            })
+            && self.cages.iter().all(|cage| cage.cells.iter().map(|&(row, column)| square.square[row][column] + 1).sum::<usize>() == cage.sum)
+    }
+}
+
+/// A puzzle: rules with some cells given.
+///
+/// ```
+/// use combinatorial_patterns::sudoku::{self, BoxShape, Rules};
+///
+/// let puzzle = sudoku::generate(&Rules::with_boxes(BoxShape::new(2, 3)), &mut rand::thread_rng()).unwrap();
+/// assert!(puzzle.has_unique_solution());
+///
+/// let puzzle = sudoku::generate(&Rules::sudoku(3).with_diagonals(), &mut rand::thread_rng()).unwrap();
+/// assert_eq!(puzzle.count_solutions(2), 1);
+/// let solution = puzzle.solve().unwrap();
+/// assert!(puzzle.rules().is_solution(&solution));
+/// assert!((0..9).all(|i| (0..9).filter(|&j| solution.square[j][j] == i).count() == 1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    rules: Rules,
+    clues: PartialLatinSquare
+}
+
+impl Puzzle {
+    /// A puzzle with the given clues. Returns None if the clues have the wrong order, or break a unit.
+    pub fn new(rules: Rules, clues: PartialLatinSquare) -> Option<Puzzle> {
+        let consistent = clues.order() == rules.order() && rules.units().iter().all(|unit| {
+            let mut seen = BitSet::new(rules.order());
+            unit.iter().filter_map(|&cell| clues.get(cell / rules.order(), cell % rules.order())).all(|symbol| {
+                let fresh = symbol < rules.order() && !seen.contains(symbol);
+                if fresh {
+                    seen.insert(symbol);
+                }
+                fresh
+            })
+        });
+        if consistent {
+            Some(Puzzle { rules, clues })
+        } else {
+            None
+        }
+    }
+
+    /// The rules.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The given cells.
+    pub fn clues(&self) -> &PartialLatinSquare {
+        &self.clues
+    }
+
+    /// The given cells, to be changed in place. Removing clues keeps the puzzle valid, adding them might not.
+    pub(crate) fn clues_mut(&mut self) -> &mut PartialLatinSquare {
+        &mut self.clues
+    }
+
+    /// Returns some solution, if there is one.
+    pub fn solve(&self) -> Option<LatinSquare> {
+        self.solutions(1).pop()
+    }
+
+    /// Returns up to `limit` distinct solutions.
+    pub fn solutions(&self, limit: usize) -> Vec<LatinSquare> {
+        let mut found = Vec::new();
+        if limit > 0 {
+            if let Some(candidates) = Candidates::new(&self.rules, &self.clues) {
+                candidates.search(&mut None::<ThreadRng>, &mut |solution| {
+                    found.push(solution);
+                    found.len() < limit
+                });
+            }
+        }
+        found
+    }
+
+    /// Counts the solutions, stopping once `cap` have been found. A cap of 2 tells whether the solution is unique.
+    pub fn count_solutions(&self, cap: usize) -> usize {
+        self.solutions(cap).len()
+    }
+
+    /// Returns true if the puzzle has exactly one solution.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+}
+
+/// The grid with the boxes outlined, and digits counting from 1 as in printed puzzles.
+///
+/// ```
+/// use combinatorial_patterns::partial::PartialLatinSquare;
+/// use combinatorial_patterns::sudoku::{BoxShape, Puzzle, Rules};
+///
+/// let mut clues = PartialLatinSquare::new(6);
+/// clues.set(0, 0, Some(0));
+/// clues.set(5, 5, Some(5));
+/// let puzzle = Puzzle::new(Rules::with_boxes(BoxShape::new(2, 3)), clues).unwrap();
+/// let text = puzzle.to_string();
+/// assert_eq!(text.lines().next(), Some("+-------+-------+"));
+/// assert_eq!(text.lines().nth(1), Some("| 1 . . | . . . |"));
+/// assert_eq!(text.lines().count(), 10);
+/// ```
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.rules.order();
+        let shape = self.rules.box_shape().unwrap_or(BoxShape::new(n, n));
+        let (band, stack) = (shape.rows.max(1), shape.columns.max(1));
+        let width = n.to_string().len();
+        let line = "+".to_string() + &(0..n.div_ceil(stack)).map(|_| "-".repeat(stack * (width + 1) + 1) + "+").collect::<String>();
+        let mut lines = vec![line.clone()];
+        for (row, cells) in self.clues.cells().iter().enumerate() {
+            let groups: Vec<String> = cells.chunks(stack).map(|group| {
+                group.iter().map(|cell| match cell {
+                    Some(symbol) => format!(" {:>width$}", symbol + 1),
+                    None => format!(" {:>width$}", ".")
+                }).collect::<String>() + " |"
+            }).collect();
+            lines.push("|".to_string() + &groups.concat());
+            if (row + 1) % band == 0 {
+                lines.push(line.clone());
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Generates a puzzle with a unique solution under the given rules, with clues removed while it stays unique.
+///
+/// Every random choice is drawn from `rng`. Fails for order 0, and if the rules have no solution.
+pub fn generate(rules: &Rules, rng: &mut impl Rng) -> Result<Puzzle, CombinatorialError> {
+    let solution = random_solution(rules, rng)?;
+    Ok(minimize(rules.clone(), &solution, rng))
+}
+
+/// Generates a Killer puzzle: a random solution of the rules split into connected cages of at most
+/// `max_cage_size` cells, with clues removed while the solution stays unique. Often no clues remain. Every
+/// random choice is drawn from `rng`.
+///
+/// ```
+/// use combinatorial_patterns::sudoku::{self, Rules};
+///
+/// let mut rng = rand::thread_rng();
+/// let puzzle = sudoku::generate_killer(&Rules::sudoku(2), 3, &mut rng).unwrap();
+/// assert_eq!(puzzle.count_solutions(2), 1);
+/// let covered: usize = puzzle.rules().cages().iter().map(|cage| cage.cells.len()).sum();
+/// assert_eq!(covered, 16);
+/// assert!(sudoku::generate_killer(&Rules::latin(0), 3, &mut rng).is_err());
+/// ```
+///
+/// Fails for order 0, and if the rules have no solution.
+pub fn generate_killer(rules: &Rules, max_cage_size: usize, rng: &mut impl Rng) -> Result<Puzzle, CombinatorialError> {
+    let solution = random_solution(rules, rng)?;
+    let cages = random_cages(&solution, max_cage_size.clamp(1, rules.order().max(1)), rng);
+    let rules = rules.clone().with_cages(cages).expect("ProgrammingError: random cages cover each free cell at most once.");
+    Ok(minimize(rules, &solution, rng))
+}
+
+fn random_solution(rules: &Rules, rng: &mut impl Rng) -> Result<LatinSquare, CombinatorialError> {
+    if rules.order() == 0 {
+        return Err(CombinatorialError::ZeroOrder)
+    }
+    let empty = PartialLatinSquare::new(rules.order());
+    let candidates = Candidates::new(rules, &empty).ok_or(CombinatorialError::NoSolution)?;
+    let mut found = None;
+    candidates.search(&mut Some(rng), &mut |solution| {
+        found = Some(solution);
+        false
+    });
+    found.ok_or(CombinatorialError::NoSolution)
+}
+
+/// Removes the clues of a full solution in random order, as long as the solution stays unique.
+pub(crate) fn minimize(rules: Rules, solution: &LatinSquare, rng: &mut impl Rng) -> Puzzle {
+    let n = rules.order();
+    let mut clues = PartialLatinSquare::from_square(solution);
+    let mut cells: Vec<(usize, usize)> = (0..n).flat_map(|row| (0..n).map(move |column| (row, column))).collect();
+    cells.shuffle(rng);
+    let mut puzzle = Puzzle { rules, clues: clues.clone() };
+    for (row, column) in cells {
+        clues.set(row, column, None);
+        puzzle.clues = clues.clone();
+        if puzzle.count_solutions(2) != 1 {
+            clues.set(row, column, Some(solution.square[row][column]));
+        }
+    }
+    puzzle.clues = clues;
+    puzzle
+}
+
+/// Splits the cells into random connected cages of distinct symbols and at most `max_size` cells.
+fn random_cages(solution: &LatinSquare, max_size: usize, rng: &mut impl Rng) -> Vec<Cage> {
+    let n = solution.size();
+    let mut caged = vec![vec![false; n]; n];
+    let mut cells: Vec<(usize, usize)> = (0..n).flat_map(|row| (0..n).map(move |column| (row, column))).collect();
+    cells.shuffle(rng);
+    let mut cages = Vec::new();
+    for start in cells {
+        if caged[start.0][start.1] {
+            continue
+        }
+        let size = rng.gen_range(1..=max_size);
+        let mut cage = vec![start];
+        caged[start.0][start.1] = true;
+        while cage.len() < size {
+            let mut neighbours: Vec<(usize, usize)> = cage.iter().flat_map(|&(row, column)| {
+                [(row.wrapping_sub(1), column), (row + 1, column), (row, column.wrapping_sub(1)), (row, column + 1)]
+            }).filter(|&(row, column)| {
+                row < n && column < n && !caged[row][column]
+                    && cage.iter().all(|&(r, c)| solution.square[r][c] != solution.square[row][column])
+            }).collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            let Some(&next) = neighbours.choose(rng) else {
+                break
+            };
+            caged[next.0][next.1] = true;
+            cage.push(next);
+        }
+        let sum = cage.iter().map(|&(row, column)| solution.square[row][column] + 1).sum();
+        cages.push(Cage { cells: cage, sum });
+    }
+    cages
+}
+
+/// The candidate symbols of every cell under some rules, narrowed by constraint propagation.
+#[derive(Clone)]
+pub(crate) struct Candidates {
+    order: usize,
+    units: Vec<Vec<usize>>,
+    /// The units of each cell.
+    units_of: Vec<Vec<usize>>,
+    /// The cages as cells and sum, with the sum counting symbols from 0.
+    cages: Vec<(Vec<usize>, usize)>,
+    pub(crate) cells: Vec<BitSet>
+}
+
+impl Candidates {
+    /// The candidates after propagating the clues, or None if propagation finds a contradiction.
+    pub(crate) fn new(rules: &Rules, clues: &PartialLatinSquare) -> Option<Candidates> {
+        let n = rules.order();
+        if clues.order() != n {
+            return None
+        }
+        let units = rules.units();
+        let mut units_of = vec![Vec::new(); n * n];
+        for (index, unit) in units.iter().enumerate() {
+            for &cell in unit {
+                units_of[cell].push(index);
+            }
+        }
+        let cages = rules.cages().iter().filter_map(|cage| {
+            let sum = cage.sum.checked_sub(cage.cells.len())?;
+            Some((cage.cells.iter().map(|&(row, column)| row * n + column).collect(), sum))
+        }).collect::<Vec<(Vec<usize>, usize)>>();
+        if cages.len() != rules.cages().len() {
+            return None
+        }
+        let mut candidates = Candidates { order: n, units, units_of, cages, cells: vec![BitSet::full(n); n * n] };
+        for (row, column, symbol) in clues.filled_cells() {
+            if symbol >= n || !candidates.cells[row * n + column].contains(symbol) {
+                return None
+            }
+            candidates.cells[row * n + column] = BitSet::from_values(n, &[symbol]);
+        }
+        if candidates.propagate() {
+            Some(candidates)
+        } else {
+            None
+        }
+    }
+
+    /// Narrows the candidates until nothing changes. Returns false if some cell has no candidates left.
+    pub(crate) fn propagate(&mut self) -> bool {
+        loop {
+            let mut changed = false;
+            for cell in 0..self.cells.len() {
+                if self.cells[cell].count() != 1 {
+                    continue
+                }
+                let symbol = self.cells[cell].first().expect("ProgrammingError: the cell has one candidate.");
+                for &unit in &self.units_of[cell] {
+                    for &other in &self.units[unit] {
+                        if other != cell && self.cells[other].contains(symbol) {
+                            self.cells[other].remove(symbol);
+                            if self.cells[other].is_empty() {
+                                return false
+                            }
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            for unit in &self.units {
+                if unit.len() != self.order {
+                    continue
+                }
+                for symbol in 0..self.order {
+                    let mut places = unit.iter().filter(|&&cell| self.cells[cell].contains(symbol));
+                    match (places.next(), places.next()) {
+                        (None, _) => return false,
+                        (Some(&cell), None) if self.cells[cell].count() > 1 => {
+                            self.cells[cell] = BitSet::from_values(self.order, &[symbol]);
+                            changed = true;
+                        },
+                        _ => {}
+                    }
+                }
+            }
+            for cage in 0..self.cages.len() {
+                let (cells, sum) = self.cages[cage].clone();
+                for (index, &cell) in cells.iter().enumerate() {
+                    for symbol in self.cells[cell].clone().iter() {
+                        let others: Vec<usize> = cells.iter().enumerate().filter(|&(other, _)| other != index).map(|(_, &other)| other).collect();
+                        let mut used = BitSet::new(self.order);
+                        used.insert(symbol);
+                        if sum < symbol || !self.can_sum(&others, sum - symbol, &mut used) {
+                            self.cells[cell].remove(symbol);
+                            if self.cells[cell].is_empty() {
+                                return false
+                            }
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return true
+            }
+        }
+    }
+
+    /// Returns true if the cells can take distinct candidates, not in `used`, that add up to `sum`.
+    fn can_sum(&self, cells: &[usize], sum: usize, used: &mut BitSet) -> bool {
+        let Some((&cell, rest)) = cells.split_first() else {
+            return sum == 0
+        };
+        for symbol in self.cells[cell].iter() {
+            if symbol > sum || used.contains(symbol) {
+                continue
+            }
+            used.insert(symbol);
+            let possible = self.can_sum(rest, sum - symbol, used);
+            used.remove(symbol);
+            if possible {
+                return true
+            }
+        }
+        false
+    }
+
+    /// Hands every solution to `on_solution` until it returns false, trying candidates in random order if
+    /// given a generator. Returns false once the callback asked to stop.
+    fn search<R: Rng>(&self, rng: &mut Option<R>, on_solution: &mut dyn FnMut(LatinSquare) -> bool) -> bool {
+        let open = (0..self.cells.len()).filter(|&cell| self.cells[cell].count() > 1).min_by_key(|&cell| self.cells[cell].count());
+        let Some(cell) = open else {
+            let n = self.order;
+            let rows = (0..n).map(|row| (0..n).map(|column| {
+                self.cells[row * n + column].first().expect("ProgrammingError: propagation leaves no empty cells.")
+            }).collect()).collect();
+            return on_solution(LatinSquare::from_rows(rows))
+        };
+        let mut symbols: Vec<Symbol> = self.cells[cell].iter().collect();
+        if let Some(rng) = rng {
+            symbols.shuffle(rng);
+        }
+        for symbol in symbols {
+            let mut next = self.clone();
+            next.cells[cell] = BitSet::from_values(self.order, &[symbol]);
+            if next.propagate() && !next.search(rng, on_solution) {
+                return false
+            }
+        }
+        true
+    }
+}
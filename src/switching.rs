@@ -0,0 +1,155 @@
+//! Random walks on latin squares by intercalate flips and cycle switches.
+//!
+//! An intercalate is a 2 x 2 subsquare: rows r1, r2 and columns c1, c2 holding a, b / b, a. Flipping it
+//! to b, a / a, b gives another latin square. More generally, for two rows and a column c, the columns
+//! c, then the column where row r1 holds the symbol row r2 has at c, and so on, form a cycle; swapping
+//! the two rows on those columns is a cycle switch. Intercalate flips are the cycle switches of length two.
+//!
+//! Unlike the Jacobson Matthews walk, these walks are not known to sample uniformly, and intercalate flips
+//! alone can't reach every square: cyclic squares of odd order have no intercalates at all. They are
+//! cheap though, a cycle switch takes O(n) steps, which makes them handy for jittering an existing design
+//! a little, see `LatinSquare::perturb`.
+//!
+//! Sources:
+//!
+//! - Cycle switches in latin squares, Ian M. Wanless, Graphs and Combinatorics 20 (2004)
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::latin_square::LatinSquare;
+
+/// A 2 x 2 subsquare, given by its rows and columns, with `rows[0] < rows[1]` and `columns[0] < columns[1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Intercalate {
+    pub rows: [usize; 2],
+    pub columns: [usize; 2]
+}
+
+/// Every intercalate of the square.
+///
+/// ```
+/// use combinatorial_patterns::{switching, IncidenceCube};
+///
+/// // The cyclic square of order 4 has rows r and r + 2 agreeing on symbols at columns c and c + 2.
+/// let square = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+/// assert_eq!(switching::intercalates(&square).len(), 4);
+/// assert!(switching::intercalates(&IncidenceCube::new_cyclic(5).unwrap().as_latin_square()).is_empty());
+/// ```
+pub fn intercalates(square: &LatinSquare) -> Vec<Intercalate> {
+    let order = square.size();
+    let mut found = Vec::new();
+    for first in 0..order {
+        let position = positions(&square.square[first]);
+        for second in (first + 1)..order {
+            for left in 0..order {
+                // The column where the first row holds what the second row has at `left`.
+                let right = position[square.square[second][left]];
+                if right > left && square.square[second][right] == square.square[first][left] {
+                    found.push(Intercalate { rows: [first, second], columns: [left, right] });
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Flips an intercalate of the square, swapping its two rows on its two columns.
+pub fn flip(square: &mut LatinSquare, intercalate: &Intercalate) {
+    let [first, second] = intercalate.rows;
+    for &column in &intercalate.columns {
+        let symbol = square.square[first][column];
+        square.square[first][column] = square.square[second][column];
+        square.square[second][column] = symbol;
+    }
+}
+
+/// Flips an intercalate chosen uniformly with `rng` among those of the square. Returns false if there are none.
+pub fn flip_random_intercalate(square: &mut LatinSquare, rng: &mut impl Rng) -> bool {
+    match intercalates(square).choose(rng) {
+        Some(intercalate) => {
+            flip(square, intercalate);
+            true
+        },
+        None => false
+    }
+}
+
+/// Swaps the given rows on the cycle of columns through `column`. Returns the length of the cycle.
+///
+/// ```
+/// use combinatorial_patterns::{switching, IncidenceCube};
+///
+/// // Rows 0 and 1 of a cyclic square only form one cycle, through every column, so they swap entirely.
+/// let mut square = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+/// assert_eq!(switching::switch_cycle(&mut square, [0, 1], 2), 5);
+/// assert_eq!(square.square[0], vec![1, 2, 3, 4, 0]);
+/// ```
+pub fn switch_cycle(square: &mut LatinSquare, rows: [usize; 2], column: usize) -> usize {
+    let [first, second] = rows;
+    let position = positions(&square.square[first]);
+    let mut cycle = vec![column];
+    let mut next = position[square.square[second][column]];
+    while next != column {
+        cycle.push(next);
+        next = position[square.square[second][next]];
+    }
+    for &column in &cycle {
+        let symbol = square.square[first][column];
+        square.square[first][column] = square.square[second][column];
+        square.square[second][column] = symbol;
+    }
+    cycle.len()
+}
+
+/// Switches the cycle through a random column of two random distinct rows, drawn from `rng`. Returns the
+/// length of the cycle, or 0 if the square has fewer than two rows.
+pub fn switch_random_cycle(square: &mut LatinSquare, rng: &mut impl Rng) -> usize {
+    let order = square.size();
+    if order < 2 {
+        return 0
+    }
+    let first = rng.gen_range(0..order);
+    let second = (first + rng.gen_range(1..order)) % order;
+    switch_cycle(square, [first, second], rng.gen_range(0..order))
+}
+
+/// Runs `steps` random cycle switches from the given square, drawn from `rng`, and returns where the walk ends.
+///
+/// ```
+/// use combinatorial_patterns::{switching, IncidenceCube, Quasigroup};
+///
+/// let square = switching::random_walk(IncidenceCube::new_cyclic(7).unwrap().as_latin_square(), 100, &mut rand::thread_rng());
+/// assert!(Quasigroup::new(square).is_some());
+/// ```
+pub fn random_walk(mut square: LatinSquare, steps: usize, rng: &mut impl Rng) -> LatinSquare {
+    square.perturb(steps, rng);
+    square
+}
+
+/// Runs `steps` random intercalate flips from the given square, drawn from `rng`, and returns where the walk ends.
+/// Stops early if it reaches a square without intercalates.
+///
+/// ```
+/// use combinatorial_patterns::{switching, IncidenceCube};
+///
+/// let square = switching::intercalate_walk(IncidenceCube::new_cyclic(4).unwrap().as_latin_square(), 10, &mut rand::thread_rng());
+/// assert!(!switching::intercalates(&square).is_empty());
+/// ```
+pub fn intercalate_walk(mut square: LatinSquare, steps: usize, rng: &mut impl Rng) -> LatinSquare {
+    for _ in 0..steps {
+        if !flip_random_intercalate(&mut square, rng) {
+            break
+        }
+    }
+    square
+}
+
+/// `position[symbol]` is the column holding the symbol in the row.
+fn positions(row: &[usize]) -> Vec<usize> {
+    let mut position = vec![0; row.len()];
+    for (column, &symbol) in row.iter().enumerate() {
+        position[symbol] = column;
+    }
+    position
+}
@@ -0,0 +1,254 @@
+//! Checks that objects are what they claim to be, with messages that say where they are not.
+//!
+//! Each check returns its violations as messages, empty when the object passes, and starts each message
+//! with what was checked, such as `row 2:` or `orthogonality:`, followed by the position. Rows, columns and
+//! symbols count from 0. The `assert_` functions panic with every violation instead, for tests. The command
+//! line `validate` tool prints the same messages.
+//!
+//! The checks work on plain grids, so they also apply to data that is not yet a `LatinSquare`, and to the
+//! incidence cubes of the Jacobson-Matthews walk and to block designs such as triple systems.
+
+use std::collections::HashMap;
+
+use crate::latin_square::{grid_violations, repeats, IncidenceCube, LatinSquare};
+
+/// Violations of the shape, the symbol range, and the latin property. Shape violations come alone, as the
+/// rest can't be checked without a square grid.
+///
+/// ```
+/// use combinatorial_patterns::testing;
+///
+/// assert!(testing::latin_violations(&[vec![0, 1], vec![1, 0]]).is_empty());
+/// assert_eq!(testing::latin_violations(&[vec![0, 1], vec![0, 1]]), vec![
+///     "column 0: symbol 0 repeats in rows 0 and 1",
+///     "column 1: symbol 1 repeats in rows 0 and 1"
+/// ]);
+/// ```
+pub fn latin_violations(grid: &[Vec<usize>]) -> Vec<String> {
+    grid_violations(grid).iter().map(|violation| violation.to_string()).collect()
+}
+
+/// Violations of both diagonals holding every symbol, of a square grid.
+pub fn diagonal_violations(grid: &[Vec<usize>]) -> Vec<String> {
+    let order = grid.len();
+    let main: Vec<usize> = (0..order).map(|i| grid[i][i]).collect();
+    let anti: Vec<usize> = (0..order).map(|i| grid[i][order - 1 - i]).collect();
+    let mut found: Vec<String> = repeats(&main).into_iter().map(|(symbol, first, second)| {
+        format!("main diagonal: symbol {} repeats in rows {} and {}", symbol, first, second)
+    }).collect();
+    found.extend(repeats(&anti).into_iter().map(|(symbol, first, second)| {
+        format!("anti-diagonal: symbol {} repeats in rows {} and {}", symbol, first, second)
+    }));
+    found
+}
+
+/// Violations of a square grid equalling its transpose.
+pub fn symmetry_violations(grid: &[Vec<usize>]) -> Vec<String> {
+    let order = grid.len();
+    (0..order).flat_map(|row| ((row + 1)..order).map(move |column| (row, column)))
+        .filter(|&(row, column)| grid[row][column] != grid[column][row])
+        .map(|(row, column)| format!(
+            "symmetry: row {}, column {} holds {} but row {}, column {} holds {}",
+            row, column, grid[row][column], column, row, grid[column][row]
+        ))
+        .collect()
+}
+
+/// Violations of two square grids of the same order being orthogonal: every pair of their symbols at the
+/// same cell appears once.
+pub fn orthogonality_violations(grid: &[Vec<usize>], other: &[Vec<usize>]) -> Vec<String> {
+    if grid.len() != other.len() {
+        return vec![format!("orthogonality: the squares have orders {} and {}", grid.len(), other.len())]
+    }
+    let mut seen: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut found = Vec::new();
+    for (row, symbols) in grid.iter().enumerate() {
+        for (column, &symbol) in symbols.iter().enumerate() {
+            let pair = (symbol, other[row][column]);
+            if let Some(&(first_row, first_column)) = seen.get(&pair) {
+                found.push(format!(
+                    "orthogonality: the pair ({}, {}) appears at row {}, column {} and row {}, column {}",
+                    pair.0, pair.1, first_row, first_column, row, column
+                ));
+            } else {
+                seen.insert(pair, (row, column));
+            }
+        }
+    }
+    found
+}
+
+/// Violations of every pair of the squares being orthogonal, after the latin property of each.
+///
+/// ```
+/// use combinatorial_patterns::{mols, testing, IncidenceCube};
+///
+/// assert!(testing::mutual_orthogonality_violations(&mols::new_prime_power(5).unwrap()).is_empty());
+/// let squares = [IncidenceCube::new_cyclic(3).unwrap().as_latin_square(), IncidenceCube::new_cyclic(3).unwrap().as_latin_square()];
+/// let violations = testing::mutual_orthogonality_violations(&squares);
+/// assert_eq!(violations[0], "squares 0 and 1: orthogonality: the pair (1, 1) appears at row 0, column 1 and row 1, column 0");
+/// ```
+pub fn mutual_orthogonality_violations(squares: &[LatinSquare]) -> Vec<String> {
+    let mut found: Vec<String> = squares.iter().enumerate()
+        .flat_map(|(index, square)| latin_violations(&square.square).into_iter().map(move |violation| format!("square {}: {}", index, violation)))
+        .collect();
+    if !found.is_empty() {
+        return found
+    }
+    for (first, a) in squares.iter().enumerate() {
+        for (second, b) in squares.iter().enumerate().skip(first + 1) {
+            found.extend(orthogonality_violations(&a.square, &b.square).into_iter()
+                .map(|violation| format!("squares {} and {}: {}", first, second, violation)));
+        }
+    }
+    found
+}
+
+/// Violations of an incidence cube being proper: every entry 0 or 1, and every line of the cube, along rows,
+/// columns, or symbols, holding exactly one 1.
+///
+/// ```
+/// use combinatorial_patterns::{testing, IncidenceCube};
+///
+/// let mut cube = IncidenceCube::new_cyclic(5).unwrap();
+/// assert!(testing::cube_violations(&cube).is_empty());
+/// cube.shuffle(&mut rand::thread_rng());
+/// assert!(testing::cube_violations(&cube).is_empty());
+/// ```
+pub fn cube_violations(cube: &IncidenceCube) -> Vec<String> {
+    let tensor = cube.to_tensor();
+    let [rows, columns, symbols] = tensor.shape;
+    let mut found = Vec::new();
+    for x in 0..rows {
+        for y in 0..columns {
+            for z in 0..symbols {
+                let entry = tensor.get(x, y, z);
+                if entry != 0 && entry != 1 {
+                    found.push(format!("entry: row {}, column {}, symbol {} is {}", x, y, z, entry));
+                }
+            }
+        }
+    }
+    let mut line = |name: &str, a: usize, b: usize, length: usize, entry: &dyn Fn(usize) -> i8| {
+        let sum: i32 = (0..length).map(|index| entry(index) as i32).sum();
+        if sum != 1 {
+            found.push(format!("line: {} {} and {} sums to {}", name, a, b, sum));
+        }
+    };
+    for x in 0..rows {
+        for y in 0..columns {
+            line("row, column", x, y, symbols, &|z| tensor.get(x, y, z));
+        }
+        for z in 0..symbols {
+            line("row, symbol", x, z, columns, &|y| tensor.get(x, y, z));
+        }
+    }
+    for y in 0..columns {
+        for z in 0..symbols {
+            line("column, symbol", y, z, rows, &|x| tensor.get(x, y, z));
+        }
+    }
+    found
+}
+
+/// Violations of the blocks being a t-(v, k, lambda) design on the points 0..v: every block has k distinct
+/// points, and every set of t distinct points is in exactly lambda blocks.
+///
+/// Counting runs through the t-subsets of every block and the t-subsets of the points, so it is meant for
+/// small designs.
+///
+/// ```
+/// use combinatorial_patterns::{testing, triple_system::SteinerTripleSystem};
+///
+/// let system = SteinerTripleSystem::new(9).unwrap();
+/// let blocks: Vec<Vec<usize>> = system.blocks().iter().map(|block| block.to_vec()).collect();
+/// assert!(testing::block_design_violations(9, &blocks, 3, 2, 1).is_empty());
+/// let violations = testing::block_design_violations(9, &blocks[1..], 3, 2, 1);
+/// assert_eq!(violations.len(), 3);
+/// assert!(violations[0].starts_with("pairs: the points"));
+/// ```
+pub fn block_design_violations(points: usize, blocks: &[Vec<usize>], block_size: usize, strength: usize, lambda: usize) -> Vec<String> {
+    let mut found = Vec::new();
+    for (index, block) in blocks.iter().enumerate() {
+        if block.len() != block_size {
+            found.push(format!("block {}: has {} points, expected {}", index, block.len(), block_size));
+        }
+        if let Some(&point) = block.iter().find(|&&point| point >= points) {
+            found.push(format!("block {}: point {} is not below {}", index, point, points));
+        }
+        found.extend(repeats(block).into_iter().map(|(point, first, second)| {
+            format!("block {}: point {} repeats in places {} and {}", index, point, first, second)
+        }));
+    }
+    if !found.is_empty() {
+        return found
+    }
+    let mut counts: HashMap<Vec<usize>, usize> = HashMap::new();
+    for block in blocks {
+        let mut sorted = block.clone();
+        sorted.sort_unstable();
+        for subset in subsets(&sorted, strength) {
+            *counts.entry(subset).or_default() += 1;
+        }
+    }
+    let name = match strength {
+        1 => "points".to_string(),
+        2 => "pairs".to_string(),
+        3 => "triples".to_string(),
+        t => format!("{}-sets", t)
+    };
+    let all: Vec<usize> = (0..points).collect();
+    for subset in subsets(&all, strength) {
+        let count = counts.get(&subset).copied().unwrap_or(0);
+        if count != lambda {
+            let listed: Vec<String> = subset.iter().map(|point| point.to_string()).collect();
+            found.push(format!("{}: the points {} are in {} blocks, expected {}", name, listed.join(", "), count, lambda));
+        }
+    }
+    found
+}
+
+/// Panics, listing every violation, unless the grid is a latin square.
+pub fn assert_latin_square(grid: &[Vec<usize>]) {
+    assert_none("a latin square", latin_violations(grid));
+}
+
+/// Panics, listing every violation, unless the squares are latin squares, mutually orthogonal.
+pub fn assert_mutually_orthogonal(squares: &[LatinSquare]) {
+    assert_none("mutually orthogonal latin squares", mutual_orthogonality_violations(squares));
+}
+
+/// Panics, listing every violation, unless the cube is proper.
+pub fn assert_proper_cube(cube: &IncidenceCube) {
+    assert_none("a proper incidence cube", cube_violations(cube));
+}
+
+/// Panics, listing every violation, unless the blocks are a t-(v, k, lambda) design.
+pub fn assert_block_design(points: usize, blocks: &[Vec<usize>], block_size: usize, strength: usize, lambda: usize) {
+    let claim = format!("a {}-({}, {}, {}) design", strength, points, block_size, lambda);
+    assert_none(&claim, block_design_violations(points, blocks, block_size, strength, lambda));
+}
+
+fn assert_none(claim: &str, violations: Vec<String>) {
+    if !violations.is_empty() {
+        panic!("not {}:\n    {}", claim, violations.join("\n    "));
+    }
+}
+
+/// Every subset of the given size of the sorted items, in lexicographic order.
+fn subsets(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()]
+    }
+    if items.len() < size {
+        return Vec::new()
+    }
+    let mut found: Vec<Vec<usize>> = subsets(&items[1..], size - 1).into_iter()
+        .map(|mut rest| {
+            rest.insert(0, items[0]);
+            rest
+        })
+        .collect();
+    found.extend(subsets(&items[1..], size));
+    found
+}
@@ -0,0 +1,443 @@
+//! Mendelsohn and directed triple systems, the ordered-triple analogues of Steiner triple systems.
+//!
+//! Both are collections of triples of distinct points in which every ordered pair of distinct points
+//! appears exactly once:
+//!
+//! - In a Mendelsohn triple system, the triple (a, b, c) is cyclic and contains the pairs
+//!   (a, b), (b, c) and (c, a). One exists exactly when v ≡ 0 or 1 (mod 3), v ≠ 6.
+//! - In a directed triple system, the triple (a, b, c) is transitive and contains the pairs
+//!   (a, b), (a, c) and (b, c). One exists exactly when v ≡ 0 or 1 (mod 3).
+//!
+//! Mendelsohn triple systems are in one-to-one correspondence with idempotent semisymmetric
+//! quasigroups, where the triple (a, b, c) means `a * b = c`, `b * c = a`, and `c * a = b`.
+//!
+//! Steiner triple systems, with unordered triples covering every unordered pair once, exist exactly when
+//! v ≡ 1 or 3 (mod 6). They correspond in the same way to idempotent totally symmetric quasigroups, the
+//! Steiner quasigroups, whose tables are closed under all six conjugates. Bose's construction gives the
+//! orders 6t + 3 and Skolem's the orders 6t + 1, both by tripling a commutative quasigroup of order 2t + 1 or 2t.
+//!
+//! Both systems are constructed by tripling an idempotent quasigroup Q of order n: the points are
+//! Q x {0, 1, 2} (plus a point at infinity when v = 3n + 1), each column {x} x {0, 1, 2} receives its
+//! own small system, and every pair x != y at level i gives a triple through (x * y) at level i + 1.
+//! The few orders where no idempotent quasigroup of the needed order exists use the Fano plane
+//! or a small search.
+//!
+//! Sources:
+//!
+//! - [Combinatorial Designs: Constructions and Analysis, Douglas R. Stinson](https://link.springer.com/book/10.1007/b97564)
+//! - [Triple Systems, Charles J. Colbourn, Alexander Rosa](https://global.oup.com/academic/product/triple-systems-9780198535768)
+
+use crate::quasigroup::Quasigroup;
+use crate::latin_square::LatinSquare;
+
+/// Three points of a triple system. How the order of the points is read depends on the system.
+pub type Triple = [usize; 3];
+
+/// A Mendelsohn triple system: cyclic triples covering every ordered pair of distinct points once.
+///
+/// ```
+/// use combinatorial_patterns::triple_system::MendelsohnTripleSystem;
+///
+/// let system = MendelsohnTripleSystem::new(9).unwrap();
+/// assert_eq!(system.triples().len(), 9 * 8 / 3);
+/// assert!(MendelsohnTripleSystem::verify(9, system.triples()));
+///
+/// // Every Mendelsohn triple system is an idempotent semisymmetric quasigroup, and back.
+/// let quasigroup = system.as_quasigroup();
+/// assert!(quasigroup.is_idempotent());
+/// assert_eq!(MendelsohnTripleSystem::from_quasigroup(&quasigroup).unwrap().triples().len(), 24);
+///
+/// assert!(MendelsohnTripleSystem::new(6).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MendelsohnTripleSystem {
+    order: usize,
+    triples: Vec<Triple>
+}
+
+impl MendelsohnTripleSystem {
+    /// Constructs a Mendelsohn triple system on `order` points.
+    ///
+    /// Returns None when none exists, i.e. when order ≡ 2 (mod 3) or order = 6.
+    pub fn new(order: usize) -> Option<MendelsohnTripleSystem> {
+        if order % 3 == 2 || order == 6 {
+            return None
+        }
+        if order < 3 {
+            // Orders 0 and 1 have no pairs to cover.
+            return Some(MendelsohnTripleSystem { order, triples: Vec::new() })
+        }
+        if order == 7 {
+            let triples = fano_blocks().iter().flat_map(|&[a, b, c]| [[a, b, c], [a, c, b]]).collect();
+            return Some(MendelsohnTripleSystem { order, triples })
+        }
+        let quasigroup = Quasigroup::new_idempotent(order / 3)
+            .expect("ProgrammingError: idempotent quasigroups exist for every order but 2.");
+        MendelsohnTripleSystem::from_idempotent_quasigroup(&quasigroup, order % 3 == 1)
+    }
+
+    /// Constructs a Mendelsohn triple system on 3n points, or 3n + 1 if `with_infinity` is set,
+    /// by tripling an idempotent quasigroup of order n.
+    ///
+    /// Point (x, i) of the tripling is numbered `i * n + x`, and the point at infinity is 3n.
+    /// Returns None if the quasigroup is not idempotent.
+    pub fn from_idempotent_quasigroup(quasigroup: &Quasigroup, with_infinity: bool) -> Option<MendelsohnTripleSystem> {
+        if !quasigroup.is_idempotent() {
+            return None
+        }
+        let n = quasigroup.order();
+        let point = |x: usize, level: usize| (level % 3) * n + x;
+        let infinity = 3 * n;
+        let mut triples = Vec::new();
+
+        for x in 0..n {
+            let column = [point(x, 0), point(x, 1), point(x, 2)];
+            if with_infinity {
+                for level in 0..3 {
+                    triples.push([infinity, column[level], column[(level + 1) % 3]]);
+                }
+            } else {
+                triples.push(column);
+            }
+            triples.push([column[0], column[2], column[1]]);
+        }
+        for (x, y, level) in cross_pairs(n) {
+            triples.push([point(x, level), point(y, level), point(quasigroup.op(x, y), level + 1)]);
+        }
+
+        Some(MendelsohnTripleSystem {
+            order: if with_infinity { infinity + 1 } else { infinity },
+            triples
+        })
+    }
+
+    /// Recovers the Mendelsohn triple system of an idempotent semisymmetric quasigroup,
+    /// i.e. one where `x * x = x` and `y * (x * y) = x` for every x and y.
+    ///
+    /// Returns None if the quasigroup does not satisfy both identities.
+    pub fn from_quasigroup(quasigroup: &Quasigroup) -> Option<MendelsohnTripleSystem> {
+        if !quasigroup.is_idempotent() || !quasigroup.is_semisymmetric() {
+            return None
+        }
+        let order = quasigroup.order();
+
+        // Each triple shows up once per rotation, so only keep the rotation starting at its smallest point.
+        let mut triples = Vec::new();
+        for x in 0..order {
+            for y in (x + 1)..order {
+                let z = quasigroup.op(x, y);
+                if x < z {
+                    triples.push([x, y, z]);
+                }
+            }
+        }
+        Some(MendelsohnTripleSystem { order, triples })
+    }
+
+    /// Checks that `triples` is a Mendelsohn triple system on `order` points.
+    pub fn verify(order: usize, triples: &[Triple]) -> bool {
+        covers_pairs_once(order, triples, |&[a, b, c]| [(a, b), (b, c), (c, a)])
+    }
+
+    /// Returns the idempotent semisymmetric quasigroup of the system,
+    /// where each triple (a, b, c) gives `a * b = c`, `b * c = a`, and `c * a = b`.
+    pub fn as_quasigroup(&self) -> Quasigroup {
+        let mut rows: Vec<Vec<usize>> = (0..self.order).map(|x| vec![x; self.order]).collect();
+        for &[a, b, c] in &self.triples {
+            rows[a][b] = c;
+            rows[b][c] = a;
+            rows[c][a] = b;
+        }
+        Quasigroup::new(LatinSquare::from_rows(rows))
+            .expect("ProgrammingError: Mendelsohn triple system did not give a quasigroup.")
+    }
+
+    /// The number of points of the system.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The cyclic triples of the system.
+    pub fn triples(&self) -> &[Triple] {
+        &self.triples
+    }
+}
+
+/// A directed triple system: transitive triples covering every ordered pair of distinct points once.
+///
+/// ```
+/// use combinatorial_patterns::triple_system::DirectedTripleSystem;
+///
+/// for order in [3, 4, 6, 7, 10] {
+///     let system = DirectedTripleSystem::new(order).unwrap();
+///     assert!(DirectedTripleSystem::verify(order, system.triples()));
+/// }
+/// assert!(DirectedTripleSystem::new(5).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectedTripleSystem {
+    order: usize,
+    triples: Vec<Triple>
+}
+
+impl DirectedTripleSystem {
+    /// Constructs a directed triple system on `order` points.
+    ///
+    /// Returns None when none exists, i.e. when order ≡ 2 (mod 3).
+    pub fn new(order: usize) -> Option<DirectedTripleSystem> {
+        if order % 3 == 2 {
+            return None
+        }
+        if order < 3 {
+            // Orders 0 and 1 have no pairs to cover.
+            return Some(DirectedTripleSystem { order, triples: Vec::new() })
+        }
+        if order == 7 {
+            let triples = fano_blocks().iter().flat_map(|&[a, b, c]| [[a, b, c], [c, b, a]]).collect();
+            return Some(DirectedTripleSystem { order, triples })
+        }
+        if order == 6 {
+            return DirectedTripleSystem::search(order)
+        }
+        let quasigroup = Quasigroup::new_idempotent(order / 3)
+            .expect("ProgrammingError: idempotent quasigroups exist for every order but 2.");
+        DirectedTripleSystem::from_idempotent_quasigroup(&quasigroup, order % 3 == 1)
+    }
+
+    /// Constructs a directed triple system on 3n points, or 3n + 1 if `with_infinity` is set,
+    /// by tripling an idempotent quasigroup of order n.
+    ///
+    /// Points are numbered as in `MendelsohnTripleSystem::from_idempotent_quasigroup`.
+    /// Returns None if the quasigroup is not idempotent.
+    pub fn from_idempotent_quasigroup(quasigroup: &Quasigroup, with_infinity: bool) -> Option<DirectedTripleSystem> {
+        if !quasigroup.is_idempotent() {
+            return None
+        }
+        let n = quasigroup.order();
+        let point = |x: usize, level: usize| (level % 3) * n + x;
+        let infinity = 3 * n;
+        let mut triples = Vec::new();
+
+        for x in 0..n {
+            let [x0, x1, x2] = [point(x, 0), point(x, 1), point(x, 2)];
+            if with_infinity {
+                // A directed triple system of order 4 on the column and the point at infinity.
+                triples.extend([[x0, x1, x2], [x1, x0, infinity], [x2, infinity, x0], [infinity, x2, x1]]);
+            } else {
+                triples.extend([[x0, x1, x2], [x2, x1, x0]]);
+            }
+        }
+        for (x, y, level) in cross_pairs(n) {
+            triples.push([point(x, level), point(quasigroup.op(x, y), level + 1), point(y, level)]);
+        }
+
+        Some(DirectedTripleSystem {
+            order: if with_infinity { infinity + 1 } else { infinity },
+            triples
+        })
+    }
+
+    /// Checks that `triples` is a directed triple system on `order` points.
+    pub fn verify(order: usize, triples: &[Triple]) -> bool {
+        covers_pairs_once(order, triples, |&[a, b, c]| [(a, b), (a, c), (b, c)])
+    }
+
+    /// The number of points of the system.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The transitive triples of the system.
+    pub fn triples(&self) -> &[Triple] {
+        &self.triples
+    }
+
+    /// Backtracking search, only used for small orders that the tripling construction can't reach.
+    /// Always extends the first uncovered ordered pair (a, b) with a third point.
+    fn search(order: usize) -> Option<DirectedTripleSystem> {
+        fn extend(order: usize, covered: &mut Vec<Vec<bool>>, triples: &mut Vec<Triple>) -> bool {
+            let next = (0..order).flat_map(|a| (0..order).map(move |b| (a, b)))
+                .find(|&(a, b)| a != b && !covered[a][b]);
+            let (a, b) = match next {
+                Some(pair) => pair,
+                None => return true
+            };
+            for c in (0..order).filter(|&c| c != a && c != b) {
+                for triple in [[a, b, c], [a, c, b], [c, a, b]] {
+                    let [p, q, r] = triple;
+                    let pairs = [(p, q), (p, r), (q, r)];
+                    if pairs.iter().any(|&(s, t)| covered[s][t]) {
+                        continue
+                    }
+                    pairs.iter().for_each(|&(s, t)| covered[s][t] = true);
+                    triples.push(triple);
+                    if extend(order, covered, triples) {
+                        return true
+                    }
+                    triples.pop();
+                    pairs.iter().for_each(|&(s, t)| covered[s][t] = false);
+                }
+            }
+            false
+        }
+
+        let mut covered = vec![vec![false; order]; order];
+        let mut triples = Vec::new();
+        if extend(order, &mut covered, &mut triples) {
+            Some(DirectedTripleSystem { order, triples })
+        } else {
+            None
+        }
+    }
+}
+
+/// A Steiner triple system: triples of points covering every unordered pair of distinct points once.
+///
+/// ```
+/// use combinatorial_patterns::triple_system::SteinerTripleSystem;
+///
+/// for order in [1, 3, 7, 9, 13, 15, 19, 21] {
+///     let system = SteinerTripleSystem::new(order).unwrap();
+///     assert!(SteinerTripleSystem::verify(order, system.blocks()));
+///     // The Steiner quasigroup is totally symmetric, and gives the system back.
+///     let quasigroup = system.as_quasigroup();
+///     assert!(quasigroup.is_idempotent() && quasigroup.is_totally_symmetric());
+///     assert_eq!(SteinerTripleSystem::from_quasigroup(&quasigroup).unwrap().blocks().len(), order * (order - 1) / 6);
+/// }
+/// assert!(SteinerTripleSystem::new(11).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteinerTripleSystem {
+    order: usize,
+    blocks: Vec<Triple>
+}
+
+impl SteinerTripleSystem {
+    /// Constructs a Steiner triple system on `order` points, by Bose's construction when order ≡ 3 (mod 6)
+    /// and Skolem's when order ≡ 1 (mod 6).
+    ///
+    /// Returns None when none exists, i.e. unless order ≡ 1 or 3 (mod 6). Points are numbered as in
+    /// `MendelsohnTripleSystem::from_idempotent_quasigroup`.
+    pub fn new(order: usize) -> Option<SteinerTripleSystem> {
+        let with_infinity = match order % 6 {
+            1 => true,
+            3 => false,
+            _ => return None
+        };
+        let n = order / 3;
+        let point = |x: usize, level: usize| (level % 3) * n + x;
+        let infinity = 3 * n;
+        let mut blocks = Vec::new();
+        if with_infinity {
+            // Skolem: the half-idempotent commutative quasigroup of order n = 2t, where x * y is (x + y) mod n
+            // with every even symbol 2i renamed i and every odd symbol 2i + 1 renamed t + i.
+            let t = n / 2;
+            let op = |x: usize, y: usize| {
+                let sum = (x + y) % n;
+                if sum.is_multiple_of(2) { sum / 2 } else { t + sum / 2 }
+            };
+            for x in 0..t {
+                blocks.push([point(x, 0), point(x, 1), point(x, 2)]);
+                for level in 0..3 {
+                    blocks.push([infinity, point(x + t, level), point(x, level + 1)]);
+                }
+            }
+            for (x, y, level) in cross_pairs(n).filter(|&(x, y, _)| x < y) {
+                blocks.push([point(x, level), point(y, level), point(op(x, y), level + 1)]);
+            }
+        } else {
+            // Bose: the idempotent commutative quasigroup x * y = (x + y) / 2 of odd order n.
+            let half = n.div_ceil(2);
+            for x in 0..n {
+                blocks.push([point(x, 0), point(x, 1), point(x, 2)]);
+            }
+            for (x, y, level) in cross_pairs(n).filter(|&(x, y, _)| x < y) {
+                blocks.push([point(x, level), point(y, level), point(((x + y) * half) % n, level + 1)]);
+            }
+        }
+        Some(SteinerTripleSystem { order, blocks })
+    }
+
+    /// Recovers the Steiner triple system of an idempotent totally symmetric quasigroup.
+    ///
+    /// Returns None if the quasigroup is not idempotent and totally symmetric.
+    pub fn from_quasigroup(quasigroup: &Quasigroup) -> Option<SteinerTripleSystem> {
+        if !quasigroup.is_idempotent() || !quasigroup.is_totally_symmetric() {
+            return None
+        }
+        let order = quasigroup.order();
+        let mut blocks = Vec::new();
+        for x in 0..order {
+            for y in (x + 1)..order {
+                let z = quasigroup.op(x, y);
+                if y < z {
+                    blocks.push([x, y, z]);
+                }
+            }
+        }
+        Some(SteinerTripleSystem { order, blocks })
+    }
+
+    /// The system with the given blocks, or None if they are not a Steiner triple system on `order` points.
+    pub fn from_blocks(order: usize, blocks: Vec<Triple>) -> Option<SteinerTripleSystem> {
+        if SteinerTripleSystem::verify(order, &blocks) { Some(SteinerTripleSystem { order, blocks }) } else { None }
+    }
+
+    /// Checks that `blocks` is a Steiner triple system on `order` points.
+    pub fn verify(order: usize, blocks: &[Triple]) -> bool {
+        let both_ways: Vec<Triple> = blocks.iter().flat_map(|&[a, b, c]| [[a, b, c], [a, c, b]]).collect();
+        covers_pairs_once(order, &both_ways, |&[a, b, c]| [(a, b), (b, c), (c, a)])
+    }
+
+    /// Returns the Steiner quasigroup of the system: `x * x = x`, and `x * y = z` for the block {x, y, z}.
+    pub fn as_quasigroup(&self) -> Quasigroup {
+        let mut rows: Vec<Vec<usize>> = (0..self.order).map(|x| vec![x; self.order]).collect();
+        for &[a, b, c] in &self.blocks {
+            for (x, y, z) in [(a, b, c), (b, c, a), (c, a, b)] {
+                rows[x][y] = z;
+                rows[y][x] = z;
+            }
+        }
+        Quasigroup::new(LatinSquare::from_rows(rows))
+            .expect("ProgrammingError: Steiner triple system did not give a quasigroup.")
+    }
+
+    /// The number of points of the system.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The blocks of the system.
+    pub fn blocks(&self) -> &[Triple] {
+        &self.blocks
+    }
+}
+
+/// The blocks of the Fano plane, the Steiner triple system of order 7.
+fn fano_blocks() -> Vec<Triple> {
+    (0..7).map(|i| [i, (i + 1) % 7, (i + 3) % 7]).collect()
+}
+
+/// Every (x, y, level) with x != y, which receives one cross-level triple in the tripling construction.
+fn cross_pairs(n: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+    (0..3).flat_map(move |level| {
+        (0..n).flat_map(move |x| (0..n).filter(move |&y| y != x).map(move |y| (x, y, level)))
+    })
+}
+
+/// Checks that the pairs read from each triple cover every ordered pair of distinct points exactly once.
+fn covers_pairs_once(order: usize, triples: &[Triple], pairs: fn(&Triple) -> [(usize, usize); 3]) -> bool {
+    let mut covered = vec![vec![false; order]; order];
+    for triple in triples {
+        if triple.iter().any(|&point| point >= order) || triple[0] == triple[1] || triple[1] == triple[2] || triple[0] == triple[2] {
+            return false
+        }
+        for (a, b) in pairs(triple) {
+            if covered[a][b] {
+                return false
+            }
+            covered[a][b] = true;
+        }
+    }
+    triples.len() * 3 == order * order.saturating_sub(1)
+}
@@ -0,0 +1,189 @@
+//! Random latin squares biased by weights on the (cell, symbol) assignments.
+//!
+//! The weight of a square is the product of the weights of the symbols in its cells, and the sampler aims
+//! at the distribution proportional to it. Every weight is positive, so any square can come out: a large
+//! weight makes a symbol likely in a cell, say to stay mostly like a template, without forcing it.
+//!
+//! The sampler is a Metropolis chain on top of the Jacobson-Matthews walk. A step proposes the next proper
+//! square the walk reaches, and accepts it with probability min(1, w(new) / w(old)), going back to the old
+//! square otherwise. The walk is reversible with respect to the uniform distribution, so the proposal from
+//! one proper square to another is as likely as the way back, and the accepted squares converge to the
+//! weighted distribution. As with `LatinSquare::new_random`, how many steps are enough is a rule of thumb;
+//! the more the weights differ, the more proposals are rejected and the longer it takes, see `convergence`.
+//!
+//! Sources:
+//!
+//! - [Generating uniformly distributed random latin squares, Mark T. Jacobson, Peter Matthews](https://onlinelibrary.wiley.com/doi/10.1002/(SICI)1520-6610(1996)4:6%3C405::AID-JCD3%3E3.0.CO;2-J)
+//! - Equation of state calculations by fast computing machines, N. Metropolis, A. W. Rosenbluth, M. N. Rosenbluth, A. H. Teller, E. Teller (1953)
+
+use rand::Rng;
+
+use crate::error::CombinatorialError;
+use crate::latin_square::{IncidenceCube, LatinSquare};
+
+/// A positive weight for every symbol in every cell of a square of some order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellWeights {
+    order: usize,
+    // Indexed by (row * order + column) * order + symbol.
+    weights: Vec<f64>
+}
+
+impl CellWeights {
+    /// Weight 1 everywhere, under which the sampler is uniform.
+    pub fn new(order: usize) -> CellWeights {
+        CellWeights { order, weights: vec![1.0; order * order * order] }
+    }
+
+    /// Weight `strength` for the symbol of the template in each cell and 1 for the others. Returns None
+    /// unless the strength is finite and positive.
+    ///
+    /// ```
+    /// use combinatorial_patterns::IncidenceCube;
+    /// use combinatorial_patterns::weighted::CellWeights;
+    ///
+    /// let template = IncidenceCube::new_cyclic(4).unwrap().as_latin_square();
+    /// let weights = CellWeights::from_template(&template, 5.0).unwrap();
+    /// assert_eq!(weights.get(1, 2, 3), 5.0);
+    /// assert_eq!(weights.get(1, 2, 0), 1.0);
+    /// assert!(CellWeights::from_template(&template, 0.0).is_none());
+    /// ```
+    pub fn from_template(template: &LatinSquare, strength: f64) -> Option<CellWeights> {
+        let mut weights = CellWeights::new(template.size());
+        for (row, symbols) in template.square.iter().enumerate() {
+            for (column, &symbol) in symbols.iter().enumerate() {
+                if !weights.set(row, column, symbol, strength) {
+                    return None
+                }
+            }
+        }
+        Some(weights)
+    }
+
+    /// The order of the squares weighed.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The weight of the symbol in the cell.
+    pub fn get(&self, row: usize, column: usize, symbol: usize) -> f64 {
+        self.weights[self.index(row, column, symbol)]
+    }
+
+    /// Sets the weight of the symbol in the cell. A weight that is not finite and positive would forbid or
+    /// force the assignment, so it is refused, returning false.
+    pub fn set(&mut self, row: usize, column: usize, symbol: usize, weight: f64) -> bool {
+        if !(weight.is_finite() && weight > 0.0) {
+            return false
+        }
+        let index = self.index(row, column, symbol);
+        self.weights[index] = weight;
+        true
+    }
+
+    /// The logarithm of the weight of a square of the same order.
+    pub fn log_weight(&self, square: &LatinSquare) -> f64 {
+        square.square.iter().enumerate().flat_map(|(row, symbols)| {
+            symbols.iter().enumerate().map(move |(column, &symbol)| self.get(row, column, symbol).ln())
+        }).sum()
+    }
+
+    fn index(&self, row: usize, column: usize, symbol: usize) -> usize {
+        (row * self.order + column) * self.order + symbol
+    }
+}
+
+/// The Metropolis chain over squares weighted by `CellWeights`, started from the cyclic square.
+///
+/// ```
+/// use combinatorial_patterns::{IncidenceCube, Quasigroup};
+/// use combinatorial_patterns::weighted::{CellWeights, WeightedSampler};
+///
+/// let template = IncidenceCube::new_cyclic(5).unwrap().as_latin_square();
+/// let mut sampler = WeightedSampler::new(CellWeights::from_template(&template, 20.0).unwrap()).unwrap();
+/// sampler.run(200, &mut rand::thread_rng());
+/// assert!(Quasigroup::new(sampler.square()).is_some());
+/// assert!(sampler.acceptance_rate() < 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedSampler {
+    weights: CellWeights,
+    cube: IncidenceCube,
+    square: LatinSquare,
+    log_weight: f64,
+    proposed: u64,
+    accepted: u64
+}
+
+impl WeightedSampler {
+    /// A sampler for the weights, at the cyclic square. Fails for weights of order 0.
+    pub fn new(weights: CellWeights) -> Result<WeightedSampler, CombinatorialError> {
+        let cube = IncidenceCube::new_cyclic(weights.order())?;
+        let square = cube.as_latin_square();
+        let log_weight = weights.log_weight(&square);
+        Ok(WeightedSampler { weights, cube, square, log_weight, proposed: 0, accepted: 0 })
+    }
+
+    /// Proposes the next proper square of the walk and accepts or rejects it, drawing from `rng`. Returns
+    /// true if it was accepted.
+    pub fn step(&mut self, rng: &mut impl Rng) -> bool {
+        self.proposed += 1;
+        self.cube.advance(1, rng);
+        let proposal = self.cube.as_latin_square();
+        let log_weight = self.weights.log_weight(&proposal);
+        let ratio = (log_weight - self.log_weight).exp();
+        if ratio >= 1.0 || rng.gen::<f64>() < ratio {
+            self.accepted += 1;
+            self.square = proposal;
+            self.log_weight = log_weight;
+            true
+        } else {
+            self.cube = IncidenceCube::from_square(&self.square);
+            false
+        }
+    }
+
+    /// Makes the given number of steps.
+    pub fn run(&mut self, steps: u64, rng: &mut impl Rng) {
+        for _ in 0..steps {
+            self.step(rng);
+        }
+    }
+
+    /// The current square.
+    pub fn square(&self) -> LatinSquare {
+        self.square.clone()
+    }
+
+    /// The fraction of the proposals accepted so far, or 1 before the first.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.proposed == 0 {
+            return 1.0
+        }
+        self.accepted as f64 / self.proposed as f64
+    }
+}
+
+/// A square drawn from the weighted distribution after n^3 steps, for a square of order n, the number of
+/// moves `LatinSquare::new_random` makes, drawn from `rng`. Fails for weights of order 0.
+///
+/// ```
+/// use combinatorial_patterns::{weighted, IncidenceCube};
+/// use rand::SeedableRng;
+///
+/// let template = IncidenceCube::new_cyclic(6).unwrap().as_latin_square();
+/// let weights = weighted::CellWeights::from_template(&template, 50.0).unwrap();
+/// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+/// let square = weighted::weighted_square(&weights, &mut rng).unwrap();
+/// let agreeing = (0..6).flat_map(|row| (0..6).map(move |column| (row, column)))
+///     .filter(|&(row, column)| square.square[row][column] == template.square[row][column])
+///     .count();
+/// // A uniform square agrees with the template in about 6 cells.
+/// assert!(agreeing > 6);
+/// ```
+pub fn weighted_square(weights: &CellWeights, rng: &mut impl Rng) -> Result<LatinSquare, CombinatorialError> {
+    let mut sampler = WeightedSampler::new(weights.clone())?;
+    let order = weights.order() as u64;
+    sampler.run(order * order * order, rng);
+    Ok(sampler.square())
+}
@@ -0,0 +1,18 @@
+use combinatorial_patterns::{IncidenceCube, LatinSquare};
+
+/// The first order whose n^3 moves no longer fit in an `i32`.
+#[test]
+fn mixing_moves_past_i32() {
+    assert!(1291u64.pow(3) > i32::MAX as u64);
+    assert!(1290u64.pow(3) < i32::MAX as u64);
+    assert_eq!(IncidenceCube::mixing_moves(1291), 2_151_685_171);
+}
+
+/// Takes about 8 GB for the cube and 8 * 10^9 moves; run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn generates_order_2000() {
+    let square = LatinSquare::new_random_seeded(2000, 2000).unwrap();
+    assert_eq!(square.size(), 2000);
+    assert!(square.is_valid());
+}